@@ -30,6 +30,21 @@ export interface GetLatestBlockhashResponse {
   };
 }
 
+export interface StateProof {
+  /** Sealed block the proof is for */
+  slot: number;
+  /** Merkle root the proof hashes up to (the state root published for `slot`) */
+  root: string;
+  accountHash: string;
+  /** Sibling hashes from the root down to the account's leaf */
+  siblings: string[];
+}
+
+export interface GetStateProofResponse {
+  context: { slot: number };
+  value: StateProof | null;
+}
+
 export type AccountSubscribeCallback = (pubkey: string, account: AccountInfo) => void;
 
 export class L2Connection {
@@ -93,6 +108,18 @@ export class L2Connection {
     return this.rpc<number>('getSlot', []);
   }
 
+  /** Get the Merkle root of all account state at the last sealed block */
+  async getStateRoot(): Promise<string> {
+    const response = await this.rpc<{ context: { slot: number }; value: string }>('getStateRoot', []);
+    return response.value;
+  }
+
+  /** Get an inclusion proof for an account (null if it doesn't exist) */
+  async getStateProof(pubkey: string): Promise<StateProof | null> {
+    const response = await this.rpc<GetStateProofResponse>('getStateProof', [pubkey]);
+    return response.value;
+  }
+
   /** Send transaction (base64 encoded) */
   async sendTransaction(txBase64: string): Promise<string> {
     return this.rpc<string>('sendTransaction', [txBase64]);
@@ -15,6 +15,7 @@ import {
   deriveWorldPda,
   deriveWorldPlayerPda,
   decodeWorldPlayer,
+  WORLD_PLAYER_SIZE,
   WorldPlayer,
 } from './state';
 
@@ -42,6 +43,7 @@ const keysPressed = new Set<string>();
 
 // Movement input loop
 let inputLoopId: number | null = null;
+let lastSentCrouch = false;
 
 // Debug logging
 function log(msg: string): void {
@@ -122,7 +124,7 @@ async function getPlayerState(): Promise<WorldPlayer | null> {
     }
 
     const data = Buffer.from(result.value.data[0], 'base64');
-    log(`[DEBUG] Account data length: ${data.length} bytes (expected: 123)`);
+    log(`[DEBUG] Account data length: ${data.length} bytes (expected: ${WORLD_PLAYER_SIZE})`);
 
     // Check if data is all zeros (uninitialized account)
     const isAllZeros = data.slice(0, 64).every(byte => byte === 0);
@@ -243,9 +245,9 @@ function updateControlsHint(): void {
   if (!isJoined) {
     controlsHint.textContent = 'Press J to join the world';
   } else if (!renderer.isLocked()) {
-    controlsHint.textContent = 'Click to enable mouse look | WASD to move | Space to jump | Shift to sprint';
+    controlsHint.textContent = 'Click to enable mouse look | WASD to move | Space to jump | Shift to sprint | Q to dash | C to crouch';
   } else {
-    controlsHint.textContent = 'WASD to move | Space to jump | Shift to sprint | ESC to release mouse';
+    controlsHint.textContent = 'WASD to move | Space to jump | Shift to sprint | Q to dash | C to crouch | ESC to release mouse';
   }
 }
 
@@ -293,6 +295,8 @@ function getMovementInput(): MovementInput3D {
   const right = keysPressed.has('d') || keysPressed.has('arrowright');
   const sprint = keysPressed.has('shift');
   const jump = keysPressed.has(' ');
+  const dash = keysPressed.has('q');
+  const crouch = keysPressed.has('c');
 
   // Calculate camera-relative movement (-127 to 127)
   let moveX = 0;
@@ -316,6 +320,8 @@ function getMovementInput(): MovementInput3D {
     cameraYaw: renderer.getCameraYawInt16(),
     sprint,
     jump,
+    dash,
+    crouch,
   };
 }
 
@@ -331,8 +337,9 @@ function startInputLoop(): void {
 
     const input = getMovementInput();
 
-    // Only send if there's movement or jump
-    if (input.moveX !== 0 || input.moveZ !== 0 || input.jump) {
+    // Only send if there's movement, an ability, or crouch was pressed/released
+    if (input.moveX !== 0 || input.moveZ !== 0 || input.jump || input.dash || input.crouch !== lastSentCrouch) {
+      lastSentCrouch = input.crouch;
       await sendMove3D(input);
     }
   }, TICK_MS);
@@ -15,6 +15,7 @@ export const WORLD_PROGRAM_ID = new PublicKey('Wor1dProgram111111111111111111111
 // Seeds
 export const WORLD_SEED = Buffer.from('world');
 export const WORLD_PLAYER_SEED = Buffer.from('world_player');
+export const POSITION_HISTORY_SEED = Buffer.from('position_history');
 
 /** World configuration */
 export interface WorldConfig {
@@ -28,6 +29,29 @@ export interface WorldConfig {
   bump: number;
   l1Game: PublicKey;
   initTs: bigint;
+  // Teams (teamCount 0 = teams disabled)
+  teamCount: number;
+  friendlyFire: boolean;
+  maxTeamSize: number;
+  // Who may join (0 = public, 1 = allowlist, 2 = password)
+  visibility: number;
+  // Waitlist (slots only AdmitVip may fill, players in line)
+  reservedSlots: number;
+  waitingCount: number;
+  // Slots without an action before a player is evicted (0 = never)
+  idleTimeoutSlots: bigint;
+  // Name rules: one wallet per name, allowed characters (0 = any,
+  // 1 = alphanumeric, 2 = identifier), and how many words are reserved
+  uniqueNames: boolean;
+  nameCharset: number;
+  reservedNameCount: number;
+  // Dash burst speed (0 = disabled) and slots between dashes
+  dashSpeed: number;
+  dashCooldownSlots: bigint;
+  // How far back attacks may rewind targets for lag compensation
+  maxRewindSlots: bigint;
+  // Missing inputs in a row filled in by repeating the last one (0 = off)
+  maxExtrapolationSlots: bigint;
 }
 
 /** Player state in the world (3D) */
@@ -54,6 +78,32 @@ export interface WorldPlayer {
   isGrounded: boolean;
   bump: number;
   name: string;
+  team: number; // 0 = no team
+  // Hit-stunned until this slot (movement input ignored, no attacks)
+  stunUntilSlot: bigint;
+  isCrouching: boolean;
+  // First slot the player may dash again
+  dashReadySlot: bigint;
+  // Slot of the last movement input, and slots predicted from it since
+  // (non-zero = the latest movement was extrapolated, not real input)
+  lastInputSlot: bigint;
+  extrapolatedSlots: number;
+}
+
+/** Where a player or NPC stood from `slot` until its next sample */
+export interface PositionSample {
+  entity: PublicKey;
+  slot: bigint;
+  positionX: number;
+  positionY: number;
+  positionZ: number;
+  isCrouching: boolean;
+}
+
+/** Recent positions of a world's players and NPCs (oldest first) */
+export interface PositionHistory {
+  world: PublicKey;
+  samples: PositionSample[];
 }
 
 /** 3D Movement input */
@@ -63,6 +113,8 @@ export interface MovementInput3D {
   cameraYaw: number;  // 0-65535 (camera facing direction)
   sprint: boolean;
   jump: boolean;
+  dash: boolean;      // Burst of speed (per-world cooldown)
+  crouch: boolean;    // Held: shorter hitbox, slower movement
 }
 
 /** Direction constants */
@@ -93,8 +145,14 @@ export function directionToVector(direction: Direction): [number, number] {
   }
 }
 
+/** Slots of positions each world's history covers */
+export const POSITION_HISTORY_SLOTS = 10;
+
+/** Size of one position history sample */
+const POSITION_SAMPLE_SIZE = 53;
+
 /** Expected size of WorldPlayer account data */
-export const WORLD_PLAYER_SIZE = 123;
+export const WORLD_PLAYER_SIZE = 141 + 8 + 8 + 1;
 
 /** Decode WorldPlayer from account data (3D layout) */
 export function decodeWorldPlayer(data: Buffer): WorldPlayer {
@@ -171,6 +229,33 @@ export function decodeWorldPlayer(data: Buffer): WorldPlayer {
   // name: [u8; 16] (16 bytes)
   const nameBytes = data.subarray(offset, offset + 16);
   const name = Buffer.from(nameBytes).toString('utf8').replace(/\0/g, '');
+  offset += 16;
+
+  // team: u8 (1 byte)
+  const team = data.readUInt8(offset);
+  offset += 1;
+
+  // stun_until_slot: u64 (8 bytes)
+  const stunUntilSlot = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // is_crouching: bool (1 byte)
+  const isCrouching = data[offset] !== 0;
+  offset += 1;
+
+  // dash_ready_slot: u64 (8 bytes)
+  const dashReadySlot = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // last_input: MovementInput3D (8 bytes, not decoded)
+  offset += 8;
+
+  // last_input_slot: u64 (8 bytes)
+  const lastInputSlot = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // extrapolated_slots: u8 (1 byte)
+  const extrapolatedSlots = data[offset];
 
   return {
     authority,
@@ -190,9 +275,34 @@ export function decodeWorldPlayer(data: Buffer): WorldPlayer {
     isGrounded,
     bump,
     name,
+    team,
+    stunUntilSlot,
+    isCrouching,
+    dashReadySlot,
+    lastInputSlot,
+    extrapolatedSlots,
   };
 }
 
+/** Decode a world's PositionHistory from account data */
+export function decodePositionHistory(data: Buffer): PositionHistory {
+  const world = new PublicKey(data.subarray(0, 32));
+  const count = data.readUInt32LE(32);
+  const samples: PositionSample[] = [];
+  for (let i = 0; i < count; i++) {
+    const sample = 36 + i * POSITION_SAMPLE_SIZE;
+    samples.push({
+      entity: new PublicKey(data.subarray(sample, sample + 32)),
+      slot: data.readBigUInt64LE(sample + 32),
+      positionX: data.readInt32LE(sample + 40),
+      positionY: data.readInt32LE(sample + 44),
+      positionZ: data.readInt32LE(sample + 48),
+      isCrouching: data[sample + 52] !== 0,
+    });
+  }
+  return { world, samples };
+}
+
 /** Decode WorldConfig from account data */
 export function decodeWorldConfig(data: Buffer): WorldConfig {
   let offset = 0;
@@ -236,6 +346,62 @@ export function decodeWorldConfig(data: Buffer): WorldConfig {
 
   // init_ts: i64 (8 bytes)
   const initTs = data.readBigInt64LE(offset);
+  offset += 8;
+
+  // team_count: u8 (1 byte)
+  const teamCount = data.readUInt8(offset);
+  offset += 1;
+
+  // friendly_fire: bool (1 byte)
+  const friendlyFire = data.readUInt8(offset) !== 0;
+  offset += 1;
+
+  // max_team_size: u16 (2 bytes)
+  const maxTeamSize = data.readUInt16LE(offset);
+  offset += 2;
+
+  // visibility: u8 (1 byte), followed by password_salt[16] + password_hash[32]
+  const visibility = data.readUInt8(offset);
+  offset += 1 + 16 + 32;
+
+  // reserved_slots: u16 (2 bytes)
+  const reservedSlots = data.readUInt16LE(offset);
+  offset += 2;
+
+  // waiting_count: u16 (2 bytes)
+  const waitingCount = data.readUInt16LE(offset);
+  offset += 2;
+
+  // idle_timeout_slots: u64 (8 bytes)
+  const idleTimeoutSlots = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // unique_names: bool (1 byte)
+  const uniqueNames = data[offset] !== 0;
+  offset += 1;
+
+  // name_charset: u8 (1 byte)
+  const nameCharset = data[offset];
+  offset += 1;
+
+  // reserved_name_count: u16 (2 bytes)
+  const reservedNameCount = data.readUInt16LE(offset);
+  offset += 2;
+
+  // dash_speed: i16 (2 bytes)
+  const dashSpeed = data.readInt16LE(offset);
+  offset += 2;
+
+  // dash_cooldown_slots: u64 (8 bytes)
+  const dashCooldownSlots = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // max_rewind_slots: u64 (8 bytes)
+  const maxRewindSlots = data.readBigUInt64LE(offset);
+  offset += 8;
+
+  // max_extrapolation_slots: u64 (8 bytes)
+  const maxExtrapolationSlots = data.readBigUInt64LE(offset);
 
   return {
     name,
@@ -248,6 +414,20 @@ export function decodeWorldConfig(data: Buffer): WorldConfig {
     bump,
     l1Game,
     initTs,
+    teamCount,
+    friendlyFire,
+    maxTeamSize,
+    visibility,
+    reservedSlots,
+    waitingCount,
+    idleTimeoutSlots,
+    uniqueNames,
+    nameCharset,
+    reservedNameCount,
+    dashSpeed,
+    dashCooldownSlots,
+    maxRewindSlots,
+    maxExtrapolationSlots,
   };
 }
 
@@ -285,3 +465,14 @@ export function deriveWorldPda(
     programId
   );
 }
+
+/** Derive a world's PositionHistory PDA */
+export function derivePositionHistoryPda(
+  world: PublicKey,
+  programId: PublicKey = WORLD_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [POSITION_HISTORY_SEED, world.toBuffer()],
+    programId
+  );
+}
@@ -10,7 +10,7 @@ import {
   Transaction,
   TransactionInstruction,
 } from '@solana/web3.js';
-import { Direction, WORLD_PROGRAM_ID, deriveWorldPda, deriveWorldPlayerPda } from './state';
+import { Direction, WORLD_PROGRAM_ID, derivePositionHistoryPda, deriveWorldPda, deriveWorldPlayerPda } from './state';
 
 /** Instruction discriminants (matches WorldInstruction enum in Borsh order) */
 enum WorldInstructionType {
@@ -89,6 +89,8 @@ export interface MovementInput3D {
   cameraYaw: number; // 0-65535 (0-360 degrees)
   sprint: boolean;
   jump: boolean;
+  dash: boolean;
+  crouch: boolean;
 }
 
 /** Build MovePlayer3D instruction */
@@ -100,14 +102,17 @@ export function buildMovePlayer3DInstruction(
   programId: PublicKey = WORLD_PROGRAM_ID
 ): TransactionInstruction {
   // Instruction data layout (Borsh):
-  // [discriminant (1 byte), move_x (i8), move_z (i8), camera_yaw (i16 LE), sprint (bool), jump (bool)]
-  const data = Buffer.alloc(1 + 6);
+  // [discriminant (1 byte), move_x (i8), move_z (i8), camera_yaw (i16 LE), sprint (bool), jump (bool),
+  //  dash (bool), crouch (bool)]
+  const data = Buffer.alloc(1 + 8);
   data.writeUInt8(WorldInstructionType.MovePlayer3D, 0);
   data.writeInt8(input.moveX, 1);
   data.writeInt8(input.moveZ, 2);
   data.writeInt16LE(input.cameraYaw, 3);
   data.writeUInt8(input.sprint ? 1 : 0, 5);
   data.writeUInt8(input.jump ? 1 : 0, 6);
+  data.writeUInt8(input.dash ? 1 : 0, 7);
+  data.writeUInt8(input.crouch ? 1 : 0, 8);
 
   return new TransactionInstruction({
     keys: [
@@ -126,13 +131,22 @@ export function buildAttackInstruction(
   attacker: PublicKey,
   target: PublicKey,
   authority: PublicKey,
-  programId: PublicKey = WORLD_PROGRAM_ID
+  programId: PublicKey = WORLD_PROGRAM_ID,
+  observedSlot?: number // slot the target was seen at (lag compensation)
 ): TransactionInstruction {
-  // Instruction data: [discriminant (1 byte), Option<WeaponStats> as None]
-  // Borsh Option::None = single 0 byte
-  const data = Buffer.alloc(1 + 1);
+  // Instruction data: [discriminant (1 byte), Option<WeaponStats> as None,
+  // Option<u64> observed slot]
+  // Borsh Option::None = single 0 byte, Some = 1 byte + value
+  const observedLen = observedSlot === undefined ? 1 : 1 + 8;
+  const data = Buffer.alloc(1 + 1 + observedLen);
   data.writeUInt8(WorldInstructionType.Attack, 0);
   data.writeUInt8(0, 1); // None
+  if (observedSlot === undefined) {
+    data.writeUInt8(0, 2); // None
+  } else {
+    data.writeUInt8(1, 2); // Some
+    data.writeBigUInt64LE(BigInt(observedSlot), 3);
+  }
 
   return new TransactionInstruction({
     keys: [
@@ -140,6 +154,7 @@ export function buildAttackInstruction(
       { pubkey: attacker, isSigner: false, isWritable: true },
       { pubkey: target, isSigner: false, isWritable: true },
       { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: derivePositionHistoryPda(world, programId)[0], isSigner: false, isWritable: false },
     ],
     programId,
     data,
@@ -289,13 +304,14 @@ export class GameClient {
   }
 
   /** Build attack transaction */
-  buildAttack(recentBlockhash: string, targetPda: PublicKey): Transaction {
+  buildAttack(recentBlockhash: string, targetPda: PublicKey, observedSlot?: number): Transaction {
     const ix = buildAttackInstruction(
       this.worldPda,
       this.playerPda,
       targetPda,
       this.keypair.publicKey,
-      this.programId
+      this.programId,
+      observedSlot
     );
 
     const tx = new Transaction();
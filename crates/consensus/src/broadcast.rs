@@ -24,7 +24,6 @@ pub struct BroadcastServer {
 
 #[derive(Debug, Clone)]
 struct ValidatorInfo {
-    pub connected_at: u64,
     pub last_verified_slot: u64,
 }
 
@@ -109,6 +108,12 @@ impl BroadcastServer {
     }
 }
 
+impl Default for BroadcastServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 async fn handle_validator_connection(
     stream: TcpStream,
     mut rx: broadcast::Receiver<Vec<u8>>,
@@ -123,10 +128,6 @@ async fn handle_validator_connection(
     validators.write().insert(
         temp_id,
         ValidatorInfo {
-            connected_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
             last_verified_slot: 0,
         },
     );
@@ -211,7 +212,7 @@ pub struct BroadcastClient {
 
 impl BroadcastClient {
     /// Connect to leader's broadcast server
-    pub async fn connect(leader_addr: &str, node_id: Pubkey) -> anyhow::Result<Self> {
+    pub async fn connect(leader_addr: &str) -> anyhow::Result<Self> {
         let url = format!("ws://{}", leader_addr);
         let (ws_stream, _) = connect_async(&url).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -56,17 +56,19 @@ impl LeaderNode {
     }
 
     /// End the slot and broadcast changes - call this at the end of each tick
-    pub fn end_slot(&self) {
+    /// with the runtime's Merkle state root for the slot
+    pub fn end_slot(&self, state_root: [u8; 32]) {
         let change = self.current_change.write().take();
 
         if let Some(mut change) = change {
-            // Only broadcast if there were writes
-            if !change.writes.is_empty() {
-                // Compute new state root
-                change.new_state_root = change.compute_hash();
+            change.new_state_root = state_root;
 
-                // Update our state root
-                *self.state_root.write() = change.new_state_root;
+            // Update our state root
+            *self.state_root.write() = state_root;
+
+            // Only broadcast if the state moved
+            if !change.writes.is_empty() || change.new_state_root != change.prev_state_root {
+                change.change_hash = change.compute_hash();
 
                 // Broadcast to validators
                 self.broadcast.broadcast_state_change(&change);
@@ -24,8 +24,10 @@ pub struct StateChange {
     pub slot: u64,
     /// Previous state root hash (for verification chain)
     pub prev_state_root: [u8; 32],
-    /// New state root hash after applying changes
+    /// Merkle root of the full account state after applying changes
     pub new_state_root: [u8; 32],
+    /// Hash of this change's contents (see `compute_hash`)
+    pub change_hash: [u8; 32],
     /// Account writes in this slot
     pub writes: Vec<AccountWrite>,
     /// Timestamp (unix millis)
@@ -41,6 +43,7 @@ impl StateChange {
             slot,
             prev_state_root,
             new_state_root: [0u8; 32],
+            change_hash: [0u8; 32],
             writes: Vec::new(),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -61,10 +64,14 @@ impl StateChange {
     }
 
     /// Compute the hash of this state change (for signing)
+    ///
+    /// Covers both state roots and the slot's writes, so the writes a
+    /// validator applies can't be swapped out under a given root.
     pub fn compute_hash(&self) -> [u8; 32] {
         let mut hasher = blake3::Hasher::new();
         hasher.update(&self.slot.to_le_bytes());
         hasher.update(&self.prev_state_root);
+        hasher.update(&self.new_state_root);
         hasher.update(&self.timestamp.to_le_bytes());
 
         for write in &self.writes {
@@ -2,13 +2,14 @@
 
 use crate::broadcast::BroadcastClient;
 use crate::types::{ConsensusConfig, NodeRole, StateChange};
+use l2_runtime::StateTree;
 use parking_lot::RwLock;
 use solana_sdk::{
-    account::AccountSharedData,
+    account::{AccountSharedData, ReadableAccount},
     pubkey::Pubkey,
+    sysvar,
 };
 use std::collections::HashMap;
-use std::sync::Arc;
 
 /// Validator node that verifies leader's state changes
 pub struct ValidatorNode {
@@ -16,6 +17,11 @@ pub struct ValidatorNode {
     client: RwLock<Option<BroadcastClient>>,
     /// Local copy of account state (for verification)
     accounts: RwLock<HashMap<Pubkey, AccountSharedData>>,
+    /// Merkle tree over the local accounts, to recompute the leader's roots
+    state_tree: RwLock<StateTree>,
+    /// Whether the local accounts are the leader's full state, so roots can
+    /// be checked (set once loaded from a snapshot or a recomputed root matches)
+    synced: RwLock<bool>,
     /// Current state root (should match leader)
     state_root: RwLock<[u8; 32]>,
     /// Last verified slot
@@ -33,6 +39,8 @@ impl ValidatorNode {
         Self {
             client: RwLock::new(None),
             accounts: RwLock::new(HashMap::new()),
+            state_tree: RwLock::new(StateTree::new()),
+            synced: RwLock::new(false),
             state_root: RwLock::new([0u8; 32]),
             last_verified_slot: RwLock::new(0),
             config,
@@ -40,9 +48,39 @@ impl ValidatorNode {
         }
     }
 
+    /// Start from the leader's full state at some slot (e.g. a snapshot)
+    ///
+    /// Fails if the accounts don't hash to `state_root`. From here on every
+    /// state change's root is recomputed and checked.
+    pub fn load_state(
+        &self,
+        accounts: Vec<(Pubkey, AccountSharedData)>,
+        state_root: [u8; 32],
+    ) -> anyhow::Result<()> {
+        let mut local = self.accounts.write();
+        let mut tree = self.state_tree.write();
+        *local = HashMap::new();
+        *tree = StateTree::new();
+        for (pubkey, account) in accounts {
+            Self::apply_account(&mut local, &mut tree, pubkey, Some(account));
+        }
+
+        let computed = tree.root().to_bytes();
+        if computed != state_root {
+            return Err(anyhow::anyhow!(
+                "Loaded accounts hash to {:?}, expected {:?}",
+                computed,
+                state_root
+            ));
+        }
+        *self.state_root.write() = state_root;
+        *self.synced.write() = true;
+        Ok(())
+    }
+
     /// Connect to the leader
     pub async fn connect(&self) -> anyhow::Result<()> {
-        let client = BroadcastClient::connect(&self.config.leader_addr, self.node_id).await?;
+        let client = BroadcastClient::connect(&self.config.leader_addr).await?;
         *self.client.write() = Some(client);
         tracing::info!("Validator connected to leader at {}", self.config.leader_addr);
         Ok(())
@@ -117,35 +155,75 @@ impl ValidatorNode {
             ));
         }
 
-        // 3. Verify the hash computation
+        // 3. Verify the change hash
         let computed_hash = change.compute_hash();
-        if computed_hash != change.new_state_root {
+        if computed_hash != change.change_hash {
             return Err(anyhow::anyhow!(
-                "State root hash mismatch: computed {:?}, claimed {:?}",
+                "State change hash mismatch: computed {:?}, claimed {:?}",
                 computed_hash,
-                change.new_state_root
+                change.change_hash
             ));
         }
 
-        // 4. Apply the changes locally
+        // 4. Apply the changes locally (accounts drained to zero lamports are closed)
         let mut accounts = self.accounts.write();
+        let mut tree = self.state_tree.write();
+        let mut previous = Vec::with_capacity(change.writes.len());
         for write in &change.writes {
-            let account = AccountSharedData::from(solana_sdk::account::Account {
-                lamports: write.lamports,
-                data: write.data.clone(),
-                owner: write.owner,
-                executable: false,
-                rent_epoch: 0,
+            let account = (write.lamports > 0).then(|| {
+                AccountSharedData::from(solana_sdk::account::Account {
+                    lamports: write.lamports,
+                    data: write.data.clone(),
+                    owner: write.owner,
+                    executable: false,
+                    rent_epoch: 0,
+                })
             });
-            accounts.insert(write.pubkey, account);
+            previous.push((write.pubkey, accounts.get(&write.pubkey).cloned()));
+            Self::apply_account(&mut accounts, &mut tree, write.pubkey, account);
         }
 
-        // 5. Update our state root
+        // 5. Recompute the new root. The full state is only known once synced;
+        // before that a matching root is what tells us we are.
+        let computed_root = tree.root().to_bytes();
+        let mut synced = self.synced.write();
+        if *synced && computed_root != change.new_state_root {
+            for (pubkey, account) in previous.into_iter().rev() {
+                Self::apply_account(&mut accounts, &mut tree, pubkey, account);
+            }
+            return Err(anyhow::anyhow!(
+                "New state root mismatch: computed {:?}, claimed {:?}",
+                computed_root,
+                change.new_state_root
+            ));
+        }
+        if !*synced && computed_root == change.new_state_root {
+            tracing::info!("Validator state synced with the leader at slot {}", change.slot);
+            *synced = true;
+        }
+
+        // 6. Update our state root
         *self.state_root.write() = change.new_state_root;
 
         Ok(())
     }
 
+    /// Write (`Some`) or close (`None`) a local account, keeping the tree in step
+    fn apply_account(
+        accounts: &mut HashMap<Pubkey, AccountSharedData>,
+        tree: &mut StateTree,
+        pubkey: Pubkey,
+        account: Option<AccountSharedData>,
+    ) {
+        // Sysvars aren't chain state (see `AccountStore::state_root`)
+        let committed = account.as_ref().filter(|account| !sysvar::check_id(account.owner()));
+        tree.update(pubkey, committed);
+        match account {
+            Some(account) => accounts.insert(pubkey, account),
+            None => accounts.remove(&pubkey),
+        };
+    }
+
     /// Get an account from local state
     pub fn get_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
         self.accounts.read().get(pubkey).cloned()
@@ -160,6 +238,11 @@ impl ValidatorNode {
     pub fn state_root(&self) -> [u8; 32] {
         *self.state_root.read()
     }
+
+    /// Check if the local state is the leader's full state (roots are checked)
+    pub fn is_synced(&self) -> bool {
+        *self.synced.read()
+    }
 }
 
 /// Builder for ValidatorNode
@@ -197,3 +280,73 @@ impl Default for ValidatorNodeBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use l2_runtime::AccountStore;
+
+    /// Sign off a change whose new root is the leader store's after `writes`
+    fn leader_change(store: &AccountStore, slot: u64, writes: &[(Pubkey, u64, Vec<u8>)]) -> StateChange {
+        let mut change = StateChange::new(slot, store.state_root().to_bytes());
+        let owner = Pubkey::new_unique();
+        for (pubkey, lamports, data) in writes {
+            change.add_write(*pubkey, data.clone(), *lamports, owner);
+            if *lamports == 0 {
+                store.remove_account(pubkey);
+            } else {
+                let mut account = AccountSharedData::new(*lamports, data.len(), &owner);
+                account.set_data_from_slice(data);
+                store.store_account(*pubkey, account, slot);
+            }
+        }
+        change.new_state_root = store.state_root().to_bytes();
+        change.change_hash = change.compute_hash();
+        change
+    }
+
+    #[test]
+    fn test_recomputes_new_state_root() {
+        let store = AccountStore::new();
+        let accounts: Vec<_> = (1..=5)
+            .map(|i| (Pubkey::new_unique(), AccountSharedData::new(i, 8, &Pubkey::new_unique())))
+            .collect();
+        store.store_accounts(accounts.clone(), 0);
+
+        let validator = ValidatorNodeBuilder::new().build();
+        validator.load_state(accounts.clone(), store.state_root().to_bytes()).unwrap();
+        assert!(validator.is_synced());
+        assert!(validator.load_state(accounts.clone(), [7; 32]).is_err());
+        validator.load_state(accounts.clone(), store.state_root().to_bytes()).unwrap();
+
+        // An honest change, including closing an account
+        let change = leader_change(&store, 1, &[(accounts[0].0, 50, vec![1, 2]), (accounts[1].0, 0, vec![])]);
+        validator.verify_and_apply(&change).unwrap();
+        assert_eq!(validator.state_root(), change.new_state_root);
+        assert!(validator.get_account(&accounts[1].0).is_none());
+
+        // A root that doesn't follow from the writes is rejected and nothing is applied
+        let mut forged = leader_change(&store, 2, &[(accounts[2].0, 60, vec![3])]);
+        forged.writes[0].lamports = 61;
+        forged.change_hash = forged.compute_hash();
+        assert!(validator.verify_and_apply(&forged).is_err());
+        assert_eq!(validator.state_root(), change.new_state_root);
+        assert_eq!(validator.get_account(&accounts[2].0).unwrap().lamports(), 3);
+    }
+
+    #[test]
+    fn test_syncs_once_a_root_matches() {
+        let store = AccountStore::new();
+        let validator = ValidatorNodeBuilder::new().build();
+
+        // Started from nothing: the local state is the leader's once it has every account
+        let first = leader_change(&store, 1, &[(Pubkey::new_unique(), 10, vec![1])]);
+        validator.verify_and_apply(&first).unwrap();
+        assert!(validator.is_synced());
+
+        let mut forged = leader_change(&store, 2, &[(Pubkey::new_unique(), 20, vec![2])]);
+        forged.new_state_root = [9; 32];
+        forged.change_hash = forged.compute_hash();
+        assert!(validator.verify_and_apply(&forged).is_err());
+    }
+}
@@ -1,13 +1,26 @@
 //! In-memory account storage using DashMap for concurrent access
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
+use parking_lot::Mutex;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
     clock::Slot,
+    hash::Hash,
     pubkey::Pubkey,
+    sysvar,
 };
 use std::sync::Arc;
 
+use crate::state_tree::{StateProof, StateTree};
+
+/// State tree with the slot whose end state it holds
+#[derive(Debug, Default)]
+struct SealedTree {
+    tree: StateTree,
+    /// Slot last sealed into the tree (see `AccountStore::seal_state_root`)
+    slot: Slot,
+}
+
 /// Thread-safe in-memory account storage
 ///
 /// Uses DashMap for lock-free concurrent reads and fine-grained write locks.
@@ -18,6 +31,12 @@ pub struct AccountStore {
     accounts: Arc<DashMap<Pubkey, AccountSharedData>>,
     /// Track which slot each account was last modified
     account_slots: Arc<DashMap<Pubkey, Slot>>,
+    /// Merkle tree over account contents, as of the last sealed slot
+    state_tree: Arc<Mutex<SealedTree>>,
+    /// Accounts written or removed since the tree was last brought up to date
+    state_dirty: Arc<DashSet<Pubkey>>,
+    /// Accounts written or removed since they were last persisted
+    persist_dirty: Arc<DashSet<Pubkey>>,
 }
 
 impl AccountStore {
@@ -26,6 +45,9 @@ impl AccountStore {
         Self {
             accounts: Arc::new(DashMap::new()),
             account_slots: Arc::new(DashMap::new()),
+            state_tree: Arc::new(Mutex::new(SealedTree::default())),
+            state_dirty: Arc::new(DashSet::new()),
+            persist_dirty: Arc::new(DashSet::new()),
         }
     }
 
@@ -45,6 +67,8 @@ impl AccountStore {
     pub fn store_account(&self, pubkey: Pubkey, account: AccountSharedData, slot: Slot) {
         self.accounts.insert(pubkey, account);
         self.account_slots.insert(pubkey, slot);
+        self.state_dirty.insert(pubkey);
+        self.persist_dirty.insert(pubkey);
     }
 
     /// Store multiple accounts atomically (best effort - not truly atomic)
@@ -85,6 +109,8 @@ impl AccountStore {
     /// Remove an account
     pub fn remove_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
         self.account_slots.remove(pubkey);
+        self.state_dirty.insert(*pubkey);
+        self.persist_dirty.insert(*pubkey);
         self.accounts.remove(pubkey).map(|(_, v)| v)
     }
 
@@ -92,6 +118,26 @@ impl AccountStore {
     pub fn clear(&self) {
         self.accounts.clear();
         self.account_slots.clear();
+        self.state_dirty.clear();
+        self.persist_dirty.clear();
+        *self.state_tree.lock() = SealedTree::default();
+    }
+
+    /// Take the accounts written or removed since the last call
+    ///
+    /// The flags are cleared before the caller reads the accounts, so a write
+    /// that lands meanwhile is picked up again by the next call.
+    pub fn take_dirty_accounts(&self) -> Vec<Pubkey> {
+        let dirty: Vec<Pubkey> = self.persist_dirty.iter().map(|r| *r.key()).collect();
+        for pubkey in &dirty {
+            self.persist_dirty.remove(pubkey);
+        }
+        dirty
+    }
+
+    /// Number of accounts written or removed since they were last taken
+    pub fn dirty_account_count(&self) -> usize {
+        self.persist_dirty.len()
     }
 
     /// Get accounts owned by a specific program
@@ -102,6 +148,50 @@ impl AccountStore {
             .map(|r| (*r.key(), r.value().clone()))
             .collect()
     }
+
+    /// Merkle root committing to every account (pubkey, lamports, owner, executable, data)
+    ///
+    /// Only accounts written since the last call are rehashed. Sysvars are
+    /// left out: the runtime rewrites them every slot (the clock carries
+    /// wall-clock time) and they aren't chain state. Writes are folded into
+    /// the tree proofs are served from, so call this between blocks only.
+    pub fn state_root(&self) -> Hash {
+        let mut sealed = self.state_tree.lock();
+        self.update_state_tree(&mut sealed.tree);
+        sealed.tree.root()
+    }
+
+    /// Merkle root after `slot`'s block, which later proofs are served against
+    pub fn seal_state_root(&self, slot: Slot) -> Hash {
+        let mut sealed = self.state_tree.lock();
+        self.update_state_tree(&mut sealed.tree);
+        sealed.slot = slot;
+        sealed.tree.root()
+    }
+
+    /// Inclusion proof for an account as of the last sealed slot
+    ///
+    /// Returns the sealed slot and its root with the proof. Writes of the
+    /// block being processed aren't included until it is sealed.
+    pub fn state_proof(&self, pubkey: &Pubkey) -> Option<(Slot, Hash, StateProof)> {
+        let mut sealed = self.state_tree.lock();
+        let slot = sealed.slot;
+        let root = sealed.tree.root();
+        sealed.tree.prove(pubkey).map(|proof| (slot, root, proof))
+    }
+
+    /// Fold accounts written since the last update into the tree
+    fn update_state_tree(&self, tree: &mut StateTree) {
+        let dirty: Vec<Pubkey> = self.state_dirty.iter().map(|r| *r.key()).collect();
+        for pubkey in dirty {
+            // Clear the flag before reading so a concurrent write re-marks it
+            self.state_dirty.remove(&pubkey);
+            let account = self
+                .get_account(&pubkey)
+                .filter(|account| !sysvar::check_id(account.owner()));
+            tree.update(pubkey, account.as_ref());
+        }
+    }
 }
 
 impl Default for AccountStore {
@@ -113,7 +203,7 @@ impl Default for AccountStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::account::Account;
+    use solana_sdk::account::{Account, WritableAccount};
 
     #[test]
     fn test_store_and_get() {
@@ -145,4 +235,85 @@ mod tests {
         let (_, slot) = store.get_account_with_slot(&pubkey).unwrap();
         assert_eq!(slot, 42);
     }
+
+    #[test]
+    fn test_state_root_tracks_contents() {
+        let store = AccountStore::new();
+        let empty_root = store.state_root();
+        let pubkey = Pubkey::new_unique();
+        let mut account = AccountSharedData::from(Account {
+            lamports: 1000,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        });
+
+        store.store_account(pubkey, account.clone(), 1);
+        let root = store.state_root();
+        assert_ne!(root, empty_root);
+
+        // Same contents at another slot: same root
+        store.store_account(pubkey, account.clone(), 2);
+        assert_eq!(store.state_root(), root);
+
+        account.set_lamports(999);
+        store.store_account(pubkey, account, 3);
+        assert_ne!(store.state_root(), root);
+
+        // Sysvars don't count
+        store.remove_account(&pubkey);
+        let clock = AccountSharedData::new(1, 40, &sysvar::id());
+        store.store_account(sysvar::clock::id(), clock, 3);
+        assert_eq!(store.state_root(), empty_root);
+    }
+
+    #[test]
+    fn test_dirty_accounts() {
+        let store = AccountStore::new();
+        let written = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        store.store_account(written, AccountSharedData::new(1, 0, &Pubkey::new_unique()), 1);
+        store.store_account(removed, AccountSharedData::new(1, 0, &Pubkey::new_unique()), 1);
+        store.take_dirty_accounts();
+        assert_eq!(store.dirty_account_count(), 0);
+
+        store.store_account(written, AccountSharedData::new(2, 0, &Pubkey::new_unique()), 2);
+        store.remove_account(&removed);
+        let mut dirty = store.take_dirty_accounts();
+        dirty.sort();
+        let mut expected = vec![written, removed];
+        expected.sort();
+        assert_eq!(dirty, expected);
+        assert!(store.take_dirty_accounts().is_empty());
+    }
+
+    #[test]
+    fn test_state_proof() {
+        let store = AccountStore::new();
+        let accounts: Vec<_> = (1..=10)
+            .map(|i| (Pubkey::new_unique(), AccountSharedData::new(i, 4, &Pubkey::new_unique())))
+            .collect();
+        store.store_accounts(accounts.clone(), 1);
+
+        let root = store.seal_state_root(1);
+        for (pubkey, account) in &accounts {
+            let (slot, proof_root, proof) = store.state_proof(pubkey).unwrap();
+            assert_eq!((slot, proof_root), (1, root));
+            assert!(proof.verify(&root, account));
+        }
+        assert!(store.state_proof(&Pubkey::new_unique()).is_none());
+
+        // The next block's writes aren't proven until it is sealed
+        let (pubkey, account) = &accounts[0];
+        store.store_account(*pubkey, AccountSharedData::new(99, 4, &Pubkey::new_unique()), 2);
+        let (slot, proof_root, proof) = store.state_proof(pubkey).unwrap();
+        assert_eq!((slot, proof_root), (1, root));
+        assert!(proof.verify(&root, account));
+
+        let next_root = store.seal_state_root(2);
+        let (slot, proof_root, proof) = store.state_proof(pubkey).unwrap();
+        assert_eq!((slot, proof_root), (2, next_root));
+        assert!(proof.verify(&next_root, &store.get_account(pubkey).unwrap()));
+    }
 }
@@ -3,7 +3,15 @@
 //! Produces blocks at 30Hz (~33ms intervals) for real-time game state updates.
 //! This is comparable to Fortnite's server tick rate.
 
-use crate::{processor::L2Processor, TransactionResult, BLOCK_TIME_MS, MAX_TXS_PER_BLOCK};
+use crate::{
+    account_store::AccountStore,
+    block_store::{BlockWriter, StoredBlock},
+    persistence::{ChainMetadata, Checkpoint, CheckpointWriter},
+    processor::L2Processor,
+    replay::ReplaySnapshot,
+    snapshot::SnapshotWriter,
+    TransactionResult, BLOCK_TIME_MS, MAX_TXS_PER_BLOCK,
+};
 use crossbeam_channel::{bounded, Receiver, Sender, TryRecvError};
 use solana_sdk::{
     clock::Slot,
@@ -28,6 +36,10 @@ pub struct BlockUpdate {
     pub slot: Slot,
     /// Blockhash for this block
     pub blockhash: Hash,
+    /// Blockhash of the previous block (this block's hash chains to it)
+    pub parent_blockhash: Hash,
+    /// Account state root after this block
+    pub state_root: Hash,
     /// Number of transactions processed
     pub transaction_count: usize,
     /// Accounts modified in this block
@@ -59,6 +71,16 @@ impl Default for BlockProducerConfig {
     }
 }
 
+/// Leader-side hook that injects transactions every tick
+///
+/// Hooks run after the tick's user transactions have been applied, so they
+/// observe this slot's state. Their transactions are processed as a separate
+/// batch in the same block (e.g. the NPC AI driver).
+pub trait TickHook: Send {
+    /// Build the transactions to process for `slot`
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction>;
+}
+
 /// Handle for submitting transactions to the block producer
 #[derive(Clone)]
 pub struct TransactionSender {
@@ -90,6 +112,14 @@ pub struct BlockProducer {
     config: BlockProducerConfig,
     /// Running flag
     running: Arc<AtomicBool>,
+    /// Hooks run every tick after user transactions
+    tick_hooks: Vec<Box<dyn TickHook>>,
+    /// Where sealed blocks are recorded, if anywhere
+    block_writer: Option<BlockWriter>,
+    /// Where periodic state snapshots go, if anywhere
+    snapshot_writer: Option<SnapshotWriter>,
+    /// Where periodic checkpoints go, if anywhere
+    checkpoint_writer: Option<CheckpointWriter>,
 }
 
 impl BlockProducer {
@@ -108,7 +138,165 @@ impl BlockProducer {
             update_sender,
             config,
             running: Arc::new(AtomicBool::new(false)),
+            tick_hooks: Vec::new(),
+            block_writer: None,
+            snapshot_writer: None,
+            checkpoint_writer: None,
+        }
+    }
+
+    /// Register a hook that injects transactions every tick
+    pub fn add_tick_hook(&mut self, hook: Box<dyn TickHook>) {
+        self.tick_hooks.push(hook);
+    }
+
+    /// Record every sealed block (transactions, results, modified accounts)
+    pub fn set_block_writer(&mut self, writer: BlockWriter) {
+        self.block_writer = Some(writer);
+    }
+
+    /// Take a state snapshot every `interval_slots` slots
+    pub fn set_snapshot_writer(&mut self, writer: SnapshotWriter) {
+        self.snapshot_writer = Some(writer);
+    }
+
+    /// Checkpoint every few slots (and whenever the writer's request handle asks)
+    pub fn set_checkpoint_writer(&mut self, writer: CheckpointWriter) {
+        self.checkpoint_writer = Some(writer);
+    }
+
+    /// Chain position at the current block boundary
+    fn chain_metadata(&self, block_time: i64) -> ChainMetadata {
+        ChainMetadata {
+            slot: self.processor.current_slot(),
+            blockhash: self.processor.current_blockhash().to_bytes(),
+            state_root: self.processor.state_root().to_bytes(),
+            epoch: self.processor.current_epoch(),
+            account_count: self.processor.account_store().len() as u64,
+            last_save_ts: block_time,
+        }
+    }
+
+    /// Capture the accounts at the current block boundary for the snapshot writer
+    fn capture_snapshot(&self, block_time: i64) {
+        let Some(writer) = &self.snapshot_writer else {
+            return;
+        };
+        if !writer.is_due(self.processor.current_slot()) {
+            return;
         }
+        let metadata = self.chain_metadata(block_time);
+        writer.submit(ReplaySnapshot::from_store(self.processor.account_store(), metadata));
+    }
+
+    /// Capture the accounts changed since the last checkpoint for the checkpoint writer
+    fn capture_checkpoint(&self, block_time: i64) {
+        let Some(writer) = &self.checkpoint_writer else {
+            return;
+        };
+        if !writer.is_due(self.processor.current_slot()) {
+            return;
+        }
+        let metadata = self.chain_metadata(block_time);
+        writer.submit(Checkpoint::capture(self.processor.account_store(), metadata));
+    }
+
+    /// Checkpoint the current block boundary now, whatever the schedule
+    ///
+    /// Call once the producer has stopped (e.g. at shutdown); the checkpoint
+    /// is on disk once the producer is dropped.
+    pub fn checkpoint_now(&self) {
+        if let Some(writer) = &self.checkpoint_writer {
+            let metadata = self.chain_metadata(self.processor.unix_timestamp());
+            writer.submit(Checkpoint::capture(self.processor.account_store(), metadata));
+        }
+    }
+
+    /// Run tick hooks and process their transactions as a second batch
+    ///
+    /// The hook transactions are appended to `transactions`.
+    fn run_tick_hooks(
+        &mut self,
+        transactions: &mut Vec<SanitizedTransaction>,
+        transaction_results: &mut Vec<TransactionResult>,
+        modified_accounts: &mut Vec<(Pubkey, AccountSharedData)>,
+    ) {
+        if self.tick_hooks.is_empty() {
+            return;
+        }
+
+        let slot = self.processor.current_slot();
+        let blockhash = self.processor.current_blockhash();
+        let mut hook_txs = Vec::new();
+        for hook in self.tick_hooks.iter_mut() {
+            hook_txs.extend(hook.on_tick(slot, blockhash, self.processor.account_store()));
+        }
+
+        if hook_txs.is_empty() {
+            return;
+        }
+
+        let results = self.processor.process_transactions(&hook_txs);
+        for result in results {
+            if result.success {
+                modified_accounts.extend(result.modified_accounts.clone());
+            } else {
+                tracing::debug!("Tick hook transaction failed: {:?}", result.error);
+            }
+            transaction_results.push(result);
+        }
+        transactions.extend(hook_txs);
+    }
+
+    /// Produce one block: process `transactions`, run the tick hooks, seal
+    /// the slot, record the block and broadcast its update
+    pub fn produce_block(&mut self, mut transactions: Vec<SanitizedTransaction>) -> BlockUpdate {
+        let start = Instant::now();
+        let block_time = self.processor.unix_timestamp();
+        let mut transaction_results = Vec::new();
+        let mut modified_accounts = Vec::new();
+
+        if !transactions.is_empty() {
+            tracing::info!("Block producer: Processing {} transaction(s)", transactions.len());
+            let results = self.processor.process_transactions(&transactions);
+
+            for result in results {
+                if result.success {
+                    modified_accounts.extend(result.modified_accounts.clone());
+                }
+                transaction_results.push(result);
+            }
+        }
+
+        // Leader-side hooks (NPC AI, etc.)
+        self.run_tick_hooks(&mut transactions, &mut transaction_results, &mut modified_accounts);
+
+        // Seal the slot (recording position histories) and advance
+        modified_accounts.extend(self.processor.advance_slot());
+
+        let update = BlockUpdate {
+            slot: self.processor.current_slot(),
+            blockhash: self.processor.current_blockhash(),
+            parent_blockhash: self.processor.parent_blockhash(),
+            state_root: self.processor.state_root(),
+            transaction_count: transactions.len(),
+            modified_accounts,
+            transaction_results,
+            processing_time_us: start.elapsed().as_micros() as u64,
+        };
+
+        // Written off the block loop
+        if let Some(writer) = &self.block_writer {
+            writer.submit(StoredBlock::from_update(&update, &transactions, block_time));
+        }
+
+        // Nothing executes between blocks, so the store is consistent here
+        self.capture_snapshot(block_time);
+        self.capture_checkpoint(block_time);
+
+        // Broadcast to subscribers (ignore errors if no subscribers)
+        let _ = self.update_sender.send(update.clone());
+        update
     }
 
     /// Get a sender for submitting transactions
@@ -133,6 +321,11 @@ impl BlockProducer {
         self.running.store(false, Ordering::SeqCst);
     }
 
+    /// Shared running flag: storing `false` stops the loop after the current block
+    pub fn running_flag(&self) -> Arc<AtomicBool> {
+        self.running.clone()
+    }
+
     /// Get current slot
     pub fn current_slot(&self) -> Slot {
         self.processor.current_slot()
@@ -180,43 +373,10 @@ impl BlockProducer {
                 }
             }
 
-            // Process transactions
-            let mut transaction_results = Vec::new();
-            let mut modified_accounts = Vec::new();
-
-            if !pending_txs.is_empty() {
-                tracing::info!("Block producer: Processing {} transaction(s)", pending_txs.len());
-                let results = self.processor.process_transactions(&pending_txs);
-
-                for result in results {
-                    if result.success {
-                        modified_accounts.extend(result.modified_accounts.clone());
-                    }
-                    transaction_results.push(result);
-                }
-            }
-
-            let tx_count = pending_txs.len();
-            pending_txs.clear();
-
-            // Advance slot
-            self.processor.advance_slot();
-
+            let update = self.produce_block(std::mem::take(&mut pending_txs));
+            let tx_count = update.transaction_count;
             let processing_time = tick_start.elapsed();
 
-            // Create and broadcast block update
-            let update = BlockUpdate {
-                slot: self.processor.current_slot(),
-                blockhash: self.processor.current_blockhash(),
-                transaction_count: tx_count,
-                modified_accounts,
-                transaction_results,
-                processing_time_us: processing_time.as_micros() as u64,
-            };
-
-            // Broadcast to subscribers (ignore errors if no subscribers)
-            let _ = self.update_sender.send(update);
-
             // Log periodically
             if self.config.verbose || (self.processor.current_slot() - last_log_slot >= 300) {
                 // Every ~10 seconds
@@ -251,7 +411,9 @@ impl BlockProducer {
     }
 
     /// Run the block producer asynchronously (tokio)
-    pub async fn run_async(mut self) {
+    ///
+    /// Returns the producer once it stops.
+    pub async fn run_async(mut self) -> Self {
         self.running.store(true, Ordering::SeqCst);
 
         let block_duration = Duration::from_millis(self.config.block_time_ms);
@@ -282,47 +444,14 @@ impl BlockProducer {
                     Err(TryRecvError::Empty) => break,
                     Err(TryRecvError::Disconnected) => {
                         tracing::warn!("Transaction channel disconnected");
-                        return;
+                        return self;
                     }
                 }
             }
 
-            // Process transactions
-            let mut transaction_results = Vec::new();
-            let mut modified_accounts = Vec::new();
-
-            if !pending_txs.is_empty() {
-                tracing::info!("Block producer: Processing {} transaction(s)", pending_txs.len());
-                let results = self.processor.process_transactions(&pending_txs);
-
-                for result in results {
-                    if result.success {
-                        modified_accounts.extend(result.modified_accounts.clone());
-                    }
-                    transaction_results.push(result);
-                }
-            }
-
-            let tx_count = pending_txs.len();
-            pending_txs.clear();
-
-            // Advance slot
-            self.processor.advance_slot();
-
+            self.produce_block(std::mem::take(&mut pending_txs));
             let processing_time = tick_start.elapsed();
 
-            // Create and broadcast block update
-            let update = BlockUpdate {
-                slot: self.processor.current_slot(),
-                blockhash: self.processor.current_blockhash(),
-                transaction_count: tx_count,
-                modified_accounts,
-                transaction_results,
-                processing_time_us: processing_time.as_micros() as u64,
-            };
-
-            let _ = self.update_sender.send(update);
-
             // Warn if we're falling behind
             if processing_time > block_duration {
                 tracing::warn!(
@@ -335,5 +464,6 @@ impl BlockProducer {
         }
 
         tracing::info!("Block producer stopped");
+        self
     }
 }
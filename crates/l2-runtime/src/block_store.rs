@@ -0,0 +1,500 @@
+//! Block Store
+//!
+//! Persists every sealed block: its parent, blockhash, state root, the
+//! ordered transactions with their results, and the accounts it modified.
+//! Transactions are indexed by signature. Old blocks are pruned after a
+//! configurable number of slots. This is what block and transaction lookups
+//! and block replay read from.
+//!
+//! The block producer hands sealed blocks to a `BlockWriter` thread, which
+//! does the disk writes and pruning off the block loop.
+
+use serde::{Deserialize, Serialize};
+use sled::Db;
+use solana_sdk::{
+    account::AccountSharedData,
+    clock::Slot,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{SanitizedTransaction, TransactionError, VersionedTransaction},
+};
+use crossbeam_channel::{unbounded, Sender};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ops::RangeBounds,
+    path::Path,
+    sync::Arc,
+    thread::JoinHandle,
+};
+
+use crate::{block_producer::BlockUpdate, TICKS_PER_SECOND};
+
+/// A transaction as executed in a block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredTransaction {
+    /// The signed transaction
+    pub transaction: VersionedTransaction,
+    /// Whether it executed successfully
+    pub success: bool,
+    /// Why it failed
+    pub error: Option<TransactionError>,
+    /// Program logs
+    pub logs: Vec<String>,
+    /// Accounts it changed
+    pub modified_accounts: Vec<Pubkey>,
+}
+
+impl StoredTransaction {
+    /// The transaction's signature
+    pub fn signature(&self) -> Signature {
+        self.transaction.signatures[0]
+    }
+}
+
+/// A sealed block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredBlock {
+    /// Slot the block's transactions executed in
+    pub slot: Slot,
+    /// Slot of the previous block
+    pub parent_slot: Slot,
+    /// This block's hash
+    pub blockhash: Hash,
+    /// Previous block's hash
+    pub parent_blockhash: Hash,
+    /// Account state root after this block
+    pub state_root: Hash,
+    /// Unix timestamp of the Clock the block executed with
+    pub block_time: i64,
+    /// Transactions in execution order
+    pub transactions: Vec<StoredTransaction>,
+    /// Final state of every account the block modified
+    pub modified_accounts: Vec<(Pubkey, AccountSharedData)>,
+}
+
+impl StoredBlock {
+    /// Build the stored block for a block update and the transactions it ran
+    ///
+    /// The update is broadcast once the slot has advanced, so its `slot` is
+    /// the next slot; the block itself belongs to the slot before.
+    pub fn from_update(update: &BlockUpdate, transactions: &[SanitizedTransaction], block_time: i64) -> Self {
+        let slot = update.slot.saturating_sub(1);
+        let transactions = transactions
+            .iter()
+            .zip(&update.transaction_results)
+            .map(|(tx, result)| StoredTransaction {
+                transaction: tx.to_versioned_transaction(),
+                success: result.success,
+                error: result.error.clone(),
+                logs: result.logs.clone(),
+                modified_accounts: result.modified_accounts.iter().map(|(pubkey, _)| *pubkey).collect(),
+            })
+            .collect();
+
+        // Later writes to an account supersede earlier ones
+        let mut modified_accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
+        let mut positions: HashMap<Pubkey, usize> = HashMap::with_capacity(update.modified_accounts.len());
+        for (pubkey, account) in &update.modified_accounts {
+            match positions.entry(*pubkey) {
+                Entry::Occupied(entry) => modified_accounts[*entry.get()].1 = account.clone(),
+                Entry::Vacant(entry) => {
+                    entry.insert(modified_accounts.len());
+                    modified_accounts.push((*pubkey, account.clone()));
+                }
+            }
+        }
+
+        Self {
+            slot,
+            parent_slot: slot.saturating_sub(1),
+            blockhash: update.blockhash,
+            parent_blockhash: update.parent_blockhash,
+            state_root: update.state_root,
+            block_time,
+            transactions,
+            modified_accounts,
+        }
+    }
+}
+
+/// Block store retention settings
+#[derive(Clone, Debug)]
+pub struct BlockStoreConfig {
+    /// Number of most recent slots to keep (None = keep every block)
+    pub retention_slots: Option<u64>,
+    /// Prune once every this many inserted blocks
+    pub prune_interval: u64,
+}
+
+impl Default for BlockStoreConfig {
+    fn default() -> Self {
+        Self {
+            // One day of blocks at 30Hz
+            retention_slots: Some(TICKS_PER_SECOND * 60 * 60 * 24),
+            // About every 10 seconds
+            prune_interval: 300,
+        }
+    }
+}
+
+/// Persistent block and transaction history
+pub struct BlockStore {
+    /// Sled database instance
+    db: Db,
+    /// Blocks by slot (big-endian, so iteration is in slot order)
+    blocks: sled::Tree,
+    /// (slot, index in block) of every transaction by signature
+    signatures: sled::Tree,
+    /// Retention settings
+    config: BlockStoreConfig,
+}
+
+impl BlockStore {
+    /// Open or create a block store at the given path
+    pub fn open<P: AsRef<Path>>(path: P, config: BlockStoreConfig) -> anyhow::Result<Self> {
+        let db = sled::open(&path)?;
+        let blocks = db.open_tree("blocks")?;
+        let signatures = db.open_tree("signatures")?;
+
+        tracing::info!("Opened block store at {:?}", path.as_ref());
+
+        Ok(Self {
+            db,
+            blocks,
+            signatures,
+            config,
+        })
+    }
+
+    /// Store a block
+    pub fn insert_block(&self, block: &StoredBlock) -> anyhow::Result<()> {
+        for (index, tx) in block.transactions.iter().enumerate() {
+            self.signatures
+                .insert(tx.signature().as_ref(), location_bytes(block.slot, index as u32).as_slice())?;
+        }
+        self.blocks.insert(block.slot.to_be_bytes(), bincode::serialize(block)?)?;
+        Ok(())
+    }
+
+    /// Retention settings
+    pub fn config(&self) -> &BlockStoreConfig {
+        &self.config
+    }
+
+    /// Remove blocks older than the retention window ending at `latest_slot`
+    ///
+    /// Returns the number of blocks removed.
+    pub fn prune_expired(&self, latest_slot: Slot) -> anyhow::Result<usize> {
+        match self.config.retention_slots {
+            Some(retention) => self.prune((latest_slot + 1).saturating_sub(retention)),
+            None => Ok(0),
+        }
+    }
+
+    /// Get the block for a slot
+    pub fn get_block(&self, slot: Slot) -> anyhow::Result<Option<StoredBlock>> {
+        match self.blocks.get(slot.to_be_bytes())? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a transaction and the slot it executed in
+    pub fn get_transaction(&self, signature: &Signature) -> anyhow::Result<Option<(Slot, StoredTransaction)>> {
+        let Some(location) = self.signatures.get(signature.as_ref())? else {
+            return Ok(None);
+        };
+        let (slot, index) = parse_location(&location)?;
+        let Some(mut block) = self.get_block(slot)? else {
+            return Ok(None);
+        };
+        if (index as usize) >= block.transactions.len() {
+            anyhow::bail!("Transaction index {} out of range in slot {}", index, slot);
+        }
+        Ok(Some((slot, block.transactions.swap_remove(index as usize))))
+    }
+
+    /// Slot and outcome of a transaction (`Ok` if it succeeded)
+    pub fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> anyhow::Result<Option<(Slot, Result<(), TransactionError>)>> {
+        Ok(self.get_transaction(signature)?.map(|(slot, tx)| {
+            let status = match tx.error {
+                Some(error) => Err(error),
+                None => Ok(()),
+            };
+            (slot, status)
+        }))
+    }
+
+    /// Blocks in a slot range, in slot order
+    pub fn blocks<R: RangeBounds<Slot>>(&self, range: R) -> impl Iterator<Item = anyhow::Result<StoredBlock>> {
+        let start = range.start_bound().map(|slot| slot.to_be_bytes());
+        let end = range.end_bound().map(|slot| slot.to_be_bytes());
+        self.blocks
+            .range::<[u8; 8], _>((start, end))
+            .map(|entry| Ok(bincode::deserialize(&entry?.1)?))
+    }
+
+    /// Oldest stored slot
+    pub fn first_slot(&self) -> anyhow::Result<Option<Slot>> {
+        self.blocks.first()?.map(|(key, _)| parse_slot(&key)).transpose()
+    }
+
+    /// Newest stored slot
+    pub fn last_slot(&self) -> anyhow::Result<Option<Slot>> {
+        self.blocks.last()?.map(|(key, _)| parse_slot(&key)).transpose()
+    }
+
+    /// Remove every block before `slot` and its transactions
+    ///
+    /// Returns the number of blocks removed.
+    pub fn prune(&self, slot: Slot) -> anyhow::Result<usize> {
+        let mut count = 0;
+        for entry in self.blocks.range(..slot.to_be_bytes()) {
+            let (key, value) = entry?;
+            let block: StoredBlock = bincode::deserialize(&value)?;
+            for tx in &block.transactions {
+                self.signatures.remove(tx.signature().as_ref())?;
+            }
+            self.blocks.remove(key)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Remove blocks at and after `slot`, returning how many were removed
+    ///
+    /// Used when rolling the chain back to a snapshot: those slots will be
+    /// produced again.
+    pub fn truncate(&self, slot: Slot) -> anyhow::Result<usize> {
+        let mut count = 0;
+        for entry in self.blocks.range(slot.to_be_bytes()..) {
+            let (key, value) = entry?;
+            let block: StoredBlock = bincode::deserialize(&value)?;
+            for tx in &block.transactions {
+                self.signatures.remove(tx.signature().as_ref())?;
+            }
+            self.blocks.remove(key)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Flush all pending writes to disk
+    pub fn flush(&self) -> anyhow::Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Get the number of stored blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if no blocks are stored
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Writes sealed blocks to a `BlockStore` on a background thread
+///
+/// Blocks are queued, never dropped, and written in order; old blocks are
+/// pruned every `prune_interval` writes. Lookups of a block can briefly
+/// trail its broadcast. Dropping the writer waits for the queue to drain.
+pub struct BlockWriter {
+    sender: Option<Sender<StoredBlock>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BlockWriter {
+    /// Start the writer thread for a block store
+    pub fn spawn(store: Arc<BlockStore>) -> Self {
+        let (sender, receiver) = unbounded::<StoredBlock>();
+        let handle = std::thread::Builder::new()
+            .name("block-writer".to_string())
+            .spawn(move || {
+                let prune_interval = store.config().prune_interval.max(1);
+                let mut inserted_since_prune = 0;
+                for block in receiver {
+                    if let Err(e) = store.insert_block(&block) {
+                        tracing::error!("Failed to record block {}: {}", block.slot, e);
+                        continue;
+                    }
+                    inserted_since_prune += 1;
+                    if inserted_since_prune < prune_interval {
+                        continue;
+                    }
+                    inserted_since_prune = 0;
+                    match store.prune_expired(block.slot) {
+                        Ok(0) => {}
+                        Ok(pruned) => tracing::debug!("Pruned {} blocks before slot {}", pruned, block.slot),
+                        Err(e) => tracing::error!("Failed to prune blocks: {}", e),
+                    }
+                }
+            })
+            .expect("failed to spawn block writer thread");
+
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    /// Queue a sealed block for writing
+    pub fn submit(&self, block: StoredBlock) {
+        if let Some(sender) = &self.sender {
+            if sender.send(block).is_err() {
+                tracing::error!("Block writer stopped, block not recorded");
+            }
+        }
+    }
+}
+
+impl Drop for BlockWriter {
+    /// Finish writing every submitted block before going away
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn location_bytes(slot: Slot, index: u32) -> [u8; 12] {
+    let mut bytes = [0u8; 12];
+    bytes[..8].copy_from_slice(&slot.to_be_bytes());
+    bytes[8..].copy_from_slice(&index.to_le_bytes());
+    bytes
+}
+
+fn parse_location(bytes: &[u8]) -> anyhow::Result<(Slot, u32)> {
+    let bytes: [u8; 12] = bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid transaction location length"))?;
+    let slot = Slot::from_be_bytes(bytes[..8].try_into().unwrap());
+    let index = u32::from_le_bytes(bytes[8..].try_into().unwrap());
+    Ok((slot, index))
+}
+
+fn parse_slot(bytes: &[u8]) -> anyhow::Result<Slot> {
+    let bytes: [u8; 8] = bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid slot key length"))?;
+    Ok(Slot::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        account::Account,
+        message::Message,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::Transaction,
+    };
+    use tempfile::tempdir;
+
+    fn make_tx(payer: &Keypair) -> StoredTransaction {
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let message = Message::new(&[ix], Some(&payer.pubkey()));
+        let tx = Transaction::new(&[payer], message, Hash::new_unique());
+        StoredTransaction {
+            transaction: tx.into(),
+            success: true,
+            error: None,
+            logs: vec!["Program log: ok".to_string()],
+            modified_accounts: vec![payer.pubkey()],
+        }
+    }
+
+    fn make_block(slot: Slot, transactions: Vec<StoredTransaction>) -> StoredBlock {
+        let account = AccountSharedData::from(Account {
+            lamports: slot + 1,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        });
+        StoredBlock {
+            slot,
+            parent_slot: slot.saturating_sub(1),
+            blockhash: Hash::new_unique(),
+            parent_blockhash: Hash::new_unique(),
+            state_root: Hash::new_unique(),
+            block_time: 1_700_000_000 + slot as i64,
+            transactions,
+            modified_accounts: vec![(Pubkey::new_unique(), account)],
+        }
+    }
+
+    #[test]
+    fn test_store_and_lookup_blocks() {
+        let dir = tempdir().unwrap();
+        let store = BlockStore::open(dir.path(), BlockStoreConfig::default()).unwrap();
+        assert!(store.is_empty());
+
+        let payer = Keypair::new();
+        let mut failed = make_tx(&payer);
+        failed.success = false;
+        failed.error = Some(TransactionError::AccountNotFound);
+        let block = make_block(5, vec![make_tx(&payer), failed.clone()]);
+        store.insert_block(&block).unwrap();
+        store.insert_block(&make_block(6, vec![])).unwrap();
+
+        assert_eq!(store.get_block(5).unwrap().unwrap(), block);
+        assert!(store.get_block(4).unwrap().is_none());
+        assert_eq!(store.first_slot().unwrap(), Some(5));
+        assert_eq!(store.last_slot().unwrap(), Some(6));
+
+        let (slot, tx) = store.get_transaction(&failed.signature()).unwrap().unwrap();
+        assert_eq!((slot, tx), (5, failed.clone()));
+        assert_eq!(
+            store.get_signature_status(&failed.signature()).unwrap(),
+            Some((5, Err(TransactionError::AccountNotFound)))
+        );
+        let ok = block.transactions[0].signature();
+        assert_eq!(store.get_signature_status(&ok).unwrap(), Some((5, Ok(()))));
+        assert!(store.get_transaction(&Signature::new_unique()).unwrap().is_none());
+
+        let slots: Vec<Slot> = store.blocks(5..).map(|block| block.unwrap().slot).collect();
+        assert_eq!(slots, vec![5, 6]);
+    }
+
+    #[test]
+    fn test_retention_prunes_old_blocks() {
+        let dir = tempdir().unwrap();
+        let config = BlockStoreConfig {
+            retention_slots: Some(4),
+            prune_interval: 2,
+        };
+        let store = Arc::new(BlockStore::open(dir.path(), config).unwrap());
+        let payer = Keypair::new();
+        let old_tx = make_tx(&payer);
+        let writer = BlockWriter::spawn(store.clone());
+        writer.submit(make_block(0, vec![old_tx.clone()]));
+        for slot in 1..10 {
+            writer.submit(make_block(slot, vec![make_tx(&payer)]));
+        }
+        // Dropping the writer waits for every block to be written
+        drop(writer);
+
+        // Pruned at slot 9: slots 6..=9 are kept
+        assert_eq!(store.first_slot().unwrap(), Some(6));
+        assert_eq!(store.len(), 4);
+        assert!(store.get_transaction(&old_tx.signature()).unwrap().is_none());
+
+        assert_eq!(store.prune(8).unwrap(), 2);
+        assert_eq!(store.first_slot().unwrap(), Some(8));
+
+        // Rolling back drops the newest blocks instead
+        let newest = store.get_block(9).unwrap().unwrap().transactions[0].signature();
+        assert_eq!(store.truncate(9).unwrap(), 1);
+        assert_eq!(store.last_slot().unwrap(), Some(8));
+        assert!(store.get_transaction(&newest).unwrap().is_none());
+    }
+}
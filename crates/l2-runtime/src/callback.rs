@@ -6,10 +6,299 @@
 use crate::account_store::AccountStore;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
+    message::SanitizedMessage,
     pubkey::Pubkey,
+    transaction::SanitizedTransaction,
 };
 use solana_svm::transaction_processing_callback::TransactionProcessingCallback;
-use std::sync::Arc;
+use std::{collections::HashMap, ops::Range, sync::Arc};
+use world_program::{constants::*, state::*};
+
+/// Lamports a synthesized account starts with
+pub const AUTO_CREATE_LAMPORTS: u64 = 1_000_000_000;
+
+/// One seed of a PDA an auto-create rule expects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Seed {
+    /// Fixed bytes (e.g. `WORLD_SEED`)
+    Literal(Vec<u8>),
+    /// Key of the instruction's account at this position
+    Account(usize),
+    /// Bytes of the instruction data
+    Data { offset: usize, len: usize },
+    /// Bytes of the stored data of the instruction's account at `position`
+    AccountData { position: usize, offset: usize, len: usize },
+    /// A 16-byte player name from another seed, normalized like name claims
+    NormalizedName(Box<Seed>),
+}
+
+/// Which missing accounts of a transaction a rule applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountMatcher {
+    /// The fee payer of a transaction calling the rule's program
+    FeePayer,
+    /// Instruction accounts at these positions
+    Position(Range<usize>),
+    /// The PDA derived from these seeds under the rule's program
+    Pda(Vec<Seed>),
+}
+
+/// Rule for synthesizing a missing account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoCreateRule {
+    /// Program whose instructions the rule applies to
+    pub program_id: Pubkey,
+    /// First byte of the instruction data, or any instruction if `None`
+    pub discriminator: Option<u8>,
+    /// Accounts of the instruction the rule covers
+    pub matcher: AccountMatcher,
+    /// Owner of the synthesized account
+    pub owner: Pubkey,
+    /// Data length of the synthesized account
+    pub space: usize,
+    /// Lamports of the synthesized account
+    pub lamports: u64,
+}
+
+impl AutoCreateRule {
+    fn template(&self) -> AccountSharedData {
+        AccountSharedData::new(self.lamports, self.space, &self.owner)
+    }
+
+    /// Check if the account at `index` of `message` is covered by this rule
+    fn matches(&self, store: &AccountStore, message: &SanitizedMessage, index: usize) -> bool {
+        let Some(pubkey) = message.account_keys().get(index) else {
+            return false;
+        };
+        let keys = message.account_keys();
+
+        message
+            .program_instructions_iter()
+            .filter(|(program_id, ix)| {
+                **program_id == self.program_id
+                    && self
+                        .discriminator
+                        .is_none_or(|discriminator| ix.data.first() == Some(&discriminator))
+            })
+            .any(|(_, ix)| match &self.matcher {
+                AccountMatcher::FeePayer => index == 0,
+                AccountMatcher::Position(positions) => ix
+                    .accounts
+                    .iter()
+                    .enumerate()
+                    .any(|(position, &account)| account as usize == index && positions.contains(&position)),
+                AccountMatcher::Pda(seeds) => {
+                    let instruction_key =
+                        |position: usize| ix.accounts.get(position).and_then(|&account| keys.get(account as usize));
+                    let seeds: Option<Vec<Vec<u8>>> = seeds
+                        .iter()
+                        .map(|seed| seed_bytes(seed, &ix.data, &instruction_key, store))
+                        .collect();
+                    seeds.is_some_and(|seeds| {
+                        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+                        ix.accounts.contains(&(index as u8))
+                            && Pubkey::find_program_address(&seeds, &self.program_id).0 == *pubkey
+                    })
+                }
+            })
+    }
+}
+
+/// Bytes of `seed` for an instruction with `data`, whose account keys
+/// `instruction_key` looks up by position
+fn seed_bytes<'a>(
+    seed: &Seed,
+    data: &[u8],
+    instruction_key: &impl Fn(usize) -> Option<&'a Pubkey>,
+    store: &AccountStore,
+) -> Option<Vec<u8>> {
+    match seed {
+        Seed::Literal(bytes) => Some(bytes.clone()),
+        Seed::Account(position) => instruction_key(*position).map(|key| key.to_bytes().to_vec()),
+        Seed::Data { offset, len } => data.get(*offset..offset + len).map(<[u8]>::to_vec),
+        Seed::AccountData { position, offset, len } => {
+            let account = store.get_account(instruction_key(*position)?)?;
+            account.data().get(*offset..offset + len).map(<[u8]>::to_vec)
+        }
+        Seed::NormalizedName(name) => {
+            let name: [u8; 16] = seed_bytes(name, data, instruction_key, store)?.try_into().ok()?;
+            Some(NameClaim::normalize(&name).to_vec())
+        }
+    }
+}
+
+/// `WorldInstruction` discriminators (borsh variant indices) the default rules use
+mod discriminator {
+    pub const INITIALIZE_WORLD: u8 = 0;
+    pub const JOIN_WORLD: u8 = 1;
+    pub const CREATE_SESSION: u8 = 9;
+    pub const SPAWN_NPC: u8 = 11;
+    pub const ATTACK_NPC: u8 = 13;
+    pub const CONFIGURE_TEAMS: u8 = 14;
+    pub const CONFIGURE_GAME_MODE: u8 = 16;
+    pub const TICK_GAME_MODE: u8 = 17;
+    pub const JOIN_QUEUE: u8 = 18;
+    pub const TRANSFER_WORLD: u8 = 22;
+    pub const UPDATE_ALLOWLIST: u8 = 24;
+    pub const JOIN_WORLD_WITH_PASSWORD: u8 = 25;
+    pub const JOIN_WAITLIST: u8 = 27;
+    pub const ADMIT_FROM_WAITLIST: u8 = 29;
+    pub const ADMIT_VIP: u8 = 30;
+    pub const UPDATE_RESERVED_NAMES: u8 = 34;
+}
+
+/// Offset of `WorldPlayer::name`
+const PLAYER_NAME_OFFSET: usize = 32 + 32 + 4 + 4 + 4 + 2 + 2 + 2 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 1;
+/// Offset of the first `WorldWaitlist` entry
+const WAITLIST_HEAD_OFFSET: usize = 32 + 2;
+
+/// Registry of rules deciding which missing accounts get synthesized
+///
+/// The first matching rule picks the account's owner, size and lamports.
+/// Accounts no rule covers stay missing: a fee payer then fails with
+/// `AccountNotFound`, any other account loads empty and system-owned, so a
+/// program can't write to it.
+#[derive(Debug, Clone, Default)]
+pub struct AutoCreateRegistry {
+    rules: Vec<AutoCreateRule>,
+}
+
+impl AutoCreateRegistry {
+    /// Create a registry with no rules (nothing is synthesized)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, checked after the existing ones
+    pub fn with_rule(mut self, rule: AutoCreateRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Rules in the order they are checked
+    pub fn rules(&self) -> &[AutoCreateRule] {
+        &self.rules
+    }
+
+    /// Gasless fee payers plus every account the world program creates
+    ///
+    /// Fee payers of world program transactions are funded system accounts;
+    /// each PDA is only synthesized for the instruction that creates it,
+    /// sized for its state.
+    pub fn world_program() -> Self {
+        use discriminator::*;
+
+        let pda = |discriminator: u8, seeds: Vec<Seed>, space: usize| AutoCreateRule {
+            program_id: world_program::id(),
+            discriminator: Some(discriminator),
+            matcher: AccountMatcher::Pda(seeds),
+            owner: world_program::id(),
+            space,
+            lamports: AUTO_CREATE_LAMPORTS,
+        };
+        let position = |discriminator: u8, positions: Range<usize>, space: usize| AutoCreateRule {
+            matcher: AccountMatcher::Position(positions),
+            ..pda(discriminator, vec![], space)
+        };
+        let literal = |seed: &[u8]| Seed::Literal(seed.to_vec());
+        let player = |world: usize, wallet: usize| {
+            vec![literal(WORLD_PLAYER_SEED), Seed::Account(world), Seed::Account(wallet)]
+        };
+        let name_claim = |world: usize, name: Seed| {
+            vec![literal(NAME_CLAIM_SEED), Seed::Account(world), Seed::NormalizedName(Box::new(name))]
+        };
+        let instruction_name = Seed::Data { offset: 1, len: 16 };
+        // Head of a waitlist (account 1): wallet and name of its first entry
+        let head_wallet = Seed::AccountData {
+            position: 1,
+            offset: WAITLIST_HEAD_OFFSET,
+            len: 32,
+        };
+        let head_name = Seed::AccountData {
+            position: 1,
+            offset: WAITLIST_HEAD_OFFSET + 32,
+            len: 16,
+        };
+
+        let mut registry = Self::new().with_rule(AutoCreateRule {
+            program_id: world_program::id(),
+            discriminator: None,
+            matcher: AccountMatcher::FeePayer,
+            owner: solana_sdk::system_program::id(),
+            space: 0,
+            lamports: AUTO_CREATE_LAMPORTS,
+        });
+        let rules = [
+            pda(
+                INITIALIZE_WORLD,
+                vec![literal(WORLD_SEED), Seed::Data { offset: 1, len: 32 }],
+                WorldConfig::LEN,
+            ),
+            pda(JOIN_WORLD, player(0, 2), WorldPlayer::LEN),
+            // Optional accounts are found by address; only the name claim is created
+            pda(JOIN_WORLD, name_claim(0, instruction_name.clone()), NameClaim::LEN),
+            pda(JOIN_WORLD_WITH_PASSWORD, player(0, 2), WorldPlayer::LEN),
+            pda(JOIN_WORLD_WITH_PASSWORD, name_claim(0, instruction_name.clone()), NameClaim::LEN),
+            pda(CREATE_SESSION, vec![literal(SESSION_SEED), Seed::Account(0)], PlayerSession::LEN),
+            pda(
+                SPAWN_NPC,
+                vec![literal(NPC_SEED), Seed::Account(0), Seed::Data { offset: 1, len: 4 }],
+                WorldNpc::LEN,
+            ),
+            // Loot and match result PDAs are keyed by counters in other accounts
+            position(ATTACK_NPC, 4..5, LootDrop::LEN),
+            pda(CONFIGURE_TEAMS, vec![literal(TEAM_SCORES_SEED), Seed::Account(0)], TeamScores::LEN),
+            pda(CONFIGURE_GAME_MODE, vec![literal(GAME_MODE_SEED), Seed::Account(0)], GameMode::LEN),
+            position(TICK_GAME_MODE, 4..5, MatchResult::LEN),
+            pda(JOIN_QUEUE, vec![literal(QUEUE_SEED), Seed::Account(1)], QueueTicket::LEN),
+            pda(TRANSFER_WORLD, player(2, 4), WorldPlayer::LEN),
+            // The name travels with the source player (account 1)
+            pda(
+                TRANSFER_WORLD,
+                name_claim(
+                    2,
+                    Seed::AccountData {
+                        position: 1,
+                        offset: PLAYER_NAME_OFFSET,
+                        len: 16,
+                    },
+                ),
+                NameClaim::LEN,
+            ),
+            pda(UPDATE_ALLOWLIST, vec![literal(ALLOWLIST_SEED), Seed::Account(0)], WorldAllowlist::LEN),
+            pda(JOIN_WAITLIST, vec![literal(WAITLIST_SEED), Seed::Account(0)], WorldWaitlist::LEN),
+            // The admitted wallet and name are only known from the waitlist
+            pda(
+                ADMIT_FROM_WAITLIST,
+                vec![literal(WORLD_PLAYER_SEED), Seed::Account(0), head_wallet],
+                WorldPlayer::LEN,
+            ),
+            pda(ADMIT_FROM_WAITLIST, name_claim(0, head_name), NameClaim::LEN),
+            pda(ADMIT_VIP, player(0, 2), WorldPlayer::LEN),
+            pda(ADMIT_VIP, name_claim(0, instruction_name), NameClaim::LEN),
+            pda(
+                UPDATE_RESERVED_NAMES,
+                vec![literal(RESERVED_NAMES_SEED), Seed::Account(0)],
+                ReservedNames::LEN,
+            ),
+        ];
+        for rule in rules {
+            registry = registry.with_rule(rule);
+        }
+        registry
+    }
+
+    /// Account to synthesize for the missing account at `index` of `message`,
+    /// if any rule covers it
+    ///
+    /// Seeds read from other accounts use their state in `store`.
+    pub fn resolve(&self, store: &AccountStore, message: &SanitizedMessage, index: usize) -> Option<AccountSharedData> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(store, message, index))
+            .map(AutoCreateRule::template)
+    }
+}
 
 /// L2 Account Loader - implements TransactionProcessingCallback
 ///
@@ -18,12 +307,42 @@ use std::sync::Arc;
 pub struct L2AccountLoader {
     /// Reference to the account store
     account_store: Arc<AccountStore>,
+    /// Accounts missing from the store that the loaded transactions may create
+    new_accounts: HashMap<Pubkey, AccountSharedData>,
 }
 
 impl L2AccountLoader {
-    /// Create a new account loader
+    /// Create a new account loader that synthesizes no accounts
     pub fn new(account_store: Arc<AccountStore>) -> Self {
-        Self { account_store }
+        Self {
+            account_store,
+            new_accounts: HashMap::new(),
+        }
+    }
+
+    /// Create a loader for a set of transactions, synthesizing the missing
+    /// accounts `registry` lets them create
+    pub fn for_transactions(
+        account_store: Arc<AccountStore>,
+        registry: &AutoCreateRegistry,
+        transactions: &[SanitizedTransaction],
+    ) -> Self {
+        let mut new_accounts = HashMap::new();
+        for tx in transactions {
+            let message = tx.message();
+            for (index, pubkey) in message.account_keys().iter().enumerate() {
+                if new_accounts.contains_key(pubkey) || account_store.account_exists(pubkey) {
+                    continue;
+                }
+                if let Some(account) = registry.resolve(&account_store, message, index) {
+                    new_accounts.insert(*pubkey, account);
+                }
+            }
+        }
+        Self {
+            account_store,
+            new_accounts,
+        }
     }
 
     /// Get a reference to the underlying account store
@@ -38,55 +357,32 @@ impl TransactionProcessingCallback for L2AccountLoader {
     /// This is called by the SVM during transaction loading to retrieve
     /// account data for all accounts referenced in a transaction.
     ///
-    /// For L2, we return a default zeroed account for missing accounts.
-    /// This allows new accounts (like player PDAs) to be created on-the-fly
-    /// during transaction execution without requiring explicit create_account
-    /// instructions via the system program.
+    /// Missing accounts come from the auto-create rules the loader was built
+    /// with, so programs can create them without a system program
+    /// `create_account`. Anything else is reported missing.
     fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
-        eprintln!("[CALLBACK] get_account_shared_data called for: {}", pubkey);
-        match self.account_store.get_account(pubkey) {
-            Some(account) => {
-                eprintln!("[CALLBACK] Account FOUND: {} (owner: {}, len: {}, exec: {})",
-                    pubkey, account.owner(), account.data().len(), account.executable());
-                tracing::info!("Account FOUND in store: {} (owner: {}, len: {}, exec: {})",
-                    pubkey, account.owner(), account.data().len(), account.executable());
-                Some(account)
-            }
-            None => {
-                // Return a default account for missing accounts
-                // This enables account creation during transaction execution
-                // The account will be properly initialized by the program
-                // and saved to the store after successful execution
-                //
-                // IMPORTANT: We differentiate between wallet addresses and PDAs:
-                // - Wallets (on ed25519 curve): need 0 data bytes for fee payer validation
-                //   and are owned by system_program
-                // - PDAs (off curve): need space for program data and are owned by
-                //   world_program so the program can write to them
-                //
-                // PDAs are specifically created to be OFF the curve, while regular
-                // wallet keypairs generate addresses ON the curve.
-                let is_pda = !pubkey.is_on_curve();
-                let (data_len, owner) = if is_pda {
-                    // PDAs are owned by world_program so it can write to them
-                    // This is the key L2 design decision - gasless PDA creation
-                    (world_program::state::WorldPlayer::LEN, world_program::id())
-                } else {
-                    // Wallets are system-owned with 0 data
-                    (0, solana_sdk::system_program::id())
-                };
-
-                tracing::warn!(
-                    "Account NOT in store, returning default: {} (is_pda={}, data_len={}, owner={})",
-                    pubkey, is_pda, data_len, owner
-                );
-                Some(AccountSharedData::new(
-                    1_000_000_000, // 1 SOL worth of lamports - ensures fee payer validation passes
-                    data_len,
-                    &owner,
-                ))
-            }
+        if let Some(account) = self.account_store.get_account(pubkey) {
+            tracing::debug!(
+                "Account FOUND in store: {} (owner: {}, len: {}, exec: {})",
+                pubkey,
+                account.owner(),
+                account.data().len(),
+                account.executable()
+            );
+            return Some(account);
         }
+
+        let account = self.new_accounts.get(pubkey).cloned();
+        match &account {
+            Some(account) => tracing::debug!(
+                "Account NOT in store, synthesizing: {} (owner: {}, len: {})",
+                pubkey,
+                account.owner(),
+                account.data().len()
+            ),
+            None => tracing::debug!("Account NOT in store and no auto-create rule: {}", pubkey),
+        }
+        account
     }
 
     /// Check if an account is owned by one of the given programs
@@ -94,26 +390,16 @@ impl TransactionProcessingCallback for L2AccountLoader {
     /// Returns the index of the matching owner, or None if no match.
     /// This is used for account validation during transaction processing.
     ///
-    /// For missing accounts, we treat them as system-owned (consistent with
-    /// get_account_shared_data returning system-owned default accounts).
+    /// Missing accounts are checked against their synthesized owner, if any.
     fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
-        let result = match self.account_store.get_account(account) {
-            Some(account_data) => {
+        let result = self
+            .account_store
+            .get_account(account)
+            .or_else(|| self.new_accounts.get(account).cloned())
+            .and_then(|account_data| {
                 let owner = account_data.owner();
                 owners.iter().position(|candidate| candidate == owner)
-            }
-            None => {
-                // For missing accounts, check if it's a PDA or wallet
-                // PDAs are owned by world_program, wallets by system_program
-                let is_pda = !account.is_on_curve();
-                let expected_owner = if is_pda {
-                    world_program::id()
-                } else {
-                    solana_sdk::system_program::id()
-                };
-                owners.iter().position(|candidate| candidate == &expected_owner)
-            }
-        };
+            });
         if result.is_none() {
             tracing::warn!(
                 "account_matches_owners: {} NOT MATCHED against owners: {:?}",
@@ -151,7 +437,16 @@ impl TransactionProcessingCallback for L2AccountLoader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::account::Account;
+    use borsh::BorshDeserialize;
+    use solana_sdk::{
+        account::Account,
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    };
+    use std::collections::HashSet;
+    use world_program::instruction::WorldInstruction;
 
     #[test]
     fn test_get_account() {
@@ -202,45 +497,281 @@ mod tests {
         assert_eq!(loader.account_matches_owners(&pubkey, &other_owners), None);
     }
 
-    #[test]
-    fn test_missing_wallet_returns_default_with_zero_data() {
-        use solana_sdk::signature::{Keypair, Signer};
+    fn sanitize(payer: &Keypair, ix: Instruction) -> SanitizedTransaction {
+        let message = Message::new(&[ix], Some(&payer.pubkey()));
+        let tx = Transaction::new(&[payer], message, Default::default());
+        SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new()).unwrap()
+    }
 
+    fn join_world(world: Pubkey, player: Pubkey, wallet: &Keypair) -> SanitizedTransaction {
+        let ix = Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::JoinWorld { name: [b'a'; 16] },
+            vec![
+                AccountMeta::new(world, false),
+                AccountMeta::new(player, false),
+                AccountMeta::new_readonly(wallet.pubkey(), true),
+                AccountMeta::new(wallet.pubkey(), true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+        );
+        sanitize(wallet, ix)
+    }
+
+    #[test]
+    fn test_missing_accounts_follow_rules() {
         let store = Arc::new(AccountStore::new());
-        let loader = L2AccountLoader::new(store);
-
-        // Use Keypair to get a real on-curve wallet address
-        let pubkey = Keypair::new().pubkey();
-        assert!(pubkey.is_on_curve(), "Keypair pubkey should be on-curve");
-
-        let account = loader.get_account_shared_data(&pubkey);
-        assert!(account.is_some());
-        let account = account.unwrap();
-        // Default accounts get 1 SOL to pass fee payer validation
-        assert_eq!(account.lamports(), 1_000_000_000);
-        // Wallets (on-curve) must have 0 data bytes for fee payer validation
+        let registry = AutoCreateRegistry::world_program();
+        let wallet = Keypair::new();
+        let world = Pubkey::new_unique();
+        let (player, _) = WorldPlayer::derive_pda(&world, &wallet.pubkey(), &world_program::id());
+        let (typo, _) = Pubkey::find_program_address(
+            &[b"world_playr", world.as_ref(), wallet.pubkey().as_ref()],
+            &world_program::id(),
+        );
+        let txs = [join_world(world, player, &wallet), join_world(world, typo, &wallet)];
+        let loader = L2AccountLoader::for_transactions(store.clone(), &registry, &txs);
+
+        // Fee payers are funded system accounts with no data
+        let account = loader.get_account_shared_data(&wallet.pubkey()).unwrap();
+        assert_eq!(account.lamports(), AUTO_CREATE_LAMPORTS);
         assert_eq!(account.data().len(), 0);
-        // Wallets are owned by system_program
         assert_eq!(account.owner(), &solana_sdk::system_program::id());
+
+        // The expected player PDA is sized for a WorldPlayer
+        let account = loader.get_account_shared_data(&player).unwrap();
+        assert_eq!(account.data().len(), WorldPlayer::LEN);
+        assert_eq!(account.owner(), &world_program::id());
+        let owners = [solana_sdk::system_program::id(), world_program::id()];
+        assert_eq!(loader.account_matches_owners(&player, &owners), Some(1));
+
+        // A misderived PDA and an unsigned account nobody creates stay missing
+        assert!(loader.get_account_shared_data(&typo).is_none());
+        assert!(loader.get_account_shared_data(&world).is_none());
+        assert_eq!(loader.account_matches_owners(&typo, &owners), None);
+
+        // Without rules nothing is synthesized
+        let loader = L2AccountLoader::for_transactions(store, &AutoCreateRegistry::new(), &txs);
+        assert!(loader.get_account_shared_data(&wallet.pubkey()).is_none());
+        assert!(loader.get_account_shared_data(&player).is_none());
     }
 
     #[test]
-    fn test_missing_pda_returns_default_with_data_space() {
-        let store = Arc::new(AccountStore::new());
-        let loader = L2AccountLoader::new(store);
-
-        // Create a PDA (off-curve address) using find_program_address
-        let program_id = Pubkey::new_unique();
-        let (pda, _bump) = Pubkey::find_program_address(&[b"test"], &program_id);
-
-        let account = loader.get_account_shared_data(&pda);
-        assert!(account.is_some());
-        let account = account.unwrap();
-        // Default accounts get 1 SOL
-        assert_eq!(account.lamports(), 1_000_000_000);
-        // PDAs (off-curve) get WorldPlayer::LEN bytes for program data
-        assert_eq!(account.data().len(), world_program::state::WorldPlayer::LEN);
-        // PDAs are owned by world_program so the program can write to them
+    fn test_world_pda_is_sized_for_its_state() {
+        let authority = Keypair::new();
+        let name = [7u8; 32];
+        let (world, _) = WorldConfig::derive_pda(&name, &world_program::id());
+        let ix = Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::InitializeWorld {
+                name,
+                width: 100,
+                height: 100,
+                max_players: 10,
+            },
+            vec![
+                AccountMeta::new(world, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+                AccountMeta::new(authority.pubkey(), true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+        );
+        let tx = sanitize(&authority, ix);
+        let index = tx.message().account_keys().iter().position(|key| *key == world).unwrap();
+        let account = AutoCreateRegistry::world_program().resolve(&AccountStore::new(), tx.message(), index).unwrap();
+        assert_eq!(account.data().len(), WorldConfig::LEN);
         assert_eq!(account.owner(), &world_program::id());
     }
+
+    /// Resolve the missing account `key` of `tx` with the default rules
+    fn resolve(store: &AccountStore, tx: &SanitizedTransaction, key: &Pubkey) -> Option<AccountSharedData> {
+        let index = tx.message().account_keys().iter().position(|k| k == key).unwrap();
+        AutoCreateRegistry::world_program().resolve(store, tx.message(), index)
+    }
+
+    fn store_state<T: borsh::BorshSerialize>(store: &AccountStore, key: Pubkey, state: &T) {
+        let data = borsh::to_vec(state).unwrap();
+        let mut account = AccountSharedData::new(1, data.len(), &world_program::id());
+        account.set_data_from_slice(&data);
+        store.store_account(key, account, 0);
+    }
+
+    #[test]
+    fn test_only_world_program_fee_payers_are_funded() {
+        let store = AccountStore::new();
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let ix = Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::JoinWorld { name: [b'a'; 16] },
+            vec![AccountMeta::new_readonly(authority.pubkey(), true)],
+        );
+        let message = Message::new(&[ix], Some(&payer.pubkey()));
+        let tx = Transaction::new(&[&payer, &authority], message, Default::default());
+        let tx = SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new()).unwrap();
+        assert_eq!(resolve(&store, &tx, &payer.pubkey()).unwrap().lamports(), AUTO_CREATE_LAMPORTS);
+        // Other signers are not funded
+        assert!(resolve(&store, &tx, &authority.pubkey()).is_none());
+
+        // Nor is the fee payer of a transaction that doesn't call the world program
+        let ix = solana_sdk::system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 1);
+        let tx = sanitize(&payer, ix);
+        assert!(resolve(&store, &tx, &payer.pubkey()).is_none());
+    }
+
+    #[test]
+    fn test_name_claims_follow_the_claimed_name() {
+        let store = AccountStore::new();
+        let program_id = world_program::id();
+        let wallet = Keypair::new();
+        let world = Pubkey::new_unique();
+        let (player, _) = WorldPlayer::derive_pda(&world, &wallet.pubkey(), &program_id);
+        let mut name = [0u8; 16];
+        name[..5].copy_from_slice(b"Alice");
+        let (claim, _) = NameClaim::derive_pda(&world, &NameClaim::normalize(&name), &program_id);
+        let stranger = Pubkey::new_unique();
+
+        let join = |name_claim: Pubkey| {
+            let ix = Instruction::new_with_borsh(
+                program_id,
+                &WorldInstruction::JoinWorld { name },
+                vec![
+                    AccountMeta::new(world, false),
+                    AccountMeta::new(player, false),
+                    AccountMeta::new_readonly(wallet.pubkey(), true),
+                    AccountMeta::new(wallet.pubkey(), true),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new(name_claim, false),
+                ],
+            );
+            sanitize(&wallet, ix)
+        };
+        assert_eq!(resolve(&store, &join(claim), &claim).unwrap().data().len(), NameClaim::LEN);
+        assert!(resolve(&store, &join(stranger), &stranger).is_none());
+
+        // A transfer claims the source player's name at the destination
+        let destination = Pubkey::new_unique();
+        let (moved, _) = WorldPlayer::derive_pda(&destination, &wallet.pubkey(), &program_id);
+        let (destination_claim, _) = NameClaim::derive_pda(&destination, &NameClaim::normalize(&name), &program_id);
+        store_state(
+            &store,
+            player,
+            &WorldPlayer {
+                authority: wallet.pubkey(),
+                world,
+                name,
+                ..Default::default()
+            },
+        );
+        let transfer = |name_claim: Pubkey| {
+            let ix = Instruction::new_with_borsh(
+                program_id,
+                &WorldInstruction::TransferWorld,
+                vec![
+                    AccountMeta::new(world, false),
+                    AccountMeta::new(player, false),
+                    AccountMeta::new(destination, false),
+                    AccountMeta::new(moved, false),
+                    AccountMeta::new(wallet.pubkey(), true),
+                    AccountMeta::new(wallet.pubkey(), true),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new(name_claim, false),
+                ],
+            );
+            sanitize(&wallet, ix)
+        };
+        assert!(resolve(&store, &transfer(destination_claim), &destination_claim).is_some());
+        assert!(resolve(&store, &transfer(stranger), &stranger).is_none());
+    }
+
+    #[test]
+    fn test_waitlist_admission_creates_the_heads_accounts() {
+        let store = AccountStore::new();
+        let program_id = world_program::id();
+        let payer = Keypair::new();
+        let world = Pubkey::new_unique();
+        let (waitlist, _) = WorldWaitlist::derive_pda(&world, &program_id);
+        let head = Pubkey::new_unique();
+        let mut name = [0u8; 16];
+        name[..3].copy_from_slice(b"Bob");
+        let mut state = WorldWaitlist {
+            world,
+            count: 1,
+            ..Default::default()
+        };
+        state.entries[0] = WaitlistEntry { wallet: head, name };
+        store_state(&store, waitlist, &state);
+        let (player, _) = WorldPlayer::derive_pda(&world, &head, &program_id);
+        let (claim, _) = NameClaim::derive_pda(&world, &NameClaim::normalize(&name), &program_id);
+
+        let admit = |player: Pubkey, name_claim: Pubkey| {
+            let ix = Instruction::new_with_borsh(
+                program_id,
+                &WorldInstruction::AdmitFromWaitlist,
+                vec![
+                    AccountMeta::new(world, false),
+                    AccountMeta::new(waitlist, false),
+                    AccountMeta::new(player, false),
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new(name_claim, false),
+                ],
+            );
+            sanitize(&payer, ix)
+        };
+        let tx = admit(player, claim);
+        assert_eq!(resolve(&store, &tx, &player).unwrap().data().len(), WorldPlayer::LEN);
+        assert_eq!(resolve(&store, &tx, &claim).unwrap().data().len(), NameClaim::LEN);
+
+        // Unrelated keys in the same positions stay missing
+        let (other_player, other_claim) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let tx = admit(other_player, other_claim);
+        assert!(resolve(&store, &tx, &other_player).is_none());
+        assert!(resolve(&store, &tx, &other_claim).is_none());
+    }
+
+    #[test]
+    fn test_seed_offsets_match_layouts() {
+        let player = WorldPlayer {
+            name: [9u8; 16],
+            ..Default::default()
+        };
+        let data = borsh::to_vec(&player).unwrap();
+        assert_eq!(&data[PLAYER_NAME_OFFSET..PLAYER_NAME_OFFSET + 16], &[9u8; 16]);
+
+        let mut waitlist = WorldWaitlist::default();
+        waitlist.entries[0].wallet = Pubkey::new_from_array([7u8; 32]);
+        let data = borsh::to_vec(&waitlist).unwrap();
+        assert_eq!(&data[WAITLIST_HEAD_OFFSET..WAITLIST_HEAD_OFFSET + 32], &[7u8; 32]);
+    }
+
+    #[test]
+    fn test_discriminators_match_instructions() {
+        use super::discriminator::*;
+
+        let decode = |discriminator: u8| {
+            let mut data = vec![0u8; 512];
+            data[0] = discriminator;
+            WorldInstruction::deserialize(&mut data.as_slice()).unwrap()
+        };
+        assert!(matches!(decode(INITIALIZE_WORLD), WorldInstruction::InitializeWorld { .. }));
+        assert!(matches!(decode(JOIN_WORLD), WorldInstruction::JoinWorld { .. }));
+        assert!(matches!(decode(CREATE_SESSION), WorldInstruction::CreateSession { .. }));
+        assert!(matches!(decode(SPAWN_NPC), WorldInstruction::SpawnNpc { .. }));
+        assert!(matches!(decode(ATTACK_NPC), WorldInstruction::AttackNpc { .. }));
+        assert!(matches!(decode(CONFIGURE_TEAMS), WorldInstruction::ConfigureTeams { .. }));
+        assert!(matches!(decode(CONFIGURE_GAME_MODE), WorldInstruction::ConfigureGameMode { .. }));
+        assert!(matches!(decode(TICK_GAME_MODE), WorldInstruction::TickGameMode));
+        assert!(matches!(decode(JOIN_QUEUE), WorldInstruction::JoinQueue { .. }));
+        assert!(matches!(decode(TRANSFER_WORLD), WorldInstruction::TransferWorld));
+        assert!(matches!(decode(UPDATE_ALLOWLIST), WorldInstruction::UpdateAllowlist { .. }));
+        assert!(matches!(
+            decode(JOIN_WORLD_WITH_PASSWORD),
+            WorldInstruction::JoinWorldWithPassword { .. }
+        ));
+        assert!(matches!(decode(JOIN_WAITLIST), WorldInstruction::JoinWaitlist { .. }));
+        assert!(matches!(decode(ADMIT_FROM_WAITLIST), WorldInstruction::AdmitFromWaitlist));
+        assert!(matches!(decode(ADMIT_VIP), WorldInstruction::AdmitVip { .. }));
+        assert!(matches!(decode(UPDATE_RESERVED_NAMES), WorldInstruction::UpdateReservedNames { .. }));
+    }
 }
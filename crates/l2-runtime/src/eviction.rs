@@ -0,0 +1,156 @@
+//! Idle Evictor
+//!
+//! Leader-side sweep for players who stopped playing without sending
+//! `LeaveWorld` (e.g. a closed browser tab). Every tick it submits an
+//! `EvictIdlePlayer` for each player whose last action is older than their
+//! world's idle timeout. Worlds with a timeout of 0 are skipped. The freed
+//! slots are picked up by the waitlist driver on the next tick.
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::WorldInstruction,
+    state::{NameClaim, TeamScores, WorldConfig, WorldPlayer},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Leader-side sweep that evicts idle players
+pub struct IdleEvictor {
+    /// Signs and pays for eviction transactions (eviction is permissionless)
+    payer: Keypair,
+}
+
+impl IdleEvictor {
+    /// Create an evictor
+    pub fn new(payer: Keypair) -> Self {
+        Self { payer }
+    }
+
+    /// Build this tick's eviction transactions (one per idle player)
+    pub fn build_transactions(
+        &self,
+        slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let mut worlds: HashMap<Pubkey, Option<WorldConfig>> = HashMap::new();
+        let mut txs = Vec::new();
+
+        for (player_key, account) in store.get_program_accounts(&program_id) {
+            if account.data().len() != WorldPlayer::LEN {
+                continue;
+            }
+            let Ok(player) = WorldPlayer::try_from_slice(account.data()) else {
+                continue;
+            };
+            let world = worlds.entry(player.world).or_insert_with(|| {
+                store
+                    .get_account(&player.world)
+                    .and_then(|account| WorldConfig::try_from_slice(account.data()).ok())
+            });
+            let Some(world) = world else {
+                continue;
+            };
+            if !world.is_idle(&player, slot) {
+                continue;
+            }
+
+            let mut accounts = vec![
+                AccountMeta::new(player.world, false),
+                AccountMeta::new(player_key, false),
+                AccountMeta::new(player.authority, false),
+            ];
+            if world.teams_enabled() {
+                let (scores_pda, _) = TeamScores::derive_pda(&player.world, &program_id);
+                accounts.push(AccountMeta::new(scores_pda, false));
+            }
+            if world.unique_names {
+                let name = NameClaim::normalize(&player.name);
+                let (claim_pda, _) = NameClaim::derive_pda(&player.world, &name, &program_id);
+                accounts.push(AccountMeta::new(claim_pda, false));
+            }
+            let ix = Instruction::new_with_borsh(program_id, &WorldInstruction::EvictIdlePlayer, accounts);
+
+            let message = Message::new(&[ix], Some(&self.payer.pubkey()));
+            let tx = Transaction::new(&[&self.payer], message, blockhash);
+            txs.push(
+                SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+                    .expect("eviction transaction is well-formed"),
+            );
+        }
+
+        txs
+    }
+}
+
+impl TickHook for IdleEvictor {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::AccountSharedData;
+
+    fn store_borsh(store: &AccountStore, key: Pubkey, value: &impl borsh::BorshSerialize) {
+        let data = borsh::to_vec(value).unwrap();
+        let mut account = AccountSharedData::new(1_000_000, data.len(), &world_program::id());
+        account.set_data_from_slice(&data);
+        store.store_account(key, account, 0);
+    }
+
+    fn setup(idle_timeout_slots: u64) -> AccountStore {
+        let store = AccountStore::new();
+        let world_key = Pubkey::new_unique();
+        let world = WorldConfig {
+            max_players: 10,
+            player_count: 2,
+            idle_timeout_slots,
+            ..Default::default()
+        };
+        store_borsh(&store, world_key, &world);
+        for last_action_slot in [10, 90] {
+            let player = WorldPlayer {
+                world: world_key,
+                authority: Pubkey::new_unique(),
+                last_action_slot,
+                ..Default::default()
+            };
+            store_borsh(&store, Pubkey::new_unique(), &player);
+        }
+        store
+    }
+
+    #[test]
+    fn test_evicts_only_idle_players() {
+        let evictor = IdleEvictor::new(Keypair::new());
+        let store = setup(50);
+
+        assert!(evictor.build_transactions(59, Hash::default(), &store).is_empty());
+        assert_eq!(evictor.build_transactions(60, Hash::default(), &store).len(), 1);
+        assert_eq!(evictor.build_transactions(140, Hash::default(), &store).len(), 2);
+    }
+
+    #[test]
+    fn test_opted_out_world_is_skipped() {
+        let evictor = IdleEvictor::new(Keypair::new());
+        let store = setup(0);
+
+        assert!(evictor.build_transactions(1_000_000, Hash::default(), &store).is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Movement Extrapolator
+//!
+//! Leader-side dead reckoning for dropped or late `MovePlayer3D` inputs.
+//! Every tick (after the block's user transactions) it submits an
+//! `ExtrapolateMovement` for each moving player who sent no input this slot,
+//! so they keep moving instead of freezing and snapping when input resumes.
+//! The program repeats the player's last input for at most the world's
+//! `max_extrapolation_slots` in a row and counts the predicted slots on the
+//! player for clients.
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::WorldInstruction,
+    state::{WorldConfig, WorldPlayer},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Leader-side crank that fills in missing movement input
+pub struct MovementExtrapolator {
+    /// Signs and pays for extrapolation transactions (extrapolation is permissionless)
+    payer: Keypair,
+}
+
+impl MovementExtrapolator {
+    /// Create an extrapolator
+    pub fn new(payer: Keypair) -> Self {
+        Self { payer }
+    }
+
+    /// Build this tick's extrapolation transactions (one per player missing input)
+    pub fn build_transactions(
+        &self,
+        slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let mut worlds: HashMap<Pubkey, Option<WorldConfig>> = HashMap::new();
+        let mut txs = Vec::new();
+
+        for (player_key, account) in store.get_program_accounts(&program_id) {
+            if account.data().len() != WorldPlayer::LEN {
+                continue;
+            }
+            let Ok(player) = WorldPlayer::try_from_slice(account.data()) else {
+                continue;
+            };
+            let world = worlds.entry(player.world).or_insert_with(|| {
+                store
+                    .get_account(&player.world)
+                    .and_then(|account| WorldConfig::try_from_slice(account.data()).ok())
+            });
+            let Some(world) = world else {
+                continue;
+            };
+            if player.extrapolation_input(world, slot).is_none() {
+                continue;
+            }
+
+            let ix = Instruction::new_with_borsh(
+                program_id,
+                &WorldInstruction::ExtrapolateMovement,
+                vec![
+                    AccountMeta::new_readonly(player.world, false),
+                    AccountMeta::new(player_key, false),
+                ],
+            );
+
+            let message = Message::new(&[ix], Some(&self.payer.pubkey()));
+            let tx = Transaction::new(&[&self.payer], message, blockhash);
+            txs.push(
+                SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+                    .expect("extrapolation transaction is well-formed"),
+            );
+        }
+
+        txs
+    }
+}
+
+impl TickHook for MovementExtrapolator {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::AccountSharedData;
+    use world_program::state::MovementInput3D;
+
+    fn store_borsh(store: &AccountStore, key: Pubkey, value: &impl borsh::BorshSerialize) {
+        let data = borsh::to_vec(value).unwrap();
+        let mut account = AccountSharedData::new(1_000_000, data.len(), &world_program::id());
+        account.set_data_from_slice(&data);
+        store.store_account(key, account, 0);
+    }
+
+    /// A world with one player who last moved at slot 10 and one who stood still
+    fn setup(max_extrapolation_slots: u64) -> AccountStore {
+        let store = AccountStore::new();
+        let world_key = Pubkey::new_unique();
+        let world = WorldConfig {
+            max_players: 10,
+            player_count: 2,
+            max_extrapolation_slots,
+            ..Default::default()
+        };
+        store_borsh(&store, world_key, &world);
+        for move_z in [127, 0] {
+            let player = WorldPlayer {
+                world: world_key,
+                authority: Pubkey::new_unique(),
+                health: 100,
+                last_input: MovementInput3D {
+                    move_z,
+                    ..Default::default()
+                },
+                last_input_slot: 10,
+                ..Default::default()
+            };
+            store_borsh(&store, Pubkey::new_unique(), &player);
+        }
+        store
+    }
+
+    #[test]
+    fn test_extrapolates_only_moving_players_missing_input() {
+        let extrapolator = MovementExtrapolator::new(Keypair::new());
+        let store = setup(3);
+
+        assert!(extrapolator.build_transactions(10, Hash::default(), &store).is_empty());
+        assert_eq!(extrapolator.build_transactions(11, Hash::default(), &store).len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_world_is_skipped() {
+        let extrapolator = MovementExtrapolator::new(Keypair::new());
+        let store = setup(0);
+
+        assert!(extrapolator.build_transactions(11, Hash::default(), &store).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Game Mode Driver
+//!
+//! Leader-side driver for objective game modes (king of the hill, capture the
+//! flag). Every tick it submits a `TickGameMode` transaction signed by the
+//! world authority, passing the players near the objectives so the world
+//! program can score the slot and roll finished matches into match results.
+
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::WorldInstruction,
+    state::{GameMode, MatchResult, TeamScores, WorldPlayer},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Most player accounts passed to one `TickGameMode` (keeps the transaction
+/// under the account lock limit)
+pub const MAX_TICK_PLAYERS: usize = 48;
+
+/// Leader-side driver for the game mode of one world
+pub struct GameModeDriver {
+    /// World authority (signs and pays for tick transactions)
+    authority: Keypair,
+    /// World the game mode runs in
+    world: Pubkey,
+}
+
+impl GameModeDriver {
+    /// Create a driver for `world`
+    pub fn new(authority: Keypair, world: Pubkey) -> Self {
+        Self { authority, world }
+    }
+
+    /// Players whose state matters for scoring this tick, in account order
+    ///
+    /// Flag carriers always come first so the limit never drops a flag.
+    pub fn contenders(&self, game_mode: &GameMode, store: &AccountStore) -> Vec<Pubkey> {
+        let mut players: Vec<Pubkey> = store
+            .get_program_accounts(&world_program::id())
+            .into_iter()
+            .filter(|(_, account)| account.data().len() == WorldPlayer::LEN)
+            .filter_map(|(key, account)| {
+                let player = WorldPlayer::try_from_slice(account.data()).ok()?;
+                let relevant = game_mode.flag_carriers.contains(&key)
+                    || (player.is_alive() && game_mode.is_near_objective(&player));
+                (player.world == self.world && relevant).then_some(key)
+            })
+            .collect();
+
+        players.sort_by_key(|key| (!game_mode.flag_carriers.contains(key), *key));
+        players.truncate(MAX_TICK_PLAYERS);
+        players
+    }
+
+    /// Build this tick's `TickGameMode` transaction (none until the world has a game mode)
+    pub fn build_transactions(
+        &self,
+        _slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let (game_mode_pda, _) = GameMode::derive_pda(&self.world, &program_id);
+        let Some(game_mode) = store
+            .get_account(&game_mode_pda)
+            .and_then(|account| GameMode::try_from_slice(account.data()).ok())
+        else {
+            return Vec::new();
+        };
+
+        let (scores_pda, _) = TeamScores::derive_pda(&self.world, &program_id);
+        let (result_pda, _) = MatchResult::derive_pda(&self.world, game_mode.match_index, &program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new(game_mode_pda, false),
+            AccountMeta::new(scores_pda, false),
+            AccountMeta::new(self.authority.pubkey(), true),
+            AccountMeta::new(result_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        accounts.extend(
+            self.contenders(&game_mode, store)
+                .into_iter()
+                .map(|player| AccountMeta::new_readonly(player, false)),
+        );
+
+        let ix = Instruction::new_with_borsh(program_id, &WorldInstruction::TickGameMode, accounts);
+        let message = Message::new(&[ix], Some(&self.authority.pubkey()));
+        let tx = Transaction::new(&[&self.authority], message, blockhash);
+        vec![SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+            .expect("game mode transaction is well-formed")]
+    }
+}
+
+impl TickHook for GameModeDriver {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::AccountSharedData;
+    use world_program::constants::{
+        DEFAULT_HEALTH, GAME_MODE_CAPTURE_THE_FLAG, GAME_MODE_KING_OF_THE_HILL,
+    };
+
+    fn store_player(store: &AccountStore, world: Pubkey, x: i32, health: u16) -> Pubkey {
+        let player = WorldPlayer {
+            world,
+            position_x: x,
+            health,
+            team: 1,
+            ..Default::default()
+        };
+        let key = Pubkey::new_unique();
+        let mut account = AccountSharedData::new(1, WorldPlayer::LEN, &world_program::id());
+        account.set_data_from_slice(&borsh::to_vec(&player).unwrap());
+        store.store_account(key, account, 0);
+        key
+    }
+
+    #[test]
+    fn test_contenders_near_hill() {
+        let store = AccountStore::new();
+        let world = Pubkey::new_unique();
+        let driver = GameModeDriver::new(Keypair::new(), world);
+        let game_mode = GameMode {
+            mode: GAME_MODE_KING_OF_THE_HILL,
+            objective_radius: 5_000,
+            ..Default::default()
+        };
+
+        let on_hill = store_player(&store, world, 1_000, DEFAULT_HEALTH);
+        store_player(&store, world, 50_000, DEFAULT_HEALTH); // far away
+        store_player(&store, world, 0, 0); // dead
+        store_player(&store, Pubkey::new_unique(), 0, DEFAULT_HEALTH); // other world
+
+        assert_eq!(driver.contenders(&game_mode, &store), vec![on_hill]);
+    }
+
+    #[test]
+    fn test_flag_carriers_always_included() {
+        let store = AccountStore::new();
+        let world = Pubkey::new_unique();
+        let driver = GameModeDriver::new(Keypair::new(), world);
+
+        let carrier = store_player(&store, world, 90_000, DEFAULT_HEALTH);
+        let mut game_mode = GameMode {
+            mode: GAME_MODE_CAPTURE_THE_FLAG,
+            objective_radius: 5_000,
+            ..Default::default()
+        };
+        game_mode.flag_carriers[1] = carrier;
+        for _ in 0..MAX_TICK_PLAYERS {
+            store_player(&store, world, 0, DEFAULT_HEALTH);
+        }
+
+        let contenders = driver.contenders(&game_mode, &store);
+        assert_eq!(contenders.len(), MAX_TICK_PLAYERS);
+        assert_eq!(contenders[0], carrier);
+    }
+}
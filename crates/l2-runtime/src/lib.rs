@@ -3,22 +3,71 @@
 //! This crate provides the core runtime for the L2 gaming chain:
 //! - Transaction processing via solana-svm
 //! - In-memory account storage with optional disk persistence
+//! - Startup migration of accounts persisted in older layouts
+//! - Rule-based synthesis of accounts transactions create
+//! - Merkle state root over all accounts, with inclusion proofs
 //! - 30Hz block production loop
+//! - Block and transaction history with retention-based pruning
+//! - Deterministic replay of recorded blocks with divergence reports
+//! - Compressed, checksummed state snapshot archives
+//! - Parallel execution of conflict-free transaction batches
+//! - Per-world position history for lag-compensated hits
+//! - Leader-side NPC AI driver
+//! - Leader-side game mode driver (king of the hill, capture the flag)
+//! - Leader-side matchmaker for instanced worlds
+//! - Leader-side waitlist crank for full worlds
+//! - Leader-side eviction of idle players
+//! - Leader-side dead reckoning for missing movement input
+//! - Spatial grid index for nearby-entity queries
 
 pub mod account_store;
 pub mod block_producer;
+pub mod block_store;
 pub mod callback;
+pub mod eviction;
+pub mod extrapolation;
+pub mod game_mode;
+pub mod matchmaking;
+pub mod migration;
+pub mod npc;
 pub mod persistence;
+pub mod position_history;
 pub mod processor;
+pub mod replay;
+pub mod scheduler;
+pub mod snapshot;
+pub mod spatial;
+pub mod state_tree;
+pub mod waitlist;
 
 #[cfg(test)]
 mod tests;
 
 pub use account_store::AccountStore;
-pub use block_producer::{BlockProducer, BlockProducerConfig, BlockUpdate, TransactionSender};
-pub use callback::L2AccountLoader;
-pub use persistence::{AccountStorePersistence, ChainMetadata, PersistentStore};
-pub use processor::{L2Processor, TransactionResult};
+pub use block_producer::{
+    BlockProducer, BlockProducerConfig, BlockUpdate, TickHook, TransactionSender,
+};
+pub use block_store::{BlockStore, BlockStoreConfig, BlockWriter, StoredBlock, StoredTransaction};
+pub use callback::{AccountMatcher, AutoCreateRegistry, AutoCreateRule, L2AccountLoader, Seed};
+pub use eviction::IdleEvictor;
+pub use extrapolation::MovementExtrapolator;
+pub use game_mode::GameModeDriver;
+pub use matchmaking::{Matchmaker, MatchmakerConfig};
+pub use migration::migrate_accounts;
+pub use npc::{ChaseBehavior, NpcBehavior, NpcCommand, NpcDriver, NpcSpawn, PatrolBehavior};
+pub use persistence::{
+    AccountStorePersistence, ChainMetadata, Checkpoint, CheckpointRequest, CheckpointWriter, PersistentStore, SlotDelta,
+};
+pub use position_history::PositionRecorder;
+pub use processor::{derive_blockhash, L2Processor, TransactionResult, GENESIS_BLOCKHASH};
+pub use replay::{Divergence, ReplayDivergence, ReplayReport, ReplaySnapshot, Replayer, TransactionOutcome};
+pub use snapshot::{
+    read_manifest, read_snapshot, restore_snapshot, RestoreReport, SnapshotArchive, SnapshotConfig, SnapshotInfo,
+    SnapshotManifest, SnapshotWriter,
+};
+pub use spatial::{EntityKind, SpatialEntity, SpatialIndex};
+pub use state_tree::{StateProof, StateTree};
+pub use waitlist::WaitlistDriver;
 
 /// Block time in milliseconds (30Hz = ~33.3ms)
 pub const BLOCK_TIME_MS: u64 = 33;
@@ -28,3 +77,6 @@ pub const TICKS_PER_SECOND: u64 = 30;
 
 /// Maximum transactions per block
 pub const MAX_TXS_PER_BLOCK: usize = 64;
+
+/// Slots per epoch (4 hours at 30Hz)
+pub const SLOTS_PER_EPOCH: u64 = 432_000;
@@ -0,0 +1,290 @@
+//! Matchmaker
+//!
+//! Leader-side lobby driver. Every tick it groups waiting queue tickets by
+//! rating, creates a fresh world instance per group with `InitializeWorld`
+//! and issues each player a join ticket naming the instance. Instances that
+//! are empty once their grace period is over are torn down with `CloseWorld`.
+
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::WorldInstruction,
+    state::{QueueTicket, WorldConfig},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Matchmaker configuration
+#[derive(Clone, Debug)]
+pub struct MatchmakerConfig {
+    /// Players per world instance
+    pub match_size: usize,
+    /// Largest rating spread allowed inside one match (0 = any spread)
+    pub rating_window: u32,
+    /// Instance width in world units
+    pub world_width: u32,
+    /// Instance depth in world units
+    pub world_depth: u32,
+    /// Slots an instance may sit empty before it is torn down
+    pub teardown_grace_slots: Slot,
+}
+
+impl Default for MatchmakerConfig {
+    fn default() -> Self {
+        Self {
+            match_size: 2,
+            rating_window: 0,
+            world_width: 100,
+            world_depth: 100,
+            teardown_grace_slots: 900, // 30 seconds at 30Hz
+        }
+    }
+}
+
+/// World instance created by the matchmaker
+#[derive(Clone, Debug)]
+struct Instance {
+    world: Pubkey,
+    created_slot: Slot,
+}
+
+/// Leader-side matchmaker for the lobby queue
+pub struct Matchmaker {
+    /// Matchmaker authority (owns the instances, signs and pays)
+    authority: Keypair,
+    config: MatchmakerConfig,
+    /// Live instances awaiting teardown
+    instances: Vec<Instance>,
+}
+
+impl Matchmaker {
+    /// Create a matchmaker
+    pub fn new(authority: Keypair, config: MatchmakerConfig) -> Self {
+        Self {
+            authority,
+            config,
+            instances: Vec::new(),
+        }
+    }
+
+    /// Number of live instances
+    pub fn instance_count(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Group waiting tickets into matches of `match_size` within the rating window
+    ///
+    /// Tickets are ordered by rating, then queue time, then key, and each match
+    /// takes the first run of consecutive tickets whose spread fits the window.
+    pub fn group_tickets(&self, tickets: &[(Pubkey, QueueTicket)]) -> Vec<Vec<Pubkey>> {
+        let size = self.config.match_size.max(1);
+        let mut waiting: Vec<&(Pubkey, QueueTicket)> =
+            tickets.iter().filter(|(_, ticket)| ticket.is_waiting()).collect();
+        waiting.sort_by_key(|(key, ticket)| (ticket.rating, ticket.enqueued_slot, *key));
+
+        let mut groups = Vec::new();
+        let mut start = 0;
+        while start + size <= waiting.len() {
+            let group = &waiting[start..start + size];
+            let spread = group[size - 1].1.rating - group[0].1.rating;
+            if self.config.rating_window == 0 || spread <= self.config.rating_window {
+                groups.push(group.iter().map(|(key, _)| *key).collect());
+                start += size;
+            } else {
+                start += 1;
+            }
+        }
+        groups
+    }
+
+    /// Build this tick's matchmaking transactions
+    ///
+    /// One transaction per new match (instance creation plus its join tickets,
+    /// so the tickets see the new world) and one per instance torn down.
+    pub fn build_transactions(
+        &mut self,
+        slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let mut txs = Vec::new();
+
+        // Tear down instances that are empty after their grace period
+        let grace = self.config.teardown_grace_slots;
+        let mut expired = Vec::new();
+        self.instances.retain(|instance| {
+            if slot < instance.created_slot + grace {
+                return true;
+            }
+            match store
+                .get_account(&instance.world)
+                .and_then(|account| WorldConfig::try_from_slice(account.data()).ok())
+            {
+                // Never created (its transaction failed) or already gone
+                None => false,
+                Some(world) if world.player_count == 0 => {
+                    expired.push(instance.world);
+                    false
+                }
+                Some(_) => true,
+            }
+        });
+        for world in expired {
+            txs.push(self.sign(self.close_instruction(world), blockhash));
+        }
+
+        // Form new matches from the queue
+        let tickets: Vec<(Pubkey, QueueTicket)> = store
+            .get_program_accounts(&program_id)
+            .into_iter()
+            .filter(|(_, account)| account.data().len() == QueueTicket::LEN)
+            .filter_map(|(key, account)| {
+                QueueTicket::try_from_slice(account.data()).ok().map(|ticket| (key, ticket))
+            })
+            .collect();
+
+        for (index, group) in self.group_tickets(&tickets).into_iter().enumerate() {
+            let name = instance_name(slot, index);
+            let (world_pda, _) = WorldConfig::derive_pda(&name, &program_id);
+
+            let mut instructions = vec![Instruction::new_with_borsh(
+                program_id,
+                &WorldInstruction::InitializeWorld {
+                    name,
+                    width: self.config.world_width,
+                    height: self.config.world_depth,
+                    max_players: group.len() as u16,
+                },
+                vec![
+                    AccountMeta::new(world_pda, false),
+                    AccountMeta::new_readonly(self.authority.pubkey(), true),
+                    AccountMeta::new(self.authority.pubkey(), true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+            )];
+            instructions.extend(group.iter().map(|ticket| {
+                Instruction::new_with_borsh(
+                    program_id,
+                    &WorldInstruction::IssueJoinTicket,
+                    vec![
+                        AccountMeta::new_readonly(world_pda, false),
+                        AccountMeta::new(*ticket, false),
+                        AccountMeta::new_readonly(self.authority.pubkey(), true),
+                    ],
+                )
+            }));
+
+            txs.push(self.sign_all(&instructions, blockhash));
+            self.instances.push(Instance {
+                world: world_pda,
+                created_slot: slot,
+            });
+        }
+
+        txs
+    }
+
+    fn close_instruction(&self, world: Pubkey) -> Instruction {
+        Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::CloseWorld,
+            vec![
+                AccountMeta::new(world, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new(self.authority.pubkey(), false),
+            ],
+        )
+    }
+
+    fn sign(&self, ix: Instruction, blockhash: Hash) -> SanitizedTransaction {
+        self.sign_all(&[ix], blockhash)
+    }
+
+    fn sign_all(&self, instructions: &[Instruction], blockhash: Hash) -> SanitizedTransaction {
+        let message = Message::new(instructions, Some(&self.authority.pubkey()));
+        let tx = Transaction::new(&[&self.authority], message, blockhash);
+        SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+            .expect("matchmaking transaction is well-formed")
+    }
+}
+
+impl TickHook for Matchmaker {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+/// Unique world name for the `index`-th match formed at `slot`
+pub fn instance_name(slot: Slot, index: usize) -> [u8; 32] {
+    let label = format!("match-{}-{}", slot, index);
+    let mut name = [0u8; 32];
+    let len = label.len().min(32);
+    name[..len].copy_from_slice(&label.as_bytes()[..len]);
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use world_program::constants::{QUEUE_STATUS_MATCHED, QUEUE_STATUS_WAITING};
+
+    fn ticket(rating: u32, enqueued_slot: u64) -> (Pubkey, QueueTicket) {
+        let ticket = QueueTicket {
+            rating,
+            status: QUEUE_STATUS_WAITING,
+            enqueued_slot,
+            ..Default::default()
+        };
+        (Pubkey::new_unique(), ticket)
+    }
+
+    fn matchmaker(match_size: usize, rating_window: u32) -> Matchmaker {
+        let config = MatchmakerConfig {
+            match_size,
+            rating_window,
+            ..Default::default()
+        };
+        Matchmaker::new(Keypair::new(), config)
+    }
+
+    #[test]
+    fn test_group_by_size() {
+        let tickets: Vec<_> = (0..5).map(|i| ticket(1000, i)).collect();
+        let groups = matchmaker(2, 0).group_tickets(&tickets);
+
+        // Oldest tickets first; the fifth waits for a partner
+        assert_eq!(groups, vec![vec![tickets[0].0, tickets[1].0], vec![tickets[2].0, tickets[3].0]]);
+    }
+
+    #[test]
+    fn test_group_by_rating_window() {
+        let mut tickets = vec![ticket(1000, 0), ticket(1500, 1), ticket(1050, 2), ticket(1480, 3)];
+        tickets.push(ticket(3000, 4));
+        let mut matched = ticket(1010, 5);
+        matched.1.status = QUEUE_STATUS_MATCHED;
+        tickets.push(matched);
+
+        let groups = matchmaker(2, 100).group_tickets(&tickets);
+        assert_eq!(groups, vec![vec![tickets[0].0, tickets[2].0], vec![tickets[3].0, tickets[1].0]]);
+    }
+
+    #[test]
+    fn test_instance_names_unique() {
+        assert_ne!(instance_name(7, 0), instance_name(7, 1));
+        assert_ne!(instance_name(7, 0), instance_name(70, 0));
+        assert!(instance_name(u64::MAX, usize::MAX).starts_with(b"match-"));
+    }
+}
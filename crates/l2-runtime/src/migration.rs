@@ -0,0 +1,108 @@
+//! Account Layout Migration
+//!
+//! World program accounts only ever grow by appending fields, so an account
+//! written by an older version is a prefix of the current layout. Players
+//! and worlds persisted before their newest fields existed are zero-padded
+//! to the current size on startup, which leaves every new setting off
+//! (teams, eviction, rewind, ...). An account is only padded if its key is
+//! the PDA its leading fields derive, so other accounts of the same length
+//! are left alone.
+
+use solana_sdk::{account::ReadableAccount, clock::Slot, pubkey::Pubkey};
+use world_program::state::{WorldConfig, WorldPlayer};
+
+use crate::account_store::AccountStore;
+
+/// Size of a `WorldPlayer` before any field was appended
+const WORLD_PLAYER_V1_LEN: usize = 123;
+/// Size of a `WorldConfig` before any field was appended
+const WORLD_CONFIG_V1_LEN: usize = 118;
+
+/// Pad world program accounts in an older layout to the current one
+///
+/// Returns how many accounts were migrated.
+pub fn migrate_accounts(store: &AccountStore, slot: Slot) -> usize {
+    let program_id = world_program::id();
+    let mut migrated = 0;
+
+    for (pubkey, mut account) in store.get_program_accounts(&program_id) {
+        let data = account.data();
+        let len = if (WORLD_PLAYER_V1_LEN..WorldPlayer::LEN).contains(&data.len())
+            && is_player_pda(&pubkey, data, &program_id)
+        {
+            WorldPlayer::LEN
+        } else if (WORLD_CONFIG_V1_LEN..WorldConfig::LEN).contains(&data.len())
+            && WorldConfig::derive_pda(&data[..32], &program_id).0 == pubkey
+        {
+            WorldConfig::LEN
+        } else {
+            continue;
+        };
+
+        tracing::info!("Migrating {} from {} to {} bytes", pubkey, data.len(), len);
+        account.resize(len, 0);
+        store.store_account(pubkey, account, slot);
+        migrated += 1;
+    }
+
+    migrated
+}
+
+/// Check that `pubkey` is the player PDA of the authority and world `data` starts with
+fn is_player_pda(pubkey: &Pubkey, data: &[u8], program_id: &Pubkey) -> bool {
+    let authority = Pubkey::try_from(&data[..32]).expect("32 bytes");
+    let world = Pubkey::try_from(&data[32..64]).expect("32 bytes");
+    WorldPlayer::derive_pda(&world, &authority, program_id).0 == *pubkey
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+    use solana_sdk::account::AccountSharedData;
+
+    fn store_bytes(store: &AccountStore, key: Pubkey, data: &[u8]) {
+        let mut account = AccountSharedData::new(1_000_000, data.len(), &world_program::id());
+        account.set_data_from_slice(data);
+        store.store_account(key, account, 0);
+    }
+
+    #[test]
+    fn test_pads_old_players_and_worlds() {
+        let store = AccountStore::new();
+        let program_id = world_program::id();
+
+        let mut name = [0u8; 32];
+        name[..3].copy_from_slice(b"old");
+        let (world_key, _) = WorldConfig::derive_pda(&name, &program_id);
+        let mut world_data = vec![0u8; WORLD_CONFIG_V1_LEN];
+        world_data[..32].copy_from_slice(&name);
+        store_bytes(&store, world_key, &world_data);
+
+        let authority = Pubkey::new_unique();
+        let (player_key, _) = WorldPlayer::derive_pda(&world_key, &authority, &program_id);
+        let player = WorldPlayer {
+            authority,
+            world: world_key,
+            health: 80,
+            ..Default::default()
+        };
+        let player_data = borsh::to_vec(&player).unwrap();
+        store_bytes(&store, player_key, &player_data[..WORLD_PLAYER_V1_LEN]);
+
+        // Same length as an old player, but not a player PDA
+        let stranger = Pubkey::new_unique();
+        store_bytes(&store, stranger, &player_data[..WORLD_PLAYER_V1_LEN]);
+
+        assert_eq!(migrate_accounts(&store, 3), 2);
+        let migrated = WorldPlayer::try_from_slice(store.get_account(&player_key).unwrap().data()).unwrap();
+        assert_eq!(migrated.health, 80);
+        assert_eq!(migrated.world, world_key);
+        let world = WorldConfig::try_from_slice(store.get_account(&world_key).unwrap().data()).unwrap();
+        assert_eq!(world.name, name);
+        assert_eq!(store.get_account(&stranger).unwrap().data().len(), WORLD_PLAYER_V1_LEN);
+
+        // Already current: nothing to do
+        assert_eq!(migrate_accounts(&store, 4), 0);
+    }
+}
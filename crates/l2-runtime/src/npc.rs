@@ -0,0 +1,415 @@
+//! NPC AI Driver
+//!
+//! Leader-side driver for authority-controlled NPCs. Every tick it reads the
+//! world from the account store, asks each NPC's behavior what to do and
+//! submits `SpawnNpc` / `NpcAct` transactions signed by the world authority.
+//!
+//! Behaviors are pure functions of (NPC, players, slot) so they can be tested
+//! deterministically without running the SVM.
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::{NpcAction, WorldInstruction},
+    state::{MovementInput3D, WorldNpc, WorldPlayer},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Command chosen by an NPC behavior for one tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpcCommand {
+    /// Stand still
+    Idle,
+    /// Move in a world-space direction (x, z), each -127..=127
+    Move { move_x: i8, move_z: i8 },
+    /// Attack the given player account
+    Attack(Pubkey),
+}
+
+/// NPC decision logic
+///
+/// Implementations must be deterministic: the same inputs always produce the
+/// same command.
+pub trait NpcBehavior: Send {
+    /// Decide what the NPC does this tick
+    ///
+    /// `players` holds the live players of the NPC's world.
+    fn decide(&self, npc: &WorldNpc, players: &[(Pubkey, WorldPlayer)], slot: Slot) -> NpcCommand;
+}
+
+/// Chase the nearest player within aggro range and attack when in reach
+#[derive(Clone, Debug)]
+pub struct ChaseBehavior {
+    /// Distance at which the NPC notices players (fixed-point units)
+    pub aggro_range: u32,
+}
+
+impl NpcBehavior for ChaseBehavior {
+    fn decide(&self, npc: &WorldNpc, players: &[(Pubkey, WorldPlayer)], _slot: Slot) -> NpcCommand {
+        let Some((key, target)) = nearest_player(npc, players, self.aggro_range) else {
+            return NpcCommand::Idle;
+        };
+
+        if npc.in_range(target) {
+            NpcCommand::Attack(*key)
+        } else {
+            steer_toward(npc, target.position_x, target.position_z)
+        }
+    }
+}
+
+/// Walk between waypoints, attacking players that come within reach
+#[derive(Clone, Debug)]
+pub struct PatrolBehavior {
+    /// Waypoints (x, z) in fixed-point units
+    pub waypoints: Vec<(i32, i32)>,
+    /// Slots spent heading to each waypoint before moving on
+    pub slots_per_waypoint: u64,
+}
+
+impl NpcBehavior for PatrolBehavior {
+    fn decide(&self, npc: &WorldNpc, players: &[(Pubkey, WorldPlayer)], slot: Slot) -> NpcCommand {
+        if let Some((key, _)) = nearest_player(npc, players, npc.attack_range) {
+            return NpcCommand::Attack(*key);
+        }
+
+        if self.waypoints.is_empty() {
+            return NpcCommand::Idle;
+        }
+
+        // Waypoint is a function of the slot so patrols need no extra state
+        let leg = slot / self.slots_per_waypoint.max(1);
+        let (x, z) = self.waypoints[(leg % self.waypoints.len() as u64) as usize];
+        steer_toward(npc, x, z)
+    }
+}
+
+/// Nearest live player whose hitbox is within `range` (ties broken by pubkey for determinism)
+fn nearest_player<'a>(
+    npc: &WorldNpc,
+    players: &'a [(Pubkey, WorldPlayer)],
+    range: u32,
+) -> Option<&'a (Pubkey, WorldPlayer)> {
+    let range_sq = (range as i64) * (range as i64);
+    players
+        .iter()
+        .filter(|(_, p)| p.is_alive())
+        .map(|entry| (npc.body.reach_distance_squared(&entry.1), entry))
+        .filter(|(dist, _)| *dist <= range_sq)
+        .min_by(|(da, a), (db, b)| da.cmp(db).then_with(|| a.0.cmp(&b.0)))
+        .map(|(_, entry)| entry)
+}
+
+/// Unit direction (scaled to 127) from the NPC to a point, or Idle if already there
+fn steer_toward(npc: &WorldNpc, x: i32, z: i32) -> NpcCommand {
+    let dx = (x - npc.body.position_x) as i64;
+    let dz = (z - npc.body.position_z) as i64;
+    let max = dx.abs().max(dz.abs());
+
+    // Close enough - within one walking step
+    if max <= world_program::constants::NORMAL_SPEED as i64 {
+        return NpcCommand::Idle;
+    }
+
+    NpcCommand::Move {
+        move_x: (dx * 127 / max) as i8,
+        move_z: (dz * 127 / max) as i8,
+    }
+}
+
+/// Spawn parameters for a driven NPC (zero stats use program defaults)
+#[derive(Clone, Debug, Default)]
+pub struct NpcSpawn {
+    pub npc_id: u32,
+    pub kind: u8,
+    pub name: [u8; 16],
+    pub max_health: u16,
+    pub damage: u16,
+    pub attack_range: u32,
+    pub spawn_x: i32,
+    pub spawn_z: i32,
+    pub respawn_delay_slots: u64,
+    pub loot_amount: u32,
+}
+
+impl NpcSpawn {
+    /// Spawn with default stats at a position
+    pub fn new(npc_id: u32, name: &str, spawn_x: i32, spawn_z: i32) -> Self {
+        let mut name_bytes = [0u8; 16];
+        let len = name.len().min(16);
+        name_bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+        Self {
+            npc_id,
+            name: name_bytes,
+            spawn_x,
+            spawn_z,
+            ..Default::default()
+        }
+    }
+}
+
+/// Leader-side AI driver for the NPCs of one world
+pub struct NpcDriver {
+    /// World authority (signs and pays for NPC transactions)
+    authority: Keypair,
+    /// World the NPCs live in
+    world: Pubkey,
+    /// Driven NPCs
+    npcs: Vec<(NpcSpawn, Box<dyn NpcBehavior>)>,
+}
+
+impl NpcDriver {
+    /// Create a driver for `world`
+    pub fn new(authority: Keypair, world: Pubkey) -> Self {
+        Self {
+            authority,
+            world,
+            npcs: Vec::new(),
+        }
+    }
+
+    /// Add an NPC, spawned on the first tick it is missing
+    pub fn add_npc(&mut self, spawn: NpcSpawn, behavior: Box<dyn NpcBehavior>) {
+        self.npcs.push((spawn, behavior));
+    }
+
+    /// Number of driven NPCs
+    pub fn npc_count(&self) -> usize {
+        self.npcs.len()
+    }
+
+    /// Build this tick's NPC transactions (one per NPC that needs to act)
+    pub fn build_transactions(
+        &self,
+        slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let mut players = Vec::new();
+        let mut npc_accounts = HashMap::new();
+        for (key, account) in store.get_program_accounts(&program_id) {
+            match account.data().len() {
+                WorldPlayer::LEN => {
+                    if let Ok(player) = WorldPlayer::try_from_slice(account.data()) {
+                        if player.world == self.world {
+                            players.push((key, player));
+                        }
+                    }
+                }
+                WorldNpc::LEN => {
+                    if let Ok(npc) = WorldNpc::try_from_slice(account.data()) {
+                        npc_accounts.insert(key, npc);
+                    }
+                }
+                _ => {}
+            }
+        }
+        players.sort_by_key(|(key, _)| *key);
+
+        // One attacker per player per tick - hook transactions share a batch
+        let mut targeted = HashSet::new();
+        let mut txs = Vec::new();
+
+        for (spawn, behavior) in &self.npcs {
+            let (npc_pda, _) = WorldNpc::derive_pda(&self.world, spawn.npc_id, &program_id);
+
+            let Some(npc) = npc_accounts.get(&npc_pda) else {
+                txs.push(self.sign(self.spawn_instruction(npc_pda, spawn), blockhash));
+                continue;
+            };
+
+            if !npc.is_alive() {
+                if npc.can_respawn(slot) {
+                    txs.push(self.sign(self.act_instruction(npc_pda, NpcAction::Idle, None), blockhash));
+                }
+                continue;
+            }
+
+            // Hit-stunned NPCs can't act, their knockback just plays out
+            let command = if npc.body.is_stunned(slot) {
+                NpcCommand::Idle
+            } else {
+                behavior.decide(npc, &players, slot)
+            };
+            let ix = match command {
+                NpcCommand::Attack(target) if targeted.insert(target) => {
+                    self.act_instruction(npc_pda, NpcAction::Attack, Some(target))
+                }
+                NpcCommand::Move { move_x, move_z } => {
+                    let input = MovementInput3D {
+                        move_x,
+                        move_z,
+                        ..Default::default()
+                    };
+                    self.act_instruction(npc_pda, NpcAction::Move { input }, None)
+                }
+                // Idle (or target taken): only tick physics while still moving
+                _ => {
+                    let body = &npc.body;
+                    if body.velocity_x == 0 && body.velocity_z == 0 && body.is_grounded {
+                        continue;
+                    }
+                    let input = MovementInput3D::default();
+                    self.act_instruction(npc_pda, NpcAction::Move { input }, None)
+                }
+            };
+            txs.push(self.sign(ix, blockhash));
+        }
+
+        txs
+    }
+
+    fn spawn_instruction(&self, npc_pda: Pubkey, spawn: &NpcSpawn) -> Instruction {
+        Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::SpawnNpc {
+                npc_id: spawn.npc_id,
+                kind: spawn.kind,
+                name: spawn.name,
+                max_health: spawn.max_health,
+                damage: spawn.damage,
+                attack_range: spawn.attack_range,
+                spawn_x: spawn.spawn_x,
+                spawn_z: spawn.spawn_z,
+                respawn_delay_slots: spawn.respawn_delay_slots,
+                loot_amount: spawn.loot_amount,
+            },
+            vec![
+                AccountMeta::new_readonly(self.world, false),
+                AccountMeta::new(npc_pda, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    }
+
+    fn act_instruction(&self, npc_pda: Pubkey, action: NpcAction, target: Option<Pubkey>) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new(npc_pda, false),
+            AccountMeta::new_readonly(self.authority.pubkey(), true),
+        ];
+        if let Some(target) = target {
+            accounts.push(AccountMeta::new(target, false));
+        }
+
+        Instruction::new_with_borsh(world_program::id(), &WorldInstruction::NpcAct { action }, accounts)
+    }
+
+    fn sign(&self, ix: Instruction, blockhash: Hash) -> SanitizedTransaction {
+        let message = Message::new(&[ix], Some(&self.authority.pubkey()));
+        let tx = Transaction::new(&[&self.authority], message, blockhash);
+        SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+            .expect("NPC transaction is well-formed")
+    }
+}
+
+impl TickHook for NpcDriver {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use world_program::constants::FIXED_POINT_SCALE;
+
+    fn npc_at(x: i32, z: i32) -> WorldNpc {
+        let mut npc = WorldNpc {
+            attack_range: 2 * FIXED_POINT_SCALE as u32,
+            ..Default::default()
+        };
+        npc.body.position_x = x;
+        npc.body.position_z = z;
+        npc.body.health = 100;
+        npc.body.max_health = 100;
+        npc
+    }
+
+    fn player_at(x: i32, z: i32) -> (Pubkey, WorldPlayer) {
+        let player = WorldPlayer {
+            position_x: x,
+            position_z: z,
+            health: 100,
+            max_health: 100,
+            ..Default::default()
+        };
+        (Pubkey::new_unique(), player)
+    }
+
+    #[test]
+    fn test_chase_attacks_player_in_range() {
+        let behavior = ChaseBehavior { aggro_range: 20_000 };
+        let npc = npc_at(10_000, 10_000);
+        let players = vec![player_at(11_000, 10_000)];
+
+        assert_eq!(behavior.decide(&npc, &players, 0), NpcCommand::Attack(players[0].0));
+    }
+
+    #[test]
+    fn test_chase_moves_toward_nearest_player() {
+        let behavior = ChaseBehavior { aggro_range: 20_000 };
+        let npc = npc_at(10_000, 10_000);
+        let players = vec![player_at(10_000, 25_000), player_at(4_000, 10_000)];
+
+        // Nearest is due -X
+        assert_eq!(
+            behavior.decide(&npc, &players, 0),
+            NpcCommand::Move { move_x: -127, move_z: 0 }
+        );
+    }
+
+    #[test]
+    fn test_chase_ignores_dead_and_distant_players() {
+        let behavior = ChaseBehavior { aggro_range: 5_000 };
+        let npc = npc_at(10_000, 10_000);
+        let mut dead = player_at(10_500, 10_000);
+        dead.1.health = 0;
+        let players = vec![dead, player_at(30_000, 30_000)];
+
+        assert_eq!(behavior.decide(&npc, &players, 0), NpcCommand::Idle);
+    }
+
+    #[test]
+    fn test_patrol_cycles_waypoints_by_slot() {
+        let behavior = PatrolBehavior {
+            waypoints: vec![(20_000, 10_000), (10_000, 20_000)],
+            slots_per_waypoint: 100,
+        };
+        let npc = npc_at(10_000, 10_000);
+
+        assert_eq!(behavior.decide(&npc, &[], 0), NpcCommand::Move { move_x: 127, move_z: 0 });
+        assert_eq!(behavior.decide(&npc, &[], 150), NpcCommand::Move { move_x: 0, move_z: 127 });
+        assert_eq!(behavior.decide(&npc, &[], 250), NpcCommand::Move { move_x: 127, move_z: 0 });
+    }
+
+    #[test]
+    fn test_behaviors_are_deterministic() {
+        let behavior = ChaseBehavior { aggro_range: 50_000 };
+        let npc = npc_at(10_000, 10_000);
+        // Equidistant players: tie broken by pubkey
+        let players = vec![player_at(11_000, 10_000), player_at(9_000, 10_000)];
+        let expected = players.iter().map(|(k, _)| *k).min().unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(behavior.decide(&npc, &players, 7), NpcCommand::Attack(expected));
+        }
+    }
+}
@@ -1,26 +1,44 @@
 //! Persistence Layer for L2 State
 //!
 //! Uses sled embedded database to persist account state across restarts.
-//! State is saved periodically and on shutdown.
+//! Every block's account changes are appended to a write-ahead log as it is
+//! produced. Periodic checkpoints write only the accounts changed since the
+//! last one, then drop the log up to that point. On startup, the log is
+//! replayed over the last checkpoint, so a crash loses at most one tick.
+//!
+//! The block producer captures each checkpoint between blocks, so it holds
+//! exactly the state its metadata describes, and a `CheckpointWriter` thread
+//! writes it to disk off the block loop.
 
 use serde::{Deserialize, Serialize};
 use sled::Db;
 use solana_sdk::{
-    account::AccountSharedData,
+    account::{AccountSharedData, ReadableAccount},
     clock::Slot,
-    hash::Hash,
     pubkey::Pubkey,
 };
-use std::path::Path;
+use crossbeam_channel::{unbounded, Sender};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use crate::{account_store::AccountStore, block_producer::BlockUpdate};
 
 /// Metadata about the chain state
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ChainMetadata {
     /// Current slot (block height)
     pub slot: Slot,
     /// Current blockhash
     pub blockhash: [u8; 32],
-    /// Current epoch
+    /// Account state root committed to by `blockhash`
+    pub state_root: [u8; 32],
+    /// Epoch of `slot` (see `SLOTS_PER_EPOCH`)
     pub epoch: u64,
     /// Total accounts stored
     pub account_count: u64,
@@ -28,18 +46,55 @@ pub struct ChainMetadata {
     pub last_save_ts: i64,
 }
 
-impl Default for ChainMetadata {
-    fn default() -> Self {
+/// Accounts one block changed, as logged ahead of the next checkpoint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SlotDelta {
+    /// Next slot to execute after the block (as in `BlockUpdate::slot`)
+    pub slot: Slot,
+    /// Hash of the block
+    pub blockhash: [u8; 32],
+    /// Account state root after the block
+    pub state_root: [u8; 32],
+    /// Accounts as the block left them (zero lamports = removed)
+    pub accounts: Vec<(Pubkey, AccountSharedData)>,
+}
+
+impl SlotDelta {
+    /// The changes a produced block made
+    pub fn from_update(update: &BlockUpdate) -> Self {
         Self {
-            slot: 0,
-            blockhash: [0u8; 32],
-            epoch: 0,
-            account_count: 0,
-            last_save_ts: 0,
+            slot: update.slot,
+            blockhash: update.blockhash.to_bytes(),
+            state_root: update.state_root.to_bytes(),
+            accounts: update.modified_accounts.clone(),
         }
     }
 }
 
+/// Accounts changed since the last checkpoint, captured at a block boundary
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// Chain position the accounts are at
+    pub metadata: ChainMetadata,
+    /// Changed accounts with the slot they were written in (`None` = removed)
+    pub accounts: Vec<(Pubkey, Option<(AccountSharedData, Slot)>)>,
+}
+
+impl Checkpoint {
+    /// Take the accounts changed since the last capture
+    ///
+    /// Nothing may execute on `store` meanwhile, or the checkpoint could hold
+    /// writes past `metadata`.
+    pub fn capture(store: &AccountStore, metadata: ChainMetadata) -> Self {
+        let accounts = store
+            .take_dirty_accounts()
+            .into_iter()
+            .map(|pubkey| (pubkey, store.get_account_with_slot(&pubkey)))
+            .collect();
+        Self { metadata, accounts }
+    }
+}
+
 /// Persistent storage for L2 state
 pub struct PersistentStore {
     /// Sled database instance
@@ -50,6 +105,8 @@ pub struct PersistentStore {
     account_slots: sled::Tree,
     /// Metadata tree
     metadata: sled::Tree,
+    /// Write-ahead log of slot deltas, keyed by big-endian slot
+    wal: sled::Tree,
 }
 
 impl PersistentStore {
@@ -59,6 +116,7 @@ impl PersistentStore {
         let accounts = db.open_tree("accounts")?;
         let account_slots = db.open_tree("account_slots")?;
         let metadata = db.open_tree("metadata")?;
+        let wal = db.open_tree("wal")?;
 
         tracing::info!("Opened persistent store at {:?}", path.as_ref());
 
@@ -67,6 +125,7 @@ impl PersistentStore {
             accounts,
             account_slots,
             metadata,
+            wal,
         })
     }
 
@@ -167,6 +226,79 @@ impl PersistentStore {
         }
     }
 
+    /// Append a block's changes to the write-ahead log
+    ///
+    /// The entry is on disk when this returns.
+    pub fn append_delta(&self, delta: &SlotDelta) -> anyhow::Result<()> {
+        self.wal.insert(delta.slot.to_be_bytes(), bincode::serialize(delta)?)?;
+        self.wal.flush()?;
+        Ok(())
+    }
+
+    /// Logged deltas after `slot`, in slot order
+    pub fn wal_deltas(&self, slot: Slot) -> anyhow::Result<Vec<SlotDelta>> {
+        let start = slot.saturating_add(1).to_be_bytes();
+        self.wal
+            .range(start..)
+            .map(|entry| Ok(bincode::deserialize(&entry?.1)?))
+            .collect()
+    }
+
+    /// Drop logged deltas up to and including `slot`, returning how many
+    pub fn truncate_wal(&self, slot: Slot) -> anyhow::Result<usize> {
+        let end = slot.saturating_add(1).to_be_bytes();
+        let mut removed = 0;
+        for entry in self.wal.range(..end) {
+            let (key, _) = entry?;
+            self.wal.remove(key)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Drop logged deltas after `slot`, returning how many
+    pub fn discard_wal_after(&self, slot: Slot) -> anyhow::Result<usize> {
+        let start = slot.saturating_add(1).to_be_bytes();
+        let mut removed = 0;
+        for entry in self.wal.range(start..) {
+            let (key, _) = entry?;
+            self.wal.remove(key)?;
+            removed += 1;
+        }
+        self.wal.flush()?;
+        Ok(removed)
+    }
+
+    /// Write a checkpoint's accounts and metadata, then drop the log it covers
+    ///
+    /// Returns the number of accounts written.
+    pub fn write_checkpoint(&self, checkpoint: &Checkpoint) -> anyhow::Result<usize> {
+        for (pubkey, account) in &checkpoint.accounts {
+            match account {
+                Some((account, slot)) => self.store_account(pubkey, account, *slot)?,
+                None => self.remove_account(pubkey)?,
+            }
+        }
+        self.save_metadata(&checkpoint.metadata)?;
+        self.flush()?;
+        // Only drop the log once the state it covers is on disk
+        self.truncate_wal(checkpoint.metadata.slot)?;
+        Ok(checkpoint.accounts.len())
+    }
+
+    /// Drop every logged delta, returning how many
+    pub fn clear_wal(&self) -> anyhow::Result<usize> {
+        let removed = self.wal.len();
+        self.wal.clear()?;
+        self.wal.flush()?;
+        Ok(removed)
+    }
+
+    /// Number of logged deltas
+    pub fn wal_len(&self) -> usize {
+        self.wal.len()
+    }
+
     /// Flush all pending writes to disk
     pub fn flush(&self) -> anyhow::Result<()> {
         self.db.flush()?;
@@ -183,36 +315,52 @@ impl PersistentStore {
         self.accounts.clear()?;
         self.account_slots.clear()?;
         self.metadata.clear()?;
+        self.wal.clear()?;
         Ok(())
     }
 }
 
 /// Extension trait to add persistence to AccountStore
 pub trait AccountStorePersistence {
-    /// Save all accounts to persistent storage
+    /// Save accounts changed since the last save to persistent storage
     fn save_to_disk(&self, store: &PersistentStore) -> anyhow::Result<usize>;
 
+    /// Save changed accounts and metadata, then drop the log they cover
+    fn checkpoint(&self, store: &PersistentStore, metadata: &ChainMetadata) -> anyhow::Result<usize>;
+
     /// Load all accounts from persistent storage
     fn load_from_disk(&self, store: &PersistentStore) -> anyhow::Result<usize>;
+
+    /// Apply logged deltas past the last checkpoint
+    ///
+    /// Only consecutive slots are applied: replay stops at the first missing
+    /// slot and discards the deltas after it. Returns the chain metadata after
+    /// the last applied delta, or `None` if the log had nothing to replay.
+    fn replay_wal(&self, store: &PersistentStore) -> anyhow::Result<Option<ChainMetadata>>;
 }
 
 impl AccountStorePersistence for crate::AccountStore {
     fn save_to_disk(&self, store: &PersistentStore) -> anyhow::Result<usize> {
-        let mut count = 0;
+        let dirty = self.take_dirty_accounts();
+        let count = dirty.len();
 
-        for pubkey in self.get_all_pubkeys() {
-            if let Some((account, slot)) = self.get_account_with_slot(&pubkey) {
-                store.store_account(&pubkey, &account, slot)?;
-                count += 1;
+        for pubkey in dirty {
+            match self.get_account_with_slot(&pubkey) {
+                Some((account, slot)) => store.store_account(&pubkey, &account, slot)?,
+                None => store.remove_account(&pubkey)?,
             }
         }
 
         store.flush()?;
-        tracing::info!("Saved {} accounts to disk", count);
+        tracing::info!("Saved {} changed accounts to disk", count);
 
         Ok(count)
     }
 
+    fn checkpoint(&self, store: &PersistentStore, metadata: &ChainMetadata) -> anyhow::Result<usize> {
+        store.write_checkpoint(&Checkpoint::capture(self, metadata.clone()))
+    }
+
     fn load_from_disk(&self, store: &PersistentStore) -> anyhow::Result<usize> {
         let accounts = store.get_all_accounts()?;
         let count = accounts.len();
@@ -220,11 +368,145 @@ impl AccountStorePersistence for crate::AccountStore {
         for (pubkey, account, slot) in accounts {
             self.store_account(pubkey, account, slot);
         }
+        // What was just loaded is already on disk
+        self.take_dirty_accounts();
 
         tracing::info!("Loaded {} accounts from disk", count);
 
         Ok(count)
     }
+
+    fn replay_wal(&self, store: &PersistentStore) -> anyhow::Result<Option<ChainMetadata>> {
+        let mut metadata = store.load_metadata()?.unwrap_or_default();
+        let mut deltas = store.wal_deltas(metadata.slot)?;
+
+        // A delta only applies on top of the slot before it
+        let contiguous = deltas
+            .iter()
+            .zip(metadata.slot + 1..)
+            .take_while(|(delta, slot)| delta.slot == *slot)
+            .count();
+        if contiguous < deltas.len() {
+            let resume_slot = metadata.slot + contiguous as u64;
+            let discarded = store.discard_wal_after(resume_slot)?;
+            tracing::warn!(
+                "Write-ahead log is missing slot {}, discarded {} logged slots after it",
+                resume_slot + 1,
+                discarded
+            );
+            deltas.truncate(contiguous);
+        }
+
+        let Some(last) = deltas.last() else {
+            return Ok(None);
+        };
+        let (slot, blockhash, state_root) = (last.slot, last.blockhash, last.state_root);
+
+        for delta in &deltas {
+            let executed_slot = delta.slot.saturating_sub(1);
+            for (pubkey, account) in &delta.accounts {
+                // Same rule as write-back: drained accounts are removed
+                if account.lamports() == 0 {
+                    self.remove_account(pubkey);
+                } else {
+                    self.store_account(*pubkey, account.clone(), executed_slot);
+                }
+            }
+        }
+        tracing::info!(
+            "Replayed {} logged slots past checkpoint {} up to slot {}",
+            deltas.len(),
+            metadata.slot,
+            slot
+        );
+
+        metadata.slot = slot;
+        metadata.blockhash = blockhash;
+        metadata.state_root = state_root;
+        metadata.account_count = self.len() as u64;
+        Ok(Some(metadata))
+    }
+}
+
+/// Asks the producer feeding a `CheckpointWriter` to checkpoint at the next block
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointRequest(Arc<AtomicBool>);
+
+impl CheckpointRequest {
+    /// Checkpoint at the next block boundary, whatever the schedule
+    pub fn request(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Writes captured checkpoints to a `PersistentStore` on a background thread
+///
+/// Checkpoints are queued, never dropped (each holds the only copy of its
+/// changed-account set), and written in order. Dropping the writer waits
+/// for the queue to drain.
+pub struct CheckpointWriter {
+    sender: Option<Sender<Checkpoint>>,
+    handle: Option<JoinHandle<()>>,
+    interval_slots: u64,
+    request: CheckpointRequest,
+}
+
+impl CheckpointWriter {
+    /// Start the writer thread, checkpointing every `interval_slots` slots (0 = only on request)
+    pub fn spawn(store: Arc<PersistentStore>, interval_slots: u64) -> Self {
+        let (sender, receiver) = unbounded::<Checkpoint>();
+        let handle = std::thread::Builder::new()
+            .name("checkpoint-writer".to_string())
+            .spawn(move || {
+                for checkpoint in receiver {
+                    let slot = checkpoint.metadata.slot;
+                    match store.write_checkpoint(&checkpoint) {
+                        Ok(saved) => tracing::info!("Checkpointed slot {} ({} changed accounts)", slot, saved),
+                        Err(e) => tracing::error!("Failed to checkpoint slot {}: {}", slot, e),
+                    }
+                }
+            })
+            .expect("failed to spawn checkpoint writer thread");
+
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+            interval_slots,
+            request: CheckpointRequest::default(),
+        }
+    }
+
+    /// Handle for requesting an unscheduled checkpoint
+    pub fn request(&self) -> CheckpointRequest {
+        self.request.clone()
+    }
+
+    /// Whether to checkpoint once the chain reaches `slot`
+    ///
+    /// Consumes a pending request.
+    pub fn is_due(&self, slot: Slot) -> bool {
+        let requested = self.request.0.swap(false, Ordering::SeqCst);
+        requested || (self.interval_slots > 0 && slot.is_multiple_of(self.interval_slots))
+    }
+
+    /// Hand a captured checkpoint to the writer thread
+    pub fn submit(&self, checkpoint: Checkpoint) {
+        if let Some(sender) = &self.sender {
+            if sender.send(checkpoint).is_err() {
+                tracing::error!("Checkpoint writer stopped, checkpoint not written");
+            }
+        }
+    }
+}
+
+impl Drop for CheckpointWriter {
+    /// Finish writing every submitted checkpoint before going away
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -264,6 +546,7 @@ mod tests {
         let metadata = ChainMetadata {
             slot: 1000,
             blockhash: [42u8; 32],
+            state_root: [7u8; 32],
             epoch: 5,
             account_count: 100,
             last_save_ts: 12345,
@@ -273,6 +556,91 @@ mod tests {
 
         let loaded = store.load_metadata().unwrap().unwrap();
         assert_eq!(loaded.slot, 1000);
+        assert_eq!(loaded.state_root, [7u8; 32]);
         assert_eq!(loaded.epoch, 5);
     }
+
+    fn delta(slot: Slot, accounts: Vec<(Pubkey, AccountSharedData)>) -> SlotDelta {
+        SlotDelta {
+            slot,
+            blockhash: [slot as u8; 32],
+            state_root: [slot as u8 + 1; 32],
+            accounts,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_saves_changed_accounts() {
+        let dir = tempdir().unwrap();
+        let store = PersistentStore::open(dir.path()).unwrap();
+        let accounts = crate::AccountStore::new();
+        let kept = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        accounts.store_account(kept, AccountSharedData::new(10, 0, &Pubkey::new_unique()), 1);
+        accounts.store_account(removed, AccountSharedData::new(10, 0, &Pubkey::new_unique()), 1);
+        assert_eq!(accounts.save_to_disk(&store).unwrap(), 2);
+        assert_eq!(accounts.save_to_disk(&store).unwrap(), 0);
+
+        store.append_delta(&delta(2, vec![])).unwrap();
+        store.append_delta(&delta(3, vec![])).unwrap();
+        accounts.remove_account(&removed);
+        let metadata = ChainMetadata {
+            slot: 2,
+            ..Default::default()
+        };
+        assert_eq!(accounts.checkpoint(&store, &metadata).unwrap(), 1);
+        assert!(store.get_account(&kept).unwrap().is_some());
+        assert!(store.get_account(&removed).unwrap().is_none());
+        // The log past the checkpoint is kept
+        assert_eq!(store.wal_len(), 1);
+        assert_eq!(store.wal_deltas(0).unwrap(), vec![delta(3, vec![])]);
+    }
+
+    #[test]
+    fn test_replay_wal_after_checkpoint() {
+        let dir = tempdir().unwrap();
+        let store = PersistentStore::open(dir.path()).unwrap();
+        let owner = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let loot = Pubkey::new_unique();
+
+        let accounts = crate::AccountStore::new();
+        accounts.store_account(player, AccountSharedData::new(10, 4, &owner), 4);
+        let checkpoint = ChainMetadata {
+            slot: 5,
+            epoch: 1,
+            ..Default::default()
+        };
+        accounts.checkpoint(&store, &checkpoint).unwrap();
+
+        // Logged but never checkpointed: one block moves the player and drops
+        // loot, the next picks the loot up
+        store.append_delta(&delta(5, vec![(player, AccountSharedData::new(99, 4, &owner))])).unwrap();
+        store
+            .append_delta(&delta(
+                6,
+                vec![
+                    (player, AccountSharedData::new(11, 4, &owner)),
+                    (loot, AccountSharedData::new(5, 0, &owner)),
+                ],
+            ))
+            .unwrap();
+        store.append_delta(&delta(7, vec![(loot, AccountSharedData::new(0, 0, &owner))])).unwrap();
+
+        let restored = crate::AccountStore::new();
+        restored.load_from_disk(&store).unwrap();
+        let metadata = restored.replay_wal(&store).unwrap().unwrap();
+        assert_eq!(metadata.slot, 7);
+        assert_eq!(metadata.blockhash, [7u8; 32]);
+        assert_eq!(metadata.state_root, [8u8; 32]);
+        assert_eq!(metadata.epoch, 1);
+        assert_eq!(metadata.account_count, 1);
+        // The delta at the checkpoint slot was already covered by it
+        let (account, slot) = restored.get_account_with_slot(&player).unwrap();
+        assert_eq!(account.lamports(), 11);
+        assert_eq!(slot, 5);
+        assert!(!restored.account_exists(&loot));
+        // Replayed accounts aren't on disk yet
+        assert_eq!(restored.dirty_account_count(), 2);
+    }
 }
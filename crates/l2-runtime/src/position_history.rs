@@ -0,0 +1,219 @@
+//! Position History Recorder
+//!
+//! Attacks are checked against where their target stood when the attacker
+//! saw it, which the world program reads from the world's `PositionHistory`
+//! account. Recording positions from the movement instructions themselves
+//! would make every move in a world write that one account and serialize
+//! the world's moves into a single batch. Instead the processor notes which
+//! players and NPCs each slot changed and records where they stand when it
+//! seals the slot, before the state root is taken.
+
+use std::collections::BTreeMap;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    clock::Slot,
+    pubkey::Pubkey,
+};
+use world_program::state::{PositionHistory, PositionSample, WorldNpc, WorldPlayer};
+
+use crate::{account_store::AccountStore, callback::AUTO_CREATE_LAMPORTS};
+
+/// Collects the players and NPCs a slot changed and records their positions
+#[derive(Debug, Default)]
+pub struct PositionRecorder {
+    /// Accounts changed this slot, with the account as the slot found it
+    changed: BTreeMap<Pubkey, Option<AccountSharedData>>,
+}
+
+impl PositionRecorder {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note an account a transaction wrote, with what it held before
+    ///
+    /// Only the first write of a slot keeps its `before`: that's where the
+    /// entity stood until this slot.
+    pub fn note(&mut self, pubkey: Pubkey, account: &AccountSharedData, before: Option<AccountSharedData>) {
+        if entity_body(account).is_some() {
+            self.changed.entry(pubkey).or_insert(before);
+        }
+    }
+
+    /// Forget the accounts noted so far (e.g. when resuming a chain)
+    pub fn clear(&mut self) {
+        self.changed.clear();
+    }
+
+    /// Record where this slot's changed players and NPCs stand from `slot` on
+    ///
+    /// Returns the history accounts written, in key order.
+    pub fn seal(&mut self, store: &AccountStore, slot: Slot) -> Vec<(Pubkey, AccountSharedData)> {
+        let program_id = world_program::id();
+        // `None` for worlds whose history PDA holds something else
+        let mut histories: BTreeMap<Pubkey, Option<PositionHistory>> = BTreeMap::new();
+
+        for (pubkey, before) in std::mem::take(&mut self.changed) {
+            let Some(body) = store.get_account(&pubkey).as_ref().and_then(entity_body) else {
+                continue;
+            };
+            let (history_key, _) = PositionHistory::derive_pda(&body.world, &program_id);
+            let history = histories
+                .entry(history_key)
+                .or_insert_with(|| load_history(store, &history_key, body.world));
+            let Some(history) = history else {
+                continue;
+            };
+
+            // An entity without samples hasn't moved within the history, so
+            // it stood where this slot found it since before the history began
+            if !history.samples.iter().any(|sample| sample.entity == pubkey) {
+                if let Some(before) = before.as_ref().and_then(entity_body) {
+                    if before.world == body.world {
+                        history.record(PositionSample::of(pubkey, &before, 0));
+                    }
+                }
+            }
+            history.record(PositionSample::of(pubkey, &body, slot));
+        }
+
+        let mut written = Vec::new();
+        for (history_key, history) in histories {
+            let Some(mut history) = history else {
+                tracing::warn!("Position history {} is not a history account, skipping", history_key);
+                continue;
+            };
+            history.prune(slot);
+            let data = borsh::to_vec(&history).expect("position history serializes");
+            let mut account = store
+                .get_account(&history_key)
+                .unwrap_or_else(|| AccountSharedData::new(AUTO_CREATE_LAMPORTS, 0, &program_id));
+            if account.data() == data.as_slice() {
+                continue;
+            }
+            account.set_data_from_slice(&data);
+            store.store_account(history_key, account.clone(), slot);
+            written.push((history_key, account));
+        }
+        written
+    }
+}
+
+/// The player, or NPC body, an account holds
+fn entity_body(account: &AccountSharedData) -> Option<WorldPlayer> {
+    if *account.owner() != world_program::id() {
+        return None;
+    }
+    match account.data().len() {
+        WorldPlayer::LEN => WorldPlayer::try_from_slice(account.data()).ok(),
+        WorldNpc::LEN => WorldNpc::try_from_slice(account.data()).ok().map(|npc| npc.body),
+        _ => None,
+    }
+}
+
+/// Load a world's history, or start one if it doesn't exist yet
+fn load_history(store: &AccountStore, history_key: &Pubkey, world: Pubkey) -> Option<PositionHistory> {
+    let Some(account) = store.get_account(history_key) else {
+        return Some(PositionHistory::new(world));
+    };
+    if *account.owner() != world_program::id() {
+        return None;
+    }
+    PositionHistory::try_from_slice(account.data())
+        .ok()
+        .filter(|history| history.world == world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use world_program::constants::POSITION_HISTORY_SLOTS;
+
+    fn player_account(player: &WorldPlayer) -> AccountSharedData {
+        let data = borsh::to_vec(player).unwrap();
+        let mut account = AccountSharedData::new(1_000_000, data.len(), &world_program::id());
+        account.set_data_from_slice(&data);
+        account
+    }
+
+    /// Move `player` to `position_x` in `slot` and seal it
+    fn step(
+        recorder: &mut PositionRecorder,
+        store: &AccountStore,
+        key: Pubkey,
+        player: &mut WorldPlayer,
+        position_x: i32,
+        slot: Slot,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        let before = store.get_account(&key);
+        player.position_x = position_x;
+        let account = player_account(player);
+        store.store_account(key, account.clone(), slot);
+        recorder.note(key, &account, before);
+        recorder.seal(store, slot)
+    }
+
+    fn history(store: &AccountStore, world: &Pubkey) -> PositionHistory {
+        let (history_key, _) = PositionHistory::derive_pda(world, &world_program::id());
+        PositionHistory::try_from_slice(store.get_account(&history_key).unwrap().data()).unwrap()
+    }
+
+    #[test]
+    fn test_seal_records_where_moved_players_stood() {
+        let store = AccountStore::new();
+        let mut recorder = PositionRecorder::new();
+        let world = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut player = WorldPlayer {
+            world,
+            position_x: 100,
+            ..Default::default()
+        };
+        store.store_account(key, player_account(&player), 0);
+
+        let written = step(&mut recorder, &store, key, &mut player, 200, 5);
+        assert_eq!(written.len(), 1);
+        let slots: Vec<_> = history(&store, &world).samples.iter().map(|sample| (sample.slot, sample.position_x)).collect();
+        // The first move also records where the player stood before it
+        assert_eq!(slots, vec![(0, 100), (5, 200)]);
+
+        // Nothing moved: nothing written
+        assert!(recorder.seal(&store, 6).is_empty());
+        // Accounts that aren't players or NPCs are ignored
+        let other = AccountSharedData::new(1, 8, &world_program::id());
+        recorder.note(Pubkey::new_unique(), &other, None);
+        assert!(recorder.seal(&store, 7).is_empty());
+    }
+
+    #[test]
+    fn test_history_keeps_only_reachable_samples() {
+        let store = AccountStore::new();
+        let mut recorder = PositionRecorder::new();
+        let world = Pubkey::new_unique();
+        let (runner, idler) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut runner_body = WorldPlayer {
+            world,
+            ..Default::default()
+        };
+        let mut idler_body = runner_body.clone();
+        store.store_account(runner, player_account(&runner_body), 0);
+        store.store_account(idler, player_account(&idler_body), 0);
+
+        step(&mut recorder, &store, idler, &mut idler_body, 7, 1);
+        for slot in 1..=30 {
+            step(&mut recorder, &store, runner, &mut runner_body, slot as i32 * 10, slot);
+        }
+
+        let history = history(&store, &world);
+        // The idler left the window, the runner keeps its window plus the sample before it
+        assert!(history.samples.iter().all(|sample| sample.entity == runner));
+        assert_eq!(history.samples.len(), POSITION_HISTORY_SLOTS as usize + 1);
+        assert_eq!(history.rewound(&runner, &runner_body, 25).position_x, 250);
+        assert_eq!(history.rewound(&runner, &runner_body, 3).position_x, 200);
+        // Without samples the body is where it is now
+        assert_eq!(history.rewound(&idler, &idler_body, 3).position_x, 7);
+    }
+}
@@ -3,20 +3,26 @@
 //! Wraps the solana-svm TransactionBatchProcessor to provide
 //! transaction execution for the L2 gaming chain.
 
-use crate::{account_store::AccountStore, callback::L2AccountLoader};
+use crate::{
+    account_store::AccountStore,
+    callback::{AutoCreateRegistry, L2AccountLoader},
+    persistence::ChainMetadata,
+    position_history::PositionRecorder,
+    scheduler, SLOTS_PER_EPOCH,
+};
 use solana_compute_budget::compute_budget::ComputeBudget;
 use solana_program_runtime::{
     invoke_context::BuiltinFunctionWithContext,
     loaded_programs::{BlockRelation, ForkGraph, ProgramCacheEntry, ProgramCacheEntryType},
 };
 use solana_sdk::{
-    account::{Account, AccountSharedData},
+    account::{Account, AccountSharedData, ReadableAccount},
     bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable,
     clock::{Clock, Slot},
     epoch_schedule::EpochSchedule,
     feature_set::FeatureSet,
     fee::FeeStructure,
-    hash::Hash,
+    hash::{Hash, Hasher},
     native_loader,
     pubkey::Pubkey,
     rent::Rent,
@@ -67,6 +73,38 @@ impl ForkGraph for L2ForkGraph {
     }
 }
 
+/// Blockhash the chain starts from (parent of the first block)
+pub const GENESIS_BLOCKHASH: Hash = Hash::new_from_array([0; 32]);
+
+/// Hash of a block, chained to its parent
+///
+/// Commits to the parent blockhash, the slot, the block's transaction
+/// signatures in execution order and the account state root after the block,
+/// so anyone holding the same blocks and state can recompute it.
+pub fn derive_blockhash(parent: &Hash, slot: Slot, signatures: &[Signature], state_root: &Hash) -> Hash {
+    let mut hasher = Hasher::default();
+    hasher.hash(parent.as_ref());
+    hasher.hash(&slot.to_le_bytes());
+    hasher.hash(&(signatures.len() as u64).to_le_bytes());
+    for signature in signatures {
+        hasher.hash(signature.as_ref());
+    }
+    hasher.hash(state_root.as_ref());
+    hasher.result()
+}
+
+/// Fixed-length epochs without warmup, so the epoch is the slot over `SLOTS_PER_EPOCH`
+fn epoch_schedule() -> EpochSchedule {
+    EpochSchedule::custom(SLOTS_PER_EPOCH, SLOTS_PER_EPOCH, false)
+}
+
+/// Whether execution changed an account's lamports, owner or data
+fn account_changed(before: &AccountSharedData, after: &AccountSharedData) -> bool {
+    before.lamports() != after.lamports()
+        || before.owner() != after.owner()
+        || before.data() != after.data()
+}
+
 /// Result of processing a single transaction
 #[derive(Debug, Clone)]
 pub struct TransactionResult {
@@ -91,14 +129,24 @@ pub struct L2Processor {
     current_slot: Slot,
     /// Current epoch
     current_epoch: u64,
-    /// Current blockhash
+    /// Current blockhash (hash of the last sealed block)
     current_blockhash: Hash,
+    /// Blockhash of the block before the last sealed one
+    parent_blockhash: Hash,
+    /// Account state root after the last sealed block
+    state_root: Hash,
+    /// Signatures of the transactions processed in the current slot, in order
+    block_signatures: Vec<Signature>,
     /// Feature set (all enabled for L2)
     feature_set: Arc<FeatureSet>,
-    /// Builtin program IDs
-    builtin_program_ids: HashSet<Pubkey>,
     /// Fork graph for program cache
     fork_graph: Arc<RwLock<L2ForkGraph>>,
+    /// Worker threads a conflict-free batch is spread over
+    execution_threads: usize,
+    /// Rules for synthesizing accounts missing from the store
+    auto_create: Arc<AutoCreateRegistry>,
+    /// Players and NPCs the current slot changed, for the position history
+    position_recorder: PositionRecorder,
 }
 
 impl L2Processor {
@@ -181,10 +229,15 @@ impl L2Processor {
             account_store,
             current_slot: slot,
             current_epoch: epoch,
-            current_blockhash: Hash::new_unique(),
+            current_blockhash: GENESIS_BLOCKHASH,
+            parent_blockhash: GENESIS_BLOCKHASH,
+            state_root: Hash::default(),
+            block_signatures: Vec::new(),
             feature_set,
-            builtin_program_ids,
             fork_graph,
+            execution_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            auto_create: Arc::new(AutoCreateRegistry::world_program()),
+            position_recorder: PositionRecorder::new(),
         };
 
         // Register builtin programs
@@ -223,6 +276,18 @@ impl L2Processor {
         this
     }
 
+    /// Create a processor that continues a persisted chain
+    ///
+    /// Slot, blockhash and the Clock sysvar pick up from `metadata` (`slot`
+    /// is the next slot to execute); the epoch follows from the slot. Load
+    /// the chain's accounts into the store first: the state root is taken
+    /// from it.
+    pub fn from_metadata(account_store: Arc<AccountStore>, metadata: &ChainMetadata) -> Self {
+        let mut this = Self::new(account_store);
+        this.resume(metadata.slot, Hash::new_from_array(metadata.blockhash));
+        this
+    }
+
     /// Set up builtin program accounts
     fn setup_builtin_accounts(store: &AccountStore, builtin_ids: &HashSet<Pubkey>) {
         for program_id in builtin_ids {
@@ -257,8 +322,7 @@ impl L2Processor {
         Self::store_sysvar(store, &rent);
 
         // EpochSchedule sysvar
-        let epoch_schedule = EpochSchedule::default();
-        Self::store_sysvar(store, &epoch_schedule);
+        Self::store_sysvar(store, &epoch_schedule());
 
         // Recent blockhashes - simplified for L2
         // In production, this would track recent blockhashes
@@ -344,7 +408,12 @@ impl L2Processor {
         }
     }
 
-    /// Process a batch of transactions
+    /// Process a block of transactions
+    ///
+    /// The block is split into conflict-free batches (see `scheduler`) which
+    /// run one after another, each spread over the execution threads. Results
+    /// come back in block order and match executing the transactions one at a
+    /// time.
     pub fn process_transactions(
         &mut self,
         transactions: &[SanitizedTransaction],
@@ -355,8 +424,96 @@ impl L2Processor {
 
         let callback = L2AccountLoader::new(self.account_store.clone());
 
-        // Fee structure for gasless transactions
-        let fee_structure = FeeStructure::default();
+        // Log program cache state for debugging
+        // NOTE: get_flattened_entries only returns Loaded entries, not Builtin entries!
+        // We verify builtins at startup using get_slot_versions_for_tests instead.
+        {
+            let cache = self.processor.program_cache.read().unwrap();
+            let builtin_ids = self.processor.builtin_program_ids.read().unwrap();
+            tracing::info!("SVM: {} builtins registered, verifying cache state...", builtin_ids.len());
+
+            let mut all_builtins_present = true;
+            for id in builtin_ids.iter() {
+                let versions = cache.get_slot_versions_for_tests(id);
+                if versions.is_empty() {
+                    tracing::error!("SVM: BUILTIN {} MISSING FROM CACHE!", id);
+                    all_builtins_present = false;
+                }
+            }
+            if all_builtins_present {
+                tracing::info!("SVM: All {} builtins present in cache", builtin_ids.len());
+            }
+        }
+
+        // CRITICAL: Fill the sysvar cache from account store before execution
+        // The invoke_context.get_sysvar_cache().get_clock() will fail if this isn't done
+        self.processor.fill_missing_sysvar_cache_entries(&callback);
+        tracing::info!("SVM: Filled sysvar cache entries");
+
+        let batches = scheduler::schedule(transactions);
+        tracing::info!(
+            "SVM: Scheduled {} txs into {} conflict-free batches",
+            transactions.len(),
+            batches.len()
+        );
+
+        let mut results: Vec<Option<TransactionResult>> = vec![None; transactions.len()];
+        for batch in batches {
+            let batch_txs: Vec<SanitizedTransaction> =
+                batch.iter().map(|&index| transactions[index].clone()).collect();
+            let loader =
+                L2AccountLoader::for_transactions(self.account_store.clone(), &self.auto_create, &batch_txs);
+
+            let chunk_size = batch.len().div_ceil(self.execution_threads);
+            let chunks: Vec<&[SanitizedTransaction]> = batch_txs.chunks(chunk_size).collect();
+
+            // Transactions in a batch touch disjoint writable accounts, so the
+            // chunks can execute side by side against the same store
+            let outputs: Vec<_> = if chunks.len() == 1 {
+                vec![self.execute(&loader, chunks[0])]
+            } else {
+                let this = &*self;
+                let loader = &loader;
+                std::thread::scope(|scope| {
+                    let workers: Vec<_> = chunks
+                        .iter()
+                        .map(|chunk| scope.spawn(move || this.execute(loader, chunk)))
+                        .collect();
+                    workers
+                        .into_iter()
+                        .map(|worker| worker.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                        .collect()
+                })
+            };
+
+            let indices = batch.chunks(chunk_size);
+            for ((indices, chunk), output) in indices.zip(&chunks).zip(outputs) {
+                for (&index, result) in indices.iter().zip(self.process_output(&loader, chunk, output)) {
+                    results[index] = Some(result);
+                }
+            }
+        }
+
+        let results: Vec<TransactionResult> = results
+            .into_iter()
+            .map(|result| result.expect("every transaction is scheduled"))
+            .collect();
+        self.block_signatures.extend(results.iter().map(|result| result.signature));
+        results
+    }
+
+    /// Execute one batch of non-conflicting transactions through the SVM
+    fn execute(
+        &self,
+        callback: &L2AccountLoader,
+        transactions: &[SanitizedTransaction],
+    ) -> LoadAndExecuteSanitizedTransactionsOutput {
+        // Fee structure for gasless transactions (the SVM charges signature
+        // fees from here, not from lamports_per_signature below)
+        let fee_structure = FeeStructure {
+            lamports_per_signature: 0,
+            ..FeeStructure::default()
+        };
 
         // Set up processing environment
         let environment = TransactionProcessingEnvironment {
@@ -399,32 +556,6 @@ impl L2Processor {
         tracing::info!("SVM: Starting load_and_execute_sanitized_transactions for {} txs", transactions.len());
         tracing::info!("SVM: Current slot = {}, epoch = {}", self.current_slot, self.current_epoch);
 
-        // Log program cache state for debugging
-        // NOTE: get_flattened_entries only returns Loaded entries, not Builtin entries!
-        // We verify builtins at startup using get_slot_versions_for_tests instead.
-        {
-            let cache = self.processor.program_cache.read().unwrap();
-            let builtin_ids = self.processor.builtin_program_ids.read().unwrap();
-            tracing::info!("SVM: {} builtins registered, verifying cache state...", builtin_ids.len());
-
-            let mut all_builtins_present = true;
-            for id in builtin_ids.iter() {
-                let versions = cache.get_slot_versions_for_tests(id);
-                if versions.is_empty() {
-                    tracing::error!("SVM: BUILTIN {} MISSING FROM CACHE!", id);
-                    all_builtins_present = false;
-                }
-            }
-            if all_builtins_present {
-                tracing::info!("SVM: All {} builtins present in cache", builtin_ids.len());
-            }
-        }
-
-        // CRITICAL: Fill the sysvar cache from account store before execution
-        // The invoke_context.get_sysvar_cache().get_clock() will fail if this isn't done
-        self.processor.fill_missing_sysvar_cache_entries(&callback);
-        tracing::info!("SVM: Filled sysvar cache entries");
-
         tracing::info!("SVM: Calling load_and_execute_sanitized_transactions...");
         eprintln!("[SVM] ABOUT TO CALL load_and_execute_sanitized_transactions");
         eprintln!("[SVM] slot={}, epoch={}", self.current_slot, self.current_epoch);
@@ -432,7 +563,7 @@ impl L2Processor {
         // Use catch_unwind to see if there's a panic
         let output_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
             self.processor.load_and_execute_sanitized_transactions(
-            &callback,
+            callback,
             transactions,
             check_results,
             &environment,
@@ -452,24 +583,26 @@ impl L2Processor {
         };
         tracing::info!("SVM: Returned from load_and_execute_sanitized_transactions");
         tracing::info!("SVM: Completed successfully with {} results", output.processing_results.len());
-
-        // Convert results and update account store
-        self.process_output(transactions, output)
+        output
     }
 
     /// Process the output from transaction execution
     fn process_output(
-        &self,
+        &mut self,
+        loader: &L2AccountLoader,
         transactions: &[SanitizedTransaction],
         output: LoadAndExecuteSanitizedTransactionsOutput,
     ) -> Vec<TransactionResult> {
-        use solana_svm::transaction_processing_result::ProcessedTransaction;
+        use solana_svm::{
+            transaction_processing_callback::TransactionProcessingCallback,
+            transaction_processing_result::ProcessedTransaction,
+        };
 
         let mut results = Vec::with_capacity(transactions.len());
 
         for (tx, result) in transactions
             .iter()
-            .zip(output.processing_results.into_iter())
+            .zip(output.processing_results)
         {
             let signature = *tx.signature();
 
@@ -492,21 +625,44 @@ impl L2Processor {
                         (true, None, vec![])
                     };
 
-                    // Extract and store modified accounts
+                    // Write back the accounts this transaction changed
                     let mut modified_accounts = Vec::new();
 
                     if success {
-                        // Get accounts from the executed transaction
                         if let ProcessedTransaction::Executed(executed) = &processed {
-                            // The loaded_transaction.accounts contains (Pubkey, AccountSharedData) tuples
-                            // Write each modified account back to the store
-                            for (pubkey, account) in &executed.loaded_transaction.accounts {
-                                // Store the account in our account store
-                                self.account_store.store_account(
-                                    *pubkey,
-                                    account.clone(),
-                                    self.current_slot,
-                                );
+                            let message = tx.message();
+                            let key_count = message.account_keys().len();
+
+                            for (index, (pubkey, account)) in
+                                executed.loaded_transaction.accounts.iter().enumerate()
+                            {
+                                // Sysvars, programs and readonly accounts (like the
+                                // world config) can't have been modified
+                                if index >= key_count || !message.is_writable(index) {
+                                    continue;
+                                }
+
+                                // Compare against what the transaction loaded: the
+                                // stored account, or the loader's default for a new one
+                                let before = loader.get_account_shared_data(pubkey);
+                                match &before {
+                                    Some(before) if !account_changed(before, account) => continue,
+                                    None if account.lamports() == 0 => continue,
+                                    _ => {}
+                                }
+                                self.position_recorder.note(*pubkey, account, before);
+
+                                if account.lamports() == 0 {
+                                    // Closed accounts (drained to zero lamports) are purged,
+                                    // matching Solana semantics
+                                    self.account_store.remove_account(pubkey);
+                                } else {
+                                    self.account_store.store_account(
+                                        *pubkey,
+                                        account.clone(),
+                                        self.current_slot,
+                                    );
+                                }
                                 modified_accounts.push((*pubkey, account.clone()));
                             }
 
@@ -565,10 +721,48 @@ impl L2Processor {
         results
     }
 
-    /// Advance to the next slot
-    pub fn advance_slot(&mut self) {
+    /// Seal the current slot's block and advance to the next slot
+    ///
+    /// Sealing records the slot's moved players and NPCs in their worlds'
+    /// position histories; the history accounts written are returned. The
+    /// new blockhash is derived from the sealed block (see `derive_blockhash`).
+    pub fn advance_slot(&mut self) -> Vec<(Pubkey, AccountSharedData)> {
+        let sealed_accounts = self.position_recorder.seal(&self.account_store, self.current_slot);
+        self.state_root = self.account_store.seal_state_root(self.current_slot);
+        self.parent_blockhash = self.current_blockhash;
+        self.current_blockhash = derive_blockhash(
+            &self.parent_blockhash,
+            self.current_slot,
+            &self.block_signatures,
+            &self.state_root,
+        );
+        self.block_signatures.clear();
+
         self.current_slot += 1;
+        self.current_epoch = epoch_schedule().get_epoch(self.current_slot);
+        self.enter_slot();
+        sealed_accounts
+    }
 
+    /// Continue a chain from a sealed position instead of genesis
+    ///
+    /// `slot` is the next slot to execute and `blockhash` the hash of the
+    /// block before it. The account store must already hold the chain's
+    /// state at that point.
+    pub(crate) fn resume(&mut self, slot: Slot, blockhash: Hash) {
+        self.current_slot = slot;
+        self.current_epoch = epoch_schedule().get_epoch(slot);
+        self.current_blockhash = blockhash;
+        // Not known until the next block is sealed
+        self.parent_blockhash = GENESIS_BLOCKHASH;
+        self.state_root = self.account_store.seal_state_root(slot.saturating_sub(1));
+        self.block_signatures.clear();
+        self.position_recorder.clear();
+        self.enter_slot();
+    }
+
+    /// Point the SVM, program cache and Clock sysvar at `current_slot`
+    fn enter_slot(&mut self) {
         // Update fork graph slot FIRST (needed for cache visibility)
         {
             let mut fg = self.fork_graph.write().unwrap();
@@ -588,22 +782,37 @@ impl L2Processor {
         // NOTE: We do NOT re-register builtins - they persist in the shared program cache
         // Builtins registered at slot 0 are visible at all future slots via ForkGraph
 
-        self.current_blockhash = Hash::new_unique();
-
         // Update clock sysvar
+        self.set_unix_timestamp(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64,
+        );
+
+        tracing::trace!("Advanced to slot {}", self.current_slot);
+    }
+
+    /// Unix timestamp of the current slot's Clock sysvar
+    pub fn unix_timestamp(&self) -> i64 {
+        self.account_store
+            .get_account(&Clock::id())
+            .and_then(|account| bincode::deserialize::<Clock>(account.data()).ok())
+            .map_or(0, |clock| clock.unix_timestamp)
+    }
+
+    /// Set the current slot's Clock timestamp (e.g. to replay a recorded block)
+    pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
         let clock = Clock {
             slot: self.current_slot,
             epoch_start_timestamp: 0,
             epoch: self.current_epoch,
             leader_schedule_epoch: self.current_epoch,
-            unix_timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64,
+            unix_timestamp,
         };
         Self::store_sysvar(&self.account_store, &clock);
-
-        tracing::trace!("Advanced to slot {}", self.current_slot);
+        // Transactions already run this slot may have cached the old Clock
+        self.processor.reset_sysvar_cache();
     }
 
     /// Get current slot
@@ -616,6 +825,26 @@ impl L2Processor {
         self.current_blockhash
     }
 
+    /// Get the blockhash the current blockhash was chained to
+    pub fn parent_blockhash(&self) -> Hash {
+        self.parent_blockhash
+    }
+
+    /// Get the account state root after the last sealed block
+    pub fn state_root(&self) -> Hash {
+        self.state_root
+    }
+
+    /// Replace the rules deciding which missing accounts get synthesized
+    pub fn set_auto_create_rules(&mut self, registry: AutoCreateRegistry) {
+        self.auto_create = Arc::new(registry);
+    }
+
+    /// Set how many worker threads execute each conflict-free batch (at least 1)
+    pub fn set_execution_threads(&mut self, threads: usize) {
+        self.execution_threads = threads.max(1);
+    }
+
     /// Get current epoch
     pub fn current_epoch(&self) -> u64 {
         self.current_epoch
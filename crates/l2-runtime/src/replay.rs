@@ -0,0 +1,380 @@
+//! Block Replay
+//!
+//! Re-executes recorded blocks on top of a starting snapshot and checks the
+//! chain comes out the same: every transaction's outcome, every modified
+//! account, the state root and the blockhash, slot by slot. Replay stops at
+//! the first divergence and reports the transaction and account involved.
+//!
+//! Blocks run with the Clock timestamp they were recorded with, so replay
+//! doesn't depend on when it runs.
+
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    clock::Slot,
+    hash::Hash,
+    pubkey::Pubkey,
+    reserved_account_keys::ReservedAccountKeys,
+    signature::Signature,
+    transaction::{MessageHash, SanitizedTransaction, SimpleAddressLoader, TransactionError},
+};
+
+use crate::{
+    account_store::AccountStore,
+    block_store::StoredBlock,
+    persistence::ChainMetadata,
+    processor::{L2Processor, TransactionResult},
+    state_tree::account_hash,
+};
+
+/// Chain state to start replaying from
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySnapshot {
+    /// Chain position: `slot` is the first slot to replay, `blockhash` the
+    /// hash of the block before it. A non-zero `state_root` is checked
+    /// against the accounts before replaying.
+    pub metadata: ChainMetadata,
+    /// Every account at that point
+    pub accounts: Vec<(Pubkey, AccountSharedData)>,
+}
+
+impl ReplaySnapshot {
+    /// Snapshot of an account store at a chain position
+    pub fn from_store(store: &AccountStore, metadata: ChainMetadata) -> Self {
+        let accounts = store
+            .get_all_pubkeys()
+            .into_iter()
+            .filter_map(|pubkey| store.get_account(&pubkey).map(|account| (pubkey, account)))
+            .collect();
+        Self { metadata, accounts }
+    }
+}
+
+/// How a transaction executed, as recorded or replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOutcome {
+    /// Whether it executed successfully
+    pub success: bool,
+    /// Why it failed
+    pub error: Option<TransactionError>,
+}
+
+impl TransactionOutcome {
+    fn new(success: bool, error: &Option<TransactionError>) -> Self {
+        Self {
+            success,
+            error: error.clone(),
+        }
+    }
+}
+
+/// Where a replayed block first differs from the recorded one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Divergence {
+    /// The snapshot's accounts don't hash to its state root
+    SnapshotStateRoot { recorded: Hash, replayed: Hash },
+    /// The block stream skips the slot replay is at
+    MissingBlock { expected_slot: Slot, found_slot: Slot },
+    /// The block doesn't chain onto the replayed blockhash
+    ParentBlockhash { recorded: Hash, replayed: Hash },
+    /// A recorded transaction can't be sanitized
+    InvalidTransaction { index: usize, signature: Signature, error: TransactionError },
+    /// A transaction succeeded or failed differently
+    Transaction {
+        index: usize,
+        signature: Signature,
+        recorded: TransactionOutcome,
+        replayed: TransactionOutcome,
+    },
+    /// An account ended the block differently (`None` = doesn't exist)
+    Account {
+        pubkey: Pubkey,
+        /// Last transaction in the block that modified the account
+        signature: Option<Signature>,
+        recorded: Option<AccountSharedData>,
+        replayed: Option<AccountSharedData>,
+    },
+    /// The state root differs
+    StateRoot { recorded: Hash, replayed: Hash },
+    /// The blockhash differs
+    Blockhash { recorded: Hash, replayed: Hash },
+}
+
+/// First divergence found by a replay
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDivergence {
+    /// Slot of the block that diverged
+    pub slot: Slot,
+    /// What differs
+    pub divergence: Divergence,
+}
+
+impl fmt::Display for ReplayDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "slot {}: ", self.slot)?;
+        match &self.divergence {
+            Divergence::SnapshotStateRoot { recorded, replayed } => {
+                write!(f, "snapshot state root is {} but its accounts hash to {}", recorded, replayed)
+            }
+            Divergence::MissingBlock { expected_slot, found_slot } => {
+                write!(f, "expected block {} but the next recorded block is {}", expected_slot, found_slot)
+            }
+            Divergence::ParentBlockhash { recorded, replayed } => {
+                write!(f, "block chains to {} but replay is at {}", recorded, replayed)
+            }
+            Divergence::InvalidTransaction { index, signature, error } => {
+                write!(f, "transaction {} ({}) can't be sanitized: {}", index, signature, error)
+            }
+            Divergence::Transaction { index, signature, recorded, replayed } => write!(
+                f,
+                "transaction {} ({}) recorded {:?}, replayed {:?}",
+                index, signature, recorded, replayed
+            ),
+            Divergence::Account { pubkey, signature, recorded, replayed } => {
+                let summary = |account: &Option<AccountSharedData>| match account {
+                    Some(account) => format!(
+                        "{} lamports, {} bytes, hash {}",
+                        account.lamports(),
+                        account.data().len(),
+                        account_hash(account)
+                    ),
+                    None => "missing".to_string(),
+                };
+                write!(f, "account {}", pubkey)?;
+                if let Some(signature) = signature {
+                    write!(f, " (last written by {})", signature)?;
+                }
+                write!(f, " recorded {}, replayed {}", summary(recorded), summary(replayed))
+            }
+            Divergence::StateRoot { recorded, replayed } => {
+                write!(f, "state root recorded {}, replayed {}", recorded, replayed)
+            }
+            Divergence::Blockhash { recorded, replayed } => {
+                write!(f, "blockhash recorded {}, replayed {}", recorded, replayed)
+            }
+        }
+    }
+}
+
+/// Outcome of a replay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    /// Blocks that replayed identically
+    pub blocks_verified: usize,
+    /// Transactions in those blocks
+    pub transactions_verified: usize,
+    /// Last slot that replayed identically
+    pub last_verified_slot: Option<Slot>,
+    /// First divergence, if any
+    pub divergence: Option<ReplayDivergence>,
+}
+
+/// Re-executes recorded blocks through an `L2Processor`
+pub struct Replayer {
+    processor: L2Processor,
+    account_store: Arc<AccountStore>,
+    snapshot_state_root: Option<Hash>,
+}
+
+impl Replayer {
+    /// Set up a fresh processor holding the snapshot's state
+    pub fn new(snapshot: &ReplaySnapshot) -> Self {
+        let account_store = Arc::new(AccountStore::new());
+        account_store.store_accounts(snapshot.accounts.clone(), 0);
+        let metadata = &snapshot.metadata;
+        let processor = L2Processor::from_metadata(account_store.clone(), metadata);
+        let snapshot_state_root =
+            (metadata.state_root != [0u8; 32]).then(|| Hash::new_from_array(metadata.state_root));
+
+        Self {
+            processor,
+            account_store,
+            snapshot_state_root,
+        }
+    }
+
+    /// The replayed processor
+    pub fn processor(&self) -> &L2Processor {
+        &self.processor
+    }
+
+    /// The replayed account state
+    pub fn account_store(&self) -> &Arc<AccountStore> {
+        &self.account_store
+    }
+
+    /// Replay blocks in slot order, stopping at the first divergence
+    ///
+    /// Blocks before the snapshot's slot are skipped.
+    pub fn replay<I>(&mut self, blocks: I) -> anyhow::Result<ReplayReport>
+    where
+        I: IntoIterator<Item = anyhow::Result<StoredBlock>>,
+    {
+        let mut report = ReplayReport::default();
+        if let Some(recorded) = self.snapshot_state_root {
+            let replayed = self.processor.state_root();
+            if recorded != replayed {
+                report.divergence = Some(ReplayDivergence {
+                    slot: self.processor.current_slot(),
+                    divergence: Divergence::SnapshotStateRoot { recorded, replayed },
+                });
+                return Ok(report);
+            }
+        }
+
+        for block in blocks {
+            let block = block?;
+            if block.slot < self.processor.current_slot() {
+                continue;
+            }
+            if let Some(divergence) = self.replay_block(&block) {
+                report.divergence = Some(ReplayDivergence {
+                    slot: block.slot,
+                    divergence,
+                });
+                break;
+            }
+            report.blocks_verified += 1;
+            report.transactions_verified += block.transactions.len();
+            report.last_verified_slot = Some(block.slot);
+        }
+        Ok(report)
+    }
+
+    /// Replay one block, returning how it diverged
+    fn replay_block(&mut self, block: &StoredBlock) -> Option<Divergence> {
+        let slot = self.processor.current_slot();
+        if block.slot != slot {
+            return Some(Divergence::MissingBlock {
+                expected_slot: slot,
+                found_slot: block.slot,
+            });
+        }
+        let blockhash = self.processor.current_blockhash();
+        if block.parent_blockhash != blockhash {
+            return Some(Divergence::ParentBlockhash {
+                recorded: block.parent_blockhash,
+                replayed: blockhash,
+            });
+        }
+
+        let mut transactions = Vec::with_capacity(block.transactions.len());
+        for (index, tx) in block.transactions.iter().enumerate() {
+            match sanitize(tx.transaction.clone()) {
+                Ok(sanitized) => transactions.push(sanitized),
+                Err(error) => {
+                    return Some(Divergence::InvalidTransaction {
+                        index,
+                        signature: tx.signature(),
+                        error,
+                    })
+                }
+            }
+        }
+
+        self.processor.set_unix_timestamp(block.block_time);
+        let results = self.processor.process_transactions(&transactions);
+
+        for (index, (recorded, replayed)) in block.transactions.iter().zip(&results).enumerate() {
+            let recorded_outcome = TransactionOutcome::new(recorded.success, &recorded.error);
+            let replayed_outcome = TransactionOutcome::new(replayed.success, &replayed.error);
+            if recorded_outcome != replayed_outcome {
+                return Some(Divergence::Transaction {
+                    index,
+                    signature: recorded.signature(),
+                    recorded: recorded_outcome,
+                    replayed: replayed_outcome,
+                });
+            }
+        }
+
+        let sealed_accounts = self.processor.advance_slot();
+        if let Some(divergence) = self.compare_accounts(block, &results, &sealed_accounts) {
+            return Some(divergence);
+        }
+
+        let state_root = self.processor.state_root();
+        if block.state_root != state_root {
+            return Some(Divergence::StateRoot {
+                recorded: block.state_root,
+                replayed: state_root,
+            });
+        }
+        let blockhash = self.processor.current_blockhash();
+        if block.blockhash != blockhash {
+            return Some(Divergence::Blockhash {
+                recorded: block.blockhash,
+                replayed: blockhash,
+            });
+        }
+        None
+    }
+
+    /// Compare every account either run modified, including those sealing the slot wrote
+    fn compare_accounts(
+        &self,
+        block: &StoredBlock,
+        results: &[TransactionResult],
+        sealed_accounts: &[(Pubkey, AccountSharedData)],
+    ) -> Option<Divergence> {
+        let mut seen = HashSet::new();
+        let replayed_keys = results
+            .iter()
+            .filter(|result| result.success)
+            .flat_map(|result| result.modified_accounts.iter().map(|(pubkey, _)| *pubkey))
+            .chain(sealed_accounts.iter().map(|(pubkey, _)| *pubkey));
+        let pubkeys: Vec<Pubkey> = block
+            .modified_accounts
+            .iter()
+            .map(|(pubkey, _)| *pubkey)
+            .chain(replayed_keys)
+            .filter(|pubkey| seen.insert(*pubkey))
+            .collect();
+
+        for pubkey in pubkeys {
+            // Accounts drained to zero lamports are removed from the store
+            let recorded = block
+                .modified_accounts
+                .iter()
+                .find(|(key, _)| *key == pubkey)
+                .map(|(_, account)| account.clone())
+                .filter(|account| account.lamports() > 0);
+            let replayed = self.account_store.get_account(&pubkey);
+            if recorded.as_ref().map(account_hash) == replayed.as_ref().map(account_hash) {
+                continue;
+            }
+
+            let signature = results
+                .iter()
+                .rev()
+                .find(|result| result.modified_accounts.iter().any(|(key, _)| *key == pubkey))
+                .map(|result| result.signature)
+                .or_else(|| {
+                    block
+                        .transactions
+                        .iter()
+                        .rev()
+                        .find(|tx| tx.modified_accounts.contains(&pubkey))
+                        .map(|tx| tx.signature())
+                });
+            return Some(Divergence::Account {
+                pubkey,
+                signature,
+                recorded,
+                replayed,
+            });
+        }
+        None
+    }
+}
+
+fn sanitize(tx: solana_sdk::transaction::VersionedTransaction) -> Result<SanitizedTransaction, TransactionError> {
+    SanitizedTransaction::try_create(
+        tx,
+        MessageHash::Compute,
+        None,
+        SimpleAddressLoader::Disabled,
+        &ReservedAccountKeys::empty_key_set(),
+    )
+}
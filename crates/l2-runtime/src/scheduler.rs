@@ -0,0 +1,175 @@
+//! Transaction Scheduler
+//!
+//! Splits a block into batches of transactions that can execute in parallel.
+//! Two transactions conflict when one writes an account the other reads or
+//! writes. Each transaction goes in the batch after the last earlier
+//! transaction it conflicts with, so conflicting transactions keep their
+//! block order and running the batches in turn gives the same result as
+//! running the block one transaction at a time.
+
+use std::collections::HashMap;
+
+use solana_sdk::{compute_budget, pubkey::Pubkey, transaction::SanitizedTransaction};
+
+/// Accounts a transaction writes and reads
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountLocks {
+    pub writable: Vec<Pubkey>,
+    pub readonly: Vec<Pubkey>,
+}
+
+impl AccountLocks {
+    /// Locks a transaction needs
+    ///
+    /// Signatures are free, so a fee payer that pays no priority fee and no
+    /// instruction uses is never modified and only needs a read lock. Without
+    /// this, every crank transaction from the same leader payer would run one
+    /// at a time.
+    pub fn of(tx: &SanitizedTransaction) -> Self {
+        let message = tx.message();
+        let payer_unused = !message
+            .instructions()
+            .iter()
+            .any(|ix| ix.accounts.contains(&0))
+            && !message
+                .program_instructions_iter()
+                .any(|(program_id, _)| compute_budget::check_id(program_id));
+
+        let mut locks = Self::default();
+        for (index, key) in message.account_keys().iter().enumerate() {
+            if message.is_writable(index) && !(index == 0 && payer_unused) {
+                locks.writable.push(*key);
+            } else {
+                locks.readonly.push(*key);
+            }
+        }
+        locks
+    }
+}
+
+/// Partition a block into conflict-free batches of transaction indices,
+/// to be executed in order
+pub fn schedule(transactions: &[SanitizedTransaction]) -> Vec<Vec<usize>> {
+    // Latest batch that writes / reads each account
+    let mut last_write: HashMap<Pubkey, usize> = HashMap::new();
+    let mut last_read: HashMap<Pubkey, usize> = HashMap::new();
+    let mut batches: Vec<Vec<usize>> = Vec::new();
+
+    for (index, tx) in transactions.iter().enumerate() {
+        let locks = AccountLocks::of(tx);
+
+        // After every earlier writer of anything it touches, and every
+        // earlier reader of anything it writes
+        let after_writes = locks
+            .writable
+            .iter()
+            .chain(&locks.readonly)
+            .filter_map(|key| last_write.get(key));
+        let after_reads = locks.writable.iter().filter_map(|key| last_read.get(key));
+        let batch = after_writes
+            .chain(after_reads)
+            .map(|batch| batch + 1)
+            .max()
+            .unwrap_or(0);
+
+        for key in locks.writable {
+            last_write.insert(key, batch);
+        }
+        for key in locks.readonly {
+            let read = last_read.entry(key).or_insert(batch);
+            *read = (*read).max(batch);
+        }
+
+        if batch == batches.len() {
+            batches.push(Vec::new());
+        }
+        batches[batch].push(index);
+    }
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    };
+    use std::collections::HashSet;
+
+    fn tx(payer: &Keypair, accounts: Vec<AccountMeta>) -> SanitizedTransaction {
+        let ix = Instruction::new_with_bytes(world_program::id(), &[], accounts);
+        tx_with(payer, vec![ix])
+    }
+
+    fn tx_with(payer: &Keypair, ixs: Vec<Instruction>) -> SanitizedTransaction {
+        let message = Message::new(&ixs, Some(&payer.pubkey()));
+        let tx = Transaction::new(&[payer], message, Default::default());
+        SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new()).unwrap()
+    }
+
+    #[test]
+    fn test_disjoint_writes_share_a_batch() {
+        let payer = Keypair::new();
+        let world = Pubkey::new_unique();
+        let txs: Vec<_> = (0..4)
+            .map(|_| {
+                tx(
+                    &payer,
+                    vec![
+                        AccountMeta::new_readonly(world, false),
+                        AccountMeta::new(Pubkey::new_unique(), false),
+                    ],
+                )
+            })
+            .collect();
+
+        assert_eq!(schedule(&txs), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_conflicts_keep_block_order() {
+        let payer = Keypair::new();
+        let (world, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let txs = vec![
+            tx(&payer, vec![AccountMeta::new_readonly(world, false), AccountMeta::new(a, false)]),
+            tx(&payer, vec![AccountMeta::new_readonly(world, false), AccountMeta::new(b, false)]),
+            // Writes both players: after both moves
+            tx(&payer, vec![AccountMeta::new(a, false), AccountMeta::new(b, false)]),
+            // Writes the world the first two read
+            tx(&payer, vec![AccountMeta::new(world, false)]),
+            // Reads a player written in batch 1
+            tx(&payer, vec![AccountMeta::new_readonly(a, false)]),
+        ];
+
+        assert_eq!(schedule(&txs), vec![vec![0, 1], vec![2, 3], vec![4]]);
+    }
+
+    #[test]
+    fn test_payer_locks() {
+        let payer = Keypair::new();
+        let unused = tx(&payer, vec![AccountMeta::new(Pubkey::new_unique(), false)]);
+        assert!(AccountLocks::of(&unused).readonly.contains(&payer.pubkey()));
+
+        let used = tx(
+            &payer,
+            vec![AccountMeta::new(payer.pubkey(), true), AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        assert!(AccountLocks::of(&used).writable.contains(&payer.pubkey()));
+        assert_eq!(schedule(&[used.clone(), used]).len(), 2);
+
+        // Priority fees come out of the payer
+        let priority = tx_with(
+            &payer,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_price(1),
+                Instruction::new_with_bytes(world_program::id(), &[], vec![]),
+            ],
+        );
+        assert!(AccountLocks::of(&priority).writable.contains(&payer.pubkey()));
+    }
+}
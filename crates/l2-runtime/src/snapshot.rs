@@ -0,0 +1,468 @@
+//! State Snapshot Archives
+//!
+//! A snapshot archive holds every account plus the `ChainMetadata` (slot,
+//! blockhash, state root) of one block boundary, so chain state can be backed
+//! up, shipped to another node or rolled back without copying the live sled
+//! directory.
+//!
+//! Each archive is one file, `snapshot-<slot>.l2snap`:
+//! - 8-byte magic, then a length-prefixed bincode `SnapshotManifest`
+//! - the accounts, bincode-encoded and zstd-compressed
+//!
+//! The manifest carries the body's length and SHA-256, so listing archives
+//! only reads manifests, and reading one checks the checksum, the account
+//! count and the state root before handing the state over.
+//!
+//! The block producer captures the accounts at a block boundary (cheap:
+//! account data is shared, not copied) and a `SnapshotWriter` thread does the
+//! encoding, compression and disk writes. `restore_snapshot` rolls a node's
+//! persisted state back to an archive.
+
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+};
+
+use crossbeam_channel::{bounded, Sender, TrySendError};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::AccountSharedData,
+    clock::Slot,
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    account_store::AccountStore,
+    block_store::BlockStore,
+    persistence::{AccountStorePersistence, ChainMetadata, PersistentStore},
+    replay::ReplaySnapshot,
+};
+
+/// Archive format version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Leading bytes of every archive
+const SNAPSHOT_MAGIC: &[u8; 8] = b"L2SNAP\0\0";
+
+/// Archive file extension
+const SNAPSHOT_EXTENSION: &str = "l2snap";
+
+/// zstd compression level (fast, still shrinks mostly-zero account data well)
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Snapshot schedule and retention
+#[derive(Clone, Debug)]
+pub struct SnapshotConfig {
+    /// Take a snapshot every N slots (0 = never)
+    pub interval_slots: u64,
+    /// Archives to keep; older ones are pruned after each write (0 = keep all)
+    pub max_snapshots: usize,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            // 5 minutes at 30Hz
+            interval_slots: 9_000,
+            max_snapshots: 4,
+        }
+    }
+}
+
+/// What an archive holds, readable without decompressing it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotManifest {
+    /// Archive format version
+    pub version: u32,
+    /// Chain position and state root the accounts are at
+    pub metadata: ChainMetadata,
+    /// Number of accounts in the archive
+    pub account_count: u64,
+    /// Compressed body length in bytes
+    pub compressed_len: u64,
+    /// SHA-256 of the compressed body
+    pub checksum: [u8; 32],
+}
+
+impl SnapshotManifest {
+    /// Slot the snapshot was taken at (the next slot to execute)
+    pub fn slot(&self) -> Slot {
+        self.metadata.slot
+    }
+}
+
+/// An archive on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotInfo {
+    /// Archive file
+    pub path: PathBuf,
+    /// Its manifest
+    pub manifest: SnapshotManifest,
+}
+
+/// Directory of snapshot archives
+pub struct SnapshotArchive {
+    dir: PathBuf,
+}
+
+impl SnapshotArchive {
+    /// Open (or create) an archive directory
+    pub fn open<P: AsRef<Path>>(dir: P) -> anyhow::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Archive directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a snapshot, returning the new archive
+    ///
+    /// The file is written under a temporary name and renamed into place, so
+    /// a crash mid-write never leaves a truncated archive behind.
+    pub fn write(&self, snapshot: &ReplaySnapshot) -> anyhow::Result<SnapshotInfo> {
+        let body = zstd::encode_all(bincode::serialize(&snapshot.accounts)?.as_slice(), COMPRESSION_LEVEL)?;
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_VERSION,
+            metadata: snapshot.metadata.clone(),
+            account_count: snapshot.accounts.len() as u64,
+            compressed_len: body.len() as u64,
+            checksum: hash(&body).to_bytes(),
+        };
+        let manifest_bytes = bincode::serialize(&manifest)?;
+
+        let path = self.path_for(manifest.slot());
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(SNAPSHOT_MAGIC)?;
+            file.write_all(&(manifest_bytes.len() as u32).to_le_bytes())?;
+            file.write_all(&manifest_bytes)?;
+            file.write_all(&body)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)?;
+
+        tracing::info!(
+            "Wrote snapshot at slot {}: {} accounts, {} bytes",
+            manifest.slot(),
+            manifest.account_count,
+            body.len()
+        );
+        Ok(SnapshotInfo { path, manifest })
+    }
+
+    /// Archives in the directory, oldest first
+    ///
+    /// Files that aren't readable archives are skipped with a warning.
+    pub fn list(&self) -> anyhow::Result<Vec<SnapshotInfo>> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            match read_manifest(&path) {
+                Ok(manifest) => snapshots.push(SnapshotInfo { path, manifest }),
+                Err(e) => tracing::warn!("Skipping unreadable snapshot {:?}: {}", path, e),
+            }
+        }
+        snapshots.sort_by_key(|info| info.manifest.slot());
+        Ok(snapshots)
+    }
+
+    /// Most recent archive, if any
+    pub fn latest(&self) -> anyhow::Result<Option<SnapshotInfo>> {
+        Ok(self.list()?.pop())
+    }
+
+    /// Delete all but the `keep` most recent archives, returning how many went
+    pub fn prune(&self, keep: usize) -> anyhow::Result<usize> {
+        let snapshots = self.list()?;
+        let excess = snapshots.len().saturating_sub(keep);
+        for info in &snapshots[..excess] {
+            fs::remove_file(&info.path)?;
+            tracing::info!("Pruned snapshot at slot {}", info.manifest.slot());
+        }
+        Ok(excess)
+    }
+
+    fn path_for(&self, slot: Slot) -> PathBuf {
+        self.dir.join(format!("snapshot-{:020}.{}", slot, SNAPSHOT_EXTENSION))
+    }
+}
+
+/// Read and verify an archive
+///
+/// Fails unless the body matches the manifest's length and checksum, holds
+/// the manifest's account count, and (when the manifest has one) hashes to
+/// its state root.
+pub fn read_snapshot<P: AsRef<Path>>(path: P) -> anyhow::Result<ReplaySnapshot> {
+    let mut file = fs::File::open(path.as_ref())?;
+    let manifest = read_manifest_from(&mut file)?;
+    let mut body = Vec::new();
+    file.read_to_end(&mut body)?;
+
+    if body.len() as u64 != manifest.compressed_len {
+        anyhow::bail!(
+            "Snapshot body is {} bytes, manifest says {}",
+            body.len(),
+            manifest.compressed_len
+        );
+    }
+    if hash(&body).to_bytes() != manifest.checksum {
+        anyhow::bail!("Snapshot checksum mismatch");
+    }
+
+    let accounts: Vec<(Pubkey, AccountSharedData)> = bincode::deserialize(&zstd::decode_all(body.as_slice())?)?;
+    if accounts.len() as u64 != manifest.account_count {
+        anyhow::bail!(
+            "Snapshot has {} accounts, manifest says {}",
+            accounts.len(),
+            manifest.account_count
+        );
+    }
+
+    let state_root = Hash::new_from_array(manifest.metadata.state_root);
+    if state_root != Hash::default() {
+        let store = AccountStore::new();
+        store.store_accounts(accounts.clone(), 0);
+        let computed = store.state_root();
+        if computed != state_root {
+            anyhow::bail!("Snapshot accounts hash to {}, manifest says {}", computed, state_root);
+        }
+    }
+
+    Ok(ReplaySnapshot {
+        metadata: manifest.metadata,
+        accounts,
+    })
+}
+
+/// What `restore_snapshot` replaced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RestoreReport {
+    /// Accounts written
+    pub accounts: usize,
+    /// Logged deltas dropped from the write-ahead log
+    pub dropped_deltas: usize,
+    /// Blocks dropped from the block store
+    pub dropped_blocks: usize,
+}
+
+/// Replace persisted state with a snapshot's
+///
+/// Everything logged or recorded past the snapshot belongs to the chain
+/// being rolled back, so the write-ahead log is emptied and blocks from the
+/// snapshot's slot on are dropped; those slots will be produced again.
+pub fn restore_snapshot(
+    snapshot: ReplaySnapshot,
+    persistent_store: &PersistentStore,
+    block_store: &BlockStore,
+) -> anyhow::Result<RestoreReport> {
+    let metadata = snapshot.metadata;
+    let accounts = AccountStore::new();
+    accounts.store_accounts(snapshot.accounts, metadata.slot.saturating_sub(1));
+    let state_root = accounts.state_root();
+    if metadata.state_root != [0u8; 32] && state_root.to_bytes() != metadata.state_root {
+        anyhow::bail!(
+            "Snapshot accounts hash to {}, metadata says {}",
+            state_root,
+            Hash::new_from_array(metadata.state_root)
+        );
+    }
+
+    // Replaying the log over the restored state would move it past the snapshot
+    let dropped_deltas = persistent_store.clear_wal()?;
+    persistent_store.clear()?;
+    let written = accounts.checkpoint(persistent_store, &metadata)?;
+
+    let dropped_blocks = block_store.truncate(metadata.slot)?;
+    block_store.flush()?;
+    Ok(RestoreReport {
+        accounts: written,
+        dropped_deltas,
+        dropped_blocks,
+    })
+}
+
+/// Read just an archive's manifest
+pub fn read_manifest<P: AsRef<Path>>(path: P) -> anyhow::Result<SnapshotManifest> {
+    read_manifest_from(&mut fs::File::open(path.as_ref())?)
+}
+
+fn read_manifest_from(file: &mut fs::File) -> anyhow::Result<SnapshotManifest> {
+    let mut magic = [0u8; 8];
+    file.read_exact(&mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        anyhow::bail!("Not a snapshot archive");
+    }
+    let mut len = [0u8; 4];
+    file.read_exact(&mut len)?;
+    let mut manifest_bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    file.read_exact(&mut manifest_bytes)?;
+
+    let manifest: SnapshotManifest = bincode::deserialize(&manifest_bytes)?;
+    if manifest.version != SNAPSHOT_VERSION {
+        anyhow::bail!("Unsupported snapshot version {}", manifest.version);
+    }
+    Ok(manifest)
+}
+
+/// Background thread that writes captured snapshots and prunes old ones
+pub struct SnapshotWriter {
+    sender: Option<Sender<ReplaySnapshot>>,
+    handle: Option<JoinHandle<()>>,
+    config: SnapshotConfig,
+}
+
+impl SnapshotWriter {
+    /// Start the writer thread for an archive directory
+    pub fn spawn(archive: SnapshotArchive, config: SnapshotConfig) -> Self {
+        // One snapshot in flight: if the writer falls behind, skip rather than queue
+        let (sender, receiver) = bounded::<ReplaySnapshot>(1);
+        let max_snapshots = config.max_snapshots;
+        let handle = std::thread::Builder::new()
+            .name("snapshot-writer".to_string())
+            .spawn(move || {
+                for snapshot in receiver {
+                    if let Err(e) = archive.write(&snapshot) {
+                        tracing::error!("Failed to write snapshot at slot {}: {}", snapshot.metadata.slot, e);
+                        continue;
+                    }
+                    if max_snapshots > 0 {
+                        if let Err(e) = archive.prune(max_snapshots) {
+                            tracing::error!("Failed to prune snapshots: {}", e);
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn snapshot writer thread");
+
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+            config,
+        }
+    }
+
+    /// Snapshot schedule
+    pub fn config(&self) -> &SnapshotConfig {
+        &self.config
+    }
+
+    /// Whether a snapshot is due once the chain reaches `slot`
+    pub fn is_due(&self, slot: Slot) -> bool {
+        self.config.interval_slots > 0 && slot.is_multiple_of(self.config.interval_slots)
+    }
+
+    /// Hand a captured snapshot to the writer thread
+    ///
+    /// Returns false (dropping the snapshot) if the previous one is still
+    /// being written.
+    pub fn submit(&self, snapshot: ReplaySnapshot) -> bool {
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        match sender.try_send(snapshot) {
+            Ok(()) => true,
+            Err(TrySendError::Full(snapshot)) => {
+                tracing::warn!("Snapshot writer busy, skipping slot {}", snapshot.metadata.slot);
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+impl Drop for SnapshotWriter {
+    /// Finish writing any submitted snapshot before going away
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn make_snapshot(slot: Slot) -> ReplaySnapshot {
+        let store = AccountStore::new();
+        for i in 0..5 {
+            store.store_account(Pubkey::new_unique(), AccountSharedData::new(100 + i, 64, &Pubkey::new_unique()), 0);
+        }
+        let metadata = ChainMetadata {
+            slot,
+            blockhash: [slot as u8; 32],
+            state_root: store.state_root().to_bytes(),
+            account_count: store.len() as u64,
+            ..Default::default()
+        };
+        ReplaySnapshot::from_store(&store, metadata)
+    }
+
+    #[test]
+    fn test_write_and_read_snapshot() {
+        let dir = tempdir().unwrap();
+        let archive = SnapshotArchive::open(dir.path()).unwrap();
+        let snapshot = make_snapshot(90);
+
+        let info = archive.write(&snapshot).unwrap();
+        assert_eq!(info.manifest.slot(), 90);
+        assert_eq!(info.manifest.account_count, 5);
+        assert_eq!(read_manifest(&info.path).unwrap(), info.manifest);
+
+        let restored = read_snapshot(&info.path).unwrap();
+        assert_eq!(restored.metadata.blockhash, [90u8; 32]);
+        let mut expected = snapshot.accounts.clone();
+        let mut accounts = restored.accounts;
+        expected.sort_by_key(|(pubkey, _)| *pubkey);
+        accounts.sort_by_key(|(pubkey, _)| *pubkey);
+        assert_eq!(accounts, expected);
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_is_rejected() {
+        let dir = tempdir().unwrap();
+        let archive = SnapshotArchive::open(dir.path()).unwrap();
+        let info = archive.write(&make_snapshot(30)).unwrap();
+
+        let mut bytes = fs::read(&info.path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&info.path, &bytes).unwrap();
+        assert!(read_snapshot(&info.path).is_err());
+
+        // A state root the accounts don't hash to is rejected too
+        let mut snapshot = make_snapshot(60);
+        snapshot.metadata.state_root = [1u8; 32];
+        let info = archive.write(&snapshot).unwrap();
+        assert!(read_snapshot(&info.path).is_err());
+    }
+
+    #[test]
+    fn test_list_and_prune() {
+        let dir = tempdir().unwrap();
+        let archive = SnapshotArchive::open(dir.path()).unwrap();
+        for slot in [300, 100, 200] {
+            archive.write(&make_snapshot(slot)).unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), b"not a snapshot").unwrap();
+
+        let slots: Vec<Slot> = archive.list().unwrap().iter().map(|info| info.manifest.slot()).collect();
+        assert_eq!(slots, vec![100, 200, 300]);
+        assert_eq!(archive.prune(2).unwrap(), 1);
+        let slots: Vec<Slot> = archive.list().unwrap().iter().map(|info| info.manifest.slot()).collect();
+        assert_eq!(slots, vec![200, 300]);
+        assert_eq!(archive.latest().unwrap().unwrap().manifest.slot(), 300);
+    }
+}
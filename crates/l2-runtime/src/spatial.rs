@@ -0,0 +1,404 @@
+//! Spatial Index - grid-based nearby-entity queries
+//!
+//! Buckets players and NPCs into fixed-size cells per world so proximity
+//! queries only touch nearby cells instead of scanning every account.
+//! The index is fed from `BlockUpdate.modified_accounts` and can be rebuilt
+//! from the account store on startup.
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::BorshDeserialize;
+use parking_lot::RwLock;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    pubkey::Pubkey,
+};
+use world_program::{
+    constants::FIXED_POINT_SCALE,
+    state::{WorldNpc, WorldPlayer},
+};
+
+use crate::{account_store::AccountStore, block_producer::BlockUpdate};
+
+/// Default cell size (fixed-point, 10 world units)
+pub const DEFAULT_CELL_SIZE: i32 = 10 * FIXED_POINT_SCALE;
+
+/// Kind of indexed entity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    Player,
+    Npc,
+}
+
+/// Indexed entity position snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpatialEntity {
+    /// Account address
+    pub pubkey: Pubkey,
+    /// Player or NPC
+    pub kind: EntityKind,
+    /// World the entity is in
+    pub world: Pubkey,
+    /// X position (fixed-point)
+    pub position_x: i32,
+    /// Y position (fixed-point)
+    pub position_y: i32,
+    /// Z position (fixed-point)
+    pub position_z: i32,
+    /// Current health (0 = dead)
+    pub health: u16,
+}
+
+impl SpatialEntity {
+    /// Decode a player or NPC account (None for anything else)
+    pub fn from_account(pubkey: Pubkey, account: &AccountSharedData) -> Option<Self> {
+        if *account.owner() != world_program::id() || account.lamports() == 0 {
+            return None;
+        }
+
+        let (kind, body) = match account.data().len() {
+            WorldPlayer::LEN => (EntityKind::Player, WorldPlayer::try_from_slice(account.data()).ok()?),
+            WorldNpc::LEN => (EntityKind::Npc, WorldNpc::try_from_slice(account.data()).ok()?.body),
+            _ => return None,
+        };
+
+        // Synthesized but never-initialized PDAs decode as zeroed players
+        if body.world == Pubkey::default() {
+            return None;
+        }
+
+        Some(Self {
+            pubkey,
+            kind,
+            world: body.world,
+            position_x: body.position_x,
+            position_y: body.position_y,
+            position_z: body.position_z,
+            health: body.health,
+        })
+    }
+
+    /// Squared distance on the ground (X/Z) plane
+    fn distance_squared_xz(&self, x: i32, z: i32) -> i64 {
+        let dx = (self.position_x - x) as i64;
+        let dz = (self.position_z - z) as i64;
+        dx * dx + dz * dz
+    }
+}
+
+/// Grid cell key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CellKey {
+    world: Pubkey,
+    x: i32,
+    z: i32,
+}
+
+#[derive(Default)]
+struct Grid {
+    cells: HashMap<CellKey, HashSet<Pubkey>>,
+    entities: HashMap<Pubkey, (CellKey, SpatialEntity)>,
+}
+
+/// Spatial grid index over world entities
+pub struct SpatialIndex {
+    cell_size: i32,
+    grid: RwLock<Grid>,
+}
+
+impl SpatialIndex {
+    /// Create an index with the default cell size
+    pub fn new() -> Self {
+        Self::with_cell_size(DEFAULT_CELL_SIZE)
+    }
+
+    /// Create an index with a custom cell size (fixed-point units)
+    pub fn with_cell_size(cell_size: i32) -> Self {
+        assert!(cell_size > 0, "cell size must be positive");
+        Self {
+            cell_size,
+            grid: RwLock::new(Grid::default()),
+        }
+    }
+
+    /// Rebuild the index from every account in the store
+    pub fn rebuild(&self, store: &AccountStore) {
+        self.grid.write().clear();
+        let accounts = store.get_program_accounts(&world_program::id());
+        self.apply_accounts(&accounts);
+    }
+
+    /// Apply the account changes of a block
+    pub fn apply_block_update(&self, update: &BlockUpdate) {
+        self.apply_accounts(&update.modified_accounts);
+    }
+
+    /// Insert, move or remove entities for a set of changed accounts
+    pub fn apply_accounts(&self, accounts: &[(Pubkey, AccountSharedData)]) {
+        let mut grid = self.grid.write();
+        for (pubkey, account) in accounts {
+            match SpatialEntity::from_account(*pubkey, account) {
+                Some(entity) => {
+                    let cell = self.cell_of(&entity.world, entity.position_x, entity.position_z);
+                    grid.insert(cell, entity);
+                }
+                None => grid.remove(pubkey),
+            }
+        }
+    }
+
+    /// Look up a single entity
+    pub fn get(&self, pubkey: &Pubkey) -> Option<SpatialEntity> {
+        self.grid.read().entities.get(pubkey).map(|(_, entity)| entity.clone())
+    }
+
+    /// Number of indexed entities
+    pub fn len(&self) -> usize {
+        self.grid.read().entities.len()
+    }
+
+    /// Check if the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entities within `radius` of (x, z) on the ground plane, nearest first
+    pub fn query_radius(&self, world: &Pubkey, x: i32, z: i32, radius: i32) -> Vec<SpatialEntity> {
+        let radius = radius.max(0);
+        let radius_sq = (radius as i64) * (radius as i64);
+
+        let mut found: Vec<SpatialEntity> = self
+            .collect_box(
+                world,
+                x.saturating_sub(radius),
+                z.saturating_sub(radius),
+                x.saturating_add(radius),
+                z.saturating_add(radius),
+            )
+            .into_iter()
+            .filter(|e| e.distance_squared_xz(x, z) <= radius_sq)
+            .collect();
+
+        found.sort_by(|a, b| {
+            a.distance_squared_xz(x, z)
+                .cmp(&b.distance_squared_xz(x, z))
+                .then_with(|| a.pubkey.cmp(&b.pubkey))
+        });
+        found
+    }
+
+    /// Entities inside the inclusive box [min, max] on the ground plane
+    pub fn query_box(
+        &self,
+        world: &Pubkey,
+        min_x: i32,
+        min_z: i32,
+        max_x: i32,
+        max_z: i32,
+    ) -> Vec<SpatialEntity> {
+        let mut found = self.collect_box(world, min_x, min_z, max_x, max_z);
+        found.sort_by_key(|e| e.pubkey);
+        found
+    }
+
+    fn collect_box(
+        &self,
+        world: &Pubkey,
+        min_x: i32,
+        min_z: i32,
+        max_x: i32,
+        max_z: i32,
+    ) -> Vec<SpatialEntity> {
+        if min_x > max_x || min_z > max_z {
+            return Vec::new();
+        }
+
+        let inside = |e: &SpatialEntity| {
+            e.world == *world
+                && (min_x..=max_x).contains(&e.position_x)
+                && (min_z..=max_z).contains(&e.position_z)
+        };
+
+        let grid = self.grid.read();
+        let min_cell = self.cell_of(world, min_x, min_z);
+        let max_cell = self.cell_of(world, max_x, max_z);
+        let cell_count = (max_cell.x as i64 - min_cell.x as i64 + 1)
+            * (max_cell.z as i64 - min_cell.z as i64 + 1);
+
+        // Huge areas: scanning every entity is cheaper than walking empty cells
+        if cell_count > grid.entities.len() as i64 {
+            return grid
+                .entities
+                .values()
+                .map(|(_, e)| e)
+                .filter(|e| inside(e))
+                .cloned()
+                .collect();
+        }
+
+        let mut found = Vec::new();
+        for cx in min_cell.x..=max_cell.x {
+            for cz in min_cell.z..=max_cell.z {
+                let key = CellKey { world: *world, x: cx, z: cz };
+                let Some(members) = grid.cells.get(&key) else { continue };
+                found.extend(
+                    members
+                        .iter()
+                        .filter_map(|pk| grid.entities.get(pk))
+                        .map(|(_, e)| e)
+                        .filter(|e| inside(e))
+                        .cloned(),
+                );
+            }
+        }
+        found
+    }
+
+    fn cell_of(&self, world: &Pubkey, x: i32, z: i32) -> CellKey {
+        CellKey {
+            world: *world,
+            x: x.div_euclid(self.cell_size),
+            z: z.div_euclid(self.cell_size),
+        }
+    }
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Grid {
+    fn insert(&mut self, cell: CellKey, entity: SpatialEntity) {
+        let pubkey = entity.pubkey;
+        if let Some((old_cell, _)) = self.entities.get(&pubkey) {
+            if *old_cell == cell {
+                self.entities.insert(pubkey, (cell, entity));
+                return;
+            }
+            self.remove(&pubkey);
+        }
+
+        self.cells.entry(cell).or_default().insert(pubkey);
+        self.entities.insert(pubkey, (cell, entity));
+    }
+
+    fn remove(&mut self, pubkey: &Pubkey) {
+        let Some((cell, _)) = self.entities.remove(pubkey) else { return };
+
+        if let Some(members) = self.cells.get_mut(&cell) {
+            members.remove(pubkey);
+            if members.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cells.clear();
+        self.entities.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::{Account, WritableAccount};
+
+    fn player_account(world: Pubkey, x: i32, z: i32) -> AccountSharedData {
+        let player = WorldPlayer {
+            world,
+            position_x: x,
+            position_z: z,
+            health: 100,
+            max_health: 100,
+            ..Default::default()
+        };
+        AccountSharedData::from(Account {
+            lamports: 1,
+            data: borsh::to_vec(&player).unwrap(),
+            owner: world_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+
+    #[test]
+    fn test_radius_query() {
+        let index = SpatialIndex::new();
+        let world = Pubkey::new_unique();
+        let near = Pubkey::new_unique();
+        let mid = Pubkey::new_unique();
+        let far = Pubkey::new_unique();
+
+        index.apply_accounts(&[
+            (far, player_account(world, 90_000, 90_000)),
+            (near, player_account(world, 51_000, 50_000)),
+            (mid, player_account(world, 50_000, 58_000)),
+        ]);
+
+        let found = index.query_radius(&world, 50_000, 50_000, 10_000);
+        let keys: Vec<Pubkey> = found.iter().map(|e| e.pubkey).collect();
+        assert_eq!(keys, vec![near, mid]);
+        assert_eq!(found[0].kind, EntityKind::Player);
+    }
+
+    #[test]
+    fn test_box_query_and_world_isolation() {
+        let index = SpatialIndex::new();
+        let world_a = Pubkey::new_unique();
+        let world_b = Pubkey::new_unique();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+
+        index.apply_accounts(&[
+            (a, player_account(world_a, 5_000, 5_000)),
+            (b, player_account(world_b, 5_000, 5_000)),
+        ]);
+
+        let found = index.query_box(&world_a, 0, 0, 10_000, 10_000);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pubkey, a);
+        assert!(index.query_box(&world_a, 6_000, 0, 10_000, 10_000).is_empty());
+    }
+
+    #[test]
+    fn test_moves_and_removals() {
+        let index = SpatialIndex::with_cell_size(1_000);
+        let world = Pubkey::new_unique();
+        let pk = Pubkey::new_unique();
+
+        index.apply_accounts(&[(pk, player_account(world, 0, 0))]);
+        assert_eq!(index.query_radius(&world, 0, 0, 500).len(), 1);
+
+        // Moves across cells
+        index.apply_accounts(&[(pk, player_account(world, 20_000, 0))]);
+        assert!(index.query_radius(&world, 0, 0, 500).is_empty());
+        assert_eq!(index.query_radius(&world, 20_000, 0, 500).len(), 1);
+        assert_eq!(index.len(), 1);
+
+        // Closed accounts leave the index
+        let mut closed = player_account(world, 20_000, 0);
+        closed.set_lamports(0);
+        index.apply_accounts(&[(pk, closed)]);
+        assert!(index.is_empty());
+        assert!(index.query_radius(&world, 20_000, 0, 500).is_empty());
+    }
+
+    #[test]
+    fn test_ignores_non_entities() {
+        let index = SpatialIndex::new();
+        let zeroed = AccountSharedData::from(Account {
+            lamports: 1,
+            data: vec![0u8; WorldPlayer::LEN],
+            owner: world_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        });
+        let wallet = AccountSharedData::new(1, 0, &solana_sdk::system_program::id());
+
+        index.apply_accounts(&[(Pubkey::new_unique(), zeroed), (Pubkey::new_unique(), wallet)]);
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+//! Accounts State Tree
+//!
+//! Sparse Merkle tree committing to every account, keyed by the bits of its
+//! pubkey (most significant first). A subtree holding a single account
+//! collapses into that account's leaf, so the tree is only as deep as it
+//! needs to be to tell accounts apart (about log2 of the account count) and
+//! inclusion proofs stay short. Updates are incremental: changing an account
+//! only rehashes the nodes on its path, the rest come from a cache.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    hash::{hashv, Hash, Hasher},
+    pubkey::Pubkey,
+};
+
+/// Root of a tree with no accounts (and the hash of any empty subtree)
+pub const EMPTY_ROOT: Hash = Hash::new_from_array([0; 32]);
+
+/// Domain separators so a leaf can never be passed off as an inner node
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hash of an account's contents (lamports, owner, executable, data)
+pub fn account_hash(account: &AccountSharedData) -> Hash {
+    let mut hasher = Hasher::default();
+    hasher.hash(&account.lamports().to_le_bytes());
+    hasher.hash(account.owner().as_ref());
+    hasher.hash(&[account.executable() as u8]);
+    hasher.hash(&(account.data().len() as u64).to_le_bytes());
+    hasher.hash(account.data());
+    hasher.result()
+}
+
+/// Leaf hash binding an account hash to its pubkey
+fn leaf_hash(pubkey: &Pubkey, account_hash: &Hash) -> Hash {
+    hashv(&[LEAF_PREFIX, pubkey.as_ref(), account_hash.as_ref()])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    hashv(&[NODE_PREFIX, left.as_ref(), right.as_ref()])
+}
+
+/// Bit `depth` of a key (0 = most significant)
+fn bit(key: &[u8; 32], depth: usize) -> bool {
+    key[depth / 8] >> (7 - depth % 8) & 1 == 1
+}
+
+/// The first `depth` bits of a key, the rest zeroed (the node's lowest key)
+fn prefix(key: &[u8; 32], depth: usize) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        let kept = depth.saturating_sub(i * 8).min(8);
+        if kept > 0 {
+            *byte = key[i] & (0xFFu8 << (8 - kept));
+        }
+    }
+    out
+}
+
+/// The first `depth` bits of a key, the rest set (the node's highest key)
+fn prefix_end(key: &[u8; 32], depth: usize) -> [u8; 32] {
+    let mut out = prefix(key, depth);
+    for (i, byte) in out.iter_mut().enumerate() {
+        let kept = depth.saturating_sub(i * 8).min(8);
+        if kept < 8 {
+            *byte |= 0xFFu8 >> kept;
+        }
+    }
+    out
+}
+
+/// Number of leading bits two keys share
+fn common_prefix_len(a: &[u8; 32], b: &[u8; 32]) -> usize {
+    for i in 0..32 {
+        let diff = a[i] ^ b[i];
+        if diff != 0 {
+            return i * 8 + diff.leading_zeros() as usize;
+        }
+    }
+    256
+}
+
+/// Proof that an account is part of the state under a root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateProof {
+    /// Proven account
+    pub pubkey: Pubkey,
+    /// Hash of the account's contents (see `account_hash`)
+    pub account_hash: Hash,
+    /// Sibling hashes on the path from the root down to the account's leaf
+    pub siblings: Vec<Hash>,
+}
+
+impl StateProof {
+    /// Root this proof hashes up to
+    pub fn root(&self) -> Hash {
+        let key = self.pubkey.to_bytes();
+        let mut hash = leaf_hash(&self.pubkey, &self.account_hash);
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            hash = if bit(&key, depth) {
+                node_hash(sibling, &hash)
+            } else {
+                node_hash(&hash, sibling)
+            };
+        }
+        hash
+    }
+
+    /// Check the proof shows `account` under `root`
+    pub fn verify(&self, root: &Hash, account: &AccountSharedData) -> bool {
+        account_hash(account) == self.account_hash && self.root() == *root
+    }
+}
+
+/// Incremental sparse Merkle tree over account hashes
+#[derive(Debug, Default)]
+pub struct StateTree {
+    /// Account hash of every account, in key order
+    leaves: BTreeMap<Pubkey, Hash>,
+    /// Cached hashes of nodes holding two or more leaves, by (depth, prefix)
+    nodes: HashMap<(usize, [u8; 32]), Hash>,
+}
+
+impl StateTree {
+    /// Create an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of accounts in the tree
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Check if the tree has no accounts
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Insert, update (`Some`) or remove (`None`) an account
+    pub fn update(&mut self, pubkey: Pubkey, account: Option<&AccountSharedData>) {
+        let hash = account.map(account_hash);
+        if self.leaves.get(&pubkey) == hash.as_ref() {
+            return;
+        }
+
+        // Only nodes this account shares with a neighbour hold two or more
+        // leaves; deeper nodes on its path collapse into the leaf itself
+        let key = pubkey.to_bytes();
+        let prev = self.leaves.range(..pubkey).next_back();
+        let next = self
+            .leaves
+            .range((Bound::Excluded(pubkey), Bound::Unbounded))
+            .next();
+        let shared = [prev, next]
+            .into_iter()
+            .flatten()
+            .map(|(other, _)| common_prefix_len(&key, &other.to_bytes()))
+            .max();
+        if let Some(shared) = shared {
+            for depth in 0..=shared {
+                self.nodes.remove(&(depth, prefix(&key, depth)));
+            }
+        }
+
+        match hash {
+            Some(hash) => self.leaves.insert(pubkey, hash),
+            None => self.leaves.remove(&pubkey),
+        };
+    }
+
+    /// Current root
+    pub fn root(&mut self) -> Hash {
+        self.subtree(0, [0u8; 32])
+    }
+
+    /// Inclusion proof for an account against the current root
+    pub fn prove(&mut self, pubkey: &Pubkey) -> Option<StateProof> {
+        let account_hash = *self.leaves.get(pubkey)?;
+        let key = pubkey.to_bytes();
+        let mut siblings = Vec::new();
+        let mut depth = 0;
+        while self.count(depth, &key) > 1 {
+            let mut sibling = prefix(&key, depth + 1);
+            sibling[depth / 8] ^= 0x80 >> (depth % 8);
+            siblings.push(self.subtree(depth + 1, sibling));
+            depth += 1;
+        }
+
+        Some(StateProof {
+            pubkey: *pubkey,
+            account_hash,
+            siblings,
+        })
+    }
+
+    /// Leaves under a node, capped at 2
+    fn count(&self, depth: usize, key: &[u8; 32]) -> usize {
+        let lo = Pubkey::new_from_array(prefix(key, depth));
+        let hi = Pubkey::new_from_array(prefix_end(key, depth));
+        self.leaves.range(lo..=hi).take(2).count()
+    }
+
+    /// Hash of the node at `depth` whose keys start with `node`
+    fn subtree(&mut self, depth: usize, node: [u8; 32]) -> Hash {
+        let lo = Pubkey::new_from_array(node);
+        let hi = Pubkey::new_from_array(prefix_end(&node, depth));
+        let mut range = self.leaves.range(lo..=hi);
+        let Some((pubkey, account_hash)) = range.next() else {
+            return EMPTY_ROOT;
+        };
+        if range.next().is_none() {
+            return leaf_hash(pubkey, account_hash);
+        }
+        if let Some(hash) = self.nodes.get(&(depth, node)) {
+            return *hash;
+        }
+
+        let left = self.subtree(depth + 1, node);
+        let mut right_node = node;
+        right_node[depth / 8] |= 0x80 >> (depth % 8);
+        let right = self.subtree(depth + 1, right_node);
+        let hash = node_hash(&left, &right);
+        self.nodes.insert((depth, node), hash);
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::WritableAccount;
+
+    /// Spread-out keys (`Pubkey::new_unique` only differs in the low bits)
+    fn random_key(seed: u64) -> Pubkey {
+        Pubkey::new_from_array(solana_sdk::hash::hash(&seed.to_le_bytes()).to_bytes())
+    }
+
+    fn make_account(lamports: u64) -> AccountSharedData {
+        AccountSharedData::new(lamports, 8, &Pubkey::new_unique())
+    }
+
+    /// Root of a tree built from scratch, without any cached nodes
+    fn fresh_root(accounts: &[(Pubkey, AccountSharedData)]) -> Hash {
+        let mut tree = StateTree::new();
+        for (pubkey, account) in accounts {
+            tree.update(*pubkey, Some(account));
+        }
+        tree.root()
+    }
+
+    #[test]
+    fn test_incremental_root_matches_rebuild() {
+        let mut tree = StateTree::new();
+        assert_eq!(tree.root(), EMPTY_ROOT);
+
+        let mut accounts: Vec<_> = (1..=40).map(|i| (random_key(i), make_account(i))).collect();
+        for (pubkey, account) in &accounts {
+            tree.update(*pubkey, Some(account));
+            tree.root();
+        }
+        assert_eq!(tree.root(), fresh_root(&accounts));
+
+        // Change a few, remove a few, then compare against a clean build
+        for (pubkey, account) in accounts.iter_mut().step_by(7) {
+            account.set_lamports(account.lamports() + 1000);
+            tree.update(*pubkey, Some(account));
+        }
+        for (pubkey, _) in accounts.drain(..5) {
+            tree.update(pubkey, None);
+        }
+        assert_eq!(tree.len(), accounts.len());
+        assert_eq!(tree.root(), fresh_root(&accounts));
+
+        for (pubkey, _) in accounts.drain(..) {
+            tree.update(pubkey, None);
+        }
+        assert_eq!(tree.root(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn test_proofs_verify_against_root() {
+        let mut tree = StateTree::new();
+        let accounts: Vec<_> = (1..=25).map(|i| (random_key(i), make_account(i))).collect();
+        for (pubkey, account) in &accounts {
+            tree.update(*pubkey, Some(account));
+        }
+        let root = tree.root();
+
+        for (pubkey, account) in &accounts {
+            let proof = tree.prove(pubkey).unwrap();
+            assert!(proof.verify(&root, account));
+            assert!(proof.siblings.len() < 32, "collapsed paths keep proofs short");
+            assert!(!proof.verify(&root, &make_account(0)));
+        }
+        assert!(tree.prove(&Pubkey::new_unique()).is_none());
+
+        // A single account is its own root
+        let mut single = StateTree::new();
+        single.update(accounts[0].0, Some(&accounts[0].1));
+        let proof = single.prove(&accounts[0].0).unwrap();
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&single.root(), &accounts[0].1));
+    }
+}
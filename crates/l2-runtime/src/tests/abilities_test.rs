@@ -0,0 +1,225 @@
+//! Movement Ability Integration Tests
+//!
+//! Tests dash and crouch end to end:
+//! - Dash bursts in the input direction and then cools down per the world's timings
+//! - Only the world authority can tune abilities, and a dash speed of 0 disables dashing
+//! - Crouching slows movement and lowers the hitbox used for weapon reach
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_world_name, setup_world_account};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{CROUCH_HEIGHT, CROUCH_SPEED, STANDING_HEIGHT},
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WeaponStats, WorldConfig, WorldPlayer},
+};
+
+const DASH_SPEED: i16 = 1_000;
+const DASH_COOLDOWN: u64 = 10;
+
+/// Set up a world, returning (world PDA, world authority)
+fn setup_world(store: &AccountStore) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Gym");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+    (world_pda, authority)
+}
+
+fn configure_abilities(world_pda: Pubkey, authority: &Keypair, dash_speed: i16, dash_cooldown_slots: u64) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ConfigureAbilities {
+            dash_speed,
+            dash_cooldown_slots,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    )
+}
+
+fn move_3d(world_pda: Pubkey, player_pda: Pubkey, wallet: &Keypair, input: MovementInput3D) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+fn attack(world_pda: Pubkey, attacker_pda: Pubkey, wallet: &Keypair, target_pda: Pubkey, range: u16) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Attack {
+            weapon_stats: Some(WeaponStats {
+                damage: 1,
+                range,
+                attack_speed: 0,
+            }),
+            observed_slot: None,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(attacker_pda, false),
+            AccountMeta::new(target_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+/// Rewrite a player's account
+fn update_player(store: &AccountStore, player_pda: Pubkey, update: impl FnOnce(&mut WorldPlayer)) {
+    let mut player = load_player(store, &player_pda);
+    update(&mut player);
+    let mut account = store.get_account(&player_pda).unwrap();
+    borsh::to_writer(account.data_as_mut_slice(), &player).unwrap();
+    store.store_account(player_pda, account, 0);
+}
+
+/// Test 1: Dash bursts forward once, then waits out the world's cooldown
+#[test]
+fn test_dash_burst_and_cooldown() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+    let result = process(
+        &mut processor,
+        &authority,
+        configure_abilities(world_pda, &authority, DASH_SPEED, DASH_COOLDOWN),
+    );
+    assert!(result.success, "ConfigureAbilities failed: {:?}", result.error);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Dasher");
+    processor.advance_slot();
+
+    let dash_forward = MovementInput3D {
+        move_z: 127,
+        dash: true,
+        ..Default::default()
+    };
+    let slot = processor.current_slot();
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, dash_forward)).success);
+    let dashed = load_player(&store, &player_pda);
+    assert_eq!((dashed.velocity_x, dashed.velocity_z), (0, DASH_SPEED));
+    assert_eq!(dashed.dash_ready_slot, slot + DASH_COOLDOWN);
+
+    // On cooldown: the burst just slows toward walking speed
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, dash_forward)).success);
+    let slowed = load_player(&store, &player_pda).velocity_z;
+    assert!(slowed < DASH_SPEED, "dash should be on cooldown");
+
+    while processor.current_slot() < dashed.dash_ready_slot {
+        processor.advance_slot();
+    }
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, dash_forward)).success);
+    assert_eq!(load_player(&store, &player_pda).velocity_z, DASH_SPEED);
+}
+
+/// Test 2: Ability timings belong to the world authority, and speed 0 turns dashing off
+#[test]
+fn test_configure_abilities_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+
+    let stranger = Keypair::new();
+    let result = process(&mut processor, &stranger, configure_abilities(world_pda, &stranger, DASH_SPEED, 1));
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+    let result = process(&mut processor, &authority, configure_abilities(world_pda, &authority, -1, 1));
+    assert_eq!(custom_error(&result), Some(WorldError::InvalidInstructionData as u32));
+
+    // The test world starts with dashing disabled
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Grounded");
+    processor.advance_slot();
+    let dash = MovementInput3D {
+        dash: true,
+        ..Default::default()
+    };
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, dash)).success);
+    let player = load_player(&store, &player_pda);
+    assert_eq!((player.velocity_x, player.velocity_z), (0, 0));
+    assert_eq!(player.dash_ready_slot, 0);
+
+    // Without movement input the dash follows the camera (yaw 16384 = +X)
+    assert!(process(&mut processor, &authority, configure_abilities(world_pda, &authority, DASH_SPEED, 1)).success);
+    let dash = MovementInput3D {
+        camera_yaw: 16384,
+        ..dash
+    };
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, dash)).success);
+    let player = load_player(&store, &player_pda);
+    assert_eq!(player.velocity_x, DASH_SPEED);
+    assert!(player.velocity_z.abs() <= 1, "dash should point along +X, got z={}", player.velocity_z);
+}
+
+/// Test 3: Crouching slows the player and ducks under reach from above
+#[test]
+fn test_crouch_speed_and_hitbox() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_world(&store);
+
+    let attacker_wallet = Keypair::new();
+    let attacker_pda = join_player(&mut processor, &store, world_pda, &attacker_wallet, "Jumper");
+    processor.advance_slot();
+    let target_wallet = Keypair::new();
+    let target_pda = join_player(&mut processor, &store, world_pda, &target_wallet, "Ducker");
+    processor.advance_slot();
+
+    // Crouch-walking is capped at crouch speed, even when sprinting
+    let crouch_sprint = MovementInput3D {
+        move_z: 127,
+        sprint: true,
+        crouch: true,
+        ..Default::default()
+    };
+    for _ in 0..5 {
+        let ix = move_3d(world_pda, target_pda, &target_wallet, crouch_sprint);
+        assert!(process(&mut processor, &target_wallet, ix).success);
+    }
+    let target = load_player(&store, &target_pda);
+    assert!(target.is_crouching);
+    assert_eq!(target.hitbox_height(), CROUCH_HEIGHT);
+    assert_eq!(target.velocity_z, CROUCH_SPEED);
+
+    // Attacker hovers right above the target; a standing target's head is in reach
+    let standing_gap = 1_000;
+    update_player(&store, attacker_pda, |attacker| {
+        attacker.position_x = target.position_x;
+        attacker.position_z = target.position_z;
+        attacker.position_y = STANDING_HEIGHT + standing_gap - STANDING_HEIGHT / 2;
+    });
+    let range = (standing_gap + 100) as u16;
+    let result = process(&mut processor, &attacker_wallet, attack(world_pda, attacker_pda, &attacker_wallet, target_pda, range));
+    assert_eq!(custom_error(&result), Some(WorldError::TargetOutOfRange as u32));
+
+    // Standing up brings the head back into reach
+    let stand = MovementInput3D::default();
+    assert!(process(&mut processor, &target_wallet, move_3d(world_pda, target_pda, &target_wallet, stand)).success);
+    assert!(!load_player(&store, &target_pda).is_crouching);
+    let result = process(&mut processor, &attacker_wallet, attack(world_pda, attacker_pda, &attacker_wallet, target_pda, range));
+    assert!(result.success, "Attack failed: {:?}", result.error);
+}
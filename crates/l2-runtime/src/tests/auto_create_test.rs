@@ -0,0 +1,81 @@
+//! Auto-Create Tests
+//!
+//! Tests that missing accounts are only synthesized where a rule expects them:
+//! - A PDA an instruction creates gets its state's size and owner
+//! - A misderived PDA isn't created
+//! - Without rules, an unknown fee payer is AccountNotFound
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    account::ReadableAccount,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+use super::join_world_test::{
+    create_initialize_world_instruction, create_join_world_instruction, create_sanitized_transaction,
+    make_player_name, make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, callback::AutoCreateRegistry, processor::L2Processor};
+
+use world_program::state::WorldConfig;
+
+/// Test 1: InitializeWorld creates the world PDA sized for a WorldConfig
+#[test]
+fn test_initialize_world_creates_world_config() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let authority = Keypair::new();
+    let name = make_world_name("Fresh");
+    let (world_pda, _) = WorldConfig::derive_pda(&name, &world_program::id());
+
+    let ix = create_initialize_world_instruction(world_pda, &authority, name, 100, 100, 10);
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert!(result.success, "InitializeWorld failed: {:?}", result.error);
+
+    let world = store.get_account(&world_pda).unwrap();
+    assert_eq!(world.data().len(), WorldConfig::LEN);
+    assert_eq!(world.owner(), &world_program::id());
+}
+
+/// Test 2: A player account at the wrong address isn't synthesized
+#[test]
+fn test_misderived_pda_is_not_created() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let authority = Keypair::new();
+    let world_name = make_world_name("Strict");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let wallet = Keypair::new();
+    let (typo_pda, _) = Pubkey::find_program_address(
+        &[b"world_playr", world_pda.as_ref(), wallet.pubkey().as_ref()],
+        &world_program::id(),
+    );
+    let ix = create_join_world_instruction(world_pda, typo_pda, &wallet, make_player_name("Typo"));
+    let tx = create_sanitized_transaction(&wallet, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert!(!result.success);
+    assert!(!store.account_exists(&typo_pda));
+}
+
+/// Test 3: With no rules, a fee payer missing from the store is not found
+#[test]
+fn test_missing_payer_without_rules_is_not_found() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    processor.set_auto_create_rules(AutoCreateRegistry::new());
+    let authority = Keypair::new();
+    let name = make_world_name("Nobody");
+    let (world_pda, _) = WorldConfig::derive_pda(&name, &world_program::id());
+
+    let ix = create_initialize_world_instruction(world_pda, &authority, name, 100, 100, 10);
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert_eq!(result.error, Some(TransactionError::AccountNotFound));
+    assert!(!store.account_exists(&world_pda));
+}
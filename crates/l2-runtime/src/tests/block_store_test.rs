@@ -0,0 +1,92 @@
+//! Block Store Tests
+//!
+//! Tests that the block producer records every sealed block:
+//! - Transactions in order, with their results and modified accounts
+//! - Blocks chain by slot and blockhash, empty blocks included
+//! - Transactions can be looked up by signature
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use tempfile::tempdir;
+
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    block_producer::{BlockProducer, BlockProducerConfig},
+    block_store::{BlockStore, BlockStoreConfig, BlockWriter},
+    processor::L2Processor,
+};
+
+use world_program::state::{WorldConfig, WorldPlayer};
+
+/// Test 1: A produced block is stored with its transactions and results
+#[test]
+fn test_produced_blocks_are_recorded() {
+    let dir = tempdir().unwrap();
+    let block_store = Arc::new(BlockStore::open(dir.path(), BlockStoreConfig::default()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    producer.set_block_writer(BlockWriter::spawn(block_store.clone()));
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("History");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let wallet = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+    let blockhash = producer.current_blockhash();
+    let join = create_sanitized_transaction(
+        &wallet,
+        vec![create_join_world_instruction(world_pda, player_pda, &wallet, make_player_name("Keeper"))],
+        blockhash,
+    );
+    // A second wallet joining with someone else's player account fails
+    let other = Keypair::new();
+    let steal = create_sanitized_transaction(
+        &other,
+        vec![create_join_world_instruction(world_pda, player_pda, &other, make_player_name("Thief"))],
+        blockhash,
+    );
+
+    let update = producer.produce_block(vec![join.clone(), steal.clone()]);
+    let empty = producer.produce_block(vec![]);
+    // Dropping the producer waits for its blocks to be written
+    drop(producer);
+
+    let block = block_store.get_block(update.slot - 1).unwrap().unwrap();
+    assert_eq!(block.blockhash, update.blockhash);
+    assert_eq!(block.parent_blockhash, update.parent_blockhash);
+    assert_eq!(block.state_root, update.state_root);
+    let signatures: Vec<_> = block.transactions.iter().map(|tx| tx.signature()).collect();
+    assert_eq!(signatures, vec![*join.signature(), *steal.signature()]);
+    assert!(block.transactions[0].success);
+    assert!(block.transactions[0].modified_accounts.contains(&player_pda));
+    assert!(!block.transactions[0].logs.is_empty());
+    assert!(!block.transactions[1].success);
+    assert!(matches!(block.transactions[1].error, Some(TransactionError::InstructionError(..))));
+    let (_, player) = block
+        .modified_accounts
+        .iter()
+        .find(|(pubkey, _)| *pubkey == player_pda)
+        .unwrap();
+    assert_eq!(Some(player), store.get_account(&player_pda).as_ref());
+
+    // The empty block chains onto it
+    let next = block_store.get_block(block.slot + 1).unwrap().unwrap();
+    assert_eq!(next.parent_slot, block.slot);
+    assert_eq!(next.parent_blockhash, block.blockhash);
+    assert_eq!(next.blockhash, empty.blockhash);
+    assert!(next.transactions.is_empty());
+
+    let (slot, tx) = block_store.get_transaction(steal.signature()).unwrap().unwrap();
+    assert_eq!(slot, block.slot);
+    assert_eq!(tx, block.transactions[1]);
+}
@@ -0,0 +1,222 @@
+//! Blockhash Chain Tests
+//!
+//! Tests that blockhashes are derived from block contents:
+//! - Each blockhash commits to the parent, slot, transactions and state root
+//! - The same blocks over the same state reproduce the same chain
+//! - Different transactions give a different chain
+//! - A processor built from persisted metadata continues the chain
+//! - The epoch follows the slot across a resume and an epoch boundary
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Clock,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    sysvar,
+};
+
+use super::UNIX_TIMESTAMP;
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    persistence::ChainMetadata,
+    processor::{derive_blockhash, L2Processor, GENESIS_BLOCKHASH},
+    SLOTS_PER_EPOCH,
+};
+
+use world_program::{
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WorldConfig, WorldPlayer},
+};
+
+/// A processor with one world, plus the world PDA
+fn setup(authority: &Keypair) -> (L2Processor, Arc<AccountStore>, Pubkey) {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    processor.set_unix_timestamp(UNIX_TIMESTAMP);
+    let world_name = make_world_name("Chain");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+    (processor, store, world_pda)
+}
+
+fn move_3d(world_pda: Pubkey, player_pda: Pubkey, wallet: &Keypair, move_z: i8) -> Instruction {
+    let input = MovementInput3D {
+        move_z,
+        ..Default::default()
+    };
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+/// Join a player, then move them once per slot, returning every sealed blockhash
+fn run_chain(authority: &Keypair, wallet: &Keypair, moves: &[i8]) -> Vec<Hash> {
+    let (mut processor, store, world_pda) = setup(authority);
+    let player_pda = join_player(&mut processor, &store, world_pda, wallet, "Chainer");
+    processor.advance_slot();
+    processor.set_unix_timestamp(UNIX_TIMESTAMP);
+
+    let mut hashes = vec![processor.current_blockhash()];
+    for &move_z in moves {
+        let ix = move_3d(world_pda, player_pda, wallet, move_z);
+        let tx = create_sanitized_transaction(wallet, vec![ix], processor.current_blockhash());
+        assert!(processor.process_transactions(&[tx])[0].success);
+        processor.advance_slot();
+        processor.set_unix_timestamp(UNIX_TIMESTAMP);
+        hashes.push(processor.current_blockhash());
+    }
+    hashes
+}
+
+/// Test 1: A sealed block's hash is derived from its parent, slot, transactions and state
+#[test]
+fn test_blockhash_commits_to_block() {
+    let authority = Keypair::new();
+    let (mut processor, store, world_pda) = setup(&authority);
+    assert_eq!(processor.current_blockhash(), GENESIS_BLOCKHASH);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Sealer");
+    processor.advance_slot();
+    let parent = processor.current_blockhash();
+    assert_eq!(processor.parent_blockhash(), GENESIS_BLOCKHASH);
+
+    let slot = processor.current_slot();
+    let ix = move_3d(world_pda, player_pda, &wallet, 127);
+    let tx = create_sanitized_transaction(&wallet, vec![ix], parent);
+    let signature = *tx.signature();
+    assert!(processor.process_transactions(&[tx])[0].success);
+    processor.advance_slot();
+
+    assert_eq!(processor.parent_blockhash(), parent);
+    assert_eq!(processor.state_root(), store.state_root());
+    assert_eq!(
+        processor.current_blockhash(),
+        derive_blockhash(&parent, slot, &[signature], &store.state_root())
+    );
+
+    // An empty block still moves the chain forward
+    let slot = processor.current_slot();
+    let parent = processor.current_blockhash();
+    processor.advance_slot();
+    let empty: [Signature; 0] = [];
+    assert_eq!(
+        processor.current_blockhash(),
+        derive_blockhash(&parent, slot, &empty, &store.state_root())
+    );
+    assert_ne!(processor.current_blockhash(), parent);
+}
+
+/// Test 2: Replaying the same blocks over the same state gives the same chain,
+/// and different transactions give a different one
+#[test]
+fn test_blockhash_chain_is_reproducible() {
+    let authority = Keypair::new();
+    let wallet = Keypair::new();
+
+    let chain = run_chain(&authority, &wallet, &[127, 127, -127]);
+    assert_eq!(run_chain(&authority, &wallet, &[127, 127, -127]), chain);
+
+    let forked = run_chain(&authority, &wallet, &[127, 64, -127]);
+    assert_eq!(forked[..2], chain[..2]);
+    assert_ne!(forked[2], chain[2]);
+    assert_ne!(forked[3], chain[3]);
+}
+
+/// Test 3: A processor restarted from persisted metadata picks up the slot,
+/// blockhash and Clock, and seals the same next block as one that never stopped
+#[test]
+fn test_processor_resumes_from_metadata() {
+    let authority = Keypair::new();
+    let (mut processor, store, world_pda) = setup(&authority);
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Sleeper");
+    for _ in 0..3 {
+        processor.advance_slot();
+    }
+
+    let metadata = ChainMetadata {
+        slot: processor.current_slot(),
+        blockhash: processor.current_blockhash().to_bytes(),
+        state_root: processor.state_root().to_bytes(),
+        epoch: 0,
+        ..Default::default()
+    };
+    let restored_store = Arc::new(AccountStore::new());
+    for pubkey in store.get_all_pubkeys() {
+        restored_store.store_account(pubkey, store.get_account(&pubkey).unwrap(), 0);
+    }
+    let mut restored = L2Processor::from_metadata(restored_store.clone(), &metadata);
+
+    assert_eq!(restored.current_slot(), processor.current_slot());
+    assert_eq!(restored.current_blockhash(), processor.current_blockhash());
+    assert_eq!(restored.state_root(), processor.state_root());
+    let clock = restored_store.get_account(&sysvar::clock::id()).unwrap();
+    let clock: Clock = bincode::deserialize(clock.data()).unwrap();
+    assert_eq!(clock.slot, metadata.slot);
+
+    // The same move in both seals the same block
+    let slot = processor.current_slot();
+    let ix = move_3d(world_pda, player_pda, &wallet, 127);
+    let tx = create_sanitized_transaction(&wallet, vec![ix], processor.current_blockhash());
+    let timestamp = processor.unix_timestamp();
+    restored.set_unix_timestamp(timestamp);
+    assert!(processor.process_transactions(std::slice::from_ref(&tx))[0].success);
+    assert!(restored.process_transactions(&[tx])[0].success);
+    processor.advance_slot();
+    restored.advance_slot();
+    assert_eq!(restored.current_blockhash(), processor.current_blockhash());
+
+    let player = restored_store.get_account(&player_pda).unwrap();
+    let player = WorldPlayer::try_from_slice(player.data()).unwrap();
+    assert_eq!(player.last_action_slot, slot);
+}
+
+/// Test 4: A chain resumed just before an epoch boundary enters the next
+/// epoch when it crosses it, whatever epoch the metadata recorded
+#[test]
+fn test_resume_crosses_epoch_boundary() {
+    let clock_epoch = |store: &AccountStore| {
+        let clock = store.get_account(&sysvar::clock::id()).unwrap();
+        bincode::deserialize::<Clock>(clock.data()).unwrap().epoch
+    };
+    let store = Arc::new(AccountStore::new());
+    let metadata = ChainMetadata {
+        slot: SLOTS_PER_EPOCH - 1,
+        blockhash: Hash::new_unique().to_bytes(),
+        ..Default::default()
+    };
+    let mut processor = L2Processor::from_metadata(store.clone(), &metadata);
+    assert_eq!(processor.current_epoch(), 0);
+
+    processor.advance_slot();
+    assert_eq!(processor.current_slot(), SLOTS_PER_EPOCH);
+    assert_eq!(processor.current_epoch(), 1);
+    assert_eq!(clock_epoch(&store), 1);
+
+    // Resuming past the boundary from metadata with a stale epoch
+    let metadata = ChainMetadata {
+        slot: processor.current_slot(),
+        blockhash: processor.current_blockhash().to_bytes(),
+        epoch: 0,
+        ..Default::default()
+    };
+    let restored_store = Arc::new(AccountStore::new());
+    let restored = L2Processor::from_metadata(restored_store.clone(), &metadata);
+    assert_eq!(restored.current_epoch(), 1);
+    assert_eq!(clock_epoch(&restored_store), 1);
+}
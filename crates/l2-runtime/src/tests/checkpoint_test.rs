@@ -0,0 +1,183 @@
+//! Checkpoint Tests
+//!
+//! Tests that checkpoints hold the state at a single block boundary:
+//! - Checkpoints taken while blocks are produced match their metadata's root
+//! - A checkpoint after stopping covers the last produced block
+//! - Replaying the write-ahead log stops at a missing slot
+
+use std::{sync::Arc, thread, time::Duration};
+
+use solana_sdk::signature::{Keypair, Signer};
+use tempfile::tempdir;
+
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    block_producer::{BlockProducer, BlockProducerConfig},
+    persistence::{AccountStorePersistence, CheckpointWriter, PersistentStore, SlotDelta},
+    processor::L2Processor,
+};
+
+use world_program::state::{WorldConfig, WorldPlayer};
+
+/// State root of the accounts checkpointed to `persistent`
+fn checkpointed_root(persistent: &PersistentStore) -> [u8; 32] {
+    let loaded = AccountStore::new();
+    loaded.load_from_disk(persistent).unwrap();
+    loaded.state_root().to_bytes()
+}
+
+/// Test 1: Checkpoints taken while players keep joining match their metadata
+#[test]
+fn test_checkpoints_match_their_state_root() {
+    let dir = tempdir().unwrap();
+    let persistent = Arc::new(PersistentStore::open(dir.path()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let config = BlockProducerConfig {
+        block_time_ms: 5,
+        ..Default::default()
+    };
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), config);
+    producer.set_checkpoint_writer(CheckpointWriter::spawn(persistent.clone(), 3));
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Checkpoint");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 50);
+
+    let sender = producer.transaction_sender();
+    let running = producer.running_flag();
+    let blockhash = producer.current_blockhash();
+    let handle = thread::spawn(move || {
+        producer.run();
+        producer
+    });
+
+    // Joins land in whichever block is open, while checkpoints are written
+    let mut players = Vec::new();
+    for i in 0..30 {
+        let wallet = Keypair::new();
+        let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+        let name = make_player_name(&format!("Player{}", i));
+        let join = create_sanitized_transaction(
+            &wallet,
+            vec![create_join_world_instruction(world_pda, player_pda, &wallet, name)],
+            blockhash,
+        );
+        sender.send(join).unwrap();
+        players.push(player_pda);
+        thread::sleep(Duration::from_millis(3));
+    }
+
+    running.store(false, std::sync::atomic::Ordering::SeqCst);
+    let producer = handle.join().unwrap();
+    let final_slot = producer.current_slot();
+    // Dropping the producer waits for the checkpoint writer
+    drop(producer);
+
+    let metadata = persistent.load_metadata().unwrap().expect("a checkpoint was written");
+    assert!(metadata.slot > 0 && metadata.slot <= final_slot);
+    assert_eq!(metadata.slot % 3, 0);
+    // Some joins made it into the checkpoint
+    assert!(players.iter().any(|player| persistent.get_account(player).unwrap().is_some()));
+    assert_eq!(checkpointed_root(&persistent), metadata.state_root);
+}
+
+/// Test 2: A checkpoint taken after stopping covers the last block
+#[test]
+fn test_final_checkpoint_covers_last_block() {
+    let dir = tempdir().unwrap();
+    let persistent = Arc::new(PersistentStore::open(dir.path()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    // Off schedule: only explicit checkpoints
+    producer.set_checkpoint_writer(CheckpointWriter::spawn(persistent.clone(), 0));
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Shutdown");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let wallet = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+    let join = create_sanitized_transaction(
+        &wallet,
+        vec![create_join_world_instruction(world_pda, player_pda, &wallet, make_player_name("Last"))],
+        producer.current_blockhash(),
+    );
+    producer.produce_block(vec![join]);
+    let update = producer.produce_block(vec![]);
+    assert!(persistent.load_metadata().unwrap().is_none());
+
+    producer.checkpoint_now();
+    drop(producer);
+
+    let metadata = persistent.load_metadata().unwrap().unwrap();
+    assert_eq!(metadata.slot, update.slot);
+    assert_eq!(metadata.blockhash, update.blockhash.to_bytes());
+    assert_eq!(metadata.state_root, update.state_root.to_bytes());
+    assert_eq!(checkpointed_root(&persistent), metadata.state_root);
+    assert!(persistent.get_account(&player_pda).unwrap().is_some());
+}
+
+/// Test 3: Replay stops at a slot missing from the write-ahead log
+#[test]
+fn test_replay_stops_at_wal_gap() {
+    let dir = tempdir().unwrap();
+    let persistent = Arc::new(PersistentStore::open(dir.path()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    producer.set_checkpoint_writer(CheckpointWriter::spawn(persistent.clone(), 0));
+    let mut updates = producer.subscribe();
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Gap");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    // One join per block; the first block is checkpointed
+    let mut players = Vec::new();
+    for i in 0..4 {
+        let wallet = Keypair::new();
+        let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+        let name = make_player_name(&format!("Player{}", i));
+        let join = create_sanitized_transaction(
+            &wallet,
+            vec![create_join_world_instruction(world_pda, player_pda, &wallet, name)],
+            producer.current_blockhash(),
+        );
+        assert!(producer.produce_block(vec![join]).transaction_results[0].success);
+        if i == 0 {
+            producer.checkpoint_now();
+        }
+        players.push(player_pda);
+    }
+    drop(producer);
+
+    // The log missed the third block (slot 3), as when the logger lags
+    let checkpoint_slot = persistent.load_metadata().unwrap().unwrap().slot;
+    assert_eq!(checkpoint_slot, 1);
+    let mut logged = Vec::new();
+    while let Ok(update) = updates.try_recv() {
+        if update.slot != 3 {
+            persistent.append_delta(&SlotDelta::from_update(&update)).unwrap();
+        }
+        logged.push(update);
+    }
+    assert_eq!(logged.len(), 4);
+
+    let restored = AccountStore::new();
+    restored.load_from_disk(&persistent).unwrap();
+    let metadata = restored.replay_wal(&persistent).unwrap().unwrap();
+    assert_eq!(metadata.slot, 2);
+    assert_eq!(metadata.blockhash, logged[1].blockhash.to_bytes());
+    assert_eq!(metadata.state_root, logged[1].state_root.to_bytes());
+    assert_eq!(restored.state_root().to_bytes(), metadata.state_root);
+    assert!(restored.account_exists(&players[1]));
+    assert!(!restored.account_exists(&players[3]));
+    // Deltas past the gap can never apply again
+    assert_eq!(persistent.wal_deltas(metadata.slot).unwrap(), vec![]);
+}
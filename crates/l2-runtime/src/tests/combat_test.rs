@@ -0,0 +1,184 @@
+//! Combat Integration Tests
+//!
+//! Tests hit reactions end to end:
+//! - Attacks knock surviving targets back, away from the attacker
+//! - Heavier hits knock harder
+//! - Hit-stun blocks attacks and movement input until it wears off
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_world_name, setup_world_account};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{FIXED_POINT_SCALE, HIT_STUN_SLOTS, KNOCKBACK_LIFT},
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WeaponStats, WorldConfig, WorldPlayer},
+};
+
+/// Set up a world with two players, returning (world PDA, [(player PDA, wallet); 2])
+fn setup_duel(processor: &mut L2Processor, store: &AccountStore) -> (Pubkey, [(Pubkey, Keypair); 2]) {
+    let world_name = make_world_name("Dojo");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, Pubkey::new_unique(), world_name, 100, 100, 10);
+
+    let players = ["Attacker", "Target"].map(|name| {
+        let wallet = Keypair::new();
+        let player_pda = join_player(processor, store, world_pda, &wallet, name);
+        processor.advance_slot();
+        (player_pda, wallet)
+    });
+    (world_pda, players)
+}
+
+fn attack(world_pda: Pubkey, attacker: &(Pubkey, Keypair), target: Pubkey, damage: u16) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Attack {
+            weapon_stats: Some(WeaponStats {
+                damage,
+                range: 0,
+                attack_speed: 0,
+            }),
+            observed_slot: None,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(attacker.0, false),
+            AccountMeta::new(target, false),
+            AccountMeta::new_readonly(attacker.1.pubkey(), true),
+        ],
+    )
+}
+
+fn move_3d(world_pda: Pubkey, player: &(Pubkey, Keypair), input: MovementInput3D) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player.0, false),
+            AccountMeta::new_readonly(player.1.pubkey(), true),
+        ],
+    )
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+/// Teleport a player by rewriting its account
+fn place_player(store: &AccountStore, player_pda: Pubkey, position_x: i32, position_z: i32) {
+    let mut player = load_player(store, &player_pda);
+    player.position_x = position_x;
+    player.position_z = position_z;
+    let mut account = store.get_account(&player_pda).unwrap();
+    borsh::to_writer(account.data_as_mut_slice(), &player).unwrap();
+    store.store_account(player_pda, account, 0);
+}
+
+/// Test 1: A hit pushes the target away from the attacker and off the ground
+#[test]
+fn test_hit_knocks_target_away() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, [attacker, target]) = setup_duel(&mut processor, &store);
+
+    // Target stands to the attacker's -X side
+    place_player(&store, target.0, 40 * FIXED_POINT_SCALE, 50 * FIXED_POINT_SCALE);
+    let slot = processor.current_slot();
+    let result = process(&mut processor, &attacker.1, attack(world_pda, &attacker, target.0, 10));
+    assert!(result.success, "Attack failed: {:?}", result.error);
+
+    let hit = load_player(&store, &target.0);
+    assert!(hit.velocity_x < 0, "pushed toward -X, got {}", hit.velocity_x);
+    assert_eq!(hit.velocity_z, 0);
+    assert_eq!(hit.velocity_y, KNOCKBACK_LIFT);
+    assert!(!hit.is_grounded);
+    assert_eq!(hit.stun_until_slot, slot + HIT_STUN_SLOTS);
+
+    // The attacker itself doesn't move
+    let attacker_state = load_player(&store, &attacker.0);
+    assert_eq!((attacker_state.velocity_x, attacker_state.velocity_z), (0, 0));
+}
+
+/// Test 2: Heavier weapons knock harder; players on the same spot are pushed
+/// along the attacker's facing
+#[test]
+fn test_knockback_scales_with_damage() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, [attacker, target]) = setup_duel(&mut processor, &store);
+
+    // Both at the spawn point, attacker facing +Z (yaw 0)
+    assert!(process(&mut processor, &attacker.1, attack(world_pda, &attacker, target.0, 5)).success);
+    let light = load_player(&store, &target.0);
+    assert_eq!(light.velocity_x, 0);
+    assert!(light.velocity_z > 0);
+
+    for _ in 0..HIT_STUN_SLOTS {
+        processor.advance_slot();
+    }
+    assert!(process(&mut processor, &attacker.1, attack(world_pda, &attacker, target.0, 40)).success);
+    let heavy = load_player(&store, &target.0);
+    assert!(heavy.velocity_z > light.velocity_z, "{} <= {}", heavy.velocity_z, light.velocity_z);
+}
+
+/// Test 3: Stunned players can't attack or steer until the stun wears off,
+/// and the knockback plays out through the normal physics
+#[test]
+fn test_hit_stun_limits_input() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, [attacker, target]) = setup_duel(&mut processor, &store);
+
+    place_player(&store, target.0, 52 * FIXED_POINT_SCALE, 50 * FIXED_POINT_SCALE);
+    assert!(process(&mut processor, &attacker.1, attack(world_pda, &attacker, target.0, 10)).success);
+    let hit = load_player(&store, &target.0);
+
+    // Striking back is refused while stunned
+    let result = process(&mut processor, &target.1, attack(world_pda, &target, attacker.0, 10));
+    assert_eq!(custom_error(&result), Some(WorldError::PlayerStunned as u32));
+
+    // Movement input is dropped: the knockback carries the player and slows
+    // down as if no key was pressed
+    let forward = MovementInput3D {
+        move_x: 0,
+        move_z: 127,
+        camera_yaw: 0,
+        sprint: true,
+        jump: true,
+        dash: true,
+        crouch: true,
+    };
+    let result = process(&mut processor, &target.1, move_3d(world_pda, &target, forward));
+    assert!(result.success, "MovePlayer3D failed: {:?}", result.error);
+    let reeling = load_player(&store, &target.0);
+    assert_eq!(reeling.velocity_z, 0);
+    assert!(reeling.velocity_x > 0 && reeling.velocity_x < hit.velocity_x);
+    assert_eq!(reeling.position_x, hit.position_x + reeling.velocity_x as i32);
+
+    // Gravity brings the player back down
+    while !load_player(&store, &target.0).is_grounded {
+        let idle = MovementInput3D::default();
+        assert!(process(&mut processor, &target.1, move_3d(world_pda, &target, idle)).success);
+    }
+    assert!(processor.current_slot() >= hit.stun_until_slot);
+
+    // Control is back once the stun is over
+    let result = process(&mut processor, &target.1, move_3d(world_pda, &target, forward));
+    assert!(result.success, "MovePlayer3D failed: {:?}", result.error);
+    assert!(load_player(&store, &target.0).velocity_z > 0);
+    let result = process(&mut processor, &target.1, attack(world_pda, &target, attacker.0, 10));
+    assert!(result.success, "Attack failed: {:?}", result.error);
+}
@@ -0,0 +1,199 @@
+//! Idle Eviction Integration Tests
+//!
+//! Tests EvictIdlePlayer and the leader's idle evictor end to end:
+//! - Players idle past the world's timeout are removed like LeaveWorld
+//! - Active players and worlds that opted out are left alone
+//! - Evicted players' slots go to the waitlist
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_player_name, make_world_name, setup_world_account};
+use crate::{
+    account_store::AccountStore, eviction::IdleEvictor, processor::L2Processor,
+    waitlist::WaitlistDriver, TransactionResult,
+};
+
+use world_program::{
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{WorldConfig, WorldPlayer, WorldWaitlist},
+};
+
+const IDLE_TIMEOUT: u64 = 5;
+
+/// Set up a world with the given idle timeout, returning its PDA
+fn setup_world(
+    processor: &mut L2Processor,
+    store: &AccountStore,
+    max_players: u16,
+    idle_timeout_slots: u64,
+) -> Pubkey {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Lobby");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 100, 100, max_players);
+
+    let set_timeout = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SetIdleTimeout { idle_timeout_slots },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    );
+    let result = process(processor, &authority, set_timeout);
+    assert!(result.success, "SetIdleTimeout failed: {:?}", result.error);
+    world_pda
+}
+
+fn evict(world_pda: Pubkey, player_pda: Pubkey, wallet: Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::EvictIdlePlayer,
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new(wallet, false),
+        ],
+    )
+}
+
+fn load_world(store: &AccountStore, world_pda: &Pubkey) -> WorldConfig {
+    WorldConfig::try_from_slice(store.get_account(world_pda).unwrap().data()).unwrap()
+}
+
+fn advance(processor: &mut L2Processor, slots: u64) {
+    for _ in 0..slots {
+        processor.advance_slot();
+    }
+}
+
+/// Run one evictor tick through the processor, returning the transaction results
+fn tick(processor: &mut L2Processor, store: &AccountStore, evictor: &IdleEvictor) -> Vec<TransactionResult> {
+    let txs = evictor.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    let results = processor.process_transactions(&txs);
+    processor.advance_slot();
+    results
+}
+
+/// Test 1: The evictor removes a player once the idle timeout passes
+#[test]
+fn test_idle_player_evicted() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let evictor = IdleEvictor::new(Keypair::new());
+    let world_pda = setup_world(&mut processor, &store, 10, IDLE_TIMEOUT);
+
+    let player = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &player, "Sleepy");
+    let player_lamports = store.get_account(&player_pda).unwrap().lamports();
+
+    // Not idle long enough yet
+    assert!(tick(&mut processor, &store, &evictor).is_empty());
+
+    advance(&mut processor, IDLE_TIMEOUT);
+    let results = tick(&mut processor, &store, &evictor);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].success, "EvictIdlePlayer failed: {:?}", results[0].error);
+    assert!(results[0].logs.iter().any(|log| log.contains("Player evicted: Sleepy")));
+
+    assert!(store.get_account(&player_pda).is_none(), "player account should be closed");
+    assert_eq!(load_world(&store, &world_pda).player_count, 0);
+    assert!(store.get_account(&player.pubkey()).unwrap().lamports() >= player_lamports);
+
+    // Nothing left to evict
+    assert!(tick(&mut processor, &store, &evictor).is_empty());
+}
+
+/// Test 2: Direct evictions are checked against the timeout and the player's wallet
+#[test]
+fn test_evict_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let world_pda = setup_world(&mut processor, &store, 10, IDLE_TIMEOUT);
+
+    let player = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &player, "Active");
+    let submitter = Keypair::new();
+
+    let result = process(&mut processor, &submitter, evict(world_pda, player_pda, player.pubkey()));
+    assert_eq!(custom_error(&result), Some(WorldError::PlayerNotIdle as u32));
+
+    // The rent can't be redirected to the submitter
+    advance(&mut processor, IDLE_TIMEOUT);
+    let result = process(&mut processor, &submitter, evict(world_pda, player_pda, submitter.pubkey()));
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+
+    let result = process(&mut processor, &submitter, evict(world_pda, player_pda, player.pubkey()));
+    assert!(result.success, "EvictIdlePlayer failed: {:?}", result.error);
+    assert_eq!(load_world(&store, &world_pda).player_count, 0);
+}
+
+/// Test 3: Worlds with a timeout of 0 never evict
+#[test]
+fn test_opted_out_world_keeps_idle_players() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let evictor = IdleEvictor::new(Keypair::new());
+    let world_pda = setup_world(&mut processor, &store, 10, 0);
+
+    let player = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &player, "Camper");
+    advance(&mut processor, 100);
+
+    assert!(tick(&mut processor, &store, &evictor).is_empty());
+    let result = process(&mut processor, &player, evict(world_pda, player_pda, player.pubkey()));
+    assert_eq!(custom_error(&result), Some(WorldError::PlayerNotIdle as u32));
+    assert_eq!(load_world(&store, &world_pda).player_count, 1);
+}
+
+/// Test 4: An evicted player's slot goes to the head of the waitlist
+#[test]
+fn test_eviction_admits_from_waitlist() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let evictor = IdleEvictor::new(Keypair::new());
+    let driver = WaitlistDriver::new(Keypair::new());
+    let world_pda = setup_world(&mut processor, &store, 1, IDLE_TIMEOUT);
+    join_player(&mut processor, &store, world_pda, &Keypair::new(), "Sleepy");
+
+    let waiting = Keypair::new();
+    let (waitlist_pda, _) = WorldWaitlist::derive_pda(&world_pda, &world_program::id());
+    let join_waitlist = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::JoinWaitlist {
+            name: make_player_name("Eager"),
+            password: [0; 32],
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(waitlist_pda, false),
+            AccountMeta::new_readonly(waiting.pubkey(), true),
+            AccountMeta::new(waiting.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    );
+    assert!(process(&mut processor, &waiting, join_waitlist).success);
+
+    advance(&mut processor, IDLE_TIMEOUT);
+    assert!(tick(&mut processor, &store, &evictor)[0].success);
+
+    let txs = driver.build_transactions(processor.current_slot(), processor.current_blockhash(), &store);
+    let results = processor.process_transactions(&txs);
+    assert!(results[0].success, "AdmitFromWaitlist failed: {:?}", results[0].error);
+
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &waiting.pubkey(), &world_program::id());
+    assert!(store.get_account(&player_pda).is_some());
+    let world = load_world(&store, &world_pda);
+    assert_eq!(world.player_count, 1);
+    assert_eq!(world.waiting_count, 0);
+}
@@ -0,0 +1,192 @@
+//! Dead Reckoning Integration Tests
+//!
+//! Tests ExtrapolateMovement and the leader's movement extrapolator end to end:
+//! - Players without input keep moving on their last input, flagged as predicted
+//! - Extrapolation stops at the world's limit and resets on real input
+//! - Players with input this slot, or standing still, are left alone
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{
+    account_store::AccountStore, extrapolation::MovementExtrapolator, processor::L2Processor,
+    TransactionResult,
+};
+
+use world_program::{
+    constants::MAX_EXTRAPOLATION_SLOTS,
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WorldConfig, WorldPlayer},
+};
+
+const MAX_EXTRAPOLATION: u64 = 3;
+
+fn set_max_extrapolation(world_pda: Pubkey, authority: &Keypair, max_extrapolation_slots: u64) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SetMaxExtrapolation {
+            max_extrapolation_slots,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    )
+}
+
+/// Set up a world that extrapolates up to MAX_EXTRAPOLATION slots, returning
+/// (world PDA, world authority)
+fn setup_world(processor: &mut L2Processor, store: &AccountStore) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Laggy");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let ix = set_max_extrapolation(world_pda, &authority, MAX_EXTRAPOLATION);
+    let result = process(processor, &authority, ix);
+    assert!(result.success, "SetMaxExtrapolation failed: {:?}", result.error);
+    (world_pda, authority)
+}
+
+fn move_3d(world_pda: Pubkey, player_pda: Pubkey, wallet: &Keypair, move_z: i8) -> Instruction {
+    let input = MovementInput3D {
+        move_z,
+        ..Default::default()
+    };
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+fn extrapolate(world_pda: Pubkey, player_pda: Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ExtrapolateMovement,
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+        ],
+    )
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+/// Run one extrapolator tick through the processor, returning the transaction results
+fn tick(processor: &mut L2Processor, store: &AccountStore, extrapolator: &MovementExtrapolator) -> Vec<TransactionResult> {
+    let txs = extrapolator.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    let results = processor.process_transactions(&txs);
+    processor.advance_slot();
+    results
+}
+
+/// Test 1: A player whose inputs stop keeps walking for the world's limit,
+/// then stops until input resumes
+#[test]
+fn test_missing_input_is_extrapolated() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let extrapolator = MovementExtrapolator::new(Keypair::new());
+    let (world_pda, _) = setup_world(&mut processor, &store);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Walker");
+    processor.advance_slot();
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, 127)).success);
+    let mut last = load_player(&store, &player_pda);
+    assert_eq!(last.extrapolated_slots, 0);
+
+    for predicted in 1..=MAX_EXTRAPOLATION {
+        let results = tick(&mut processor, &store, &extrapolator);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success, "ExtrapolateMovement failed: {:?}", results[0].error);
+
+        let player = load_player(&store, &player_pda);
+        assert_eq!(player.extrapolated_slots as u64, predicted);
+        assert!(player.position_z > last.position_z, "should keep walking forward");
+        // Predicted movement doesn't count as activity
+        assert_eq!(player.last_action_slot, last.last_action_slot);
+        last = player;
+    }
+
+    // Out of predictions: the player holds still
+    assert!(tick(&mut processor, &store, &extrapolator).is_empty());
+    assert_eq!(load_player(&store, &player_pda).position_z, last.position_z);
+
+    // Real input clears the flag and re-arms extrapolation
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, 127)).success);
+    assert_eq!(load_player(&store, &player_pda).extrapolated_slots, 0);
+    assert_eq!(tick(&mut processor, &store, &extrapolator).len(), 1);
+}
+
+/// Test 2: Only missing input is filled in, and only for moving players
+#[test]
+fn test_extrapolate_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let extrapolator = MovementExtrapolator::new(Keypair::new());
+    let (world_pda, _) = setup_world(&mut processor, &store);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Runner");
+    processor.advance_slot();
+
+    // Input arrived this slot
+    let ixs = vec![move_3d(world_pda, player_pda, &wallet, 127), extrapolate(world_pda, player_pda)];
+    let tx = create_sanitized_transaction(&wallet, ixs, processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    processor.advance_slot();
+    assert_eq!(custom_error(&result), Some(WorldError::InputNotMissing as u32));
+
+    // Standing still needs no prediction
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, 0)).success);
+    assert!(tick(&mut processor, &store, &extrapolator).is_empty());
+    let result = process(&mut processor, &wallet, extrapolate(world_pda, player_pda));
+    assert_eq!(custom_error(&result), Some(WorldError::InputNotMissing as u32));
+}
+
+/// Test 3: Only the world authority sets the limit, within MAX_EXTRAPOLATION_SLOTS,
+/// and 0 turns extrapolation off
+#[test]
+fn test_set_max_extrapolation_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let extrapolator = MovementExtrapolator::new(Keypair::new());
+    let (world_pda, authority) = setup_world(&mut processor, &store);
+
+    let stranger = Keypair::new();
+    let result = process(&mut processor, &stranger, set_max_extrapolation(world_pda, &stranger, 1));
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+    let ix = set_max_extrapolation(world_pda, &authority, MAX_EXTRAPOLATION_SLOTS + 1);
+    let result = process(&mut processor, &authority, ix);
+    assert_eq!(custom_error(&result), Some(WorldError::InvalidInstructionData as u32));
+
+    assert!(process(&mut processor, &authority, set_max_extrapolation(world_pda, &authority, 0)).success);
+    let world = WorldConfig::try_from_slice(store.get_account(&world_pda).unwrap().data()).unwrap();
+    assert_eq!(world.max_extrapolation_slots, 0);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Frozen");
+    processor.advance_slot();
+    assert!(process(&mut processor, &wallet, move_3d(world_pda, player_pda, &wallet, 127)).success);
+    assert!(tick(&mut processor, &store, &extrapolator).is_empty());
+}
@@ -0,0 +1,304 @@
+//! Game Mode Integration Tests
+//!
+//! Tests objective game modes end to end:
+//! - ConfigureGameMode sets up king of the hill / capture the flag in team worlds
+//! - GameModeDriver ticks score the objectives into the world's team scores
+//! - Finished matches write an immutable match result and reset for the next round
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, join_player, make_player_name,
+    make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, game_mode::GameModeDriver, processor::L2Processor};
+
+use world_program::{
+    constants::{
+        FIXED_POINT_SCALE, GAME_MODE_CAPTURE_THE_FLAG, GAME_MODE_KING_OF_THE_HILL, MAX_TEAMS, TEAM_NONE,
+    },
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{GameMode, MatchResult, TeamScores, WorldConfig, WorldPlayer},
+};
+
+/// World center in fixed-point units (players join here)
+const CENTER: i32 = 500 * FIXED_POINT_SCALE;
+
+/// Objective radius used by the tests (5 world units)
+const RADIUS: u32 = 5 * FIXED_POINT_SCALE as u32;
+
+fn configure_teams_instruction(world_pda: Pubkey, authority: &Keypair, team_count: u8) -> Instruction {
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ConfigureTeams {
+            team_count,
+            friendly_fire: false,
+            max_team_size: 0,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(scores_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn configure_game_mode_instruction(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    mode: u8,
+    flag_base_x: [i32; MAX_TEAMS],
+    match_duration_slots: u64,
+    score_limit: u32,
+) -> Instruction {
+    let (game_mode_pda, _) = GameMode::derive_pda(&world_pda, &world_program::id());
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ConfigureGameMode {
+            mode,
+            objective_x: CENTER,
+            objective_z: CENTER,
+            objective_radius: RADIUS,
+            flag_base_x,
+            flag_base_z: [CENTER; MAX_TEAMS],
+            match_duration_slots,
+            score_limit,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(game_mode_pda, false),
+            AccountMeta::new(scores_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn process(processor: &mut L2Processor, signer: &Keypair, ix: Instruction) -> crate::TransactionResult {
+    let tx = create_sanitized_transaction(signer, vec![ix], processor.current_blockhash());
+    processor.process_transactions(&[tx]).remove(0)
+}
+
+/// Set up a world with `team_count` teams, returning (world PDA, world authority)
+fn setup_team_world(processor: &mut L2Processor, store: &AccountStore, team_count: u8) -> (Pubkey, Keypair) {
+    let world_authority = Keypair::new();
+    let world_name = make_world_name("ModeWorld");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, world_authority.pubkey(), world_name, 1000, 1000, 100);
+
+    let result = process(processor, &world_authority, configure_teams_instruction(world_pda, &world_authority, team_count));
+    assert!(result.success, "ConfigureTeams failed: {:?}", result.error);
+    processor.advance_slot();
+
+    (world_pda, world_authority)
+}
+
+/// Join a player into a team world, returning (player PDA, wallet)
+fn join_team_player(processor: &mut L2Processor, world_pda: Pubkey, name: &str) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &authority.pubkey(), &world_program::id());
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+
+    let mut ix = create_join_world_instruction(world_pda, player_pda, &authority, make_player_name(name));
+    ix.accounts.push(AccountMeta::new(scores_pda, false));
+    let result = process(processor, &authority, ix);
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+    processor.advance_slot();
+
+    (player_pda, authority)
+}
+
+/// Run one driver tick through the processor, then advance the slot
+fn tick(processor: &mut L2Processor, store: &AccountStore, driver: &GameModeDriver) {
+    let txs = driver.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    for result in processor.process_transactions(&txs) {
+        assert!(result.success, "TickGameMode failed: {:?}", result.error);
+    }
+    processor.advance_slot();
+}
+
+fn load_scores(store: &AccountStore, world_pda: &Pubkey) -> TeamScores {
+    let (scores_pda, _) = TeamScores::derive_pda(world_pda, &world_program::id());
+    TeamScores::try_from_slice(store.get_account(&scores_pda).unwrap().data()).unwrap()
+}
+
+fn load_game_mode(store: &AccountStore, world_pda: &Pubkey) -> GameMode {
+    let (game_mode_pda, _) = GameMode::derive_pda(world_pda, &world_program::id());
+    GameMode::try_from_slice(store.get_account(&game_mode_pda).unwrap().data()).unwrap()
+}
+
+fn load_result(store: &AccountStore, world_pda: &Pubkey, match_index: u32) -> MatchResult {
+    let (result_pda, _) = MatchResult::derive_pda(world_pda, match_index, &world_program::id());
+    MatchResult::try_from_slice(store.get_account(&result_pda).unwrap().data()).unwrap()
+}
+
+/// Test 1: Holding the hill scores every slot until the score limit ends the match
+#[test]
+fn test_king_of_the_hill_match() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2);
+    join_team_player(&mut processor, world_pda, "Holder");
+
+    let ix = configure_game_mode_instruction(world_pda, &world_authority, GAME_MODE_KING_OF_THE_HILL, [0; MAX_TEAMS], 0, 3);
+    let result = process(&mut processor, &world_authority, ix);
+    assert!(result.success, "ConfigureGameMode failed: {:?}", result.error);
+    processor.advance_slot();
+
+    let driver = GameModeDriver::new(world_authority, world_pda);
+    tick(&mut processor, &store, &driver);
+    tick(&mut processor, &store, &driver);
+    assert_eq!(load_scores(&store, &world_pda).scores, [2, 0, 0, 0]);
+
+    // Third point hits the limit: result recorded, next round starts from zero
+    tick(&mut processor, &store, &driver);
+    let result = load_result(&store, &world_pda, 0);
+    assert_eq!(result.world, world_pda);
+    assert_eq!(result.mode, GAME_MODE_KING_OF_THE_HILL);
+    assert_eq!(result.scores, [3, 0, 0, 0]);
+    assert_eq!(result.winner, 1);
+    assert!(result.end_slot > result.start_slot);
+
+    assert_eq!(load_scores(&store, &world_pda).scores, [0; MAX_TEAMS]);
+    let game_mode = load_game_mode(&store, &world_pda);
+    assert_eq!(game_mode.match_index, 1);
+    assert_eq!(game_mode.match_start_slot, result.end_slot);
+
+    // The new round scores into the next result index
+    tick(&mut processor, &store, &driver);
+    assert_eq!(load_scores(&store, &world_pda).scores, [1, 0, 0, 0]);
+    assert_eq!(load_result(&store, &world_pda, 0).scores, [3, 0, 0, 0]);
+}
+
+/// Test 2: A hill held by two teams scores for nobody
+#[test]
+fn test_contested_hill_does_not_score() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2);
+    join_team_player(&mut processor, world_pda, "Red");
+    join_team_player(&mut processor, world_pda, "Blue");
+
+    let ix = configure_game_mode_instruction(world_pda, &world_authority, GAME_MODE_KING_OF_THE_HILL, [0; MAX_TEAMS], 0, 0);
+    assert!(process(&mut processor, &world_authority, ix).success);
+    processor.advance_slot();
+
+    let driver = GameModeDriver::new(world_authority, world_pda);
+    for _ in 0..3 {
+        tick(&mut processor, &store, &driver);
+    }
+    assert_eq!(load_scores(&store, &world_pda).scores, [0; MAX_TEAMS]);
+}
+
+/// Test 3: Carrying the enemy flag back to your base captures it
+#[test]
+fn test_capture_the_flag() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2);
+
+    // Move the lone player to team 2 so they stand on both flag bases as an enemy of team 1
+    let (player_pda, player) = join_team_player(&mut processor, world_pda, "Runner");
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    let ix = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ChooseTeam { team: 2 },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(scores_pda, false),
+        ],
+    );
+    assert!(process(&mut processor, &player, ix).success);
+    processor.advance_slot();
+
+    let ix = configure_game_mode_instruction(world_pda, &world_authority, GAME_MODE_CAPTURE_THE_FLAG, [CENTER; MAX_TEAMS], 0, 0);
+    assert!(process(&mut processor, &world_authority, ix).success);
+    processor.advance_slot();
+
+    let driver = GameModeDriver::new(world_authority, world_pda);
+
+    // First tick: the runner picks up team 1's flag
+    tick(&mut processor, &store, &driver);
+    let game_mode = load_game_mode(&store, &world_pda);
+    assert_eq!(game_mode.flag_carriers[0], player_pda);
+    assert_eq!(game_mode.flag_carriers[1], Pubkey::default());
+
+    // Second tick: standing on their own base with their flag home captures it
+    tick(&mut processor, &store, &driver);
+    assert_eq!(load_scores(&store, &world_pda).scores, [0, 1, 0, 0]);
+    assert_eq!(load_game_mode(&store, &world_pda).flag_carriers[0], Pubkey::default());
+}
+
+/// Test 4: The match timer ends a scoreless match in a tie
+#[test]
+fn test_match_timer_ends_in_tie() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2);
+
+    let ix = configure_game_mode_instruction(world_pda, &world_authority, GAME_MODE_KING_OF_THE_HILL, [0; MAX_TEAMS], 2, 0);
+    assert!(process(&mut processor, &world_authority, ix).success);
+    let start_slot = processor.current_slot();
+    processor.advance_slot();
+
+    let driver = GameModeDriver::new(world_authority, world_pda);
+    tick(&mut processor, &store, &driver);
+    assert_eq!(load_game_mode(&store, &world_pda).match_index, 0);
+    tick(&mut processor, &store, &driver);
+
+    let result = load_result(&store, &world_pda, 0);
+    assert_eq!(result.winner, TEAM_NONE);
+    assert_eq!((result.start_slot, result.end_slot), (start_slot, start_slot + 2));
+    assert_eq!(load_game_mode(&store, &world_pda).match_index, 1);
+}
+
+/// Test 5: Game modes need teams, and only the world authority can run them
+#[test]
+fn test_game_mode_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+
+    // Free-for-all worlds cannot host objective modes
+    let world_authority = Keypair::new();
+    let world_name = make_world_name("FreeForAll");
+    let (ffa_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, ffa_pda, world_authority.pubkey(), world_name, 1000, 1000, 100);
+    join_player(&mut processor, &store, ffa_pda, &Keypair::new(), "Loner");
+    let ix = configure_game_mode_instruction(ffa_pda, &world_authority, GAME_MODE_KING_OF_THE_HILL, [0; MAX_TEAMS], 0, 0);
+    let result = process(&mut processor, &world_authority, ix);
+    assert!(matches!(
+        result.error,
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) if code == WorldError::InvalidTeam as u32
+    ));
+
+    // Strangers cannot tick someone else's game mode
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2);
+    let ix = configure_game_mode_instruction(world_pda, &world_authority, GAME_MODE_KING_OF_THE_HILL, [0; MAX_TEAMS], 0, 0);
+    assert!(process(&mut processor, &world_authority, ix).success);
+    processor.advance_slot();
+
+    let stranger_driver = GameModeDriver::new(Keypair::new(), world_pda);
+    let txs = stranger_driver.build_transactions(processor.current_slot(), processor.current_blockhash(), &store);
+    let results = processor.process_transactions(&txs);
+    assert!(matches!(
+        results[0].error,
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(2)))
+    ));
+}
@@ -37,7 +37,7 @@ use world_program::{
 };
 
 /// Helper to create a world name array from string
-fn make_world_name(name: &str) -> [u8; 32] {
+pub(super) fn make_world_name(name: &str) -> [u8; 32] {
     let mut arr = [0u8; 32];
     let bytes = name.as_bytes();
     let len = bytes.len().min(32);
@@ -46,7 +46,7 @@ fn make_world_name(name: &str) -> [u8; 32] {
 }
 
 /// Helper to create a player name array from string
-fn make_player_name(name: &str) -> [u8; 16] {
+pub(super) fn make_player_name(name: &str) -> [u8; 16] {
     let mut arr = [0u8; 16];
     let bytes = name.as_bytes();
     let len = bytes.len().min(16);
@@ -55,7 +55,7 @@ fn make_player_name(name: &str) -> [u8; 16] {
 }
 
 /// Helper to create and sanitize a transaction
-fn create_sanitized_transaction(
+pub(super) fn create_sanitized_transaction(
     payer: &Keypair,
     instructions: Vec<Instruction>,
     blockhash: Hash,
@@ -67,7 +67,7 @@ fn create_sanitized_transaction(
 }
 
 /// Helper to create InitializeWorld instruction
-fn create_initialize_world_instruction(
+pub(super) fn create_initialize_world_instruction(
     world_pda: Pubkey,
     authority: &Keypair,
     name: [u8; 32],
@@ -95,7 +95,7 @@ fn create_initialize_world_instruction(
 }
 
 /// Helper to create JoinWorld instruction
-fn create_join_world_instruction(
+pub(super) fn create_join_world_instruction(
     world_pda: Pubkey,
     player_pda: Pubkey,
     authority: &Keypair,
@@ -117,7 +117,7 @@ fn create_join_world_instruction(
 }
 
 /// Helper to set up a world account in the store
-fn setup_world_account(
+pub(super) fn setup_world_account(
     store: &AccountStore,
     world_pda: Pubkey,
     authority: Pubkey,
@@ -142,6 +142,22 @@ fn setup_world_account(
         bump,
         l1_game: Pubkey::default(),
         init_ts: 0,
+        team_count: 0,
+        friendly_fire: false,
+        max_team_size: 0,
+        visibility: 0,
+        password_salt: [0; 16],
+        password_hash: [0; 32],
+        reserved_slots: 0,
+        waiting_count: 0,
+        idle_timeout_slots: 0,
+        unique_names: false,
+        name_charset: 0,
+        reserved_name_count: 0,
+        dash_speed: 0,
+        dash_cooldown_slots: 0,
+        max_rewind_slots: 0,
+        max_extrapolation_slots: 0,
     };
 
     let mut data = vec![0u8; WorldConfig::LEN];
@@ -158,6 +174,34 @@ fn setup_world_account(
     store.store_account(world_pda, account, 0);
 }
 
+/// Helper to join a player into an already set up world, returning the player PDA
+pub(super) fn join_player(
+    processor: &mut L2Processor,
+    store: &AccountStore,
+    world_pda: Pubkey,
+    authority: &Keypair,
+    name: &str,
+) -> Pubkey {
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &authority.pubkey(), &world_program::id());
+
+    // Pre-create player account (owned by world_program for writability)
+    let player_account = AccountSharedData::from(Account {
+        lamports: 1_000_000_000,
+        data: vec![0u8; WorldPlayer::LEN],
+        owner: world_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    });
+    store.store_account(player_pda, player_account, 0);
+
+    let join_ix = create_join_world_instruction(world_pda, player_pda, authority, make_player_name(name));
+    let tx = create_sanitized_transaction(authority, vec![join_ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "JoinWorld failed: {:?}", results[0].error);
+
+    player_pda
+}
+
 // ============================================================================
 // Test Cases
 // ============================================================================
@@ -357,23 +401,11 @@ fn test_account_loader_callback() {
     let store = Arc::new(AccountStore::new());
     let loader = L2AccountLoader::new(store.clone());
 
-    // Test 1: Missing wallet (on-curve) returns account with 0 data bytes
+    // Test 1: A loader without auto-create rules reports missing accounts as missing
     let wallet = Keypair::new().pubkey();
-    let wallet_account = loader.get_account_shared_data(&wallet);
-    assert!(wallet_account.is_some());
-    let wallet_account = wallet_account.unwrap();
-    assert_eq!(wallet_account.lamports(), 1_000_000_000);
-    assert_eq!(wallet_account.data().len(), 0, "Wallet should have 0 data bytes");
-    assert_eq!(wallet_account.owner(), &solana_sdk::system_program::id());
-
-    // Test 2: Missing PDA (off-curve) returns account owned by world_program
+    assert!(loader.get_account_shared_data(&wallet).is_none());
     let (pda, _) = Pubkey::find_program_address(&[b"test"], &world_program::id());
-    let pda_account = loader.get_account_shared_data(&pda);
-    assert!(pda_account.is_some());
-    let pda_account = pda_account.unwrap();
-    assert_eq!(pda_account.lamports(), 1_000_000_000);
-    assert_eq!(pda_account.data().len(), WorldPlayer::LEN, "PDA should have WorldPlayer::LEN data bytes");
-    assert_eq!(pda_account.owner(), &world_program::id(), "PDA should be owned by world_program");
+    assert!(loader.get_account_shared_data(&pda).is_none());
 
     // Test 3: Existing account returns correctly
     let existing_pubkey = Pubkey::new_unique();
@@ -393,22 +425,13 @@ fn test_account_loader_callback() {
     assert_eq!(retrieved.data(), &[1, 2, 3, 4]);
     assert_eq!(retrieved.owner(), &world_program::id());
 
-    // Test 4: account_matches_owners works for missing wallets (system-owned)
-    // Use Keypair to get a real on-curve wallet address
-    let missing_wallet = Keypair::new().pubkey();
-    assert!(missing_wallet.is_on_curve(), "Keypair pubkey should be on-curve");
+    // Test 4: account_matches_owners finds no owner for missing accounts
     let owners = vec![
         world_program::id(),
         solana_sdk::system_program::id(),
     ];
-    let result = loader.account_matches_owners(&missing_wallet, &owners);
-    assert_eq!(result, Some(1), "Missing wallet should match system_program");
-
-    // Test 4b: account_matches_owners works for missing PDAs (world_program-owned)
-    let (missing_pda, _) = Pubkey::find_program_address(&[b"missing"], &world_program::id());
-    assert!(!missing_pda.is_on_curve(), "PDA should be off-curve");
-    let result = loader.account_matches_owners(&missing_pda, &owners);
-    assert_eq!(result, Some(0), "Missing PDA should match world_program");
+    assert_eq!(loader.account_matches_owners(&wallet, &owners), None);
+    assert_eq!(loader.account_matches_owners(&pda, &owners), None);
 
     // Test 5: account_matches_owners works for existing accounts
     let result = loader.account_matches_owners(&existing_pubkey, &owners);
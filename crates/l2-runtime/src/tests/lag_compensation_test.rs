@@ -0,0 +1,188 @@
+//! Lag Compensation Integration Tests
+//!
+//! Tests rewound hit validation end to end:
+//! - Sealing a slot records where moved players stood in the world's history
+//! - Attacks check range against the target's position at the observed slot
+//! - The rewind is clamped to the world's limit, which only the authority can set
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_world_name, setup_world_account};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::MAX_REWIND_SLOTS,
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{MovementInput3D, PositionHistory, WeaponStats, WorldConfig, WorldPlayer},
+};
+
+/// Weapon reach used by the tests (half a unit)
+const REACH: u16 = 500;
+
+/// Set up a world with two players at the spawn point, returning
+/// (world PDA, world authority, [(player PDA, wallet); 2])
+fn setup_duel(processor: &mut L2Processor, store: &AccountStore) -> (Pubkey, Keypair, [(Pubkey, Keypair); 2]) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Lagland");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let players = ["Shooter", "Runner"].map(|name| {
+        let wallet = Keypair::new();
+        let player_pda = join_player(processor, store, world_pda, &wallet, name);
+        processor.advance_slot();
+        (player_pda, wallet)
+    });
+    (world_pda, authority, players)
+}
+
+fn set_max_rewind(world_pda: Pubkey, authority: &Keypair, max_rewind_slots: u64) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SetMaxRewind { max_rewind_slots },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    )
+}
+
+fn attack(world_pda: Pubkey, attacker: &(Pubkey, Keypair), target: Pubkey, observed_slot: Option<u64>) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Attack {
+            weapon_stats: Some(WeaponStats {
+                damage: 1,
+                range: REACH,
+                attack_speed: 0,
+            }),
+            observed_slot,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(attacker.0, false),
+            AccountMeta::new(target, false),
+            AccountMeta::new_readonly(attacker.1.pubkey(), true),
+            AccountMeta::new_readonly(PositionHistory::derive_pda(&world_pda, &world_program::id()).0, false),
+        ],
+    )
+}
+
+/// Sprint forward (+Z) for one slot
+fn run(world_pda: Pubkey, player: &(Pubkey, Keypair)) -> Instruction {
+    let input = MovementInput3D {
+        move_z: 127,
+        sprint: true,
+        ..Default::default()
+    };
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player.0, false),
+            AccountMeta::new_readonly(player.1.pubkey(), true),
+        ],
+    )
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+fn load_history(store: &AccountStore, world_pda: &Pubkey) -> PositionHistory {
+    let (history_pda, _) = PositionHistory::derive_pda(world_pda, &world_program::id());
+    PositionHistory::try_from_slice(store.get_account(&history_pda).unwrap().data()).unwrap()
+}
+
+/// Sprint the target away for a few slots
+fn run_away(processor: &mut L2Processor, world_pda: Pubkey, target: &(Pubkey, Keypair), steps: usize) {
+    for _ in 0..steps {
+        let result = process(processor, &target.1, run(world_pda, target));
+        assert!(result.success, "MovePlayer3D failed: {:?}", result.error);
+    }
+}
+
+/// Test 1: A hit the attacker saw lands even though the target has since moved away
+#[test]
+fn test_rewound_hit_lands() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority, [shooter, runner]) = setup_duel(&mut processor, &store);
+    assert!(process(&mut processor, &authority, set_max_rewind(world_pda, &authority, MAX_REWIND_SLOTS)).success);
+
+    // The shooter's client still shows the runner at the spawn point
+    let observed = processor.current_slot();
+    let start = load_player(&store, &runner.0);
+    run_away(&mut processor, world_pda, &runner, 4);
+    let runner_now = load_player(&store, &runner.0);
+    assert!(runner_now.position_z - start.position_z > REACH as i32);
+    let history = load_history(&store, &world_pda);
+    assert!(history.samples.iter().filter(|sample| sample.entity == runner.0).count() > 1);
+
+    let result = process(&mut processor, &shooter.1, attack(world_pda, &shooter, runner.0, None));
+    assert_eq!(custom_error(&result), Some(WorldError::TargetOutOfRange as u32));
+
+    let result = process(&mut processor, &shooter.1, attack(world_pda, &shooter, runner.0, Some(observed)));
+    assert!(result.success, "rewound Attack failed: {:?}", result.error);
+    assert_eq!(load_player(&store, &runner.0).health, runner_now.health - 1);
+}
+
+/// Test 2: Rewinding is capped at the world's limit, and a world without
+/// rewind checks the current position
+#[test]
+fn test_rewind_is_clamped() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority, [shooter, runner]) = setup_duel(&mut processor, &store);
+
+    let observed = processor.current_slot();
+    run_away(&mut processor, world_pda, &runner, 4);
+
+    // The test world starts without rewind
+    let result = process(&mut processor, &shooter.1, attack(world_pda, &shooter, runner.0, Some(observed)));
+    assert_eq!(custom_error(&result), Some(WorldError::TargetOutOfRange as u32));
+
+    // Two slots back the runner was already out of reach
+    assert!(process(&mut processor, &authority, set_max_rewind(world_pda, &authority, 2)).success);
+    let result = process(&mut processor, &shooter.1, attack(world_pda, &shooter, runner.0, Some(observed)));
+    assert_eq!(custom_error(&result), Some(WorldError::TargetOutOfRange as u32));
+
+    // The full history still reaches the observed slot
+    assert!(process(&mut processor, &authority, set_max_rewind(world_pda, &authority, MAX_REWIND_SLOTS)).success);
+    assert!(processor.current_slot() - observed <= MAX_REWIND_SLOTS);
+    let result = process(&mut processor, &shooter.1, attack(world_pda, &shooter, runner.0, Some(observed)));
+    assert!(result.success, "rewound Attack failed: {:?}", result.error);
+}
+
+/// Test 3: Only the world authority sets the limit, and only as far back as
+/// the history reaches
+#[test]
+fn test_set_max_rewind_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority, [shooter, _]) = setup_duel(&mut processor, &store);
+
+    let result = process(&mut processor, &shooter.1, set_max_rewind(world_pda, &shooter.1, 3));
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+    let result = process(&mut processor, &authority, set_max_rewind(world_pda, &authority, MAX_REWIND_SLOTS + 1));
+    assert_eq!(custom_error(&result), Some(WorldError::InvalidInstructionData as u32));
+
+    let result = process(&mut processor, &authority, set_max_rewind(world_pda, &authority, 3));
+    assert!(result.success, "SetMaxRewind failed: {:?}", result.error);
+    let world = WorldConfig::try_from_slice(store.get_account(&world_pda).unwrap().data()).unwrap();
+    assert_eq!(world.max_rewind_slots, 3);
+    assert_eq!(world.rewind_slot(Some(10), 20), 17);
+    assert_eq!(world.rewind_slot(Some(30), 20), 20);
+    assert_eq!(world.rewind_slot(None, 20), 20);
+}
@@ -0,0 +1,255 @@
+//! Matchmaking Integration Tests
+//!
+//! Tests the lobby flow end to end:
+//! - JoinQueue / LeaveQueue manage a player's queue ticket
+//! - The matchmaker creates a world instance per match and issues join tickets
+//! - Matched players join their instance; empty instances are torn down
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_world_name, setup_world_account};
+use crate::{
+    account_store::AccountStore,
+    matchmaking::{Matchmaker, MatchmakerConfig},
+    processor::L2Processor,
+};
+
+use world_program::{
+    constants::{QUEUE_STATUS_IDLE, QUEUE_STATUS_MATCHED, QUEUE_STATUS_WAITING},
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{QueueTicket, WorldConfig},
+};
+
+/// Queue a new player with `rating`, returning (wallet, ticket PDA)
+fn enqueue(processor: &mut L2Processor, rating: u32) -> (Keypair, Pubkey) {
+    let player = Keypair::new();
+    let (ticket_pda, _) = QueueTicket::derive_pda(&player.pubkey(), &world_program::id());
+    let ix = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::JoinQueue { rating },
+        vec![
+            AccountMeta::new(ticket_pda, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    );
+    let result = process(processor, &player, ix);
+    assert!(result.success, "JoinQueue failed: {:?}", result.error);
+    (player, ticket_pda)
+}
+
+fn load_ticket(store: &AccountStore, ticket_pda: &Pubkey) -> QueueTicket {
+    QueueTicket::try_from_slice(store.get_account(ticket_pda).unwrap().data()).unwrap()
+}
+
+/// Run one matchmaker tick through the processor, then advance the slot
+fn tick(processor: &mut L2Processor, store: &AccountStore, matchmaker: &mut Matchmaker) {
+    let txs = matchmaker.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    for result in processor.process_transactions(&txs) {
+        assert!(result.success, "Matchmaker transaction failed: {:?}", result.error);
+    }
+    processor.advance_slot();
+}
+
+fn matchmaker(match_size: usize, rating_window: u32) -> Matchmaker {
+    let config = MatchmakerConfig {
+        match_size,
+        rating_window,
+        teardown_grace_slots: 5,
+        ..Default::default()
+    };
+    Matchmaker::new(Keypair::new(), config)
+}
+
+/// Test 1: Players queue, and can leave while waiting
+#[test]
+fn test_join_and_leave_queue() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+
+    let (player, ticket_pda) = enqueue(&mut processor, 1200);
+    let ticket = load_ticket(&store, &ticket_pda);
+    assert_eq!(ticket.authority, player.pubkey());
+    assert_eq!(ticket.rating, 1200);
+    assert_eq!(ticket.status, QUEUE_STATUS_WAITING);
+
+    let leave = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::LeaveQueue,
+        vec![
+            AccountMeta::new(ticket_pda, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+        ],
+    );
+    assert!(process(&mut processor, &player, leave.clone()).success);
+    assert_eq!(load_ticket(&store, &ticket_pda).status, QUEUE_STATUS_IDLE);
+
+    // Not waiting any more
+    let result = process(&mut processor, &player, leave);
+    assert_eq!(custom_error(&result), Some(WorldError::TicketNotWaiting as u32));
+}
+
+/// Test 2: The matchmaker creates an instance, issues tickets, and matched players join it
+#[test]
+fn test_matchmaker_creates_instance() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let mut matchmaker = matchmaker(2, 0);
+
+    let (first, first_ticket) = enqueue(&mut processor, 1000);
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(matchmaker.instance_count(), 0, "one player is not a match");
+
+    let (_, second_ticket) = enqueue(&mut processor, 1000);
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(matchmaker.instance_count(), 1);
+
+    let ticket = load_ticket(&store, &first_ticket);
+    assert_eq!(ticket.status, QUEUE_STATUS_MATCHED);
+    assert_eq!(load_ticket(&store, &second_ticket).world, ticket.world);
+
+    let world = WorldConfig::try_from_slice(store.get_account(&ticket.world).unwrap().data()).unwrap();
+    assert!(world.name_str().starts_with("match-"));
+    assert_eq!(world.max_players, 2);
+    assert_eq!(world.player_count, 0);
+
+    // The ticket names the world to join
+    join_player(&mut processor, &store, ticket.world, &first, "First");
+    let world = WorldConfig::try_from_slice(store.get_account(&ticket.world).unwrap().data()).unwrap();
+    assert_eq!(world.player_count, 1);
+
+    // Matched tickets are not matched again
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(matchmaker.instance_count(), 1);
+}
+
+/// Test 3: Players outside the rating window wait for closer opponents
+#[test]
+fn test_matchmaker_respects_rating_window() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let mut matchmaker = matchmaker(2, 100);
+
+    let (_, low) = enqueue(&mut processor, 1000);
+    let (_, high) = enqueue(&mut processor, 2000);
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(load_ticket(&store, &low).status, QUEUE_STATUS_WAITING);
+    assert_eq!(load_ticket(&store, &high).status, QUEUE_STATUS_WAITING);
+
+    let (_, near_high) = enqueue(&mut processor, 1950);
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(load_ticket(&store, &low).status, QUEUE_STATUS_WAITING);
+    assert_eq!(load_ticket(&store, &high).status, QUEUE_STATUS_MATCHED);
+    assert_eq!(load_ticket(&store, &near_high).world, load_ticket(&store, &high).world);
+}
+
+/// Test 4: Empty instances are torn down after the grace period, occupied ones are kept
+#[test]
+fn test_empty_instances_torn_down() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let mut matchmaker = matchmaker(1, 0);
+
+    let (player, played_ticket) = enqueue(&mut processor, 1000);
+    tick(&mut processor, &store, &mut matchmaker);
+    let (_, no_show_ticket) = enqueue(&mut processor, 1000);
+    tick(&mut processor, &store, &mut matchmaker);
+    assert_eq!(matchmaker.instance_count(), 2);
+
+    let played = load_ticket(&store, &played_ticket).world;
+    let no_show = load_ticket(&store, &no_show_ticket).world;
+    join_player(&mut processor, &store, played, &player, "Stayer");
+
+    for _ in 0..6 {
+        tick(&mut processor, &store, &mut matchmaker);
+    }
+    assert!(store.get_account(&no_show).is_none(), "empty instance should be closed");
+    assert!(store.get_account(&played).is_some(), "occupied instance should stay");
+    assert_eq!(matchmaker.instance_count(), 1);
+}
+
+/// Test 5: Only the world authority can issue tickets or close worlds, and never with players inside
+#[test]
+fn test_lobby_authority_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let mut matchmaker = matchmaker(1, 0);
+
+    let (player, ticket_pda) = enqueue(&mut processor, 1000);
+    tick(&mut processor, &store, &mut matchmaker);
+    let world_pda = load_ticket(&store, &ticket_pda).world;
+    join_player(&mut processor, &store, world_pda, &player, "Inside");
+
+    // A stranger cannot assign tickets to a world they do not own
+    let (_, other_ticket) = enqueue(&mut processor, 1000);
+    let stranger = Keypair::new();
+    let issue = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::IssueJoinTicket,
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(other_ticket, false),
+            AccountMeta::new_readonly(stranger.pubkey(), true),
+        ],
+    );
+    assert_eq!(custom_error(&process(&mut processor, &stranger, issue)), Some(2)); // InvalidAuthority
+
+    // Nor close it
+    let close = |signer: &Keypair| {
+        Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::CloseWorld,
+            vec![
+                AccountMeta::new(world_pda, false),
+                AccountMeta::new_readonly(signer.pubkey(), true),
+                AccountMeta::new(signer.pubkey(), false),
+            ],
+        )
+    };
+    assert_eq!(custom_error(&process(&mut processor, &stranger, close(&stranger))), Some(2));
+
+    // The owner cannot close a world with players inside, only once it is empty
+    let owner = Keypair::new();
+    let world_name = make_world_name("Owned");
+    let (owned_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, owned_pda, owner.pubkey(), world_name, 100, 100, 10);
+    let resident = Keypair::new();
+    let resident_pda = join_player(&mut processor, &store, owned_pda, &resident, "Resident");
+
+    let close_owned = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::CloseWorld,
+        vec![
+            AccountMeta::new(owned_pda, false),
+            AccountMeta::new_readonly(owner.pubkey(), true),
+            AccountMeta::new(owner.pubkey(), false),
+        ],
+    );
+    let result = process(&mut processor, &owner, close_owned.clone());
+    assert_eq!(custom_error(&result), Some(WorldError::WorldNotEmpty as u32));
+
+    let leave = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::LeaveWorld,
+        vec![
+            AccountMeta::new(owned_pda, false),
+            AccountMeta::new(resident_pda, false),
+            AccountMeta::new_readonly(resident.pubkey(), true),
+            AccountMeta::new(resident.pubkey(), false),
+        ],
+    );
+    assert!(process(&mut processor, &resident, leave).success);
+    assert!(process(&mut processor, &owner, close_owned).success);
+    assert!(store.get_account(&owned_pda).is_none());
+}
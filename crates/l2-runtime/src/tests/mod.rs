@@ -2,4 +2,55 @@
 //!
 //! Contains integration tests for the JoinWorld flow and related functionality.
 
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    signature::Keypair,
+    transaction::TransactionError,
+};
+
+use crate::{processor::L2Processor, TransactionResult};
+
+use join_world_test::create_sanitized_transaction;
+
+mod abilities_test;
+mod auto_create_test;
+mod block_store_test;
+mod blockhash_test;
+mod checkpoint_test;
+mod combat_test;
+mod eviction_test;
+mod extrapolation_test;
+mod game_mode_test;
 mod join_world_test;
+mod lag_compensation_test;
+mod matchmaking_test;
+mod name_registry_test;
+mod npc_test;
+mod parallel_execution_test;
+mod portal_test;
+mod private_world_test;
+mod replay_test;
+mod session_test;
+mod snapshot_test;
+mod team_test;
+mod waitlist_test;
+mod write_back_test;
+
+/// Clock time tests pin a chain to when they compare state built at different times
+const UNIX_TIMESTAMP: i64 = 1_700_000_000;
+
+/// Helper to extract a custom program error code from a failed transaction
+fn custom_error(result: &TransactionResult) -> Option<u32> {
+    match &result.error {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Some(*code),
+        _ => None,
+    }
+}
+
+/// Helper to process a single-instruction transaction, then seal the slot
+fn process(processor: &mut L2Processor, signer: &Keypair, ix: Instruction) -> TransactionResult {
+    let tx = create_sanitized_transaction(signer, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    processor.advance_slot();
+    result
+}
@@ -0,0 +1,355 @@
+//! Name Registry Integration Tests
+//!
+//! Tests player name rules end to end:
+//! - Unique-name worlds claim a normalized name on join and release it on leave
+//! - Unreleased claims can be taken once they expire
+//! - Character sets and the authority's reserved words are enforced
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{
+    create_join_world_instruction, make_player_name, make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{NAME_CHARSET_ALPHANUMERIC, NAME_CHARSET_ANY},
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{NameClaim, ReservedNames, WorldConfig, WorldPlayer},
+};
+
+/// Set up a world, returning (world PDA, world authority)
+fn setup_world(store: &AccountStore) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Arena");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(
+        store,
+        world_pda,
+        authority.pubkey(),
+        world_name,
+        100,
+        100,
+        10,
+    );
+    (world_pda, authority)
+}
+
+fn configure_names(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    unique_names: bool,
+    name_charset: u8,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ConfigureNames {
+            unique_names,
+            name_charset,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    )
+}
+
+fn update_reserved_names(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    word: &str,
+    reserved: bool,
+) -> Instruction {
+    let (reserved_pda, _) = ReservedNames::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::UpdateReservedNames {
+            word: make_player_name(word),
+            reserved,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(reserved_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn claim_pda(world_pda: &Pubkey, name: &str) -> Pubkey {
+    let normalized = NameClaim::normalize(&make_player_name(name));
+    NameClaim::derive_pda(world_pda, &normalized, &world_program::id()).0
+}
+
+/// JoinWorld followed by the given name accounts
+fn join(world_pda: Pubkey, player: &Keypair, name: &str, extra: &[AccountMeta]) -> Instruction {
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &player.pubkey(), &world_program::id());
+    let mut ix =
+        create_join_world_instruction(world_pda, player_pda, player, make_player_name(name));
+    ix.accounts.extend_from_slice(extra);
+    ix
+}
+
+fn leave(world_pda: Pubkey, player: &Keypair, extra: &[AccountMeta]) -> Instruction {
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &player.pubkey(), &world_program::id());
+    let mut accounts = vec![
+        AccountMeta::new(world_pda, false),
+        AccountMeta::new(player_pda, false),
+        AccountMeta::new_readonly(player.pubkey(), true),
+        AccountMeta::new(player.pubkey(), false),
+    ];
+    accounts.extend_from_slice(extra);
+    Instruction::new_with_borsh(world_program::id(), &WorldInstruction::LeaveWorld, accounts)
+}
+
+fn load_claim(store: &AccountStore, claim_pda: &Pubkey) -> NameClaim {
+    NameClaim::try_from_slice(store.get_account(claim_pda).unwrap().data()).unwrap()
+}
+
+/// Test 1: A name is held by one wallet until they leave
+#[test]
+fn test_unique_names_claimed_and_released() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+    let result = process(
+        &mut processor,
+        &authority,
+        configure_names(world_pda, &authority, true, NAME_CHARSET_ANY),
+    );
+    assert!(result.success, "ConfigureNames failed: {:?}", result.error);
+
+    let claim = AccountMeta::new(claim_pda(&world_pda, "Hero"), false);
+    let first = Keypair::new();
+    let result = process(
+        &mut processor,
+        &first,
+        join(world_pda, &first, "Hero", std::slice::from_ref(&claim)),
+    );
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+    assert_eq!(load_claim(&store, &claim.pubkey).owner, first.pubkey());
+
+    // The claim account is required, and names match case-insensitively
+    let second = Keypair::new();
+    let result = process(
+        &mut processor,
+        &second,
+        join(world_pda, &second, "HERO", &[]),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::AccountNotInitialized as u32)
+    );
+    let result = process(
+        &mut processor,
+        &second,
+        join(world_pda, &second, "HERO", std::slice::from_ref(&claim)),
+    );
+    assert_eq!(custom_error(&result), Some(WorldError::NameTaken as u32));
+
+    // Leaving with the claim account frees the name
+    let result = process(
+        &mut processor,
+        &first,
+        leave(world_pda, &first, std::slice::from_ref(&claim)),
+    );
+    assert!(result.success, "LeaveWorld failed: {:?}", result.error);
+    assert!(
+        store.get_account(&claim.pubkey).is_none(),
+        "claim should be closed"
+    );
+
+    let result = process(
+        &mut processor,
+        &second,
+        join(world_pda, &second, "HERO", std::slice::from_ref(&claim)),
+    );
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+    assert_eq!(load_claim(&store, &claim.pubkey).owner, second.pubkey());
+}
+
+/// Test 2: A claim that was never released can be taken once it expires
+#[test]
+fn test_expired_claim_can_be_taken() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            configure_names(world_pda, &authority, true, NAME_CHARSET_ANY)
+        )
+        .success
+    );
+
+    let claim = AccountMeta::new(claim_pda(&world_pda, "Ghost"), false);
+    let first = Keypair::new();
+    assert!(
+        process(
+            &mut processor,
+            &first,
+            join(world_pda, &first, "Ghost", std::slice::from_ref(&claim))
+        )
+        .success
+    );
+
+    // Left without releasing the name
+    assert!(process(&mut processor, &first, leave(world_pda, &first, &[])).success);
+    let second = Keypair::new();
+    let result = process(
+        &mut processor,
+        &second,
+        join(world_pda, &second, "ghost", std::slice::from_ref(&claim)),
+    );
+    assert_eq!(custom_error(&result), Some(WorldError::NameTaken as u32));
+
+    // Fast-forward the claim to its expiry
+    let mut expired = load_claim(&store, &claim.pubkey);
+    expired.expires_slot = processor.current_slot();
+    let mut account = store.get_account(&claim.pubkey).unwrap();
+    borsh::to_writer(account.data_as_mut_slice(), &expired).unwrap();
+    store.store_account(claim.pubkey, account, 0);
+
+    let result = process(
+        &mut processor,
+        &second,
+        join(world_pda, &second, "ghost", std::slice::from_ref(&claim)),
+    );
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+    assert_eq!(load_claim(&store, &claim.pubkey).owner, second.pubkey());
+}
+
+/// Test 3: The world's character set limits what names may contain
+#[test]
+fn test_name_charset() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+
+    // Authority only, known character sets only
+    let stranger = Keypair::new();
+    let result = process(
+        &mut processor,
+        &stranger,
+        configure_names(world_pda, &stranger, false, NAME_CHARSET_ALPHANUMERIC),
+    );
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+    let result = process(
+        &mut processor,
+        &authority,
+        configure_names(world_pda, &authority, false, 9),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::InvalidInstructionData as u32)
+    );
+
+    let result = process(
+        &mut processor,
+        &authority,
+        configure_names(world_pda, &authority, false, NAME_CHARSET_ALPHANUMERIC),
+    );
+    assert!(result.success, "ConfigureNames failed: {:?}", result.error);
+
+    for bad in ["Space Man", "x_x", ""] {
+        let player = Keypair::new();
+        let result = process(&mut processor, &player, join(world_pda, &player, bad, &[]));
+        assert_eq!(
+            custom_error(&result),
+            Some(WorldError::InvalidName as u32),
+            "{bad:?}"
+        );
+    }
+
+    let player = Keypair::new();
+    let result = process(
+        &mut processor,
+        &player,
+        join(world_pda, &player, "Player1", &[]),
+    );
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+}
+
+/// Test 4: Names containing a reserved word are rejected, except for the authority
+#[test]
+fn test_reserved_words() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store);
+
+    let result = process(
+        &mut processor,
+        &authority,
+        update_reserved_names(world_pda, &authority, "Admin", true),
+    );
+    assert!(
+        result.success,
+        "UpdateReservedNames failed: {:?}",
+        result.error
+    );
+    let world = WorldConfig::try_from_slice(store.get_account(&world_pda).unwrap().data()).unwrap();
+    assert_eq!(world.reserved_name_count, 1);
+
+    let (reserved_pda, _) = ReservedNames::derive_pda(&world_pda, &world_program::id());
+    let reserved = AccountMeta::new_readonly(reserved_pda, false);
+
+    // The list must be passed once the world reserves names
+    let player = Keypair::new();
+    let result = process(
+        &mut processor,
+        &player,
+        join(world_pda, &player, "Guest", &[]),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::AccountNotInitialized as u32)
+    );
+
+    let result = process(
+        &mut processor,
+        &player,
+        join(world_pda, &player, "xADMINx", std::slice::from_ref(&reserved)),
+    );
+    assert_eq!(custom_error(&result), Some(WorldError::NameReserved as u32));
+
+    let result = process(
+        &mut processor,
+        &authority,
+        join(world_pda, &authority, "Admin", std::slice::from_ref(&reserved)),
+    );
+    assert!(
+        result.success,
+        "authority JoinWorld failed: {:?}",
+        result.error
+    );
+
+    // Releasing the word lets players use it again
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            update_reserved_names(world_pda, &authority, "ADMIN", false)
+        )
+        .success
+    );
+    let result = process(
+        &mut processor,
+        &player,
+        join(world_pda, &player, "xADMINx", &[]),
+    );
+    assert!(result.success, "JoinWorld failed: {:?}", result.error);
+}
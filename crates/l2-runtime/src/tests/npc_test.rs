@@ -0,0 +1,359 @@
+//! NPC Integration Tests
+//!
+//! Tests authority-driven NPCs end to end:
+//! - NpcDriver spawns NPCs and submits their actions under the world authority
+//! - NPCs chase and damage players with the shared combat model
+//! - Players kill NPCs, which drop loot and respawn on a timer
+//! - Spawns and kills never overwrite an existing NPC or loot drop
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{Account, AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+use super::custom_error;
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    npc::{ChaseBehavior, NpcDriver, NpcSpawn},
+    processor::L2Processor,
+};
+
+use world_program::{
+    constants::{DEFAULT_DAMAGE, DEFAULT_HEALTH, FIXED_POINT_SCALE, NPC_KIND_MONSTER},
+    error::WorldError,
+    instruction::{NpcAction, WorldInstruction},
+    state::{LootDrop, WorldConfig, WorldNpc, WorldPlayer},
+};
+
+/// World center in fixed-point units (players join here)
+const CENTER: i32 = 500 * FIXED_POINT_SCALE;
+
+/// Set up a 1000x1000 world with one joined player
+fn setup_world(processor: &mut L2Processor, store: &AccountStore) -> (Pubkey, Keypair, Pubkey, Keypair) {
+    let world_authority = Keypair::new();
+    let world_name = make_world_name("NpcWorld");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, world_authority.pubkey(), world_name, 1000, 1000, 100);
+
+    let player = Keypair::new();
+    let player_pda = join_player(processor, store, world_pda, &player, "Hero");
+    (world_pda, world_authority, player_pda, player)
+}
+
+/// Run one driver tick through the processor
+fn tick(processor: &mut L2Processor, store: &AccountStore, driver: &NpcDriver) {
+    let txs = driver.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    for result in processor.process_transactions(&txs) {
+        assert!(result.success, "NPC transaction failed: {:?}", result.error);
+    }
+}
+
+fn load_npc(store: &AccountStore, npc_pda: &Pubkey) -> WorldNpc {
+    WorldNpc::try_from_slice(store.get_account(npc_pda).unwrap().data()).unwrap()
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+fn attack_npc_instruction(
+    world_pda: Pubkey,
+    player_pda: Pubkey,
+    npc_pda: Pubkey,
+    player: &Keypair,
+    loot_pda: Pubkey,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::AttackNpc {
+            weapon_stats: None,
+            observed_slot: None,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new(npc_pda, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new(loot_pda, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn spawn_npc_instruction(world_pda: Pubkey, npc_pda: Pubkey, authority: &Keypair, npc_id: u32) -> Instruction {
+    let spawn = NpcSpawn::new(npc_id, "Imposter", 0, 0);
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SpawnNpc {
+            npc_id,
+            kind: NPC_KIND_MONSTER,
+            name: spawn.name,
+            max_health: 0,
+            damage: 0,
+            attack_range: 0,
+            spawn_x: spawn.spawn_x,
+            spawn_z: spawn.spawn_z,
+            respawn_delay_slots: 0,
+            loot_amount: 0,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(npc_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+/// Test 1: The driver spawns missing NPCs owned by the world authority
+#[test]
+fn test_driver_spawns_npc() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, _, _) = setup_world(&mut processor, &store);
+    let authority_key = world_authority.pubkey();
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    driver.add_npc(NpcSpawn::new(1, "Goblin", 100_000, 200_000), Box::new(ChaseBehavior { aggro_range: 0 }));
+    tick(&mut processor, &store, &driver);
+
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 1, &world_program::id());
+    let npc_account = store.get_account(&npc_pda).unwrap();
+    assert_eq!(npc_account.data().len(), WorldNpc::LEN);
+
+    let npc = load_npc(&store, &npc_pda);
+    assert_eq!(npc.body.authority, authority_key);
+    assert_eq!(npc.body.world, world_pda);
+    assert_eq!(npc.body.name_str(), "Goblin");
+    assert_eq!(npc.body.health, npc.body.max_health);
+    assert_eq!((npc.body.position_x, npc.body.position_z), (100_000, 200_000));
+
+    // Spawned NPCs are not respawned again
+    processor.advance_slot();
+    assert!(driver
+        .build_transactions(processor.current_slot(), processor.current_blockhash(), &store)
+        .is_empty());
+}
+
+/// Test 2: Chasing NPCs close in on players and attack in range
+#[test]
+fn test_npc_chases_and_attacks_player() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, player_pda, _) = setup_world(&mut processor, &store);
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    driver.add_npc(NpcSpawn::new(1, "Wolf", CENTER + 10_000, CENTER), Box::new(ChaseBehavior { aggro_range: 50_000 }));
+    tick(&mut processor, &store, &driver);
+
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 1, &world_program::id());
+    let start_x = load_npc(&store, &npc_pda).body.position_x;
+
+    // Out of range: the wolf walks toward the player
+    processor.advance_slot();
+    tick(&mut processor, &store, &driver);
+    let npc = load_npc(&store, &npc_pda);
+    assert!(npc.body.position_x < start_x, "NPC should move toward the player");
+    assert_eq!(load_player(&store, &player_pda).health, DEFAULT_HEALTH);
+
+    // Keep chasing until the player is hit
+    for _ in 0..60 {
+        processor.advance_slot();
+        tick(&mut processor, &store, &driver);
+        if load_player(&store, &player_pda).health < DEFAULT_HEALTH {
+            break;
+        }
+    }
+    assert_eq!(load_player(&store, &player_pda).health, DEFAULT_HEALTH - DEFAULT_DAMAGE);
+}
+
+/// Test 3: Killing an NPC drops loot, and the driver respawns it after the timer
+#[test]
+fn test_npc_death_drops_loot_and_respawns() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, player_pda, player) = setup_world(&mut processor, &store);
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    let spawn = NpcSpawn {
+        max_health: DEFAULT_DAMAGE,
+        respawn_delay_slots: 5,
+        loot_amount: 42,
+        ..NpcSpawn::new(7, "Slime", 100_000, 100_000)
+    };
+    driver.add_npc(spawn, Box::new(ChaseBehavior { aggro_range: 0 }));
+    tick(&mut processor, &store, &driver);
+
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 7, &world_program::id());
+    let (loot_pda, _) = LootDrop::derive_pda(&npc_pda, 0, &world_program::id());
+
+    processor.advance_slot();
+    let kill_slot = processor.current_slot();
+    let ix = attack_npc_instruction(world_pda, player_pda, npc_pda, &player, loot_pda);
+    let tx = create_sanitized_transaction(&player, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "AttackNpc failed: {:?}", results[0].error);
+
+    let npc = load_npc(&store, &npc_pda);
+    assert!(!npc.is_alive());
+    assert_eq!(npc.deaths, 1);
+    assert_eq!(npc.respawn_at_slot, kill_slot + 5);
+
+    let loot = LootDrop::try_from_slice(store.get_account(&loot_pda).unwrap().data()).unwrap();
+    assert_eq!(loot.npc, npc_pda);
+    assert_eq!(loot.killer, player_pda);
+    assert_eq!(loot.amount, 42);
+    assert_eq!((loot.position_x, loot.position_z), (100_000, 100_000));
+
+    // Dead NPCs cannot be attacked again
+    processor.advance_slot();
+    let (next_loot, _) = LootDrop::derive_pda(&npc_pda, 1, &world_program::id());
+    let ix = attack_npc_instruction(world_pda, player_pda, npc_pda, &player, next_loot);
+    let tx = create_sanitized_transaction(&player, vec![ix], processor.current_blockhash());
+    assert!(!processor.process_transactions(&[tx])[0].success);
+
+    // Driver stays quiet until the respawn slot, then revives the NPC
+    while processor.current_slot() < kill_slot + 5 {
+        assert!(driver
+            .build_transactions(processor.current_slot(), processor.current_blockhash(), &store)
+            .is_empty());
+        processor.advance_slot();
+    }
+    tick(&mut processor, &store, &driver);
+
+    let npc = load_npc(&store, &npc_pda);
+    assert!(npc.is_alive());
+    assert_eq!(npc.body.health, npc.body.max_health);
+    assert_eq!(npc.respawn_at_slot, 0);
+}
+
+/// Test 4: Only the world authority can drive NPCs
+#[test]
+fn test_npc_act_requires_world_authority() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, _, _) = setup_world(&mut processor, &store);
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    driver.add_npc(NpcSpawn::new(1, "Golem", 100_000, 100_000), Box::new(ChaseBehavior { aggro_range: 0 }));
+    tick(&mut processor, &store, &driver);
+
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 1, &world_program::id());
+    let stranger = Keypair::new();
+    let ix = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::NpcAct { action: NpcAction::Idle },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(npc_pda, false),
+            AccountMeta::new_readonly(stranger.pubkey(), true),
+        ],
+    );
+    let tx = create_sanitized_transaction(&stranger, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success);
+    assert!(matches!(
+        results[0].error,
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(2)))
+    ));
+}
+
+/// Test 5: Spawning an existing NPC fails and keeps its death count
+#[test]
+fn test_spawn_npc_rejects_existing_npc() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, player_pda, player) = setup_world(&mut processor, &store);
+    let authority = world_authority.insecure_clone();
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    let spawn = NpcSpawn {
+        max_health: DEFAULT_DAMAGE,
+        ..NpcSpawn::new(3, "Bat", 100_000, 100_000)
+    };
+    driver.add_npc(spawn, Box::new(ChaseBehavior { aggro_range: 0 }));
+    tick(&mut processor, &store, &driver);
+
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 3, &world_program::id());
+    let (loot_pda, _) = LootDrop::derive_pda(&npc_pda, 0, &world_program::id());
+    processor.advance_slot();
+    let ix = attack_npc_instruction(world_pda, player_pda, npc_pda, &player, loot_pda);
+    let tx = create_sanitized_transaction(&player, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+    let killed = load_npc(&store, &npc_pda);
+    assert_eq!(killed.deaths, 1);
+
+    // Neither a dead nor a live NPC can be spawned over
+    processor.advance_slot();
+    let ix = spawn_npc_instruction(world_pda, npc_pda, &authority, 3);
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert_eq!(custom_error(&result), Some(WorldError::AccountAlreadyInitialized as u32));
+
+    let npc = load_npc(&store, &npc_pda);
+    assert_eq!(npc.deaths, 1);
+    assert_eq!(npc.body.name_str(), "Bat");
+    assert_eq!(npc.respawn_at_slot, killed.respawn_at_slot);
+}
+
+/// Test 6: Killing an NPC fails when its loot drop already exists
+#[test]
+fn test_attack_npc_rejects_existing_loot() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority, player_pda, player) = setup_world(&mut processor, &store);
+
+    let mut driver = NpcDriver::new(world_authority, world_pda);
+    let spawn = NpcSpawn {
+        max_health: DEFAULT_DAMAGE,
+        loot_amount: 5,
+        ..NpcSpawn::new(4, "Rat", 100_000, 100_000)
+    };
+    driver.add_npc(spawn, Box::new(ChaseBehavior { aggro_range: 0 }));
+    tick(&mut processor, &store, &driver);
+
+    // An unclaimed drop already sits at the NPC's next loot PDA
+    let (npc_pda, _) = WorldNpc::derive_pda(&world_pda, 4, &world_program::id());
+    let (loot_pda, bump) = LootDrop::derive_pda(&npc_pda, 0, &world_program::id());
+    let unclaimed = LootDrop {
+        world: world_pda,
+        npc: npc_pda,
+        killer: Pubkey::new_unique(),
+        position_x: 1,
+        position_z: 2,
+        amount: 99,
+        dropped_slot: 1,
+        bump,
+    };
+    let loot_account = AccountSharedData::from(Account {
+        lamports: 1_000_000,
+        data: borsh::to_vec(&unclaimed).unwrap(),
+        owner: world_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    });
+    store.store_account(loot_pda, loot_account, 0);
+
+    processor.advance_slot();
+    let ix = attack_npc_instruction(world_pda, player_pda, npc_pda, &player, loot_pda);
+    let tx = create_sanitized_transaction(&player, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert_eq!(custom_error(&result), Some(WorldError::AccountAlreadyInitialized as u32));
+
+    let loot = LootDrop::try_from_slice(store.get_account(&loot_pda).unwrap().data()).unwrap();
+    assert_eq!(loot.killer, unclaimed.killer);
+    assert_eq!(loot.amount, 99);
+    let npc = load_npc(&store, &npc_pda);
+    assert!(npc.is_alive());
+    assert_eq!(npc.deaths, 0);
+}
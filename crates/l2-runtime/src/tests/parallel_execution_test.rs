@@ -0,0 +1,172 @@
+//! Parallel Execution Tests
+//!
+//! Tests that scheduling a block into conflict-free batches and running them
+//! on worker threads is invisible to the chain:
+//! - Disjoint player moves share a batch, conflicting ones keep block order
+//! - Results and resulting state match executing the block one transaction at a time
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::SanitizedTransaction,
+};
+
+use super::{custom_error, UNIX_TIMESTAMP};
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor, scheduler, TransactionResult};
+
+use world_program::{
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WeaponStats, WorldConfig},
+};
+
+const PLAYERS: usize = 6;
+
+/// A processor with one world and PLAYERS joined players, returning
+/// (processor, store, world PDA, player PDAs)
+fn setup(authority: &Keypair, wallets: &[Keypair]) -> (L2Processor, Arc<AccountStore>, Pubkey, Vec<Pubkey>) {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    processor.set_unix_timestamp(UNIX_TIMESTAMP);
+    let world_name = make_world_name("Parallel");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let players = wallets
+        .iter()
+        .map(|wallet| {
+            let player_pda = join_player(&mut processor, &store, world_pda, wallet, "Racer");
+            processor.advance_slot();
+            processor.set_unix_timestamp(UNIX_TIMESTAMP);
+            player_pda
+        })
+        .collect();
+    (processor, store, world_pda, players)
+}
+
+fn move_3d(world_pda: Pubkey, player_pda: Pubkey, signer: &Keypair, move_x: i8) -> Instruction {
+    let input = MovementInput3D {
+        move_x,
+        move_z: 127,
+        ..Default::default()
+    };
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(signer.pubkey(), true),
+        ],
+    )
+}
+
+fn attack(world_pda: Pubkey, attacker: Pubkey, wallet: &Keypair, target: Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Attack {
+            weapon_stats: Some(WeaponStats {
+                damage: 5,
+                range: 5_000,
+                attack_speed: 0,
+            }),
+            observed_slot: None,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(attacker, false),
+            AccountMeta::new(target, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+/// Everyone moves, then a few attacks and follow-up moves that depend on
+/// earlier transactions in the block, plus one with the wrong signer
+fn build_block(processor: &L2Processor, world_pda: Pubkey, players: &[Pubkey], wallets: &[Keypair]) -> Vec<SanitizedTransaction> {
+    let blockhash = processor.current_blockhash();
+    let tx = |signer: &Keypair, ix: Instruction| create_sanitized_transaction(signer, vec![ix], blockhash);
+
+    let mut block: Vec<_> = (0..PLAYERS)
+        .map(|i| tx(&wallets[i], move_3d(world_pda, players[i], &wallets[i], 0)))
+        .collect();
+    block.push(tx(&wallets[0], attack(world_pda, players[0], &wallets[0], players[1])));
+    block.push(tx(&wallets[1], move_3d(world_pda, players[1], &wallets[1], 127)));
+    block.push(tx(&wallets[2], attack(world_pda, players[2], &wallets[2], players[3])));
+    block.push(tx(&wallets[3], attack(world_pda, players[3], &wallets[3], players[2])));
+    // Wrong signer for player 4
+    block.push(tx(&wallets[5], move_3d(world_pda, players[4], &wallets[5], -127)));
+    block.push(tx(&wallets[4], move_3d(world_pda, players[4], &wallets[4], -127)));
+    block
+}
+
+fn assert_same_results(parallel: &[TransactionResult], sequential: &[TransactionResult]) {
+    assert_eq!(parallel.len(), sequential.len());
+    for (p, s) in parallel.iter().zip(sequential) {
+        assert_eq!(p.signature, s.signature);
+        assert_eq!(p.success, s.success, "outcome differs for {}", p.signature);
+        assert_eq!(p.error, s.error);
+        assert_eq!(p.logs, s.logs);
+        assert_eq!(p.modified_accounts, s.modified_accounts);
+    }
+}
+
+/// Test 1: The scheduler packs independent moves together and orders conflicts
+#[test]
+fn test_block_is_split_into_conflict_free_batches() {
+    let authority = Keypair::new();
+    let wallets: Vec<Keypair> = (0..PLAYERS).map(|_| Keypair::new()).collect();
+    let (processor, _, world_pda, players) = setup(&authority, &wallets);
+    let block = build_block(&processor, world_pda, &players, &wallets);
+
+    let batches = scheduler::schedule(&block);
+    assert_eq!(batches[0], (0..PLAYERS).collect::<Vec<_>>());
+    assert!(batches.len() > 1);
+    // Each attack comes after the moves it touches, the follow-ups after the attacks
+    let batch_of = |index: usize| batches.iter().position(|batch| batch.contains(&index)).unwrap();
+    assert!(batch_of(PLAYERS) > batch_of(1));
+    assert!(batch_of(PLAYERS + 1) > batch_of(PLAYERS));
+    assert!(batch_of(PLAYERS + 3) > batch_of(PLAYERS + 2));
+    assert!(batch_of(PLAYERS + 5) > batch_of(PLAYERS + 4));
+}
+
+/// Test 2: Running the block in parallel matches running it one transaction at a time
+#[test]
+fn test_parallel_matches_sequential() {
+    let authority = Keypair::new();
+    let wallets: Vec<Keypair> = (0..PLAYERS).map(|_| Keypair::new()).collect();
+
+    let (mut parallel, parallel_store, world_pda, players) = setup(&authority, &wallets);
+    parallel.set_execution_threads(4);
+    let (mut sequential, sequential_store, _, _) = setup(&authority, &wallets);
+    sequential.set_execution_threads(1);
+    assert_eq!(parallel.current_blockhash(), sequential.current_blockhash());
+
+    let block = build_block(&parallel, world_pda, &players, &wallets);
+    let parallel_results = parallel.process_transactions(&block);
+    let sequential_results: Vec<_> = block
+        .iter()
+        .map(|tx| sequential.process_transactions(std::slice::from_ref(tx)).remove(0))
+        .collect();
+
+    assert_same_results(&parallel_results, &sequential_results);
+    // The counterattack sees the hit that stunned its attacker earlier in the block
+    assert_eq!(custom_error(&parallel_results[PLAYERS + 3]), Some(WorldError::PlayerStunned as u32));
+    assert_eq!(custom_error(&parallel_results[PLAYERS + 4]), Some(2)); // InvalidAuthority
+    assert_eq!(parallel_results.iter().filter(|result| result.success).count(), block.len() - 2);
+
+    for player in &players {
+        assert_eq!(parallel_store.get_account(player), sequential_store.get_account(player));
+    }
+    assert_eq!(parallel_store.state_root(), sequential_store.state_root());
+
+    parallel.advance_slot();
+    sequential.advance_slot();
+    assert_eq!(parallel.current_blockhash(), sequential.current_blockhash());
+}
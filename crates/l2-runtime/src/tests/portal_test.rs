@@ -0,0 +1,242 @@
+//! Portal Integration Tests
+//!
+//! Tests TransferWorld end to end:
+//! - The player is closed in the source world and recreated at the destination spawn
+//! - Health and name carry over, both player counts are updated
+//! - Destination capacity, ownership and team membership rules
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{join_player, make_player_name, make_world_name, setup_world_account};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{FIXED_POINT_SCALE, TEAM_NONE},
+    instruction::WorldInstruction,
+    state::{TeamScores, WorldConfig, WorldPlayer},
+};
+
+/// Set up a world owned by a fresh authority, returning its PDA
+fn world(store: &AccountStore, name: &str, size: u32, max_players: u16) -> Pubkey {
+    let world_name = make_world_name(name);
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, Keypair::new().pubkey(), world_name, size, size, max_players);
+    world_pda
+}
+
+fn transfer_instruction(
+    source_world: Pubkey,
+    source_player: Pubkey,
+    dest_world: Pubkey,
+    signer: &Keypair,
+    team_scores: &[Pubkey],
+) -> Instruction {
+    let (dest_player, _) = WorldPlayer::derive_pda(&dest_world, &signer.pubkey(), &world_program::id());
+    let mut accounts = vec![
+        AccountMeta::new(source_world, false),
+        AccountMeta::new(source_player, false),
+        AccountMeta::new(dest_world, false),
+        AccountMeta::new(dest_player, false),
+        AccountMeta::new(signer.pubkey(), true),
+        AccountMeta::new(signer.pubkey(), true),
+        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+    ];
+    accounts.extend(team_scores.iter().map(|scores| AccountMeta::new(*scores, false)));
+    Instruction::new_with_borsh(world_program::id(), &WorldInstruction::TransferWorld, accounts)
+}
+
+fn load_world(store: &AccountStore, world_pda: &Pubkey) -> WorldConfig {
+    WorldConfig::try_from_slice(store.get_account(world_pda).unwrap().data()).unwrap()
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+/// Overwrite a player's health directly in the store
+fn set_health(store: &AccountStore, player_pda: Pubkey, health: u16) {
+    let mut player = load_player(store, &player_pda);
+    player.health = health;
+    let mut account = store.get_account(&player_pda).unwrap();
+    borsh::to_writer(account.data_as_mut_slice(), &player).unwrap();
+    store.store_account(player_pda, account, 0);
+}
+
+/// Test 1: The player moves to the destination spawn with health and name intact
+#[test]
+fn test_transfer_world_moves_player() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let source = world(&store, "Overworld", 100, 10);
+    let dest = world(&store, "Dungeon", 300, 10);
+
+    let player = Keypair::new();
+    let source_player = join_player(&mut processor, &store, source, &player, "Traveler");
+    set_health(&store, source_player, 40);
+
+    let result = process(
+        &mut processor,
+        &player,
+        transfer_instruction(source, source_player, dest, &player, &[]),
+    );
+    assert!(result.success, "TransferWorld failed: {:?}", result.error);
+
+    assert!(store.get_account(&source_player).is_none(), "source player should be closed");
+    assert_eq!(load_world(&store, &source).player_count, 0);
+    assert_eq!(load_world(&store, &dest).player_count, 1);
+
+    let (dest_player, _) = WorldPlayer::derive_pda(&dest, &player.pubkey(), &world_program::id());
+    let moved = load_player(&store, &dest_player);
+    assert_eq!(moved.world, dest);
+    assert_eq!(moved.authority, player.pubkey());
+    assert_eq!(moved.name_str(), "Traveler");
+    assert_eq!(moved.health, 40);
+    assert_eq!(moved.position_x, 150 * FIXED_POINT_SCALE);
+    assert_eq!(moved.position_z, 150 * FIXED_POINT_SCALE);
+}
+
+/// Test 2: A full destination rejects the move and leaves the player where they were
+#[test]
+fn test_transfer_world_respects_max_players() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let source = world(&store, "Overworld", 100, 10);
+    let dest = world(&store, "Closet", 100, 1);
+    join_player(&mut processor, &store, dest, &Keypair::new(), "Occupant");
+
+    let player = Keypair::new();
+    let source_player = join_player(&mut processor, &store, source, &player, "Traveler");
+
+    let result = process(
+        &mut processor,
+        &player,
+        transfer_instruction(source, source_player, dest, &player, &[]),
+    );
+    assert_eq!(custom_error(&result), Some(1)); // WorldFull
+
+    assert_eq!(load_player(&store, &source_player).world, source);
+    assert_eq!(load_world(&store, &source).player_count, 1);
+    assert_eq!(load_world(&store, &dest).player_count, 1);
+}
+
+/// Test 3: Only the living owner can move a player, and only to another world
+#[test]
+fn test_transfer_world_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let source = world(&store, "Overworld", 100, 10);
+    let dest = world(&store, "Dungeon", 100, 10);
+
+    let player = Keypair::new();
+    let source_player = join_player(&mut processor, &store, source, &player, "Traveler");
+
+    // Someone else's player
+    let stranger = Keypair::new();
+    let result = process(
+        &mut processor,
+        &stranger,
+        transfer_instruction(source, source_player, dest, &stranger, &[]),
+    );
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+
+    // The world the player is already in
+    let result = process(
+        &mut processor,
+        &player,
+        transfer_instruction(source, source_player, source, &player, &[]),
+    );
+    assert_eq!(custom_error(&result), Some(3)); // InvalidWorld
+
+    // Dead players stay put
+    set_health(&store, source_player, 0);
+    let result = process(
+        &mut processor,
+        &player,
+        transfer_instruction(source, source_player, dest, &player, &[]),
+    );
+    assert_eq!(custom_error(&result), Some(4)); // PlayerDead
+    assert_eq!(load_world(&store, &dest).player_count, 0);
+}
+
+/// Test 4: Moving between team worlds swaps the player's team membership
+#[test]
+fn test_transfer_world_between_team_worlds() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+
+    let mut scores = Vec::new();
+    let mut worlds = Vec::new();
+    for name in ["RedBase", "BlueBase"] {
+        let world_authority = Keypair::new();
+        let world_name = make_world_name(name);
+        let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+        setup_world_account(&store, world_pda, world_authority.pubkey(), world_name, 100, 100, 10);
+
+        let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+        let configure = Instruction::new_with_borsh(
+            world_program::id(),
+            &WorldInstruction::ConfigureTeams {
+                team_count: 2,
+                friendly_fire: false,
+                max_team_size: 0,
+            },
+            vec![
+                AccountMeta::new(world_pda, false),
+                AccountMeta::new(scores_pda, false),
+                AccountMeta::new_readonly(world_authority.pubkey(), true),
+                AccountMeta::new(world_authority.pubkey(), true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+        );
+        assert!(process(&mut processor, &world_authority, configure).success);
+        worlds.push(world_pda);
+        scores.push(scores_pda);
+    }
+    let (source, dest) = (worlds[0], worlds[1]);
+
+    // JoinWorld needs the team scores account after the regular accounts
+    let player = Keypair::new();
+    let (source_player, _) = WorldPlayer::derive_pda(&source, &player.pubkey(), &world_program::id());
+    let join = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::JoinWorld {
+            name: make_player_name("Traveler"),
+        },
+        vec![
+            AccountMeta::new(source, false),
+            AccountMeta::new(source_player, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new(scores[0], false),
+        ],
+    );
+    assert!(process(&mut processor, &player, join).success);
+    let old_team = load_player(&store, &source_player).team;
+    assert_ne!(old_team, TEAM_NONE);
+
+    let result = process(
+        &mut processor,
+        &player,
+        transfer_instruction(source, source_player, dest, &player, &scores),
+    );
+    assert!(result.success, "TransferWorld failed: {:?}", result.error);
+
+    let load_scores =
+        |key: &Pubkey| TeamScores::try_from_slice(store.get_account(key).unwrap().data()).unwrap();
+    assert_eq!(load_scores(&scores[0]).member_counts, [0; 4]);
+
+    let (dest_player, _) = WorldPlayer::derive_pda(&dest, &player.pubkey(), &world_program::id());
+    let new_team = load_player(&store, &dest_player).team;
+    assert_ne!(new_team, TEAM_NONE);
+    assert_eq!(load_scores(&scores[1]).member_counts[new_team as usize - 1], 1);
+}
@@ -0,0 +1,380 @@
+//! Private World Integration Tests
+//!
+//! Tests world visibility end to end:
+//! - SetWorldVisibility switches a world between public, allowlist and password
+//! - UpdateAllowlist manages the world's allowlist account
+//! - JoinWorld / JoinWorldWithPassword / TransferWorld enforce the settings
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{
+    create_join_world_instruction, join_player, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{
+        MAX_ALLOWLIST_SIZE, WORLD_VISIBILITY_ALLOWLIST, WORLD_VISIBILITY_PASSWORD,
+        WORLD_VISIBILITY_PUBLIC,
+    },
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{WorldAllowlist, WorldConfig, WorldPlayer},
+};
+
+const SALT: [u8; 16] = [7; 16];
+
+fn password(text: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..text.len()].copy_from_slice(text.as_bytes());
+    bytes
+}
+
+/// Set up a world, returning (world PDA, world authority)
+fn setup_world(store: &AccountStore, name: &str) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name(name);
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(
+        store,
+        world_pda,
+        authority.pubkey(),
+        world_name,
+        100,
+        100,
+        100,
+    );
+    (world_pda, authority)
+}
+
+fn set_visibility(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    visibility: u8,
+    secret: &str,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SetWorldVisibility {
+            visibility,
+            password_salt: SALT,
+            password_hash: WorldConfig::hash_password(&SALT, &password(secret)),
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    )
+}
+
+fn update_allowlist(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    wallet: Pubkey,
+    allowed: bool,
+) -> Instruction {
+    let (allowlist_pda, _) = WorldAllowlist::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::UpdateAllowlist { wallet, allowed },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(allowlist_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+/// JoinWorld with the world's allowlist appended
+fn join_with_allowlist(world_pda: Pubkey, player: &Keypair) -> Instruction {
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &player.pubkey(), &world_program::id());
+    let (allowlist_pda, _) = WorldAllowlist::derive_pda(&world_pda, &world_program::id());
+    let mut ix =
+        create_join_world_instruction(world_pda, player_pda, player, make_player_name("Guest"));
+    ix.accounts
+        .push(AccountMeta::new_readonly(allowlist_pda, false));
+    ix
+}
+
+fn join_with_password(world_pda: Pubkey, player: &Keypair, secret: &str) -> Instruction {
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &player.pubkey(), &world_program::id());
+    let mut ix =
+        create_join_world_instruction(world_pda, player_pda, player, make_player_name("Guest"));
+    ix.data = borsh::to_vec(&WorldInstruction::JoinWorldWithPassword {
+        name: make_player_name("Guest"),
+        password: password(secret),
+    })
+    .unwrap();
+    ix
+}
+
+fn load_world(store: &AccountStore, world_pda: &Pubkey) -> WorldConfig {
+    WorldConfig::try_from_slice(store.get_account(world_pda).unwrap().data()).unwrap()
+}
+
+/// Test 1: Only allowlisted wallets (and the world authority) can join an allowlist world
+#[test]
+fn test_allowlist_world() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store, "Guild");
+
+    let result = process(
+        &mut processor,
+        &authority,
+        set_visibility(world_pda, &authority, WORLD_VISIBILITY_ALLOWLIST, ""),
+    );
+    assert!(
+        result.success,
+        "SetWorldVisibility failed: {:?}",
+        result.error
+    );
+    assert_eq!(
+        load_world(&store, &world_pda).visibility,
+        WORLD_VISIBILITY_ALLOWLIST
+    );
+
+    // Not listed yet (and no allowlist account exists)
+    let member = Keypair::new();
+    let result = process(
+        &mut processor,
+        &member,
+        join_with_allowlist(world_pda, &member),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::NotAllowlisted as u32)
+    );
+
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            update_allowlist(world_pda, &authority, member.pubkey(), true)
+        )
+        .success
+    );
+    let (allowlist_pda, _) = WorldAllowlist::derive_pda(&world_pda, &world_program::id());
+    let allowlist =
+        WorldAllowlist::try_from_slice(store.get_account(&allowlist_pda).unwrap().data()).unwrap();
+    assert_eq!(allowlist.members(), &[member.pubkey()]);
+
+    // Listed wallets must still pass the allowlist account
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &member.pubkey(), &world_program::id());
+    let bare_join =
+        create_join_world_instruction(world_pda, player_pda, &member, make_player_name("Guest"));
+    assert_eq!(
+        custom_error(&process(&mut processor, &member, bare_join)),
+        Some(WorldError::NotAllowlisted as u32)
+    );
+
+    let result = process(
+        &mut processor,
+        &member,
+        join_with_allowlist(world_pda, &member),
+    );
+    assert!(
+        result.success,
+        "allowlisted JoinWorld failed: {:?}",
+        result.error
+    );
+
+    // Removed wallets are turned away
+    let other = Keypair::new();
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            update_allowlist(world_pda, &authority, other.pubkey(), true)
+        )
+        .success
+    );
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            update_allowlist(world_pda, &authority, other.pubkey(), false)
+        )
+        .success
+    );
+    let result = process(
+        &mut processor,
+        &other,
+        join_with_allowlist(world_pda, &other),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::NotAllowlisted as u32)
+    );
+
+    // The world authority is always let in
+    join_player(&mut processor, &store, world_pda, &authority, "Owner");
+    assert_eq!(load_world(&store, &world_pda).player_count, 2);
+}
+
+/// Test 2: Password worlds need the right password
+#[test]
+fn test_password_world() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store, "Speakeasy");
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            set_visibility(
+                world_pda,
+                &authority,
+                WORLD_VISIBILITY_PASSWORD,
+                "swordfish"
+            )
+        )
+        .success
+    );
+
+    // The world stores only the salted hash
+    let world = load_world(&store, &world_pda);
+    assert_eq!(world.password_salt, SALT);
+    assert_ne!(world.password_hash, password("swordfish"));
+
+    let player = Keypair::new();
+    let (player_pda, _) =
+        WorldPlayer::derive_pda(&world_pda, &player.pubkey(), &world_program::id());
+    let plain_join =
+        create_join_world_instruction(world_pda, player_pda, &player, make_player_name("Guest"));
+    assert_eq!(
+        custom_error(&process(&mut processor, &player, plain_join)),
+        Some(WorldError::InvalidPassword as u32)
+    );
+
+    let result = process(
+        &mut processor,
+        &player,
+        join_with_password(world_pda, &player, "trout"),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::InvalidPassword as u32)
+    );
+
+    let result = process(
+        &mut processor,
+        &player,
+        join_with_password(world_pda, &player, "swordfish"),
+    );
+    assert!(
+        result.success,
+        "JoinWorldWithPassword failed: {:?}",
+        result.error
+    );
+    assert_eq!(load_world(&store, &world_pda).player_count, 1);
+
+    // Back to public: anyone joins again
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            set_visibility(world_pda, &authority, WORLD_VISIBILITY_PUBLIC, "")
+        )
+        .success
+    );
+    join_player(
+        &mut processor,
+        &store,
+        world_pda,
+        &Keypair::new(),
+        "Walk-in",
+    );
+}
+
+/// Test 3: Visibility is authority-only, and portals cannot bypass it
+#[test]
+fn test_visibility_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, authority) = setup_world(&store, "Vault");
+
+    let stranger = Keypair::new();
+    let result = process(
+        &mut processor,
+        &stranger,
+        set_visibility(world_pda, &stranger, WORLD_VISIBILITY_PASSWORD, "mine"),
+    );
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+    let result = process(
+        &mut processor,
+        &stranger,
+        update_allowlist(world_pda, &stranger, stranger.pubkey(), true),
+    );
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+
+    let result = process(
+        &mut processor,
+        &authority,
+        set_visibility(world_pda, &authority, 9, ""),
+    );
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::InvalidVisibility as u32)
+    );
+
+    // The allowlist holds a bounded number of wallets
+    for _ in 0..MAX_ALLOWLIST_SIZE {
+        let ix = update_allowlist(world_pda, &authority, Pubkey::new_unique(), true);
+        assert!(process(&mut processor, &authority, ix).success);
+    }
+    let ix = update_allowlist(world_pda, &authority, Pubkey::new_unique(), true);
+    assert_eq!(
+        custom_error(&process(&mut processor, &authority, ix)),
+        Some(WorldError::AllowlistFull as u32)
+    );
+
+    // A portal into a password world is refused
+    assert!(
+        process(
+            &mut processor,
+            &authority,
+            set_visibility(world_pda, &authority, WORLD_VISIBILITY_PASSWORD, "mine")
+        )
+        .success
+    );
+    let (lobby_pda, _) = setup_world(&store, "Lobby");
+    let traveler = Keypair::new();
+    let source_player = join_player(&mut processor, &store, lobby_pda, &traveler, "Traveler");
+    let (dest_player, _) =
+        WorldPlayer::derive_pda(&world_pda, &traveler.pubkey(), &world_program::id());
+    let transfer = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::TransferWorld,
+        vec![
+            AccountMeta::new(lobby_pda, false),
+            AccountMeta::new(source_player, false),
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(dest_player, false),
+            AccountMeta::new(traveler.pubkey(), true),
+            AccountMeta::new(traveler.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    );
+    let result = process(&mut processor, &traveler, transfer);
+    assert_eq!(
+        custom_error(&result),
+        Some(WorldError::InvalidPassword as u32)
+    );
+    assert_eq!(load_world(&store, &world_pda).player_count, 0);
+}
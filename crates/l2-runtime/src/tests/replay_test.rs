@@ -0,0 +1,234 @@
+//! Replay Tests
+//!
+//! Tests that recorded blocks replay from a snapshot to the same chain:
+//! - An untouched block stream replays with no divergence
+//! - A tampered account is reported with the transaction that wrote it
+//! - A tampered transaction result or error is reported at its slot
+//! - A gap in the block stream is reported
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    account::WritableAccount,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use tempfile::{tempdir, TempDir};
+
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    block_producer::{BlockProducer, BlockProducerConfig},
+    block_store::{BlockStore, BlockStoreConfig, BlockWriter, StoredBlock},
+    persistence::ChainMetadata,
+    processor::L2Processor,
+    replay::{Divergence, ReplaySnapshot, Replayer, TransactionOutcome},
+};
+
+use world_program::state::{WorldConfig, WorldPlayer};
+
+/// A recorded chain: the snapshot it started from and its blocks
+struct RecordedChain {
+    _dir: TempDir,
+    snapshot: ReplaySnapshot,
+    blocks: Vec<StoredBlock>,
+    store: Arc<AccountStore>,
+    player_pda: Pubkey,
+}
+
+/// Record three blocks: a join, an empty block, then a join and a failed steal
+fn record_chain() -> RecordedChain {
+    let dir = tempdir().unwrap();
+    let block_store = Arc::new(BlockStore::open(dir.path(), BlockStoreConfig::default()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    producer.set_block_writer(BlockWriter::spawn(block_store.clone()));
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Replay");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+    producer.produce_block(vec![]);
+
+    let metadata = ChainMetadata {
+        slot: producer.current_slot(),
+        blockhash: producer.current_blockhash().to_bytes(),
+        state_root: store.state_root().to_bytes(),
+        ..Default::default()
+    };
+    let snapshot = ReplaySnapshot::from_store(&store, metadata);
+
+    let wallet = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+    let join = create_sanitized_transaction(
+        &wallet,
+        vec![create_join_world_instruction(world_pda, player_pda, &wallet, make_player_name("Echo"))],
+        producer.current_blockhash(),
+    );
+    producer.produce_block(vec![join]);
+    producer.produce_block(vec![]);
+
+    let second = Keypair::new();
+    let (second_pda, _) = WorldPlayer::derive_pda(&world_pda, &second.pubkey(), &world_program::id());
+    let blockhash = producer.current_blockhash();
+    let join = create_sanitized_transaction(
+        &second,
+        vec![create_join_world_instruction(world_pda, second_pda, &second, make_player_name("Reprise"))],
+        blockhash,
+    );
+    let steal = create_sanitized_transaction(
+        &second,
+        vec![create_join_world_instruction(world_pda, player_pda, &second, make_player_name("Thief"))],
+        blockhash,
+    );
+    producer.produce_block(vec![join, steal]);
+    // Dropping the producer waits for its blocks to be written
+    drop(producer);
+
+    let blocks = block_store
+        .blocks(snapshot.metadata.slot..)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(blocks.len(), 3);
+    RecordedChain {
+        _dir: dir,
+        snapshot,
+        blocks,
+        store,
+        player_pda,
+    }
+}
+
+/// Test 1: Replaying the recorded blocks reproduces the chain
+#[test]
+fn test_replay_matches_recorded_chain() {
+    let chain = record_chain();
+    let mut replayer = Replayer::new(&chain.snapshot);
+    let report = replayer.replay(chain.blocks.iter().cloned().map(Ok)).unwrap();
+
+    assert!(report.divergence.is_none(), "{}", report.divergence.unwrap());
+    assert_eq!(report.blocks_verified, 3);
+    assert_eq!(report.transactions_verified, 3);
+    assert_eq!(report.last_verified_slot, Some(chain.blocks[2].slot));
+    assert_eq!(replayer.account_store().state_root(), chain.store.state_root());
+    assert_eq!(
+        replayer.account_store().get_account(&chain.player_pda),
+        chain.store.get_account(&chain.player_pda)
+    );
+}
+
+/// Test 2: A recorded account that doesn't match is reported with its writer
+#[test]
+fn test_replay_reports_account_divergence() {
+    let mut chain = record_chain();
+    let block = &mut chain.blocks[0];
+    let (_, player) = block
+        .modified_accounts
+        .iter_mut()
+        .find(|(pubkey, _)| *pubkey == chain.player_pda)
+        .unwrap();
+    player.data_as_mut_slice()[40] ^= 1;
+    let join = block.transactions[0].signature();
+
+    let report = Replayer::new(&chain.snapshot)
+        .replay(chain.blocks.iter().cloned().map(Ok))
+        .unwrap();
+    let divergence = report.divergence.unwrap();
+    assert_eq!(divergence.slot, chain.blocks[0].slot);
+    assert_eq!(report.blocks_verified, 0);
+    match divergence.divergence {
+        Divergence::Account { pubkey, signature, .. } => {
+            assert_eq!(pubkey, chain.player_pda);
+            assert_eq!(signature, Some(join));
+        }
+        other => panic!("expected an account divergence, got {:?}", other),
+    }
+}
+
+/// Test 3: A transaction recorded as succeeding that fails on replay is reported
+#[test]
+fn test_replay_reports_transaction_divergence() {
+    let mut chain = record_chain();
+    let steal = &mut chain.blocks[2].transactions[1];
+    steal.success = true;
+    steal.error = None;
+    let signature = steal.signature();
+
+    let report = Replayer::new(&chain.snapshot)
+        .replay(chain.blocks.iter().cloned().map(Ok))
+        .unwrap();
+    assert_eq!(report.blocks_verified, 2);
+    let divergence = report.divergence.unwrap();
+    assert_eq!(divergence.slot, chain.blocks[2].slot);
+    match divergence.divergence {
+        Divergence::Transaction {
+            index,
+            signature: reported,
+            recorded,
+            replayed,
+        } => {
+            assert_eq!(index, 1);
+            assert_eq!(reported, signature);
+            assert_eq!(
+                recorded,
+                TransactionOutcome {
+                    success: true,
+                    error: None
+                }
+            );
+            assert!(!replayed.success);
+            assert!(matches!(replayed.error, Some(TransactionError::InstructionError(..))));
+        }
+        other => panic!("expected a transaction divergence, got {:?}", other),
+    }
+}
+
+/// Test 4: A failure recorded without an error doesn't match the replayed error
+#[test]
+fn test_replay_compares_recorded_errors() {
+    let mut chain = record_chain();
+    let steal = &mut chain.blocks[2].transactions[1];
+    steal.error = None;
+
+    let report = Replayer::new(&chain.snapshot)
+        .replay(chain.blocks.iter().cloned().map(Ok))
+        .unwrap();
+    match report.divergence.unwrap().divergence {
+        Divergence::Transaction { index, recorded, replayed, .. } => {
+            assert_eq!(index, 1);
+            assert_eq!(
+                recorded,
+                TransactionOutcome {
+                    success: false,
+                    error: None
+                }
+            );
+            assert!(!replayed.success && replayed.error.is_some());
+        }
+        other => panic!("expected a transaction divergence, got {:?}", other),
+    }
+}
+
+/// Test 5: A missing block stops replay at the gap
+#[test]
+fn test_replay_reports_missing_block() {
+    let mut chain = record_chain();
+    chain.blocks.remove(1);
+
+    let report = Replayer::new(&chain.snapshot)
+        .replay(chain.blocks.iter().cloned().map(Ok))
+        .unwrap();
+    assert_eq!(report.blocks_verified, 1);
+    let divergence = report.divergence.unwrap();
+    assert_eq!(
+        divergence.divergence,
+        Divergence::MissingBlock {
+            expected_slot: chain.blocks[0].slot + 1,
+            found_slot: chain.blocks[1].slot,
+        }
+    );
+}
@@ -0,0 +1,291 @@
+//! Session Key Integration Tests
+//!
+//! Tests gasless signing through ephemeral session keys:
+//! - CreateSession registers a session PDA for the player
+//! - MovePlayer3D / Heal accept a valid session signer
+//! - Expired, revoked and disallowed sessions are rejected
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::custom_error;
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::{SESSION_ALLOW_ALL, SESSION_ALLOW_HEAL, SESSION_ALLOW_MOVE},
+    instruction::WorldInstruction,
+    state::{MovementInput3D, PlayerSession, WorldConfig, WorldPlayer},
+    WorldError,
+};
+
+/// Set up a world with one joined player
+fn setup_player(
+    processor: &mut L2Processor,
+    store: &AccountStore,
+) -> (Pubkey, Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("SessionWorld");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 1000, 1000, 100);
+
+    let player_pda = join_player(processor, store, world_pda, &authority, "SessionPlayer");
+    (world_pda, player_pda, authority)
+}
+
+fn create_session_instruction(
+    player_pda: Pubkey,
+    authority: &Keypair,
+    session_key: Pubkey,
+    expiry_slot: u64,
+    allowed_instructions: u8,
+) -> Instruction {
+    let (session_pda, _) = PlayerSession::derive_pda(&player_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::CreateSession {
+            session_key,
+            expiry_slot,
+            allowed_instructions,
+        },
+        vec![
+            AccountMeta::new_readonly(player_pda, false),
+            AccountMeta::new(session_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn move_with_session_instruction(world_pda: Pubkey, player_pda: Pubkey, signer: &Keypair) -> Instruction {
+    let (session_pda, _) = PlayerSession::derive_pda(&player_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D {
+            input: MovementInput3D {
+                move_x: 0,
+                move_z: 127,
+                camera_yaw: 0,
+                sprint: false,
+                jump: false,
+                dash: false,
+                crouch: false,
+            },
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(signer.pubkey(), true),
+            AccountMeta::new_readonly(session_pda, false),
+        ],
+    )
+}
+
+fn heal_with_session_instruction(world_pda: Pubkey, player_pda: Pubkey, signer: &Keypair) -> Instruction {
+    let (session_pda, _) = PlayerSession::derive_pda(&player_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Heal { amount: 0 },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(signer.pubkey(), true),
+            AccountMeta::new_readonly(session_pda, false),
+        ],
+    )
+}
+
+/// Test 1: A registered session key can move the player without the wallet
+#[test]
+fn test_session_key_can_move_player() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, player_pda, authority) = setup_player(&mut processor, &store);
+    let session_key = Keypair::new();
+
+    let ix = create_session_instruction(
+        player_pda,
+        &authority,
+        session_key.pubkey(),
+        processor.current_slot() + 100,
+        SESSION_ALLOW_ALL,
+    );
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "CreateSession failed: {:?}", results[0].error);
+
+    // Session account is sized and populated
+    let (session_pda, _) = PlayerSession::derive_pda(&player_pda, &world_program::id());
+    let session_account = store.get_account(&session_pda).unwrap();
+    let session = PlayerSession::try_from_slice(session_account.data()).unwrap();
+    assert_eq!(session.player, player_pda);
+    assert_eq!(session.authority, authority.pubkey());
+    assert_eq!(session.session_key, session_key.pubkey());
+
+    let before = WorldPlayer::try_from_slice(store.get_account(&player_pda).unwrap().data()).unwrap();
+
+    // Session key signs and pays - wallet is not involved
+    processor.advance_slot();
+    let ix = move_with_session_instruction(world_pda, player_pda, &session_key);
+    let tx = create_sanitized_transaction(&session_key, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "Session move failed: {:?}", results[0].error);
+
+    let after = WorldPlayer::try_from_slice(store.get_account(&player_pda).unwrap().data()).unwrap();
+    assert!(after.position_z > before.position_z, "Player should have moved forward");
+    assert_eq!(after.last_action_slot, processor.current_slot());
+}
+
+/// Test 2: Unknown signers without a session are rejected
+#[test]
+fn test_move_without_session_rejected() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, player_pda, _authority) = setup_player(&mut processor, &store);
+    let stranger = Keypair::new();
+
+    // No session registered: the synthesized session PDA does not decode
+    let ix = move_with_session_instruction(world_pda, player_pda, &stranger);
+    let tx = create_sanitized_transaction(&stranger, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success, "Move by stranger should fail");
+}
+
+/// Test 3: Sessions stop working after their expiry slot
+#[test]
+fn test_expired_session_rejected() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, player_pda, authority) = setup_player(&mut processor, &store);
+    let session_key = Keypair::new();
+
+    let expiry_slot = processor.current_slot() + 2;
+    let ix = create_session_instruction(player_pda, &authority, session_key.pubkey(), expiry_slot, SESSION_ALLOW_MOVE);
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+
+    for _ in 0..3 {
+        processor.advance_slot();
+    }
+    assert!(processor.current_slot() > expiry_slot);
+
+    let ix = move_with_session_instruction(world_pda, player_pda, &session_key);
+    let tx = create_sanitized_transaction(&session_key, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success, "Expired session should be rejected");
+    assert_eq!(custom_error(&results[0]), Some(WorldError::SessionExpired as u32));
+}
+
+/// Test 4: Sessions can only sign the instructions they were granted
+#[test]
+fn test_session_instruction_allowlist() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, player_pda, authority) = setup_player(&mut processor, &store);
+    let session_key = Keypair::new();
+
+    let ix = create_session_instruction(
+        player_pda,
+        &authority,
+        session_key.pubkey(),
+        processor.current_slot() + 100,
+        SESSION_ALLOW_MOVE,
+    );
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+
+    let ix = heal_with_session_instruction(world_pda, player_pda, &session_key);
+    let tx = create_sanitized_transaction(&session_key, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success, "Heal should not be allowed for a move-only session");
+    assert_eq!(custom_error(&results[0]), Some(WorldError::SessionNotAllowed as u32));
+
+    // Granting heal makes it pass
+    let ix = create_session_instruction(
+        player_pda,
+        &authority,
+        session_key.pubkey(),
+        processor.current_slot() + 100,
+        SESSION_ALLOW_MOVE | SESSION_ALLOW_HEAL,
+    );
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+
+    processor.advance_slot();
+    let ix = heal_with_session_instruction(world_pda, player_pda, &session_key);
+    let tx = create_sanitized_transaction(&session_key, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "Heal with session failed: {:?}", results[0].error);
+}
+
+/// Test 5: Sessions cannot outlive the maximum duration
+#[test]
+fn test_session_duration_limited() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (_world_pda, player_pda, authority) = setup_player(&mut processor, &store);
+
+    let ix = create_session_instruction(
+        player_pda,
+        &authority,
+        Keypair::new().pubkey(),
+        processor.current_slot() + world_program::constants::MAX_SESSION_DURATION_SLOTS + 1,
+        SESSION_ALLOW_ALL,
+    );
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::InvalidSession as u32));
+}
+
+/// Test 6: RevokeSession closes the session and disables the key
+#[test]
+fn test_revoke_session() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, player_pda, authority) = setup_player(&mut processor, &store);
+    let session_key = Keypair::new();
+
+    let ix = create_session_instruction(
+        player_pda,
+        &authority,
+        session_key.pubkey(),
+        processor.current_slot() + 100,
+        SESSION_ALLOW_ALL,
+    );
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+
+    let (session_pda, _) = PlayerSession::derive_pda(&player_pda, &world_program::id());
+    let revoke_ix = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::RevokeSession,
+        vec![
+            AccountMeta::new(session_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+        ],
+    );
+    let tx = create_sanitized_transaction(&authority, vec![revoke_ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "RevokeSession failed: {:?}", results[0].error);
+
+    // Closed accounts are purged from the store
+    assert!(store.get_account(&session_pda).is_none(), "Session account should be closed");
+
+    processor.advance_slot();
+    let ix = move_with_session_instruction(world_pda, player_pda, &session_key);
+    let tx = create_sanitized_transaction(&session_key, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(!results[0].success, "Revoked session should be rejected");
+}
@@ -0,0 +1,163 @@
+//! Snapshot Tests
+//!
+//! Tests that the block producer writes state snapshots at block boundaries:
+//! - Archives are written on schedule
+//! - A restored archive matches the chain at its slot
+//! - Blocks recorded after the snapshot replay on top of it
+//! - Restoring an archive rolls persisted state, log and blocks back to it
+
+use std::sync::Arc;
+
+use solana_sdk::signature::{Keypair, Signer};
+use tempfile::tempdir;
+
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{
+    account_store::AccountStore,
+    block_producer::{BlockProducer, BlockProducerConfig},
+    block_store::{BlockStore, BlockStoreConfig, BlockWriter},
+    persistence::{AccountStorePersistence, CheckpointWriter, PersistentStore, SlotDelta},
+    processor::L2Processor,
+    replay::Replayer,
+    snapshot::{read_snapshot, restore_snapshot, SnapshotArchive, SnapshotConfig, SnapshotWriter},
+};
+
+use world_program::state::{WorldConfig, WorldPlayer};
+
+/// Test 1: Snapshots taken while producing blocks restore and replay forward
+#[test]
+fn test_snapshots_restore_and_replay() {
+    let dir = tempdir().unwrap();
+    let block_store = Arc::new(BlockStore::open(dir.path().join("blocks"), BlockStoreConfig::default()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    producer.set_block_writer(BlockWriter::spawn(block_store.clone()));
+    let config = SnapshotConfig {
+        interval_slots: 2,
+        ..Default::default()
+    };
+    producer.set_snapshot_writer(SnapshotWriter::spawn(
+        SnapshotArchive::open(dir.path().join("snapshots")).unwrap(),
+        config,
+    ));
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Archive");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let mut players = Vec::new();
+    for name in ["First", "Second", "Third"] {
+        let wallet = Keypair::new();
+        let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+        let join = create_sanitized_transaction(
+            &wallet,
+            vec![create_join_world_instruction(world_pda, player_pda, &wallet, make_player_name(name))],
+            producer.current_blockhash(),
+        );
+        let update = producer.produce_block(vec![join]);
+        assert!(update.transaction_results[0].success);
+        players.push(player_pda);
+    }
+    producer.produce_block(vec![]);
+    let final_root = store.state_root();
+    // Dropping the producer waits for the writer to finish
+    drop(producer);
+
+    // Slot 2 is always snapshotted; slot 4 too unless the writer was still busy
+    let archive = SnapshotArchive::open(dir.path().join("snapshots")).unwrap();
+    let snapshots = archive.list().unwrap();
+    let slots: Vec<_> = snapshots.iter().map(|info| info.manifest.slot()).collect();
+    assert!(slots == vec![2] || slots == vec![2, 4], "unexpected snapshots {:?}", slots);
+
+    // At slot 2 only the first player (joined in slot 0) and second exist
+    let snapshot = read_snapshot(&snapshots[0].path).unwrap();
+    let block = block_store.get_block(1).unwrap().unwrap();
+    assert_eq!(snapshot.metadata.blockhash, block.blockhash.to_bytes());
+    assert_eq!(snapshot.metadata.state_root, block.state_root.to_bytes());
+    let has = |pubkey| snapshot.accounts.iter().any(|(key, _)| *key == pubkey);
+    assert!(has(players[0]) && has(players[1]) && !has(players[2]));
+
+    // Replaying the rest of the chain on top of it reaches the same state
+    let mut replayer = Replayer::new(&snapshot);
+    let report = replayer.replay(block_store.blocks(..)).unwrap();
+    assert!(report.divergence.is_none(), "{}", report.divergence.unwrap());
+    assert_eq!(report.blocks_verified, 2);
+    assert_eq!(replayer.account_store().state_root(), final_root);
+}
+
+/// Test 2: Restoring an archive over a running chain's state resumes exactly at the archive
+#[test]
+fn test_restore_rolls_back_persisted_state() {
+    let dir = tempdir().unwrap();
+    let persistent = Arc::new(PersistentStore::open(dir.path().join("state")).unwrap());
+    let block_store = Arc::new(BlockStore::open(dir.path().join("blocks"), BlockStoreConfig::default()).unwrap());
+    let store = Arc::new(AccountStore::new());
+    let mut producer = BlockProducer::new(L2Processor::new(store.clone()), BlockProducerConfig::default());
+    producer.set_block_writer(BlockWriter::spawn(block_store.clone()));
+    producer.set_checkpoint_writer(CheckpointWriter::spawn(persistent.clone(), 3));
+    let config = SnapshotConfig {
+        interval_slots: 2,
+        ..Default::default()
+    };
+    producer.set_snapshot_writer(SnapshotWriter::spawn(
+        SnapshotArchive::open(dir.path().join("snapshots")).unwrap(),
+        config,
+    ));
+    let mut updates = producer.subscribe();
+
+    let authority = Keypair::new();
+    let world_name = make_world_name("Rollback");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    // Slots 0..5: a join in slot 0 (before the snapshot at 2) and in slot 3 (after)
+    let mut players = Vec::new();
+    for slot in 0..5 {
+        let mut transactions = Vec::new();
+        if slot == 0 || slot == 3 {
+            let wallet = Keypair::new();
+            let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &wallet.pubkey(), &world_program::id());
+            transactions.push(create_sanitized_transaction(
+                &wallet,
+                vec![create_join_world_instruction(world_pda, player_pda, &wallet, make_player_name("Joiner"))],
+                producer.current_blockhash(),
+            ));
+            players.push(player_pda);
+        }
+        producer.produce_block(transactions);
+    }
+    drop(producer);
+    // The write-ahead log holds the blocks past the slot 3 checkpoint
+    while let Ok(update) = updates.try_recv() {
+        persistent.append_delta(&SlotDelta::from_update(&update)).unwrap();
+    }
+    assert!(persistent.wal_len() > 0);
+
+    let archive = SnapshotArchive::open(dir.path().join("snapshots")).unwrap();
+    let snapshot = read_snapshot(&archive.list().unwrap()[0].path).unwrap();
+    assert_eq!(snapshot.metadata.slot, 2);
+    let metadata = snapshot.metadata.clone();
+
+    let report = restore_snapshot(snapshot, &persistent, &block_store).unwrap();
+    assert!(report.dropped_deltas > 0);
+    assert_eq!(report.dropped_blocks, 3);
+    assert_eq!(persistent.wal_len(), 0);
+    assert_eq!(block_store.last_slot().unwrap(), Some(1));
+
+    // Starting up from the restored state resumes at the snapshot
+    let restored = Arc::new(AccountStore::new());
+    restored.load_from_disk(&persistent).unwrap();
+    assert!(restored.replay_wal(&persistent).unwrap().is_none());
+    let loaded = persistent.load_metadata().unwrap().unwrap();
+    assert_eq!(loaded, metadata);
+    let processor = L2Processor::from_metadata(restored.clone(), &loaded);
+    assert_eq!(processor.current_slot(), metadata.slot);
+    assert_eq!(processor.current_blockhash().to_bytes(), metadata.blockhash);
+    assert_eq!(processor.state_root().to_bytes(), metadata.state_root);
+    assert!(restored.get_account(&players[0]).is_some());
+    assert!(restored.get_account(&players[1]).is_none());
+}
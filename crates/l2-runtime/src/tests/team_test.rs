@@ -0,0 +1,310 @@
+//! Team Integration Tests
+//!
+//! Tests team play end to end:
+//! - ConfigureTeams enables teams and creates the world's team scores account
+//! - JoinWorld auto-balances players across teams
+//! - ChooseTeam respects team size limits
+//! - Friendly-fire rules and team score aggregation in Attack
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::{Account, AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::custom_error;
+use super::join_world_test::{
+    create_join_world_instruction, create_sanitized_transaction, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor};
+
+use world_program::{
+    constants::TEAM_NONE,
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{TeamScores, WeaponStats, WorldConfig, WorldPlayer},
+};
+
+fn configure_teams_instruction(
+    world_pda: Pubkey,
+    authority: &Keypair,
+    team_count: u8,
+    friendly_fire: bool,
+    max_team_size: u16,
+) -> Instruction {
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ConfigureTeams {
+            team_count,
+            friendly_fire,
+            max_team_size,
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(scores_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn attack_instruction(world_pda: Pubkey, attacker: Pubkey, target: Pubkey, signer: &Keypair, damage: u16) -> Instruction {
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::Attack {
+            weapon_stats: Some(WeaponStats {
+                damage,
+                range: 0,
+                attack_speed: 0,
+            }),
+            observed_slot: None,
+        },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(attacker, false),
+            AccountMeta::new(target, false),
+            AccountMeta::new_readonly(signer.pubkey(), true),
+            AccountMeta::new(scores_pda, false),
+        ],
+    )
+}
+
+fn choose_team_instruction(world_pda: Pubkey, player_pda: Pubkey, authority: &Keypair, team: u8) -> Instruction {
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::ChooseTeam { team },
+        vec![
+            AccountMeta::new_readonly(world_pda, false),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(scores_pda, false),
+        ],
+    )
+}
+
+/// Set up a world and configure teams, returning (world PDA, world authority)
+fn setup_team_world(
+    processor: &mut L2Processor,
+    store: &AccountStore,
+    team_count: u8,
+    friendly_fire: bool,
+    max_team_size: u16,
+) -> (Pubkey, Keypair) {
+    let world_authority = Keypair::new();
+    let world_name = make_world_name("TeamWorld");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, world_authority.pubkey(), world_name, 1000, 1000, 100);
+
+    let ix = configure_teams_instruction(world_pda, &world_authority, team_count, friendly_fire, max_team_size);
+    let tx = create_sanitized_transaction(&world_authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "ConfigureTeams failed: {:?}", results[0].error);
+
+    (world_pda, world_authority)
+}
+
+/// Join a player into a team world (team scores account appended)
+fn join_team_player(processor: &mut L2Processor, store: &AccountStore, world_pda: Pubkey, name: &str) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &authority.pubkey(), &world_program::id());
+    store.store_account(
+        player_pda,
+        AccountSharedData::from(Account {
+            lamports: 1_000_000_000,
+            data: vec![0u8; WorldPlayer::LEN],
+            owner: world_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        }),
+        0,
+    );
+
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    let mut ix = create_join_world_instruction(world_pda, player_pda, &authority, make_player_name(name));
+    ix.accounts.push(AccountMeta::new(scores_pda, false));
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "JoinWorld failed: {:?}", results[0].error);
+
+    (player_pda, authority)
+}
+
+fn load_player(store: &AccountStore, player_pda: &Pubkey) -> WorldPlayer {
+    WorldPlayer::try_from_slice(store.get_account(player_pda).unwrap().data()).unwrap()
+}
+
+fn load_scores(store: &AccountStore, world_pda: &Pubkey) -> TeamScores {
+    let (scores_pda, _) = TeamScores::derive_pda(world_pda, &world_program::id());
+    TeamScores::try_from_slice(store.get_account(&scores_pda).unwrap().data()).unwrap()
+}
+
+/// Test 1: ConfigureTeams updates the world and creates the team scores account
+#[test]
+fn test_configure_teams() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_team_world(&mut processor, &store, 2, false, 3);
+
+    let world = WorldConfig::try_from_slice(store.get_account(&world_pda).unwrap().data()).unwrap();
+    assert_eq!((world.team_count, world.friendly_fire, world.max_team_size), (2, false, 3));
+
+    let scores = load_scores(&store, &world_pda);
+    assert_eq!(scores.world, world_pda);
+    assert_eq!(scores.member_counts, [0; 4]);
+    assert_eq!(scores.scores, [0; 4]);
+}
+
+/// Test 2: Only the world authority can configure teams
+#[test]
+fn test_configure_teams_requires_authority() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let world_name = make_world_name("TeamWorld");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, Pubkey::new_unique(), world_name, 1000, 1000, 100);
+
+    let stranger = Keypair::new();
+    let ix = configure_teams_instruction(world_pda, &stranger, 2, false, 0);
+    let tx = create_sanitized_transaction(&stranger, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(2)); // InvalidAuthority
+}
+
+/// Test 3: Joining players are balanced across teams
+#[test]
+fn test_join_auto_balances_teams() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 2, false, 0);
+
+    let teams: Vec<u8> = ["A", "B", "C", "D"]
+        .iter()
+        .map(|name| {
+            let (player_pda, _) = join_team_player(&mut processor, &store, world_pda, name);
+            processor.advance_slot();
+            load_player(&store, &player_pda).team
+        })
+        .collect();
+    assert_eq!(teams, vec![1, 2, 1, 2]);
+    assert_eq!(load_scores(&store, &world_pda).member_counts, [2, 2, 0, 0]);
+
+    // Team count is locked while players are in the world
+    let ix = configure_teams_instruction(world_pda, &world_authority, 3, false, 0);
+    let tx = create_sanitized_transaction(&world_authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::InvalidTeam as u32));
+}
+
+/// Test 4: ChooseTeam switches teams within the size limit
+#[test]
+fn test_choose_team_respects_size_limit() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_team_world(&mut processor, &store, 2, false, 1);
+
+    let (first_pda, first) = join_team_player(&mut processor, &store, world_pda, "First");
+    processor.advance_slot();
+    let (second_pda, _) = join_team_player(&mut processor, &store, world_pda, "Second");
+    processor.advance_slot();
+    assert_eq!(load_player(&store, &first_pda).team, 1);
+    assert_eq!(load_player(&store, &second_pda).team, 2);
+
+    // Team 2 already has its single member
+    let ix = choose_team_instruction(world_pda, first_pda, &first, 2);
+    let tx = create_sanitized_transaction(&first, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::TeamFull as u32));
+
+    // Teams outside the configured range are rejected
+    let ix = choose_team_instruction(world_pda, first_pda, &first, 3);
+    let tx = create_sanitized_transaction(&first, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::InvalidTeam as u32));
+
+    // A third player finds every team full
+    let authority = Keypair::new();
+    let (player_pda, _) = WorldPlayer::derive_pda(&world_pda, &authority.pubkey(), &world_program::id());
+    let (scores_pda, _) = TeamScores::derive_pda(&world_pda, &world_program::id());
+    let mut ix = create_join_world_instruction(world_pda, player_pda, &authority, make_player_name("Third"));
+    ix.accounts.push(AccountMeta::new(scores_pda, false));
+    let tx = create_sanitized_transaction(&authority, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::TeamFull as u32));
+    assert_eq!(load_player(&store, &first_pda).team, 1);
+}
+
+/// Test 5: Friendly fire is blocked unless the world allows it
+#[test]
+fn test_friendly_fire_rules() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, world_authority) = setup_team_world(&mut processor, &store, 1, false, 0);
+
+    let (attacker_pda, attacker) = join_team_player(&mut processor, &store, world_pda, "Attacker");
+    processor.advance_slot();
+    let (target_pda, _) = join_team_player(&mut processor, &store, world_pda, "Target");
+    processor.advance_slot();
+
+    let ix = attack_instruction(world_pda, attacker_pda, target_pda, &attacker, 10);
+    let tx = create_sanitized_transaction(&attacker, vec![ix.clone()], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert_eq!(custom_error(&results[0]), Some(WorldError::FriendlyFire as u32));
+    let health = load_player(&store, &target_pda).health;
+
+    // Enable friendly fire (team count unchanged, so allowed with players in the world)
+    processor.advance_slot();
+    let configure = configure_teams_instruction(world_pda, &world_authority, 1, true, 0);
+    let tx = create_sanitized_transaction(&world_authority, vec![configure], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+
+    processor.advance_slot();
+    let tx = create_sanitized_transaction(&attacker, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "Attack failed: {:?}", results[0].error);
+    assert_eq!(load_player(&store, &target_pda).health, health - 10);
+
+    // Team kills never score
+    processor.advance_slot();
+    let ix = attack_instruction(world_pda, attacker_pda, target_pda, &attacker, u16::MAX);
+    let tx = create_sanitized_transaction(&attacker, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+    assert!(!load_player(&store, &target_pda).is_alive());
+    assert_eq!(load_scores(&store, &world_pda).scores, [0; 4]);
+}
+
+/// Test 6: Killing an enemy adds to the attacker's team score
+#[test]
+fn test_enemy_kill_scores_for_team() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_team_world(&mut processor, &store, 2, false, 0);
+
+    let (attacker_pda, attacker) = join_team_player(&mut processor, &store, world_pda, "Red");
+    processor.advance_slot();
+    let (target_pda, _) = join_team_player(&mut processor, &store, world_pda, "Blue");
+    processor.advance_slot();
+    assert_ne!(load_player(&store, &attacker_pda).team, TEAM_NONE);
+
+    // Damage without a kill does not score
+    let ix = attack_instruction(world_pda, attacker_pda, target_pda, &attacker, 10);
+    let tx = create_sanitized_transaction(&attacker, vec![ix], processor.current_blockhash());
+    assert!(processor.process_transactions(&[tx])[0].success);
+    assert_eq!(load_scores(&store, &world_pda).scores, [0; 4]);
+
+    processor.advance_slot();
+    let ix = attack_instruction(world_pda, attacker_pda, target_pda, &attacker, u16::MAX);
+    let tx = create_sanitized_transaction(&attacker, vec![ix], processor.current_blockhash());
+    let results = processor.process_transactions(&[tx]);
+    assert!(results[0].success, "Attack failed: {:?}", results[0].error);
+    assert!(!load_player(&store, &target_pda).is_alive());
+    assert_eq!(load_scores(&store, &world_pda).scores, [1, 0, 0, 0]);
+}
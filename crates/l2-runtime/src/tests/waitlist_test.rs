@@ -0,0 +1,276 @@
+//! Waitlist Integration Tests
+//!
+//! Tests world waitlists end to end:
+//! - JoinWaitlist queues players in order once a world is full
+//! - The waitlist driver admits the head of the line when a slot opens
+//! - Reserved slots are only filled by AdmitVip
+
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::{custom_error, process};
+use super::join_world_test::{
+    create_join_world_instruction, join_player, make_player_name, make_world_name,
+    setup_world_account,
+};
+use crate::{
+    account_store::AccountStore, processor::L2Processor, waitlist::WaitlistDriver,
+    TransactionResult,
+};
+
+use world_program::{
+    error::WorldError,
+    instruction::WorldInstruction,
+    state::{WorldConfig, WorldPlayer, WorldWaitlist},
+};
+
+/// Set up a world, returning (world PDA, world authority)
+fn setup_world(store: &AccountStore, max_players: u16) -> (Pubkey, Keypair) {
+    let authority = Keypair::new();
+    let world_name = make_world_name("Crowded");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(store, world_pda, authority.pubkey(), world_name, 100, 100, max_players);
+    (world_pda, authority)
+}
+
+fn waitlist_pda(world_pda: &Pubkey) -> Pubkey {
+    WorldWaitlist::derive_pda(world_pda, &world_program::id()).0
+}
+
+fn player_pda(world_pda: &Pubkey, wallet: &Keypair) -> Pubkey {
+    WorldPlayer::derive_pda(world_pda, &wallet.pubkey(), &world_program::id()).0
+}
+
+fn join_waitlist(world_pda: Pubkey, player: &Keypair) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::JoinWaitlist {
+            name: make_player_name("Hopeful"),
+            password: [0; 32],
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(waitlist_pda(&world_pda), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn leave_waitlist(world_pda: Pubkey, player: &Keypair) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::LeaveWaitlist,
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(waitlist_pda(&world_pda), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+        ],
+    )
+}
+
+fn leave_world(world_pda: Pubkey, player: &Keypair) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::LeaveWorld,
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(player_pda(&world_pda, player), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(player.pubkey(), false),
+        ],
+    )
+}
+
+fn admit_vip(world_pda: Pubkey, vip: &Keypair, authority: &Keypair) -> Instruction {
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::AdmitVip {
+            name: make_player_name("Celebrity"),
+        },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new(player_pda(&world_pda, vip), false),
+            AccountMeta::new_readonly(vip.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+fn load_world(store: &AccountStore, world_pda: &Pubkey) -> WorldConfig {
+    WorldConfig::try_from_slice(store.get_account(world_pda).unwrap().data()).unwrap()
+}
+
+fn load_waitlist(store: &AccountStore, world_pda: &Pubkey) -> WorldWaitlist {
+    WorldWaitlist::try_from_slice(store.get_account(&waitlist_pda(world_pda)).unwrap().data()).unwrap()
+}
+
+/// Run one driver tick through the processor, returning the transaction results
+fn tick(processor: &mut L2Processor, store: &AccountStore, driver: &WaitlistDriver) -> Vec<TransactionResult> {
+    let txs = driver.build_transactions(processor.current_slot(), processor.current_blockhash(), store);
+    let results = processor.process_transactions(&txs);
+    processor.advance_slot();
+    results
+}
+
+/// Test 1: A full world turns players away, who then queue in order
+#[test]
+fn test_waitlist_queues_in_order() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_world(&store, 1);
+    join_player(&mut processor, &store, world_pda, &Keypair::new(), "Occupant");
+
+    let late = Keypair::new();
+    let join = create_join_world_instruction(
+        world_pda,
+        player_pda(&world_pda, &late),
+        &late,
+        make_player_name("Late"),
+    );
+    assert_eq!(custom_error(&process(&mut processor, &late, join)), Some(1)); // WorldFull
+
+    let players: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for player in &players {
+        let result = process(&mut processor, player, join_waitlist(world_pda, player));
+        assert!(result.success, "JoinWaitlist failed: {:?}", result.error);
+    }
+
+    let waitlist = load_waitlist(&store, &world_pda);
+    for (index, player) in players.iter().enumerate() {
+        assert_eq!(waitlist.position_of(&player.pubkey()), Some(index + 1));
+    }
+    assert_eq!(load_world(&store, &world_pda).waiting_count, 3);
+
+    // Queuing twice is rejected
+    let result = process(&mut processor, &players[0], join_waitlist(world_pda, &players[0]));
+    assert_eq!(custom_error(&result), Some(WorldError::AlreadyWaiting as u32));
+}
+
+/// Test 2: When a player leaves, the driver admits the head of the line and
+/// nobody can skip the line with JoinWorld
+#[test]
+fn test_driver_admits_head_when_slot_opens() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let driver = WaitlistDriver::new(Keypair::new());
+    let (world_pda, _) = setup_world(&store, 1);
+
+    let occupant = Keypair::new();
+    join_player(&mut processor, &store, world_pda, &occupant, "Occupant");
+    let first = Keypair::new();
+    let second = Keypair::new();
+    assert!(process(&mut processor, &first, join_waitlist(world_pda, &first)).success);
+    assert!(process(&mut processor, &second, join_waitlist(world_pda, &second)).success);
+
+    // Nothing to do while the world is full
+    assert!(tick(&mut processor, &store, &driver).is_empty());
+
+    assert!(process(&mut processor, &occupant, leave_world(world_pda, &occupant)).success);
+
+    // The open slot belongs to the waitlist, not a newcomer
+    let newcomer = Keypair::new();
+    let join = create_join_world_instruction(
+        world_pda,
+        player_pda(&world_pda, &newcomer),
+        &newcomer,
+        make_player_name("Newcomer"),
+    );
+    assert_eq!(custom_error(&process(&mut processor, &newcomer, join)), Some(1)); // WorldFull
+
+    let results = tick(&mut processor, &store, &driver);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].success, "AdmitFromWaitlist failed: {:?}", results[0].error);
+    assert!(results[0].logs.iter().any(|log| log.contains("Waitlist admitted")));
+
+    let admitted = WorldPlayer::try_from_slice(
+        store.get_account(&player_pda(&world_pda, &first)).unwrap().data(),
+    )
+    .unwrap();
+    assert_eq!(admitted.authority, first.pubkey());
+    assert_eq!(admitted.name_str(), "Hopeful");
+
+    let world = load_world(&store, &world_pda);
+    assert_eq!(world.player_count, 1);
+    assert_eq!(world.waiting_count, 1);
+    assert_eq!(load_waitlist(&store, &world_pda).position_of(&second.pubkey()), Some(1));
+}
+
+/// Test 3: Leaving keeps everyone else's order
+#[test]
+fn test_leave_waitlist_preserves_order() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let (world_pda, _) = setup_world(&store, 1);
+    join_player(&mut processor, &store, world_pda, &Keypair::new(), "Occupant");
+
+    let players: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for player in &players {
+        assert!(process(&mut processor, player, join_waitlist(world_pda, player)).success);
+    }
+
+    let result = process(&mut processor, &players[1], leave_waitlist(world_pda, &players[1]));
+    assert!(result.success, "LeaveWaitlist failed: {:?}", result.error);
+
+    let waitlist = load_waitlist(&store, &world_pda);
+    assert_eq!(waitlist.position_of(&players[0].pubkey()), Some(1));
+    assert_eq!(waitlist.position_of(&players[1].pubkey()), None);
+    assert_eq!(waitlist.position_of(&players[2].pubkey()), Some(2));
+    assert_eq!(load_world(&store, &world_pda).waiting_count, 2);
+
+    let result = process(&mut processor, &players[1], leave_waitlist(world_pda, &players[1]));
+    assert_eq!(custom_error(&result), Some(WorldError::NotOnWaitlist as u32));
+}
+
+/// Test 4: Reserved slots stay closed to the waitlist and open to VIPs
+#[test]
+fn test_reserved_slots_for_vips() {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let driver = WaitlistDriver::new(Keypair::new());
+    let (world_pda, authority) = setup_world(&store, 2);
+
+    let reserve = Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::SetReservedSlots { reserved_slots: 1 },
+        vec![
+            AccountMeta::new(world_pda, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+        ],
+    );
+    assert!(process(&mut processor, &authority, reserve).success);
+
+    // One regular slot: the second player has to wait
+    join_player(&mut processor, &store, world_pda, &Keypair::new(), "Regular");
+    let waiting = Keypair::new();
+    assert!(process(&mut processor, &waiting, join_waitlist(world_pda, &waiting)).success);
+    assert!(tick(&mut processor, &store, &driver).is_empty());
+
+    // Only the world authority can admit VIPs
+    let vip = Keypair::new();
+    let stranger = Keypair::new();
+    let result = process(&mut processor, &stranger, admit_vip(world_pda, &vip, &stranger));
+    assert_eq!(custom_error(&result), Some(2)); // InvalidAuthority
+
+    let result = process(&mut processor, &authority, admit_vip(world_pda, &vip, &authority));
+    assert!(result.success, "AdmitVip failed: {:?}", result.error);
+    assert!(result.logs.iter().any(|log| log.contains("VIP admitted")));
+
+    let world = load_world(&store, &world_pda);
+    assert_eq!(world.player_count, 2);
+    assert_eq!(world.waiting_count, 1);
+
+    // Now truly full, even for VIPs
+    let result = process(&mut processor, &authority, admit_vip(world_pda, &Keypair::new(), &authority));
+    assert_eq!(custom_error(&result), Some(1)); // WorldFull
+}
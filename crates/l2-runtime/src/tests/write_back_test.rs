@@ -0,0 +1,94 @@
+//! Write-Back Tests
+//!
+//! Tests that a transaction only persists and reports what it changed:
+//! - Readonly accounts, sysvars and programs are never written back
+//! - Writable accounts the program left alone aren't either
+//! - Wallets the loader made up for signing don't end up in the store
+
+use std::sync::Arc;
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::join_world_test::{
+    create_sanitized_transaction, join_player, make_world_name, setup_world_account,
+};
+use crate::{account_store::AccountStore, processor::L2Processor, TransactionResult};
+
+use world_program::{
+    instruction::WorldInstruction,
+    state::{MovementInput3D, WorldConfig},
+};
+
+fn setup() -> (L2Processor, Arc<AccountStore>, Pubkey, Keypair, Pubkey) {
+    let store = Arc::new(AccountStore::new());
+    let mut processor = L2Processor::new(store.clone());
+    let authority = Keypair::new();
+    let world_name = make_world_name("Diffs");
+    let (world_pda, _) = WorldConfig::derive_pda(&world_name, &world_program::id());
+    setup_world_account(&store, world_pda, authority.pubkey(), world_name, 100, 100, 10);
+
+    let wallet = Keypair::new();
+    let player_pda = join_player(&mut processor, &store, world_pda, &wallet, "Mover");
+    processor.advance_slot();
+    (processor, store, world_pda, wallet, player_pda)
+}
+
+/// MovePlayer3D, optionally (needlessly) marking the world writable
+fn move_3d(world: AccountMeta, player_pda: Pubkey, wallet: &Keypair) -> Instruction {
+    let input = MovementInput3D {
+        move_z: 127,
+        ..Default::default()
+    };
+    Instruction::new_with_borsh(
+        world_program::id(),
+        &WorldInstruction::MovePlayer3D { input },
+        vec![
+            world,
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+    )
+}
+
+fn process(processor: &mut L2Processor, wallet: &Keypair, ix: Instruction) -> TransactionResult {
+    let tx = create_sanitized_transaction(wallet, vec![ix], processor.current_blockhash());
+    let result = processor.process_transactions(&[tx]).remove(0);
+    assert!(result.success, "MovePlayer3D failed: {:?}", result.error);
+    result
+}
+
+fn modified_keys(result: &TransactionResult) -> Vec<Pubkey> {
+    result.modified_accounts.iter().map(|(pubkey, _)| *pubkey).collect()
+}
+
+/// Test 1: A move reports and stores only the player account
+#[test]
+fn test_only_changed_accounts_are_written() {
+    let (mut processor, store, world_pda, wallet, player_pda) = setup();
+    let (_, world_slot) = store.get_account_with_slot(&world_pda).unwrap();
+
+    let ix = move_3d(AccountMeta::new_readonly(world_pda, false), player_pda, &wallet);
+    let result = process(&mut processor, &wallet, ix);
+    assert_eq!(modified_keys(&result), vec![player_pda]);
+
+    let (_, player_slot) = store.get_account_with_slot(&player_pda).unwrap();
+    assert_eq!(player_slot, processor.current_slot());
+    assert_eq!(store.get_account_with_slot(&world_pda).unwrap().1, world_slot);
+    assert!(!store.account_exists(&wallet.pubkey()));
+}
+
+/// Test 2: A writable account the program only read isn't written back
+#[test]
+fn test_unchanged_writable_account_is_skipped() {
+    let (mut processor, store, world_pda, wallet, player_pda) = setup();
+    let world_before = store.get_account_with_slot(&world_pda).unwrap();
+
+    let ix = move_3d(AccountMeta::new(world_pda, false), player_pda, &wallet);
+    let result = process(&mut processor, &wallet, ix);
+    assert_eq!(modified_keys(&result), vec![player_pda]);
+    assert_eq!(store.get_account_with_slot(&world_pda).unwrap(), world_before);
+}
@@ -0,0 +1,166 @@
+//! Waitlist Driver
+//!
+//! Leader-side crank for world waitlists. Every tick it finds worlds with
+//! players waiting and open regular slots, and submits one `AdmitFromWaitlist`
+//! per slot (in one transaction per world, so each admission sees the last).
+//! Slots open up when players leave or are kicked, and the program logs every
+//! admission.
+
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::ReadableAccount,
+    clock::Slot,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{SanitizedTransaction, Transaction},
+};
+use world_program::{
+    instruction::WorldInstruction,
+    state::{NameClaim, ReservedNames, TeamScores, WaitlistEntry, WorldConfig, WorldPlayer, WorldWaitlist},
+};
+
+use crate::{account_store::AccountStore, block_producer::TickHook};
+
+/// Leader-side crank that admits waiting players into open slots
+pub struct WaitlistDriver {
+    /// Signs and pays for admission transactions (admission is permissionless)
+    payer: Keypair,
+}
+
+impl WaitlistDriver {
+    /// Create a driver
+    pub fn new(payer: Keypair) -> Self {
+        Self { payer }
+    }
+
+    /// Heads of the line that fit into the world's open regular slots
+    pub fn admissions<'a>(world: &WorldConfig, waitlist: &'a WorldWaitlist) -> &'a [WaitlistEntry] {
+        let open = (world.max_players as usize)
+            .saturating_sub(world.player_count as usize + world.reserved_slots as usize);
+        let waiting = waitlist.waiting();
+        &waiting[..open.min(waiting.len())]
+    }
+
+    /// Build this tick's admission transactions
+    pub fn build_transactions(
+        &self,
+        _slot: Slot,
+        blockhash: Hash,
+        store: &AccountStore,
+    ) -> Vec<SanitizedTransaction> {
+        let program_id = world_program::id();
+        let mut txs = Vec::new();
+
+        for (waitlist_key, account) in store.get_program_accounts(&program_id) {
+            if account.data().len() != WorldWaitlist::LEN {
+                continue;
+            }
+            let Ok(waitlist) = WorldWaitlist::try_from_slice(account.data()) else {
+                continue;
+            };
+            let Some(world) = store
+                .get_account(&waitlist.world)
+                .and_then(|account| WorldConfig::try_from_slice(account.data()).ok())
+            else {
+                continue;
+            };
+
+            let instructions: Vec<Instruction> = Self::admissions(&world, &waitlist)
+                .iter()
+                .map(|entry| {
+                    let (player_pda, _) = WorldPlayer::derive_pda(&waitlist.world, &entry.wallet, &program_id);
+                    let mut accounts = vec![
+                        AccountMeta::new(waitlist.world, false),
+                        AccountMeta::new(waitlist_key, false),
+                        AccountMeta::new(player_pda, false),
+                        AccountMeta::new(self.payer.pubkey(), true),
+                        AccountMeta::new_readonly(system_program::id(), false),
+                    ];
+                    if world.teams_enabled() {
+                        let (scores_pda, _) = TeamScores::derive_pda(&waitlist.world, &program_id);
+                        accounts.push(AccountMeta::new(scores_pda, false));
+                    }
+                    if world.unique_names {
+                        let name = NameClaim::normalize(&entry.name);
+                        let (claim_pda, _) = NameClaim::derive_pda(&waitlist.world, &name, &program_id);
+                        accounts.push(AccountMeta::new(claim_pda, false));
+                    }
+                    if world.reserved_name_count > 0 {
+                        let (reserved_pda, _) = ReservedNames::derive_pda(&waitlist.world, &program_id);
+                        accounts.push(AccountMeta::new_readonly(reserved_pda, false));
+                    }
+                    Instruction::new_with_borsh(program_id, &WorldInstruction::AdmitFromWaitlist, accounts)
+                })
+                .collect();
+            if instructions.is_empty() {
+                continue;
+            }
+
+            let message = Message::new(&instructions, Some(&self.payer.pubkey()));
+            let tx = Transaction::new(&[&self.payer], message, blockhash);
+            txs.push(
+                SanitizedTransaction::try_from_legacy_transaction(tx, &HashSet::new())
+                    .expect("waitlist transaction is well-formed"),
+            );
+        }
+
+        txs
+    }
+}
+
+impl TickHook for WaitlistDriver {
+    fn on_tick(&mut self, slot: Slot, blockhash: Hash, store: &AccountStore) -> Vec<SanitizedTransaction> {
+        self.build_transactions(slot, blockhash, store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn waitlist(count: usize) -> WorldWaitlist {
+        let mut waitlist = WorldWaitlist::default();
+        for _ in 0..count {
+            waitlist.push(Pubkey::new_unique(), [0; 16]).unwrap();
+        }
+        waitlist
+    }
+
+    #[test]
+    fn test_admissions_fill_open_slots() {
+        let world = WorldConfig {
+            max_players: 10,
+            player_count: 7,
+            ..Default::default()
+        };
+        let waitlist = waitlist(5);
+
+        // Three open slots, oldest entries first
+        assert_eq!(WaitlistDriver::admissions(&world, &waitlist), &waitlist.waiting()[..3]);
+        assert_eq!(WaitlistDriver::admissions(&world, &self::waitlist(2)).len(), 2);
+    }
+
+    #[test]
+    fn test_admissions_skip_reserved_slots() {
+        let world = WorldConfig {
+            max_players: 10,
+            player_count: 8,
+            reserved_slots: 2,
+            ..Default::default()
+        };
+        assert!(WaitlistDriver::admissions(&world, &waitlist(3)).is_empty());
+
+        // More players than regular slots (VIPs in reserved ones)
+        let crowded = WorldConfig {
+            player_count: 9,
+            ..world
+        };
+        assert!(WaitlistDriver::admissions(&crowded, &waitlist(3)).is_empty());
+    }
+}
@@ -3,8 +3,11 @@
 //! Provides HTTP endpoint for JSON-RPC methods.
 
 use crate::methods::{
-    handle_get_account_info, handle_get_health, handle_get_latest_blockhash, handle_get_slot,
-    handle_send_transaction, GetAccountInfoRequest, RpcContext, RpcError, SendTransactionRequest,
+    handle_get_account_info, handle_get_entities_in_box, handle_get_entities_in_radius,
+    handle_get_health, handle_get_latest_blockhash, handle_get_slot, handle_get_state_proof,
+    handle_get_state_root, handle_send_transaction,
+    GetAccountInfoRequest, GetEntitiesInBoxRequest, GetEntitiesInRadiusRequest, RpcContext,
+    RpcError, SendTransactionRequest,
 };
 use axum::{
     extract::State,
@@ -157,6 +160,61 @@ fn dispatch_method(ctx: &RpcContext, method: &str, params: Value) -> Result<Valu
             Ok(serde_json::to_value(response).unwrap())
         }
 
+        "getStateRoot" => {
+            let response = handle_get_state_root(ctx)?;
+            Ok(serde_json::to_value(response).unwrap())
+        }
+
+        "getStateProof" => {
+            let params: Vec<Value> = serde_json::from_value(params).unwrap_or_default();
+            let pubkey = params
+                .first()
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| RpcError::InvalidParams("Missing pubkey".to_string()))?;
+
+            let response = handle_get_state_proof(ctx, pubkey)?;
+            Ok(serde_json::to_value(response).unwrap())
+        }
+
+        "getEntitiesInRadius" => {
+            // Params: [world, x, z, radius] (fixed-point units)
+            let params: Vec<Value> = serde_json::from_value(params).unwrap_or_default();
+            let world = params
+                .first()
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| RpcError::InvalidParams("Missing world".to_string()))?;
+
+            let request = GetEntitiesInRadiusRequest {
+                world: world.to_string(),
+                x: int_param(&params, 1, "x")?,
+                z: int_param(&params, 2, "z")?,
+                radius: int_param(&params, 3, "radius")?,
+            };
+
+            let response = handle_get_entities_in_radius(ctx, request)?;
+            Ok(serde_json::to_value(response).unwrap())
+        }
+
+        "getEntitiesInBox" => {
+            // Params: [world, minX, minZ, maxX, maxZ] (fixed-point units)
+            let params: Vec<Value> = serde_json::from_value(params).unwrap_or_default();
+            let world = params
+                .first()
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| RpcError::InvalidParams("Missing world".to_string()))?;
+
+            let request = GetEntitiesInBoxRequest {
+                world: world.to_string(),
+                min_x: int_param(&params, 1, "minX")?,
+                min_z: int_param(&params, 2, "minZ")?,
+                max_x: int_param(&params, 3, "maxX")?,
+                max_z: int_param(&params, 4, "maxZ")?,
+            };
+
+            let response = handle_get_entities_in_box(ctx, request)?;
+            Ok(serde_json::to_value(response).unwrap())
+        }
+
         "getSlot" => {
             let slot = handle_get_slot(ctx)?;
             Ok(json!(slot))
@@ -177,6 +235,15 @@ fn dispatch_method(ctx: &RpcContext, method: &str, params: Value) -> Result<Valu
     }
 }
 
+/// Read a positional i32 parameter
+fn int_param(params: &[Value], index: usize, name: &str) -> Result<i32, RpcError> {
+    params
+        .get(index)
+        .and_then(|v| v.as_i64())
+        .and_then(|v| i32::try_from(v).ok())
+        .ok_or_else(|| RpcError::InvalidParams(format!("Missing or invalid {}", name)))
+}
+
 /// Map error to JSON-RPC error code
 fn error_code(error: &RpcError) -> i32 {
     match error {
@@ -2,6 +2,8 @@
 //!
 //! Provides Solana-compatible RPC interface:
 //! - HTTP JSON-RPC: sendTransaction, getAccountInfo, getLatestBlockhash, etc.
+//! - Spatial queries: getEntitiesInRadius, getEntitiesInBox
+//! - State commitments: getStateRoot, getStateProof
 //! - WebSocket: accountSubscribe, accountUnsubscribe
 
 pub mod http_server;
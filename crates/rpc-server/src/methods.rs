@@ -3,7 +3,7 @@
 //! Implements Solana-compatible RPC methods for the L2.
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use l2_runtime::{AccountStore, TransactionSender};
+use l2_runtime::{AccountStore, EntityKind, SpatialEntity, SpatialIndex, TransactionSender};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     clock::Slot,
@@ -20,6 +20,8 @@ pub struct RpcContext {
     pub tx_sender: TransactionSender,
     pub current_slot: Arc<RwLock<Slot>>,
     pub current_blockhash: Arc<RwLock<Hash>>,
+    pub current_state_root: Arc<RwLock<Hash>>,
+    pub spatial_index: Arc<SpatialIndex>,
 }
 
 // ============ Request/Response Types ============
@@ -72,6 +74,80 @@ pub struct GetLatestBlockhashResponse {
     pub value: BlockhashInfo,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetStateRootResponse {
+    pub context: RpcContext_,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StateProofInfo {
+    /// Sealed block the proof is for
+    pub slot: Slot,
+    /// Root the proof hashes up to (the state root published for `slot`)
+    pub root: String,
+    #[serde(rename = "accountHash")]
+    pub account_hash: String,
+    /// Sibling hashes from the root down to the account's leaf
+    pub siblings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetStateProofResponse {
+    pub context: RpcContext_,
+    pub value: Option<StateProofInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetEntitiesInRadiusRequest {
+    pub world: String,
+    pub x: i32,
+    pub z: i32,
+    pub radius: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetEntitiesInBoxRequest {
+    pub world: String,
+    pub min_x: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_z: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntityInfo {
+    pub pubkey: String,
+    pub kind: String, // "player" | "npc"
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub health: u16,
+}
+
+impl From<SpatialEntity> for EntityInfo {
+    fn from(entity: SpatialEntity) -> Self {
+        Self {
+            pubkey: entity.pubkey.to_string(),
+            kind: match entity.kind {
+                EntityKind::Player => "player",
+                EntityKind::Npc => "npc",
+            }
+            .to_string(),
+            x: entity.position_x,
+            y: entity.position_y,
+            z: entity.position_z,
+            health: entity.health,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetEntitiesResponse {
+    pub context: RpcContext_,
+    pub value: Vec<EntityInfo>,
+}
+
 // ============ RPC Handlers ============
 
 /// Handle sendTransaction RPC method
@@ -104,7 +180,7 @@ pub fn handle_send_transaction(
     tracing::info!("RPC: Received transaction {}", signature);
     ctx.tx_sender
         .send(sanitized)
-        .map_err(|e| RpcError::InternalError(e))?;
+        .map_err(RpcError::InternalError)?;
     tracing::info!("RPC: Transaction {} queued for processing", signature);
 
     Ok(signature.to_string())
@@ -158,6 +234,89 @@ pub fn handle_get_latest_blockhash(ctx: &RpcContext) -> Result<GetLatestBlockhas
     })
 }
 
+/// Handle getStateRoot RPC method (Merkle root of the last sealed block)
+pub fn handle_get_state_root(ctx: &RpcContext) -> Result<GetStateRootResponse, RpcError> {
+    let slot = *ctx.current_slot.read();
+    let root = *ctx.current_state_root.read();
+
+    Ok(GetStateRootResponse {
+        context: RpcContext_ { slot },
+        value: root.to_string(),
+    })
+}
+
+/// Handle getStateProof RPC method (against the root of the last sealed block)
+pub fn handle_get_state_proof(ctx: &RpcContext, pubkey: &str) -> Result<GetStateProofResponse, RpcError> {
+    let pubkey = Pubkey::from_str(pubkey)
+        .map_err(|_| RpcError::InvalidParams("Invalid pubkey".to_string()))?;
+
+    let slot = *ctx.current_slot.read();
+    let value = ctx
+        .account_store
+        .state_proof(&pubkey)
+        .map(|(proof_slot, root, proof)| StateProofInfo {
+            slot: proof_slot,
+            root: root.to_string(),
+            account_hash: proof.account_hash.to_string(),
+            siblings: proof.siblings.iter().map(Hash::to_string).collect(),
+        });
+
+    Ok(GetStateProofResponse {
+        context: RpcContext_ { slot },
+        value,
+    })
+}
+
+/// Handle getEntitiesInRadius RPC method
+pub fn handle_get_entities_in_radius(
+    ctx: &RpcContext,
+    params: GetEntitiesInRadiusRequest,
+) -> Result<GetEntitiesResponse, RpcError> {
+    let world = Pubkey::from_str(&params.world)
+        .map_err(|_| RpcError::InvalidParams("Invalid world pubkey".to_string()))?;
+    if params.radius < 0 {
+        return Err(RpcError::InvalidParams("Radius must be non-negative".to_string()));
+    }
+
+    let slot = *ctx.current_slot.read();
+    let value = ctx
+        .spatial_index
+        .query_radius(&world, params.x, params.z, params.radius)
+        .into_iter()
+        .map(EntityInfo::from)
+        .collect();
+
+    Ok(GetEntitiesResponse {
+        context: RpcContext_ { slot },
+        value,
+    })
+}
+
+/// Handle getEntitiesInBox RPC method
+pub fn handle_get_entities_in_box(
+    ctx: &RpcContext,
+    params: GetEntitiesInBoxRequest,
+) -> Result<GetEntitiesResponse, RpcError> {
+    let world = Pubkey::from_str(&params.world)
+        .map_err(|_| RpcError::InvalidParams("Invalid world pubkey".to_string()))?;
+    if params.min_x > params.max_x || params.min_z > params.max_z {
+        return Err(RpcError::InvalidParams("Box min must not exceed max".to_string()));
+    }
+
+    let slot = *ctx.current_slot.read();
+    let value = ctx
+        .spatial_index
+        .query_box(&world, params.min_x, params.min_z, params.max_x, params.max_z)
+        .into_iter()
+        .map(EntityInfo::from)
+        .collect();
+
+    Ok(GetEntitiesResponse {
+        context: RpcContext_ { slot },
+        value,
+    })
+}
+
 /// Handle getSlot RPC method
 pub fn handle_get_slot(ctx: &RpcContext) -> Result<Slot, RpcError> {
     Ok(*ctx.current_slot.read())
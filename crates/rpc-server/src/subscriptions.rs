@@ -4,10 +4,7 @@
 
 use dashmap::DashMap;
 use solana_sdk::{account::AccountSharedData, pubkey::Pubkey};
-use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
-};
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::broadcast;
 
 /// Subscription ID
@@ -2,13 +2,10 @@
 //!
 //! Provides WebSocket endpoint for subscriptions.
 
-use crate::{
-    methods::RpcContext,
-    subscriptions::{AccountNotification, SubscriptionManager},
-};
+use crate::subscriptions::{AccountNotification, SubscriptionManager};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use futures::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::{json, Value};
 use solana_sdk::{account::ReadableAccount, pubkey::Pubkey};
 use std::{str::FromStr, sync::Arc};
@@ -27,17 +24,13 @@ pub struct WsJsonRpcRequest {
 
 /// WebSocket Server
 pub struct WebSocketServer {
-    context: Arc<RpcContext>,
     subscription_manager: Arc<SubscriptionManager>,
 }
 
 impl WebSocketServer {
     /// Create a new WebSocket server
-    pub fn new(context: Arc<RpcContext>, subscription_manager: Arc<SubscriptionManager>) -> Self {
-        Self {
-            context,
-            subscription_manager,
-        }
+    pub fn new(subscription_manager: Arc<SubscriptionManager>) -> Self {
+        Self { subscription_manager }
     }
 
     /// Run the WebSocket server
@@ -45,15 +38,13 @@ impl WebSocketServer {
         let listener = TcpListener::bind(addr).await?;
         tracing::info!("WebSocket server listening on {}", addr);
 
-        let context = self.context;
         let subscription_manager = self.subscription_manager;
 
         while let Ok((stream, peer_addr)) = listener.accept().await {
-            let ctx = context.clone();
             let sub_mgr = subscription_manager.clone();
 
             tokio::spawn(async move {
-                if let Err(e) = handle_connection(stream, ctx, sub_mgr).await {
+                if let Err(e) = handle_connection(stream, sub_mgr).await {
                     tracing::warn!("WebSocket connection error from {}: {}", peer_addr, e);
                 }
             });
@@ -66,7 +57,6 @@ impl WebSocketServer {
 /// Handle a single WebSocket connection
 async fn handle_connection(
     stream: TcpStream,
-    context: Arc<RpcContext>,
     subscription_manager: Arc<SubscriptionManager>,
 ) -> anyhow::Result<()> {
     let ws_stream = accept_async(stream).await?;
@@ -84,14 +74,7 @@ async fn handle_connection(
                 Err(_) => continue,
             };
 
-            let response = handle_ws_method(
-                &context,
-                &subscription_manager,
-                &request,
-                &mut active_subscriptions,
-                &mut ws_sender,
-            )
-            .await;
+            let response = handle_ws_method(&subscription_manager, &request, &mut active_subscriptions);
 
             let response_json = serde_json::to_string(&response)?;
             ws_sender.send(Message::Text(response_json)).await?;
@@ -107,16 +90,11 @@ async fn handle_connection(
 }
 
 /// Handle WebSocket JSON-RPC method
-async fn handle_ws_method<S>(
-    context: &RpcContext,
+fn handle_ws_method(
     subscription_manager: &SubscriptionManager,
     request: &WsJsonRpcRequest,
     active_subscriptions: &mut Vec<u64>,
-    ws_sender: &mut S,
-) -> Value
-where
-    S: SinkExt<Message> + Unpin,
-{
+) -> Value {
     match request.method.as_str() {
         "accountSubscribe" => {
             let params: Vec<Value> = serde_json::from_value(request.params.clone()).unwrap_or_default();
@@ -8,24 +8,50 @@ use anyhow::Result;
 use clap::{Parser, ValueEnum};
 use l2_consensus::{LeaderNodeBuilder, ValidatorNodeBuilder};
 use l2_runtime::{
-    AccountStore, AccountStorePersistence, BlockProducer, BlockProducerConfig,
-    ChainMetadata, L2Processor, PersistentStore,
+    AccountStore, AccountStorePersistence, BlockProducer, BlockProducerConfig, BlockStore,
+    BlockStoreConfig, BlockWriter, ChainMetadata, CheckpointWriter, ChaseBehavior, GameModeDriver, L2Processor,
+    Matchmaker, MatchmakerConfig, IdleEvictor, MovementExtrapolator, NpcDriver, NpcSpawn, PersistentStore,
+    SlotDelta, SnapshotArchive, SnapshotConfig, SnapshotWriter, SpatialIndex, WaitlistDriver, migrate_accounts,
+    read_snapshot, restore_snapshot,
 };
 use rpc_server::{
     methods::RpcContext, HttpRpcServer, SubscriptionManager, WebSocketServer,
 };
-use solana_sdk::pubkey::Pubkey;
-use std::path::PathBuf;
-use std::sync::Arc;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+};
+use std::path::{Path, PathBuf};
+use std::sync::{atomic::Ordering, Arc};
 use parking_lot::RwLock;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
-mod config;
-
+use borsh::BorshDeserialize;
 use solana_sdk::account::{Account, AccountSharedData, ReadableAccount};
 
+/// Load the world authority keypair, generating it on first run
+fn load_authority_keypair(path: &Path) -> Result<Keypair> {
+    if path.exists() {
+        return read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read authority keypair {:?}: {}", path, e));
+    }
+
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, path)
+        .map_err(|e| anyhow::anyhow!("Failed to write authority keypair {:?}: {}", path, e))?;
+    tracing::info!("Generated world authority keypair: {:?}", path);
+    Ok(keypair)
+}
+
+/// Derive the default world PDA
+fn default_world_pda() -> Pubkey {
+    let mut name_bytes = [0u8; 32];
+    name_bytes[..7].copy_from_slice(b"default");
+    Pubkey::find_program_address(&[b"world", &name_bytes], &world_program::id()).0
+}
+
 /// Create the default world account if it doesn't exist
-fn create_default_world(account_store: &AccountStore, slot: u64) {
+fn create_default_world(account_store: &AccountStore, authority: &Pubkey, slot: u64) {
     // World program ID
     let world_program_id = world_program::id();
 
@@ -48,9 +74,15 @@ fn create_default_world(account_store: &AccountStore, slot: u64) {
 
     // Create world config using Borsh serialization
     // WorldConfig layout: name[32] + authority[32] + width[4] + depth[4] + max_players[2] + player_count[2] + tick_rate[1] + bump[1] + l1_game[32] + init_ts[8]
-    let mut data = Vec::with_capacity(118);
+    //                    + team_count[1] + friendly_fire[1] + max_team_size[2]
+    //                    + visibility[1] + password_salt[16] + password_hash[32]
+    //                    + reserved_slots[2] + waiting_count[2] + idle_timeout_slots[8]
+    //                    + unique_names[1] + name_charset[1] + reserved_name_count[2]
+    //                    + dash_speed[2] + dash_cooldown_slots[8] + max_rewind_slots[8]
+    //                    + max_extrapolation_slots[8]
+    let mut data = Vec::with_capacity(world_program::WorldConfig::LEN);
     data.extend_from_slice(&name_bytes); // name: [u8; 32]
-    data.extend_from_slice(&[0u8; 32]); // authority: Pubkey (default)
+    data.extend_from_slice(authority.as_ref()); // authority: Pubkey
     data.extend_from_slice(&100u32.to_le_bytes()); // width: u32
     data.extend_from_slice(&100u32.to_le_bytes()); // depth: u32
     data.extend_from_slice(&100u16.to_le_bytes()); // max_players: u16
@@ -63,6 +95,22 @@ fn create_default_world(account_store: &AccountStore, slot: u64) {
         .unwrap()
         .as_secs() as i64;
     data.extend_from_slice(&init_ts.to_le_bytes()); // init_ts: i64
+    data.push(0); // team_count: u8 (teams disabled)
+    data.push(0); // friendly_fire: bool
+    data.extend_from_slice(&0u16.to_le_bytes()); // max_team_size: u16
+    data.push(world_program::constants::WORLD_VISIBILITY_PUBLIC); // visibility: u8
+    data.extend_from_slice(&[0u8; 16]); // password_salt: [u8; 16]
+    data.extend_from_slice(&[0u8; 32]); // password_hash: [u8; 32]
+    data.extend_from_slice(&0u16.to_le_bytes()); // reserved_slots: u16
+    data.extend_from_slice(&0u16.to_le_bytes()); // waiting_count: u16
+    data.extend_from_slice(&world_program::constants::DEFAULT_IDLE_TIMEOUT_SLOTS.to_le_bytes()); // idle_timeout_slots: u64
+    data.push(0); // unique_names: bool
+    data.push(world_program::constants::NAME_CHARSET_ANY); // name_charset: u8
+    data.extend_from_slice(&0u16.to_le_bytes()); // reserved_name_count: u16
+    data.extend_from_slice(&world_program::constants::DEFAULT_DASH_SPEED.to_le_bytes()); // dash_speed: i16
+    data.extend_from_slice(&world_program::constants::DEFAULT_DASH_COOLDOWN_SLOTS.to_le_bytes()); // dash_cooldown_slots: u64
+    data.extend_from_slice(&world_program::constants::DEFAULT_MAX_REWIND_SLOTS.to_le_bytes()); // max_rewind_slots: u64
+    data.extend_from_slice(&world_program::constants::DEFAULT_MAX_EXTRAPOLATION_SLOTS.to_le_bytes()); // max_extrapolation_slots: u64
 
     let account = AccountSharedData::from(Account {
         lamports: 1,
@@ -126,9 +174,54 @@ struct Args {
     #[arg(long, default_value = "./data")]
     data_dir: PathBuf,
 
-    /// Save state every N slots (0 = only on shutdown)
+    /// Checkpoint state every N slots (0 = only on shutdown); blocks in between are logged
     #[arg(long, default_value = "300")]
     save_interval: u64,
+
+    /// Keep block and transaction history for the last N slots (0 = keep everything, leader mode)
+    #[arg(long, default_value = "2592000")]
+    block_retention_slots: u64,
+
+    /// Snapshot archive directory (default: <data-dir>/snapshots)
+    #[arg(long)]
+    snapshot_dir: Option<PathBuf>,
+
+    /// Write a state snapshot archive every N slots (0 = never, leader mode)
+    #[arg(long, default_value = "9000")]
+    snapshot_interval: u64,
+
+    /// Snapshot archives to keep; older ones are pruned (0 = keep all)
+    #[arg(long, default_value = "4")]
+    snapshots_to_keep: usize,
+
+    /// Roll persisted state back to this snapshot archive before starting (leader mode),
+    /// or start verifying from the state it holds (validator mode)
+    #[arg(long)]
+    restore_snapshot: Option<PathBuf>,
+
+    /// List snapshot archives and exit
+    #[arg(long)]
+    list_snapshots: bool,
+
+    /// Delete all but the newest N snapshot archives and exit
+    #[arg(long)]
+    prune_snapshots: Option<usize>,
+
+    /// World authority keypair (default: <data-dir>/authority.json, generated if missing)
+    #[arg(long)]
+    authority_keypair: Option<PathBuf>,
+
+    /// Number of NPCs to spawn in the default world (leader mode)
+    #[arg(long, default_value = "0")]
+    npc_count: u32,
+
+    /// Players per matchmade world instance (0 = matchmaking disabled, leader mode)
+    #[arg(long, default_value = "0")]
+    match_size: usize,
+
+    /// Largest rating spread within one match (0 = any)
+    #[arg(long, default_value = "0")]
+    rating_window: u32,
 }
 
 #[tokio::main]
@@ -144,12 +237,70 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Snapshot maintenance runs instead of a node
+    if args.list_snapshots || args.prune_snapshots.is_some() {
+        return manage_snapshots(&args);
+    }
+
     match args.mode {
         Mode::Leader => run_leader(args).await,
         Mode::Validator => run_validator(args).await,
     }
 }
 
+/// Where snapshot archives live
+fn snapshot_dir(args: &Args) -> PathBuf {
+    args.snapshot_dir.clone().unwrap_or_else(|| args.data_dir.join("snapshots"))
+}
+
+/// List and/or prune snapshot archives
+fn manage_snapshots(args: &Args) -> Result<()> {
+    let archive = SnapshotArchive::open(snapshot_dir(args))?;
+    if let Some(keep) = args.prune_snapshots {
+        let pruned = archive.prune(keep)?;
+        tracing::info!("Pruned {} snapshot(s)", pruned);
+    }
+    if args.list_snapshots {
+        for info in archive.list()? {
+            let manifest = &info.manifest;
+            tracing::info!(
+                "slot {:>10}  {:>7} accounts  {:>10} bytes  state root {}  {}",
+                manifest.slot(),
+                manifest.account_count,
+                manifest.compressed_len,
+                solana_sdk::hash::Hash::new_from_array(manifest.metadata.state_root),
+                info.path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Replace persisted state with a snapshot archive's, returning the chain position it restored
+fn restore_snapshot_archive(
+    path: &Path,
+    persistent_store: &PersistentStore,
+    block_store: &BlockStore,
+) -> Result<ChainMetadata> {
+    let snapshot = read_snapshot(path)?;
+    let metadata = snapshot.metadata.clone();
+    tracing::info!(
+        "Restoring snapshot {:?}: slot {}, {} accounts",
+        path,
+        metadata.slot,
+        snapshot.accounts.len()
+    );
+
+    let report = restore_snapshot(snapshot, persistent_store, block_store)?;
+    tracing::info!(
+        "Restored snapshot at slot {} ({} logged and {} recorded later blocks dropped)",
+        metadata.slot,
+        report.dropped_deltas,
+        report.dropped_blocks
+    );
+    Ok(metadata)
+}
+
 /// Run in leader mode - execute transactions and broadcast state
 async fn run_leader(args: Args) -> Result<()> {
     tracing::info!("Starting Solana L2 Gaming Chain - LEADER MODE");
@@ -163,15 +314,38 @@ async fn run_leader(args: Args) -> Result<()> {
     // Create data directory if it doesn't exist
     std::fs::create_dir_all(&args.data_dir)?;
 
+    // Load world authority (signs NPC actions)
+    let authority_path = args
+        .authority_keypair
+        .clone()
+        .unwrap_or_else(|| args.data_dir.join("authority.json"));
+    let authority = load_authority_keypair(&authority_path)?;
+    tracing::info!("  World authority: {}", authority.pubkey());
+
     // Open persistent store
     let persistent_store = Arc::new(PersistentStore::open(&args.data_dir)?);
 
+    // Block and transaction history
+    let block_store = Arc::new(BlockStore::open(
+        args.data_dir.join("blocks"),
+        BlockStoreConfig {
+            retention_slots: (args.block_retention_slots > 0).then_some(args.block_retention_slots),
+            ..Default::default()
+        },
+    )?);
+
+    // Roll back to a snapshot archive if asked
+    let restored = match &args.restore_snapshot {
+        Some(path) => Some(restore_snapshot_archive(path, &persistent_store, &block_store)?),
+        None => None,
+    };
+
     // Initialize account store
     let account_store = Arc::new(AccountStore::new());
 
-    // Load existing state from disk
-    let loaded_metadata = persistent_store.load_metadata()?;
-    let start_slot = if let Some(ref metadata) = loaded_metadata {
+    // Load the last checkpoint from disk
+    let checkpoint = persistent_store.load_metadata()?;
+    if let Some(ref metadata) = checkpoint {
         tracing::info!(
             "Loading state from disk: slot {}, {} accounts",
             metadata.slot,
@@ -179,11 +353,29 @@ async fn run_leader(args: Args) -> Result<()> {
         );
         let loaded = account_store.load_from_disk(&persistent_store)?;
         tracing::info!("Loaded {} accounts from persistent storage", loaded);
-        metadata.slot
     } else {
         tracing::info!("No existing state found, starting fresh");
-        0
-    };
+    }
+
+    // Replay blocks logged after the checkpoint (present after a crash)
+    let replayed = account_store.replay_wal(&persistent_store)?;
+    if let Some(metadata) = &replayed {
+        let state_root = account_store.state_root();
+        anyhow::ensure!(
+            state_root.to_bytes() == metadata.state_root,
+            "Write-ahead log replay to slot {} gives root {}, the log recorded {}",
+            metadata.slot,
+            state_root,
+            solana_sdk::hash::Hash::new_from_array(metadata.state_root)
+        );
+    }
+    let loaded_metadata = replayed.or(checkpoint);
+
+    // Bring accounts persisted by older versions up to the current layouts
+    let migrated = migrate_accounts(&account_store, loaded_metadata.as_ref().map_or(0, |metadata| metadata.slot));
+    if migrated > 0 {
+        tracing::info!("Migrated {} accounts to the current layout", migrated);
+    }
 
     // Initialize leader node for broadcasting
     let leader = Arc::new(
@@ -196,9 +388,25 @@ async fn run_leader(args: Args) -> Result<()> {
     // Start broadcast server
     leader.start().await?;
 
-    // Initialize L2 processor
-    let processor = L2Processor::new(account_store.clone());
-    tracing::info!("L2 Processor initialized");
+    // Initialize L2 processor, continuing the persisted chain if there is one
+    let processor = match &loaded_metadata {
+        Some(metadata) => L2Processor::from_metadata(account_store.clone(), metadata),
+        None => L2Processor::new(account_store.clone()),
+    };
+    let start_slot = processor.current_slot();
+    let start_state_root = processor.state_root();
+    // The restored state must resume at the snapshot's root (unless migrating changed it)
+    if let Some(metadata) = restored.as_ref().filter(|_| migrated == 0) {
+        anyhow::ensure!(
+            start_state_root.to_bytes() == metadata.state_root && start_slot == metadata.slot,
+            "Restored chain resumes at slot {} with root {}, snapshot is slot {} with root {}",
+            start_slot,
+            start_state_root,
+            metadata.slot,
+            solana_sdk::hash::Hash::new_from_array(metadata.state_root)
+        );
+    }
+    tracing::info!("L2 Processor initialized at slot {}", start_slot);
 
     // Initialize block producer
     let block_config = BlockProducerConfig {
@@ -206,29 +414,46 @@ async fn run_leader(args: Args) -> Result<()> {
         verbose: args.verbose,
         ..Default::default()
     };
-    let block_producer = BlockProducer::new(processor, block_config);
+    let mut block_producer = BlockProducer::new(processor, block_config);
+
+    // Record block and transaction history
+    block_producer.set_block_writer(BlockWriter::spawn(block_store.clone()));
+
+    // Checkpoints are captured between blocks and written off the block loop
+    let checkpoint_writer = CheckpointWriter::spawn(persistent_store.clone(), args.save_interval);
+    let checkpoint_request = checkpoint_writer.request();
+    block_producer.set_checkpoint_writer(checkpoint_writer);
+
+    // Periodic state snapshots, written off the block loop
+    if args.snapshot_interval > 0 {
+        let config = SnapshotConfig {
+            interval_slots: args.snapshot_interval,
+            max_snapshots: args.snapshots_to_keep,
+        };
+        let archive = SnapshotArchive::open(snapshot_dir(&args))?;
+        tracing::info!("  Snapshots: every {} slots in {:?}", config.interval_slots, archive.dir());
+        block_producer.set_snapshot_writer(SnapshotWriter::spawn(archive, config));
+    }
 
     // Get transaction sender and subscriber
     let tx_sender = block_producer.transaction_sender();
     let mut block_updates = block_producer.subscribe();
+    let mut persist_updates = block_producer.subscribe();
 
     // Initialize subscription manager
     let subscription_manager = Arc::new(SubscriptionManager::new());
 
     // Set up RPC context
-    let current_slot = Arc::new(RwLock::new(0u64));
-    let current_blockhash = Arc::new(RwLock::new(solana_sdk::hash::Hash::new_unique()));
+    let current_slot = Arc::new(RwLock::new(start_slot));
+    let current_blockhash = Arc::new(RwLock::new(block_producer.current_blockhash()));
+    let current_state_root = Arc::new(RwLock::new(start_state_root));
 
     // Create default world account if it doesn't exist
-    create_default_world(&account_store, 0);
+    create_default_world(&account_store, &authority.pubkey(), start_slot);
 
     // Verify world account was created correctly
+    let world_pda = default_world_pda();
     {
-        let world_program_id = world_program::id();
-        let mut name_bytes = [0u8; 32];
-        name_bytes[..7].copy_from_slice(b"default");
-        let (world_pda, _) = Pubkey::find_program_address(&[b"world", &name_bytes], &world_program_id);
-
         if let Some(account) = account_store.get_account(&world_pda) {
             tracing::info!(
                 "World account verified: {} (len={}, owner={})",
@@ -239,34 +464,102 @@ async fn run_leader(args: Args) -> Result<()> {
         }
     }
 
+    let world_authority = account_store
+        .get_account(&world_pda)
+        .and_then(|account| world_program::WorldConfig::try_from_slice(account.data()).ok())
+        .map(|world| world.authority);
+
+    // Leader-side game mode driver (idle until the world authority configures a game mode)
+    if world_authority == Some(authority.pubkey()) {
+        block_producer.add_tick_hook(Box::new(GameModeDriver::new(authority.insecure_clone(), world_pda)));
+    }
+
+    // Leader-side NPC AI driver
+    if args.npc_count > 0 {
+        if world_authority == Some(authority.pubkey()) {
+            let mut driver = NpcDriver::new(authority.insecure_clone(), world_pda);
+            for i in 0..args.npc_count {
+                // Ring of spawn points around the world center
+                let angle = i as f32 * std::f32::consts::TAU / args.npc_count as f32;
+                let spawn_x = 50_000 + (angle.cos() * 20_000.0) as i32;
+                let spawn_z = 50_000 + (angle.sin() * 20_000.0) as i32;
+                driver.add_npc(
+                    NpcSpawn::new(i, &format!("Monster{}", i), spawn_x, spawn_z),
+                    Box::new(ChaseBehavior { aggro_range: 15_000 }),
+                );
+            }
+            tracing::info!("NPC driver enabled: {} NPCs", driver.npc_count());
+            block_producer.add_tick_hook(Box::new(driver));
+        } else {
+            tracing::warn!(
+                "NPCs disabled: default world authority {:?} does not match {}",
+                world_authority,
+                authority.pubkey()
+            );
+        }
+    }
+
+    // Leader-side matchmaker (instances are owned by the authority keypair)
+    if args.match_size > 0 {
+        let config = MatchmakerConfig {
+            match_size: args.match_size,
+            rating_window: args.rating_window,
+            ..Default::default()
+        };
+        tracing::info!(
+            "Matchmaking enabled: {} players per instance, rating window {}",
+            config.match_size,
+            config.rating_window
+        );
+        block_producer.add_tick_hook(Box::new(Matchmaker::new(authority.insecure_clone(), config)));
+    }
+
+    // Leader-side idle eviction (frees slots held by players who stopped playing)
+    block_producer.add_tick_hook(Box::new(IdleEvictor::new(authority.insecure_clone())));
+
+    // Leader-side waitlist crank (admits waiting players as slots open in any world)
+    block_producer.add_tick_hook(Box::new(WaitlistDriver::new(authority.insecure_clone())));
+
+    // Leader-side dead reckoning (keeps players moving through dropped or late inputs)
+    block_producer.add_tick_hook(Box::new(MovementExtrapolator::new(authority.insecure_clone())));
+
+    // Spatial index for nearby-entity queries (kept current from block updates)
+    let spatial_index = Arc::new(SpatialIndex::new());
+    spatial_index.rebuild(&account_store);
+    tracing::info!("Spatial index built: {} entities", spatial_index.len());
+
     let rpc_context = Arc::new(RpcContext {
         account_store: account_store.clone(),
         tx_sender,
         current_slot: current_slot.clone(),
         current_blockhash: current_blockhash.clone(),
+        current_state_root: current_state_root.clone(),
+        spatial_index: spatial_index.clone(),
     });
 
-    // Spawn block producer
-    let block_producer_handle = tokio::spawn(async move {
-        block_producer.run_async().await;
-    });
+    // Spawn block producer (handed back once stopped)
+    let producer_running = block_producer.running_flag();
+    let block_producer_handle = tokio::spawn(block_producer.run_async());
 
     // Spawn block update handler with leader slot management
     let sub_mgr = subscription_manager.clone();
     let slot_ref = current_slot.clone();
     let hash_ref = current_blockhash.clone();
+    let root_ref = current_state_root.clone();
     let leader_ref = leader.clone();
-    let persist_store = persistent_store.clone();
-    let persist_accounts = account_store.clone();
-    let save_interval = args.save_interval;
+    let update_index = spatial_index.clone();
     let update_handler = tokio::spawn(async move {
         while let Ok(update) = block_updates.recv().await {
             // Begin new slot on leader
             leader_ref.begin_slot(update.slot);
 
+            // Keep spatial index in sync with changed player/NPC accounts
+            update_index.apply_block_update(&update);
+
             // Update current slot and blockhash
             *slot_ref.write() = update.slot;
             *hash_ref.write() = update.blockhash;
+            *root_ref.write() = update.state_root;
 
             // Notify subscribers of account updates
             for (pubkey, account) in &update.modified_accounts {
@@ -274,26 +567,7 @@ async fn run_leader(args: Args) -> Result<()> {
             }
 
             // End slot - broadcasts state changes to validators
-            leader_ref.end_slot();
-
-            // Periodic save to disk
-            if save_interval > 0 && update.slot % save_interval == 0 && update.slot > 0 {
-                let metadata = ChainMetadata {
-                    slot: update.slot,
-                    blockhash: update.blockhash.to_bytes(),
-                    epoch: update.slot / 432000, // ~2 days at 30Hz
-                    account_count: persist_accounts.len() as u64,
-                    last_save_ts: chrono::Utc::now().timestamp(),
-                };
-                if let Err(e) = persist_store.save_metadata(&metadata) {
-                    tracing::error!("Failed to save metadata: {}", e);
-                }
-                if let Err(e) = persist_accounts.save_to_disk(&persist_store) {
-                    tracing::error!("Failed to save accounts: {}", e);
-                } else {
-                    tracing::info!("Saved state at slot {}", update.slot);
-                }
-            }
+            leader_ref.end_slot(update.state_root.to_bytes());
 
             // Log validator stats periodically
             if update.slot % 100 == 0 {
@@ -308,6 +582,25 @@ async fn run_leader(args: Args) -> Result<()> {
         }
     });
 
+    // Log every block to the write-ahead log, off the block update task
+    let persist_store = persistent_store.clone();
+    let persist_handler = tokio::task::spawn_blocking(move || loop {
+        let update = match persist_updates.blocking_recv() {
+            Ok(update) => update,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                // The log now has a gap; the next checkpoint closes it, until
+                // then a restart replays up to the gap only
+                tracing::warn!("Write-ahead log missed {} blocks, checkpointing", missed);
+                checkpoint_request.request();
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+        if let Err(e) = persist_store.append_delta(&SlotDelta::from_update(&update)) {
+            tracing::error!("Failed to log slot {}: {}", update.slot, e);
+        }
+    });
+
     // Start HTTP RPC server
     let http_context = rpc_context.clone();
     let http_addr = args.rpc_addr.clone();
@@ -319,11 +612,10 @@ async fn run_leader(args: Args) -> Result<()> {
     });
 
     // Start WebSocket server
-    let ws_context = rpc_context.clone();
     let ws_sub_mgr = subscription_manager.clone();
     let ws_addr = args.ws_addr.clone();
     let ws_server = tokio::spawn(async move {
-        let server = WebSocketServer::new(ws_context, ws_sub_mgr);
+        let server = WebSocketServer::new(ws_sub_mgr);
         if let Err(e) = server.run(&ws_addr).await {
             tracing::error!("WebSocket server error: {}", e);
         }
@@ -337,30 +629,25 @@ async fn run_leader(args: Args) -> Result<()> {
 
     tracing::info!("Shutting down...");
 
-    // Save state before shutdown
-    let final_slot = *current_slot.read();
-    let final_hash = *current_blockhash.read();
-    tracing::info!("Saving final state at slot {}...", final_slot);
-
-    let metadata = ChainMetadata {
-        slot: final_slot,
-        blockhash: final_hash.to_bytes(),
-        epoch: final_slot / 432000,
-        account_count: account_store.len() as u64,
-        last_save_ts: chrono::Utc::now().timestamp(),
-    };
-
-    if let Err(e) = persistent_store.save_metadata(&metadata) {
-        tracing::error!("Failed to save final metadata: {}", e);
+    // Stop producing so the final checkpoint is taken at a block boundary
+    producer_running.store(false, Ordering::SeqCst);
+    match block_producer_handle.await {
+        Ok(block_producer) => {
+            let final_slot = block_producer.current_slot();
+            tracing::info!("Saving final state at slot {}...", final_slot);
+            block_producer.checkpoint_now();
+            // Dropping the producer waits for its block, snapshot and checkpoint writers
+            drop(block_producer);
+            tracing::info!("Final state saved: {} accounts at slot {}", account_store.len(), final_slot);
+        }
+        Err(e) => tracing::error!("Block producer task failed, final state not saved: {}", e),
     }
-    if let Err(e) = account_store.save_to_disk(&persistent_store) {
-        tracing::error!("Failed to save final state: {}", e);
-    } else {
-        tracing::info!("Final state saved: {} accounts at slot {}", account_store.len(), final_slot);
+    if let Err(e) = block_store.flush() {
+        tracing::error!("Failed to flush block store: {}", e);
     }
 
-    // Abort tasks
-    block_producer_handle.abort();
+    // The producer's update channel is closed, so the log drains and stops
+    let _ = persist_handler.await;
     update_handler.abort();
     http_server.abort();
     ws_server.abort();
@@ -381,6 +668,14 @@ async fn run_validator(args: Args) -> Result<()> {
         .node_id(Pubkey::new_unique())
         .build();
 
+    // Start from a snapshot of the leader's state so every root can be checked;
+    // otherwise roots are checked once the applied writes reproduce one
+    if let Some(path) = &args.restore_snapshot {
+        let snapshot = read_snapshot(path)?;
+        validator.load_state(snapshot.accounts, snapshot.metadata.state_root)?;
+        tracing::info!("Loaded leader state at slot {} from {:?}", snapshot.metadata.slot, path);
+    }
+
     // Connect to leader
     validator.connect().await?;
 
@@ -12,13 +12,21 @@
 use solana_sdk;
 
 use borsh::BorshDeserialize;
-use solana_program::instruction::InstructionError;
-use solana_program_runtime::invoke_context::InvokeContext;
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_runtime::{invoke_context::InvokeContext, stable_log};
+use solana_sdk::transaction_context::{
+    BorrowedAccount, IndexOfAccount, InstructionContext, TransactionContext,
+};
 
 use crate::{
     constants::*,
-    instruction::WorldInstruction,
-    state::{MovementInput, MovementInput3D, WeaponStats, WorldConfig, WorldPlayer},
+    error::WorldError,
+    instruction::{NpcAction, WorldInstruction},
+    state::{
+        GameMode, LootDrop, MatchResult, MovementInput, MovementInput3D, PlayerSession, PositionHistory,
+        QueueTicket, NameClaim, ReservedNames, TeamScores, WeaponStats, WorldAllowlist, WorldConfig, WorldNpc,
+        WorldPlayer, WorldWaitlist,
+    },
 };
 
 // Use the declare_process_instruction! macro to create a properly typed builtin entrypoint
@@ -66,11 +74,14 @@ fn world_instruction_dispatch(invoke_context: &mut InvokeContext) -> Result<(),
             max_players,
         } => process_initialize_world(invoke_context, name, width, height, max_players),
 
-        WorldInstruction::JoinWorld { name } => process_join_world(invoke_context, name),
+        WorldInstruction::JoinWorld { name } => process_join_world(invoke_context, name, None),
 
         WorldInstruction::MovePlayer { input } => process_move_player(invoke_context, input),
 
-        WorldInstruction::Attack { weapon_stats } => process_attack(invoke_context, weapon_stats),
+        WorldInstruction::Attack {
+            weapon_stats,
+            observed_slot,
+        } => process_attack(invoke_context, weapon_stats, observed_slot),
 
         WorldInstruction::Heal { amount } => process_heal(invoke_context, amount),
 
@@ -87,7 +98,590 @@ fn world_instruction_dispatch(invoke_context: &mut InvokeContext) -> Result<(),
         WorldInstruction::MovePlayer3D { input } => {
             process_move_player_3d(invoke_context, input)
         }
+
+        WorldInstruction::CreateSession {
+            session_key,
+            expiry_slot,
+            allowed_instructions,
+        } => process_create_session(invoke_context, session_key, expiry_slot, allowed_instructions),
+
+        WorldInstruction::RevokeSession => process_revoke_session(invoke_context),
+
+        WorldInstruction::SpawnNpc {
+            npc_id,
+            kind,
+            name,
+            max_health,
+            damage,
+            attack_range,
+            spawn_x,
+            spawn_z,
+            respawn_delay_slots,
+            loot_amount,
+        } => {
+            let npc = WorldNpc {
+                npc_id,
+                kind,
+                damage,
+                attack_range,
+                spawn_x,
+                spawn_z,
+                respawn_delay_slots,
+                loot_amount,
+                body: WorldPlayer {
+                    max_health,
+                    name,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            process_spawn_npc(invoke_context, npc)
+        }
+
+        WorldInstruction::NpcAct { action } => process_npc_act(invoke_context, action),
+
+        WorldInstruction::AttackNpc {
+            weapon_stats,
+            observed_slot,
+        } => process_attack_npc(invoke_context, weapon_stats, observed_slot),
+
+        WorldInstruction::ConfigureTeams {
+            team_count,
+            friendly_fire,
+            max_team_size,
+        } => process_configure_teams(invoke_context, team_count, friendly_fire, max_team_size),
+
+        WorldInstruction::ChooseTeam { team } => process_choose_team(invoke_context, team),
+
+        WorldInstruction::ConfigureGameMode {
+            mode,
+            objective_x,
+            objective_z,
+            objective_radius,
+            flag_base_x,
+            flag_base_z,
+            match_duration_slots,
+            score_limit,
+        } => {
+            let game_mode = GameMode {
+                mode,
+                objective_x,
+                objective_z,
+                objective_radius,
+                flag_base_x,
+                flag_base_z,
+                match_duration_slots,
+                score_limit,
+                ..Default::default()
+            };
+            process_configure_game_mode(invoke_context, game_mode)
+        }
+
+        WorldInstruction::TickGameMode => process_tick_game_mode(invoke_context),
+
+        WorldInstruction::JoinQueue { rating } => process_join_queue(invoke_context, rating),
+
+        WorldInstruction::LeaveQueue => process_leave_queue(invoke_context),
+
+        WorldInstruction::IssueJoinTicket => process_issue_join_ticket(invoke_context),
+
+        WorldInstruction::CloseWorld => process_close_world(invoke_context),
+
+        WorldInstruction::TransferWorld => process_transfer_world(invoke_context),
+
+        WorldInstruction::SetWorldVisibility {
+            visibility,
+            password_salt,
+            password_hash,
+        } => process_set_world_visibility(invoke_context, visibility, password_salt, password_hash),
+
+        WorldInstruction::UpdateAllowlist { wallet, allowed } => {
+            process_update_allowlist(invoke_context, wallet, allowed)
+        }
+
+        WorldInstruction::JoinWorldWithPassword { name, password } => {
+            process_join_world(invoke_context, name, Some(password))
+        }
+
+        WorldInstruction::SetReservedSlots { reserved_slots } => {
+            process_set_reserved_slots(invoke_context, reserved_slots)
+        }
+
+        WorldInstruction::JoinWaitlist { name, password } => {
+            process_join_waitlist(invoke_context, name, password)
+        }
+
+        WorldInstruction::LeaveWaitlist => process_leave_waitlist(invoke_context),
+
+        WorldInstruction::AdmitFromWaitlist => process_admit_from_waitlist(invoke_context),
+
+        WorldInstruction::AdmitVip { name } => process_admit_vip(invoke_context, name),
+
+        WorldInstruction::SetIdleTimeout { idle_timeout_slots } => {
+            process_set_idle_timeout(invoke_context, idle_timeout_slots)
+        }
+
+        WorldInstruction::EvictIdlePlayer => process_evict_idle_player(invoke_context),
+
+        WorldInstruction::ConfigureNames {
+            unique_names,
+            name_charset,
+        } => process_configure_names(invoke_context, unique_names, name_charset),
+
+        WorldInstruction::UpdateReservedNames { word, reserved } => {
+            process_update_reserved_names(invoke_context, word, reserved)
+        }
+
+        WorldInstruction::ConfigureAbilities {
+            dash_speed,
+            dash_cooldown_slots,
+        } => process_configure_abilities(invoke_context, dash_speed, dash_cooldown_slots),
+
+        WorldInstruction::SetMaxRewind { max_rewind_slots } => {
+            process_set_max_rewind(invoke_context, max_rewind_slots)
+        }
+
+        WorldInstruction::ExtrapolateMovement => process_extrapolate_movement(invoke_context),
+
+        WorldInstruction::SetMaxExtrapolation {
+            max_extrapolation_slots,
+        } => process_set_max_extrapolation(invoke_context, max_extrapolation_slots),
+    }
+}
+
+/// Map a WorldError to the builtin's InstructionError
+fn world_error(error: WorldError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
+
+/// Position of the account with `key` among the instruction accounts
+fn find_instruction_account(
+    transaction_context: &TransactionContext,
+    instruction_context: &InstructionContext,
+    key: &Pubkey,
+) -> Result<Option<IndexOfAccount>, InstructionError> {
+    for index in 0..instruction_context.get_number_of_instruction_accounts() {
+        let index_in_transaction = instruction_context
+            .get_index_of_instruction_account_in_transaction(index)?;
+        if transaction_context.get_key_of_account_at_index(index_in_transaction)? == key {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// Load a world's allowlist if it is among the instruction accounts
+fn load_allowlist(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+) -> Result<Option<WorldAllowlist>, InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let (allowlist_pda, _) = WorldAllowlist::derive_pda(world_key, program_id);
+    let Some(allowlist_index) =
+        find_instruction_account(transaction_context, instruction_context, &allowlist_pda)?
+    else {
+        return Ok(None);
+    };
+
+    let allowlist_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, allowlist_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    // Missing or never-written allowlist - nobody is on the list yet
+    if allowlist_account.get_data().iter().all(|byte| *byte == 0) {
+        return Ok(None);
+    }
+    if allowlist_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    WorldAllowlist::try_from_slice(allowlist_account.get_data())
+        .map(Some)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Copy of `body` where it stood at `slot`, for lag-compensated hit tests
+///
+/// Positions come from the world's position history when it is among the
+/// instruction accounts. The current slot's moves are only recorded once
+/// it is sealed, so rewinding to it (or without a history) uses `body`.
+fn load_rewound(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    entity: &Pubkey,
+    body: &WorldPlayer,
+    slot: u64,
+    current_slot: u64,
+) -> Result<WorldPlayer, InstructionError> {
+    if slot >= current_slot {
+        return Ok(body.clone());
+    }
+
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let (history_pda, _) = PositionHistory::derive_pda(world_key, program_id);
+    let Some(history_index) =
+        find_instruction_account(transaction_context, instruction_context, &history_pda)?
+    else {
+        return Ok(body.clone());
+    };
+
+    let history_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, history_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    // Not recorded yet - nobody has moved
+    if history_account.get_data().is_empty() {
+        return Ok(body.clone());
+    }
+    if history_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    let history = PositionHistory::try_from_slice(history_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    Ok(history.rewound(entity, body, slot))
+}
+
+/// Load a world's reserved-name list (required while the world reserves names)
+fn load_reserved_names(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+) -> Result<Option<ReservedNames>, InstructionError> {
+    if world.reserved_name_count == 0 {
+        return Ok(None);
+    }
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let (reserved_pda, _) = ReservedNames::derive_pda(world_key, program_id);
+    let reserved_index = find_instruction_account(transaction_context, instruction_context, &reserved_pda)?
+        .ok_or(world_error(WorldError::AccountNotInitialized))?;
+
+    let reserved_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, reserved_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if reserved_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    ReservedNames::try_from_slice(reserved_account.get_data())
+        .map(Some)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Position of the claim account for `name` among the instruction accounts
+fn find_name_claim(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    name: &[u8; 16],
+) -> Result<Option<IndexOfAccount>, InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let (claim_pda, _) = NameClaim::derive_pda(world_key, &NameClaim::normalize(name), program_id);
+    find_instruction_account(transaction_context, instruction_context, &claim_pda)
+}
+
+/// Load a name claim, or None if the account is new (synthesized all-zero by the loader)
+fn load_name_claim(
+    invoke_context: &InvokeContext,
+    claim_index: IndexOfAccount,
+) -> Result<Option<NameClaim>, InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let claim_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, claim_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if claim_account.get_data().iter().all(|byte| *byte == 0) {
+        return Ok(None);
+    }
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    if claim_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    NameClaim::try_from_slice(claim_account.get_data())
+        .map(Some)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Check `name` for `wallet` against the world's name rules and claims
+///
+/// Account problems are returned as the outer error, rule violations as the inner one.
+fn check_name(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+    wallet: &Pubkey,
+    name: &[u8; 16],
+    slot: u64,
+) -> Result<Result<(), WorldError>, InstructionError> {
+    let reserved = load_reserved_names(invoke_context, world_key, world)?;
+    let claim = if world.unique_names {
+        let claim_index = find_name_claim(invoke_context, world_key, name)?
+            .ok_or(world_error(WorldError::AccountNotInitialized))?;
+        load_name_claim(invoke_context, claim_index)?
+    } else {
+        None
+    };
+    Ok(world.check_name(wallet, name, reserved.as_ref(), claim.as_ref(), slot))
+}
+
+/// Claim a checked name for `wallet` (unique-name worlds only)
+fn claim_name(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+    wallet: &Pubkey,
+    name: &[u8; 16],
+    slot: u64,
+) -> Result<(), InstructionError> {
+    if !world.unique_names {
+        return Ok(());
+    }
+    let claim_index = find_name_claim(invoke_context, world_key, name)?
+        .ok_or(world_error(WorldError::AccountNotInitialized))?;
+
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let mut claim_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, claim_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let normalized = NameClaim::normalize(name);
+    let (_, bump) = NameClaim::derive_pda(world_key, &normalized, program_id);
+    let claim = NameClaim {
+        world: *world_key,
+        owner: *wallet,
+        name: normalized,
+        expires_slot: slot.saturating_add(NAME_CLAIM_EXPIRY_SLOTS),
+        bump,
+    };
+
+    // Missing PDAs may be synthesized with another size - fit to NameClaim
+    if claim_account.get_data().len() != NameClaim::LEN {
+        claim_account.set_data_length(NameClaim::LEN)?;
+    }
+    let claim_data = claim_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut claim_data[..], &claim)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Release a player's name claim if it is among the instruction accounts
+///
+/// Claims taken over by another wallet after expiring are left alone.
+fn release_name(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    player: &WorldPlayer,
+    destination_account: &mut BorrowedAccount,
+) -> Result<(), InstructionError> {
+    let Some(claim_index) = find_name_claim(invoke_context, world_key, &player.name)? else {
+        return Ok(());
+    };
+    match load_name_claim(invoke_context, claim_index)? {
+        Some(claim) if claim.owner == player.authority => {}
+        _ => return Ok(()),
+    }
+
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+    let mut claim_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, claim_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let lamports = claim_account.get_lamports();
+    claim_account.set_lamports(0)?;
+    destination_account.checked_add_lamports(lamports)?;
+    claim_account.set_data_length(0)?;
+
+    Ok(())
+}
+
+/// Write a `Program log:` line, as `msg!` does for the BPF build
+fn program_log(invoke_context: &InvokeContext, message: &str) {
+    stable_log::program_log(&invoke_context.get_log_collector(), message);
+}
+
+/// Load, update and store a world's team scores account
+///
+/// The account is located by its PDA among the instruction accounts, so it can
+/// trail optional accounts like sessions.
+fn update_team_scores(
+    invoke_context: &InvokeContext,
+    world_key: &Pubkey,
+    update: impl FnOnce(&mut TeamScores) -> Result<(), WorldError>,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    let (scores_pda, _) = TeamScores::derive_pda(world_key, program_id);
+    let scores_index = find_instruction_account(transaction_context, instruction_context, &scores_pda)?
+        .ok_or(world_error(WorldError::AccountNotInitialized))?;
+
+    let mut scores_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, scores_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if scores_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let mut scores = TeamScores::try_from_slice(scores_account.get_data())
+        .map_err(|_| world_error(WorldError::AccountNotInitialized))?;
+    update(&mut scores).map_err(world_error)?;
+
+    let scores_data = scores_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut scores_data[..], &scores)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Write a new player for `wallet` at the world's spawn point
+///
+/// Used by the waitlist and VIP admissions: verifies the player PDA, checks
+/// and claims the name, auto-balances teams and fits the account. Capacity,
+/// access and storing the world are up to the caller.
+fn spawn_player(
+    invoke_context: &InvokeContext,
+    world: &mut WorldConfig,
+    world_key: &Pubkey,
+    player_account: &mut BorrowedAccount,
+    wallet: &Pubkey,
+    name: [u8; 16],
+) -> Result<WorldPlayer, InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify player PDA
+    let (expected_pda, bump) = WorldPlayer::derive_pda(world_key, wallet, program_id);
+    if expected_pda != *player_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+
+    // Enforce the world's name rules
+    check_name(invoke_context, world_key, world, wallet, &name, clock.slot)?.map_err(world_error)?;
+    claim_name(invoke_context, world_key, world, wallet, &name, clock.slot)?;
+
+    // Auto-balance onto the smallest team in team worlds
+    let mut team = TEAM_NONE;
+    if world.teams_enabled() {
+        update_team_scores(invoke_context, world_key, |scores| {
+            team = scores.auto_assign(world)?;
+            scores.add_member(team);
+            Ok(())
+        })?;
+    }
+
+    // Missing PDAs may be synthesized with another size - fit to WorldPlayer
+    if player_account.get_data().len() != WorldPlayer::LEN {
+        player_account.set_data_length(WorldPlayer::LEN)?;
+    }
+    let player = WorldPlayer::spawn(*wallet, *world_key, world, name, bump, team, clock.slot);
+    let player_data = player_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut player_data[..], &player)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    world.player_count += 1;
+    Ok(player)
+}
+
+/// Verify the signer is the player's wallet authority or a valid session key
+///
+/// `session_index` is the position of the optional trailing session account.
+fn verify_player_signer(
+    invoke_context: &InvokeContext,
+    player_key: &Pubkey,
+    player: &WorldPlayer,
+    signer_key: &Pubkey,
+    session_index: IndexOfAccount,
+    permission: u8,
+) -> Result<(), InstructionError> {
+    if player.authority == *signer_key {
+        return Ok(());
+    }
+
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Not the wallet - must be a registered session key
+    if instruction_context.get_number_of_instruction_accounts() <= session_index {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
     }
+
+    let session_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, session_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    if session_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let (session_pda, _) = PlayerSession::derive_pda(player_key, program_id);
+    if session_pda != *session_account.get_key() {
+        return Err(world_error(WorldError::InvalidSession));
+    }
+
+    let session = PlayerSession::try_from_slice(session_account.get_data())
+        .map_err(|_| world_error(WorldError::InvalidSession))?;
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+
+    session
+        .validate(player_key, signer_key, permission, clock.slot)
+        .map_err(world_error)
 }
 
 /// Initialize a new world
@@ -128,6 +722,16 @@ fn process_initialize_world(
         return Err(InstructionError::InvalidSeeds);
     }
 
+    // Never re-initialize an existing world
+    if world_account.get_data().iter().any(|byte| *byte != 0) {
+        return Err(world_error(WorldError::AccountAlreadyInitialized));
+    }
+
+    // Missing PDAs may be synthesized with another size - fit to WorldConfig
+    if world_account.get_data().len() != WorldConfig::LEN {
+        world_account.set_data_length(WorldConfig::LEN)?;
+    }
+
     // Get clock for timestamp
     let clock = invoke_context.get_sysvar_cache().get_clock()
         .map_err(|_| InstructionError::UnsupportedSysvar)?;
@@ -144,6 +748,22 @@ fn process_initialize_world(
         bump,
         l1_game: solana_program::pubkey::Pubkey::default(),
         init_ts: clock.unix_timestamp,
+        team_count: 0,
+        friendly_fire: false,
+        max_team_size: 0,
+        visibility: WORLD_VISIBILITY_PUBLIC,
+        password_salt: [0; 16],
+        password_hash: [0; 32],
+        reserved_slots: 0,
+        waiting_count: 0,
+        idle_timeout_slots: DEFAULT_IDLE_TIMEOUT_SLOTS,
+        unique_names: false,
+        name_charset: NAME_CHARSET_ANY,
+        reserved_name_count: 0,
+        dash_speed: DEFAULT_DASH_SPEED,
+        dash_cooldown_slots: DEFAULT_DASH_COOLDOWN_SLOTS,
+        max_rewind_slots: DEFAULT_MAX_REWIND_SLOTS,
+        max_extrapolation_slots: DEFAULT_MAX_EXTRAPOLATION_SLOTS,
     };
 
     // Serialize to account data
@@ -164,6 +784,7 @@ fn process_initialize_world(
 fn process_join_world(
     invoke_context: &mut InvokeContext,
     name: [u8; 16],
+    password: Option<[u8; 32]>,
 ) -> Result<(), InstructionError> {
     eprintln!("[BUILTIN] process_join_world ENTRY");
     solana_program::msg!("World program: process_join_world called");
@@ -208,12 +829,18 @@ fn process_join_world(
         .map_err(|_| InstructionError::InvalidAccountData)?;
     eprintln!("[BUILTIN] deserialized WorldConfig, player_count={}", world.player_count);
 
-    // Check if world is full
-    if world.is_full() {
+    // Check if world is full (or players are waiting for the next slot)
+    if !world.can_join_directly() {
         return Err(InstructionError::Custom(1)); // WorldFull
     }
     eprintln!("[BUILTIN] world not full: OK");
 
+    // Enforce the world's visibility settings
+    let allowlist = load_allowlist(invoke_context, world_account.get_key())?;
+    world
+        .check_access(authority_account.get_key(), allowlist.as_ref(), password.as_ref())
+        .map_err(world_error)?;
+
     // Get program ID for PDA derivation
     eprintln!("[BUILTIN] getting program_id");
     let program_id = instruction_context
@@ -240,26 +867,45 @@ fn process_join_world(
         .map_err(|_| InstructionError::UnsupportedSysvar)?;
     eprintln!("[BUILTIN] got clock, slot={}", clock.slot);
 
-    // Initialize player at world center
-    let player = WorldPlayer {
-        authority: *authority_account.get_key(),
-        world: *world_account.get_key(),
-        position_x: (world.width as i32 / 2) * FIXED_POINT_SCALE,
-        position_z: (world.depth as i32 / 2) * FIXED_POINT_SCALE,
-        position_y: 0, // Start on ground
-        velocity_x: 0,
-        velocity_z: 0,
-        velocity_y: 0,
-        yaw: 0,
-        health: DEFAULT_HEALTH,
-        max_health: DEFAULT_MAX_HEALTH,
-        last_action_slot: clock.slot,
-        last_combat_ts: 0,
-        in_pvp_zone: false,
-        is_grounded: true,
-        bump,
+    // Enforce the world's name rules
+    check_name(
+        invoke_context,
+        world_account.get_key(),
+        &world,
+        authority_account.get_key(),
+        &name,
+        clock.slot,
+    )?
+    .map_err(world_error)?;
+    claim_name(
+        invoke_context,
+        world_account.get_key(),
+        &world,
+        authority_account.get_key(),
+        &name,
+        clock.slot,
+    )?;
+
+    // Auto-balance onto the smallest team in team worlds
+    let mut team = TEAM_NONE;
+    if world.teams_enabled() {
+        update_team_scores(invoke_context, world_account.get_key(), |scores| {
+            team = scores.auto_assign(&world)?;
+            scores.add_member(team);
+            Ok(())
+        })?;
+    }
+
+    // Initialize player at the world's spawn point
+    let player = WorldPlayer::spawn(
+        *authority_account.get_key(),
+        *world_account.get_key(),
+        &world,
         name,
-    };
+        bump,
+        team,
+        clock.slot,
+    );
 
     // Serialize player to account data
     eprintln!("[BUILTIN] about to call player_account.get_data_mut() - THIS IS THE CRITICAL POINT");
@@ -335,10 +981,15 @@ fn process_move_player(
     let mut player = WorldPlayer::try_from_slice(player_data)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    // Verify authority
-    if player.authority != *authority_account.get_key() {
-        return Err(InstructionError::Custom(2)); // InvalidAuthority
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        invoke_context,
+        player_account.get_key(),
+        &player,
+        authority_account.get_key(),
+        3,
+        SESSION_ALLOW_MOVE,
+    )?;
 
     // Verify world
     if player.world != *world_account.get_key() {
@@ -350,12 +1001,14 @@ fn process_move_player(
         return Err(InstructionError::Custom(4)); // PlayerDead
     }
 
-    // Apply movement
-    player.apply_movement(input.direction, input.sprint, &world);
-
-    // Update last action slot
+    // Apply movement (dropped while hit-stunned)
     let clock = invoke_context.get_sysvar_cache().get_clock()
         .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if !player.is_stunned(clock.slot) {
+        player.apply_movement(input.direction, input.sprint, &world);
+    }
+
+    // Update last action slot
     player.last_action_slot = clock.slot;
 
     // Serialize player back
@@ -371,6 +1024,7 @@ fn process_move_player(
 fn process_attack(
     invoke_context: &mut InvokeContext,
     weapon_stats: Option<WeaponStats>,
+    observed_slot: Option<u64>,
 ) -> Result<(), InstructionError> {
     let transaction_context = &*invoke_context.transaction_context;
     let instruction_context = transaction_context
@@ -378,6 +1032,10 @@ fn process_attack(
         .map_err(|_| InstructionError::InvalidInstructionData)?;
 
     // Account indices: 0=world, 1=attacker, 2=target, 3=authority
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
     let mut attacker_account = instruction_context
         .try_borrow_instruction_account(transaction_context, 1)
         .map_err(|_| InstructionError::InvalidAccountData)?;
@@ -410,25 +1068,72 @@ fn process_attack(
     let mut target = WorldPlayer::try_from_slice(target_data)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    // Verify attacker authority
-    if attacker.authority != *authority_account.get_key() {
-        return Err(InstructionError::Custom(2)); // InvalidAuthority
-    }
+    // Verify attacker authority (wallet or session key)
+    verify_player_signer(
+        invoke_context,
+        attacker_account.get_key(),
+        &attacker,
+        authority_account.get_key(),
+        4,
+        SESSION_ALLOW_ATTACK,
+    )?;
 
     // Both must be alive
     if !attacker.is_alive() || !target.is_alive() {
         return Err(InstructionError::Custom(4)); // PlayerDead
     }
 
+    // No striking back while reeling from a hit
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if attacker.is_stunned(clock.slot) {
+        return Err(world_error(WorldError::PlayerStunned));
+    }
+
+    // Team rules
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if attacker.world != *world_account.get_key() || target.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+    world.check_friendly_fire(&attacker, &target).map_err(world_error)?;
+
+    // Weapon reach is measured to the target's hitbox (lower when crouching),
+    // where the attacker's client showed it
+    let seen = load_rewound(
+        invoke_context,
+        world_account.get_key(),
+        target_account.get_key(),
+        &target,
+        world.rewind_slot(observed_slot, clock.slot),
+        clock.slot,
+    )?;
+    if weapon_stats.is_some_and(|weapon| !weapon.in_range(&attacker, &seen)) {
+        return Err(world_error(WorldError::TargetOutOfRange));
+    }
+
     // Calculate damage
     let damage = weapon_stats.map(|w| w.damage).unwrap_or(DEFAULT_DAMAGE);
 
-    // Apply damage
+    // Apply damage, knocking the target back if it survives
     target.apply_damage(damage);
+    if target.is_alive() {
+        target.apply_knockback(attacker.position_x, attacker.position_z, attacker.yaw, damage, clock.slot);
+    }
+
+    // Enemy kills score for the attacker's team
+    if !target.is_alive()
+        && world.teams_enabled()
+        && attacker.team != TEAM_NONE
+        && attacker.team != target.team
+    {
+        update_team_scores(invoke_context, world_account.get_key(), |scores| {
+            scores.add_score(attacker.team, TEAM_KILL_SCORE);
+            Ok(())
+        })?;
+    }
 
     // Update timestamps
-    let clock = invoke_context.get_sysvar_cache().get_clock()
-        .map_err(|_| InstructionError::UnsupportedSysvar)?;
     attacker.last_combat_ts = clock.unix_timestamp;
     attacker.last_action_slot = clock.slot;
 
@@ -473,10 +1178,15 @@ fn process_heal(invoke_context: &mut InvokeContext, amount: u16) -> Result<(), I
     let mut player = WorldPlayer::try_from_slice(player_data)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    // Verify authority
-    if player.authority != *authority_account.get_key() {
-        return Err(InstructionError::Custom(2)); // InvalidAuthority
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        invoke_context,
+        player_account.get_key(),
+        &player,
+        authority_account.get_key(),
+        3,
+        SESSION_ALLOW_HEAL,
+    )?;
 
     // Apply heal
     let heal_amount = if amount > 0 { amount } else { DEFAULT_HEAL };
@@ -508,7 +1218,7 @@ fn process_leave_world(invoke_context: &mut InvokeContext) -> Result<(), Instruc
         .try_borrow_instruction_account(transaction_context, 0)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    let player_account = instruction_context
+    let mut player_account = instruction_context
         .try_borrow_instruction_account(transaction_context, 1)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
@@ -530,10 +1240,29 @@ fn process_leave_world(invoke_context: &mut InvokeContext) -> Result<(), Instruc
     if player.authority != *authority_account.get_key() {
         return Err(InstructionError::Custom(2)); // InvalidAuthority
     }
+    // The destination is usually the authority itself
+    drop(authority_account);
 
+    remove_player(invoke_context, &mut world_account, &mut player_account, &player, 3)?;
+
+    program_log(invoke_context, &format!("Player left: {}", player.name_str()));
+
+    Ok(())
+}
+
+/// Remove a player from its world and close the player account
+///
+/// Shared cleanup of `LeaveWorld` and `EvictIdlePlayer`; the rent goes to the
+/// instruction account at `destination_index`.
+fn remove_player(
+    invoke_context: &InvokeContext,
+    world_account: &mut BorrowedAccount,
+    player_account: &mut BorrowedAccount,
+    player: &WorldPlayer,
+    destination_index: IndexOfAccount,
+) -> Result<(), InstructionError> {
     // Load and update world
-    let world_data = world_account.get_data();
-    let mut world = WorldConfig::try_from_slice(world_data)
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
         .map_err(|_| InstructionError::InvalidAccountData)?;
     world.player_count = world.player_count.saturating_sub(1);
 
@@ -542,8 +1271,29 @@ fn process_leave_world(invoke_context: &mut InvokeContext) -> Result<(), Instruc
     borsh::to_writer(&mut world_data_mut[..], &world)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    // Note: Account closing (lamport transfer) would be handled by system program
-    // In builtin context, we just update the world player count
+    // Free the player's team slot
+    if world.teams_enabled() && player.team != TEAM_NONE {
+        update_team_scores(invoke_context, world_account.get_key(), |scores| {
+            scores.remove_member(player.team);
+            Ok(())
+        })?;
+    }
+
+    // Close the player (zero-lamport accounts are purged from the store)
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+    let mut destination_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, destination_index)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    release_name(invoke_context, world_account.get_key(), player, &mut destination_account)?;
+
+    let lamports = player_account.get_lamports();
+    player_account.set_lamports(0)?;
+    destination_account.checked_add_lamports(lamports)?;
+    player_account.set_data_length(0)?;
 
     Ok(())
 }
@@ -680,10 +1430,15 @@ fn process_move_player_3d(
     let mut player = WorldPlayer::try_from_slice(player_data)
         .map_err(|_| InstructionError::InvalidAccountData)?;
 
-    // Verify authority
-    if player.authority != *authority_account.get_key() {
-        return Err(InstructionError::Custom(2)); // InvalidAuthority
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        invoke_context,
+        player_account.get_key(),
+        &player,
+        authority_account.get_key(),
+        3,
+        SESSION_ALLOW_MOVE,
+    )?;
 
     // Verify world
     if player.world != *world_account.get_key() {
@@ -695,12 +1450,14 @@ fn process_move_player_3d(
         return Err(InstructionError::Custom(4)); // PlayerDead
     }
 
-    // Apply 3D movement with physics
-    player.apply_movement_3d(&input, &world);
-
-    // Update last action slot
+    // Apply 3D movement with physics (input is limited while hit-stunned)
     let clock = invoke_context.get_sysvar_cache().get_clock()
         .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    let limited = player.limit_input(&input, clock.slot);
+    player.apply_movement_3d(&limited, &world, clock.slot);
+    player.record_input(&input, clock.slot);
+
+    // Update last action slot
     player.last_action_slot = clock.slot;
 
     // Serialize player back
@@ -711,3 +1468,1999 @@ fn process_move_player_3d(
 
     Ok(())
 }
+
+/// Register an ephemeral session key for a player
+fn process_create_session(
+    invoke_context: &mut InvokeContext,
+    session_key: Pubkey,
+    expiry_slot: u64,
+    allowed_instructions: u8,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=player, 1=session, 2=authority, 3=payer, 4=system_program
+    let player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut session_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owner
+    if player_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    // Only the wallet authority can register sessions
+    let player = WorldPlayer::try_from_slice(player_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if player.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    // Verify session PDA
+    let (expected_pda, bump) = PlayerSession::derive_pda(player_account.get_key(), program_id);
+    if expected_pda != *session_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Sessions are short-lived and must allow at least one instruction
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if expiry_slot <= clock.slot || expiry_slot > clock.slot + MAX_SESSION_DURATION_SLOTS {
+        return Err(world_error(WorldError::InvalidSession));
+    }
+    if allowed_instructions == 0 || allowed_instructions & !SESSION_ALLOW_ALL != 0 {
+        return Err(InstructionError::InvalidInstructionData);
+    }
+
+    let session = PlayerSession {
+        player: *player_account.get_key(),
+        authority: *authority_account.get_key(),
+        session_key,
+        expiry_slot,
+        allowed_instructions,
+        created_slot: clock.slot,
+        bump,
+    };
+
+    // Missing PDAs may be synthesized with another size - fit to PlayerSession
+    if session_account.get_data().len() != PlayerSession::LEN {
+        session_account.set_data_length(PlayerSession::LEN)?;
+    }
+
+    let session_data = session_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut session_data[..], &session)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Revoke a player's session key (close the session account)
+fn process_revoke_session(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=session, 1=signer, 2=destination
+    // The signer is usually also the destination, so release it before borrowing index 2
+    let signer_key = {
+        let signer_account = instruction_context
+            .try_borrow_instruction_account(transaction_context, 1)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+
+        // Verify signer
+        if !signer_account.is_signer() {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        *signer_account.get_key()
+    };
+
+    let mut session_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut destination_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owner
+    if session_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    // Either the wallet or the session key itself may revoke
+    let session = PlayerSession::try_from_slice(session_account.get_data())
+        .map_err(|_| world_error(WorldError::InvalidSession))?;
+    if session.authority != signer_key && session.session_key != signer_key {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    // Close session account (lamports to destination, data cleared)
+    let lamports = session_account.get_lamports();
+    session_account.set_lamports(0)?;
+    destination_account.checked_add_lamports(lamports)?;
+    session_account.set_data_length(0)?;
+
+    Ok(())
+}
+
+/// Spawn an NPC (world authority only)
+fn process_spawn_npc(
+    invoke_context: &mut InvokeContext,
+    mut npc: WorldNpc,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=npc, 2=authority, 3=payer, 4=system_program
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut npc_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Only the world authority can spawn NPCs
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify NPC PDA
+    let (expected_pda, bump) = WorldNpc::derive_pda(world_account.get_key(), npc.npc_id, program_id);
+    if expected_pda != *npc_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Never re-spawn over an existing NPC - its death count keys its loot drops
+    if npc_account.get_data().iter().any(|byte| *byte != 0) {
+        return Err(world_error(WorldError::AccountAlreadyInitialized));
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    npc.initialize(&world, *world_account.get_key(), bump, clock.slot);
+
+    // Missing PDAs may be synthesized with another size - fit to WorldNpc
+    if npc_account.get_data().len() != WorldNpc::LEN {
+        npc_account.set_data_length(WorldNpc::LEN)?;
+    }
+
+    let npc_data = npc_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut npc_data[..], &npc)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Drive an NPC for one tick (world authority only)
+fn process_npc_act(
+    invoke_context: &mut InvokeContext,
+    action: NpcAction,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=npc, 2=authority, 3=target (optional)
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut npc_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owner
+    if npc_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut npc = WorldNpc::try_from_slice(npc_account.get_data())
+        .map_err(|_| world_error(WorldError::NpcNotFound))?;
+
+    // Only the world authority drives NPCs
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if npc.body.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+
+    if !npc.is_alive() {
+        // Dead NPCs only wait for their respawn timer
+        if npc.can_respawn(clock.slot) {
+            npc.respawn(clock.slot);
+        }
+    } else {
+        match action {
+            NpcAction::Idle => {}
+            NpcAction::Move { input } => {
+                let input = npc.body.limit_input(&input, clock.slot);
+                npc.body.apply_movement_3d(&input, &world, clock.slot);
+            }
+            NpcAction::Attack => {
+                if npc.body.is_stunned(clock.slot) {
+                    return Err(world_error(WorldError::PlayerStunned));
+                }
+                if instruction_context.get_number_of_instruction_accounts() <= 3 {
+                    return Err(world_error(WorldError::PlayerNotFound));
+                }
+
+                let mut target_account = instruction_context
+                    .try_borrow_instruction_account(transaction_context, 3)
+                    .map_err(|_| InstructionError::InvalidAccountData)?;
+
+                if target_account.get_owner() != program_id {
+                    return Err(world_error(WorldError::InvalidAccountOwner));
+                }
+
+                let mut target = WorldPlayer::try_from_slice(target_account.get_data())
+                    .map_err(|_| InstructionError::InvalidAccountData)?;
+                if target.world != *world_account.get_key() {
+                    return Err(InstructionError::Custom(3)); // InvalidWorld
+                }
+                if !target.is_alive() {
+                    return Err(InstructionError::Custom(4)); // PlayerDead
+                }
+                if !npc.in_range(&target) {
+                    return Err(world_error(WorldError::TargetOutOfRange));
+                }
+
+                target.apply_damage(npc.damage);
+                if target.is_alive() {
+                    let body = &npc.body;
+                    target.apply_knockback(body.position_x, body.position_z, body.yaw, npc.damage, clock.slot);
+                }
+                npc.body.last_combat_ts = clock.unix_timestamp;
+
+                let target_data_mut = target_account.get_data_mut()
+                    .map_err(|_| InstructionError::InvalidAccountData)?;
+                borsh::to_writer(&mut target_data_mut[..], &target)
+                    .map_err(|_| InstructionError::InvalidAccountData)?;
+            }
+        }
+        npc.body.last_action_slot = clock.slot;
+    }
+
+    let npc_data_mut = npc_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut npc_data_mut[..], &npc)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Attack an NPC (drops loot on kill)
+fn process_attack_npc(
+    invoke_context: &mut InvokeContext,
+    weapon_stats: Option<WeaponStats>,
+    observed_slot: Option<u64>,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=attacker, 2=npc, 3=authority, 4=loot, 5=system_program, 6=session
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut attacker_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut npc_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 3)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owners
+    if attacker_account.get_owner() != program_id || npc_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let mut attacker = WorldPlayer::try_from_slice(attacker_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut npc = WorldNpc::try_from_slice(npc_account.get_data())
+        .map_err(|_| world_error(WorldError::NpcNotFound))?;
+
+    // Verify attacker authority (wallet or session key)
+    verify_player_signer(
+        invoke_context,
+        attacker_account.get_key(),
+        &attacker,
+        authority_account.get_key(),
+        6,
+        SESSION_ALLOW_ATTACK,
+    )?;
+
+    // Both must be in this world and alive
+    if attacker.world != *world_account.get_key() || npc.body.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+    if !attacker.is_alive() || !npc.is_alive() {
+        return Err(InstructionError::Custom(4)); // PlayerDead
+    }
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if attacker.is_stunned(clock.slot) {
+        return Err(world_error(WorldError::PlayerStunned));
+    }
+
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let seen = load_rewound(
+        invoke_context,
+        world_account.get_key(),
+        npc_account.get_key(),
+        &npc.body,
+        world.rewind_slot(observed_slot, clock.slot),
+        clock.slot,
+    )?;
+    if weapon_stats.is_some_and(|weapon| !weapon.in_range(&attacker, &seen)) {
+        return Err(world_error(WorldError::TargetOutOfRange));
+    }
+
+    let damage = weapon_stats.map(|w| w.damage).unwrap_or(DEFAULT_DAMAGE);
+    npc.body.apply_damage(damage);
+    if npc.is_alive() {
+        npc.body.apply_knockback(attacker.position_x, attacker.position_z, attacker.yaw, damage, clock.slot);
+    }
+
+    attacker.last_combat_ts = clock.unix_timestamp;
+    attacker.last_action_slot = clock.slot;
+
+    if !npc.is_alive() {
+        // Drop loot at the NPC's position, keyed by its death count
+        let mut loot_account = instruction_context
+            .try_borrow_instruction_account(transaction_context, 4)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+
+        let (expected_pda, bump) = LootDrop::derive_pda(npc_account.get_key(), npc.deaths, program_id);
+        if expected_pda != *loot_account.get_key() {
+            return Err(InstructionError::InvalidSeeds);
+        }
+
+        // Unclaimed loot is never replaced
+        if loot_account.get_data().iter().any(|byte| *byte != 0) {
+            return Err(world_error(WorldError::AccountAlreadyInitialized));
+        }
+
+        let loot = LootDrop {
+            world: *world_account.get_key(),
+            npc: *npc_account.get_key(),
+            killer: *attacker_account.get_key(),
+            position_x: npc.body.position_x,
+            position_z: npc.body.position_z,
+            amount: npc.loot_amount,
+            dropped_slot: clock.slot,
+            bump,
+        };
+
+        // Missing PDAs may be synthesized with another size - fit to LootDrop
+        if loot_account.get_data().len() != LootDrop::LEN {
+            loot_account.set_data_length(LootDrop::LEN)?;
+        }
+
+        let loot_data = loot_account.get_data_mut()
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        borsh::to_writer(&mut loot_data[..], &loot)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+
+        npc.on_death(clock.slot);
+    }
+
+    let attacker_data_mut = attacker_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut attacker_data_mut[..], &attacker)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let npc_data_mut = npc_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut npc_data_mut[..], &npc)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Configure teams and friendly-fire rules (world authority only)
+fn process_configure_teams(
+    invoke_context: &mut InvokeContext,
+    team_count: u8,
+    friendly_fire: bool,
+    max_team_size: u16,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=team_scores, 2=authority, 3=payer, 4=system_program
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut scores_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    // Team count is bounded and fixed once players are in the world
+    if team_count as usize > MAX_TEAMS {
+        return Err(world_error(WorldError::InvalidTeam));
+    }
+    if team_count != world.team_count && world.player_count > 0 {
+        return Err(world_error(WorldError::InvalidTeam));
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify team scores PDA
+    let (expected_pda, bump) = TeamScores::derive_pda(world_account.get_key(), program_id);
+    if expected_pda != *scores_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Missing PDAs are synthesized zeroed by the loader - initialize on first use
+    if scores_account.get_data().iter().all(|byte| *byte == 0) {
+        scores_account.set_data_length(TeamScores::LEN)?;
+        let scores = TeamScores {
+            world: *world_account.get_key(),
+            bump,
+            ..Default::default()
+        };
+        let scores_data = scores_account.get_data_mut()
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        borsh::to_writer(&mut scores_data[..], &scores)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+    }
+
+    world.team_count = team_count;
+    world.friendly_fire = friendly_fire;
+    world.max_team_size = max_team_size;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Switch a player to a different team
+fn process_choose_team(invoke_context: &mut InvokeContext, team: u8) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=player, 2=authority, 3=team_scores
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut player = WorldPlayer::try_from_slice(player_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Only the wallet can change teams
+    if player.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if player.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+    if player.team == team {
+        return Ok(());
+    }
+
+    let old_team = player.team;
+    update_team_scores(invoke_context, world_account.get_key(), |scores| {
+        scores.check_join(team, &world)?;
+        scores.remove_member(old_team);
+        scores.add_member(team);
+        Ok(())
+    })?;
+    player.team = team;
+
+    let player_data = player_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut player_data[..], &player)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Configure the world's game mode and start a new match (world authority only)
+fn process_configure_game_mode(
+    invoke_context: &mut InvokeContext,
+    mut game_mode: GameMode,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=game_mode, 2=team_scores, 3=authority, 4=payer, 5=system_program
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut game_mode_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 3)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    game_mode.validate(&world).map_err(world_error)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify game mode PDA
+    let (expected_pda, bump) = GameMode::derive_pda(world_account.get_key(), program_id);
+    if expected_pda != *game_mode_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Reconfiguring keeps the match history
+    if let Ok(existing) = GameMode::try_from_slice(game_mode_account.get_data()) {
+        game_mode.match_index = existing.match_index;
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    game_mode.world = *world_account.get_key();
+    game_mode.bump = bump;
+
+    update_team_scores(invoke_context, world_account.get_key(), |scores| {
+        game_mode.start_match(scores, clock.slot);
+        Ok(())
+    })?;
+
+    // Missing PDAs may be synthesized with another size - fit to GameMode
+    if game_mode_account.get_data().len() != GameMode::LEN {
+        game_mode_account.set_data_length(GameMode::LEN)?;
+    }
+
+    let game_mode_data = game_mode_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut game_mode_data[..], &game_mode)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Evaluate game mode objectives for one slot (world authority only)
+fn process_tick_game_mode(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=game_mode, 2=team_scores, 3=authority, 4=match_result,
+    // 5=system_program, 6..=players
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut game_mode_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 3)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify game mode account
+    if game_mode_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    let mut game_mode = GameMode::try_from_slice(game_mode_account.get_data())
+        .map_err(|_| world_error(WorldError::AccountNotInitialized))?;
+    if game_mode.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+
+    // Remaining accounts are the players near the objectives
+    let mut players = Vec::new();
+    for index in 6..instruction_context.get_number_of_instruction_accounts() {
+        let player_account = instruction_context
+            .try_borrow_instruction_account(transaction_context, index)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        if player_account.get_owner() != program_id {
+            return Err(world_error(WorldError::InvalidAccountOwner));
+        }
+        let player = WorldPlayer::try_from_slice(player_account.get_data())
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        if player.world != *world_account.get_key() {
+            return Err(InstructionError::Custom(3)); // InvalidWorld
+        }
+        players.push((*player_account.get_key(), player));
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+
+    let mut finished = None;
+    update_team_scores(invoke_context, world_account.get_key(), |scores| {
+        game_mode.evaluate(&world, scores, &players);
+        if game_mode.is_over(scores, clock.slot) {
+            let (result_pda, bump) =
+                MatchResult::derive_pda(&game_mode.world, game_mode.match_index, program_id);
+            finished = Some((result_pda, game_mode.finish_match(scores, clock.slot, bump)));
+        }
+        Ok(())
+    })?;
+
+    // Record the final scores under the finished match's index
+    if let Some((result_pda, result)) = finished {
+        let mut result_account = instruction_context
+            .try_borrow_instruction_account(transaction_context, 4)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        if result_pda != *result_account.get_key() {
+            return Err(InstructionError::InvalidSeeds);
+        }
+
+        // Results are written once - an initialized account is never overwritten
+        if result_account.get_data().iter().any(|byte| *byte != 0) {
+            return Err(world_error(WorldError::AccountAlreadyInitialized));
+        }
+        if result_account.get_data().len() != MatchResult::LEN {
+            result_account.set_data_length(MatchResult::LEN)?;
+        }
+
+        let result_data = result_account.get_data_mut()
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+        borsh::to_writer(&mut result_data[..], &result)
+            .map_err(|_| InstructionError::InvalidAccountData)?;
+    }
+
+    let game_mode_data = game_mode_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut game_mode_data[..], &game_mode)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Enter the matchmaking queue
+fn process_join_queue(invoke_context: &mut InvokeContext, rating: u32) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=ticket, 1=authority, 2=payer, 3=system_program
+    let mut ticket_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify ticket PDA
+    let (expected_pda, bump) = QueueTicket::derive_pda(authority_account.get_key(), program_id);
+    if expected_pda != *ticket_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    let mut ticket = QueueTicket {
+        authority: *authority_account.get_key(),
+        bump,
+        ..Default::default()
+    };
+    ticket.enqueue(rating, clock.slot);
+
+    // Missing PDAs may be synthesized with another size - fit to QueueTicket
+    if ticket_account.get_data().len() != QueueTicket::LEN {
+        ticket_account.set_data_length(QueueTicket::LEN)?;
+    }
+
+    let ticket_data = ticket_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut ticket_data[..], &ticket)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Leave the matchmaking queue
+fn process_leave_queue(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=ticket, 1=authority
+    let mut ticket_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let mut ticket = QueueTicket::try_from_slice(ticket_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if ticket.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if !ticket.is_waiting() {
+        return Err(world_error(WorldError::TicketNotWaiting));
+    }
+
+    ticket.status = QUEUE_STATUS_IDLE;
+
+    let ticket_data = ticket_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut ticket_data[..], &ticket)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Assign a waiting ticket to a world instance (world authority only)
+fn process_issue_join_ticket(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=ticket, 2=authority
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut ticket_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let mut ticket = QueueTicket::try_from_slice(ticket_account.get_data())
+        .map_err(|_| world_error(WorldError::TicketNotWaiting))?;
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    ticket.issue(*world_account.get_key(), clock.slot).map_err(world_error)?;
+
+    let ticket_data = ticket_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut ticket_data[..], &ticket)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Tear down an empty world (world authority only)
+fn process_close_world(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority, 2=destination
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    // The destination is usually the authority itself - release it before borrowing
+    drop(authority_account);
+    let mut destination_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.player_count > 0 {
+        return Err(world_error(WorldError::WorldNotEmpty));
+    }
+
+    // Drain lamports (zero-lamport accounts are purged from the store) and wipe the data
+    let lamports = world_account.get_lamports();
+    world_account.set_lamports(0)?;
+    destination_account.checked_add_lamports(lamports)?;
+    world_account.set_data_length(0)?;
+
+    Ok(())
+}
+
+/// Move a player to another world through a portal
+fn process_transfer_world(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=source world, 1=source player, 2=dest world, 3=dest player,
+    // 4=authority, 5=payer, 6=system_program, then optional team scores accounts
+    if instruction_context.is_instruction_account_duplicate(2)?.is_some() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+
+    let mut source_world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut source_player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut dest_world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut dest_player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 3)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 4)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owners
+    if source_player_account.get_owner() != program_id
+        || source_world_account.get_owner() != program_id
+        || dest_world_account.get_owner() != program_id
+    {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    // Load the player (wallet only - sessions are bound to the source player)
+    let player = WorldPlayer::try_from_slice(source_player_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if player.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if player.world != *source_world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+    if !player.is_alive() {
+        return Err(InstructionError::Custom(4)); // PlayerDead
+    }
+
+    // Respect the destination's capacity
+    let mut source_world = WorldConfig::try_from_slice(source_world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut dest_world = WorldConfig::try_from_slice(dest_world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if !dest_world.can_join_directly() {
+        return Err(InstructionError::Custom(1)); // WorldFull
+    }
+    let allowlist = load_allowlist(invoke_context, dest_world_account.get_key())?;
+    dest_world
+        .check_access(authority_account.get_key(), allowlist.as_ref(), None)
+        .map_err(world_error)?;
+
+    // Verify the destination player PDA
+    let (dest_pda, bump) = WorldPlayer::derive_pda(
+        dest_world_account.get_key(),
+        authority_account.get_key(),
+        program_id,
+    );
+    if dest_pda != *dest_player_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+    if dest_player_account.get_data().iter().any(|byte| *byte != 0) {
+        return Err(world_error(WorldError::PlayerAlreadyExists));
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+
+    // The name moves with the player: claim it at the destination, release it at the source
+    check_name(
+        invoke_context,
+        dest_world_account.get_key(),
+        &dest_world,
+        authority_account.get_key(),
+        &player.name,
+        clock.slot,
+    )?
+    .map_err(world_error)?;
+    claim_name(
+        invoke_context,
+        dest_world_account.get_key(),
+        &dest_world,
+        authority_account.get_key(),
+        &player.name,
+        clock.slot,
+    )?;
+    release_name(invoke_context, source_world_account.get_key(), &player, &mut authority_account)?;
+
+    // Swap team slots
+    if source_world.teams_enabled() && player.team != TEAM_NONE {
+        update_team_scores(invoke_context, source_world_account.get_key(), |scores| {
+            scores.remove_member(player.team);
+            Ok(())
+        })?;
+    }
+    let mut team = TEAM_NONE;
+    if dest_world.teams_enabled() {
+        update_team_scores(invoke_context, dest_world_account.get_key(), |scores| {
+            team = scores.auto_assign(&dest_world)?;
+            scores.add_member(team);
+            Ok(())
+        })?;
+    }
+
+    // Missing PDAs may be synthesized with another size - fit to WorldPlayer
+    if dest_player_account.get_data().len() != WorldPlayer::LEN {
+        dest_player_account.set_data_length(WorldPlayer::LEN)?;
+    }
+    let moved = player.transfer_to(*dest_world_account.get_key(), &dest_world, bump, team, clock.slot);
+    let dest_player_data = dest_player_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut dest_player_data[..], &moved)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Update both player counts
+    source_world.player_count = source_world.player_count.saturating_sub(1);
+    let source_world_data = source_world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut source_world_data[..], &source_world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    dest_world.player_count += 1;
+    let dest_world_data = dest_world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut dest_world_data[..], &dest_world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Close the source player (zero-lamport accounts are purged from the store)
+    let lamports = source_player_account.get_lamports();
+    source_player_account.set_lamports(0)?;
+    authority_account.checked_add_lamports(lamports)?;
+    source_player_account.set_data_length(0)?;
+
+    Ok(())
+}
+
+/// Set who may join the world (world authority only)
+fn process_set_world_visibility(
+    invoke_context: &mut InvokeContext,
+    visibility: u8,
+    password_salt: [u8; 16],
+    password_hash: [u8; 32],
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if visibility > WORLD_VISIBILITY_PASSWORD {
+        return Err(world_error(WorldError::InvalidVisibility));
+    }
+
+    world.visibility = visibility;
+    world.password_salt = password_salt;
+    world.password_hash = password_hash;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Add a wallet to or remove it from the world's allowlist (world authority only)
+fn process_update_allowlist(
+    invoke_context: &mut InvokeContext,
+    wallet: Pubkey,
+    allowed: bool,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=allowlist, 2=authority, 3=payer, 4=system_program
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut allowlist_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify allowlist PDA
+    let (expected_pda, bump) = WorldAllowlist::derive_pda(world_account.get_key(), program_id);
+    if expected_pda != *allowlist_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Missing PDAs are synthesized zeroed by the loader - initialize on first use
+    let mut allowlist = if allowlist_account.get_data().iter().all(|byte| *byte == 0) {
+        allowlist_account.set_data_length(WorldAllowlist::LEN)?;
+        WorldAllowlist {
+            world: *world_account.get_key(),
+            bump,
+            ..Default::default()
+        }
+    } else {
+        WorldAllowlist::try_from_slice(allowlist_account.get_data())
+            .map_err(|_| InstructionError::InvalidAccountData)?
+    };
+
+    if allowed {
+        allowlist.add(wallet).map_err(world_error)?;
+    } else {
+        allowlist.remove(&wallet);
+    }
+
+    let allowlist_data = allowlist_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut allowlist_data[..], &allowlist)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Hold back slots for VIPs (world authority only)
+fn process_set_reserved_slots(
+    invoke_context: &mut InvokeContext,
+    reserved_slots: u16,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if reserved_slots > world.max_players {
+        return Err(world_error(WorldError::InvalidInstructionData));
+    }
+
+    world.reserved_slots = reserved_slots;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Write a world and its waitlist back, keeping the world's waiting count in sync
+fn store_waitlist(
+    world_account: &mut BorrowedAccount,
+    world: &mut WorldConfig,
+    waitlist_account: &mut BorrowedAccount,
+    waitlist: &WorldWaitlist,
+) -> Result<(), InstructionError> {
+    world.waiting_count = waitlist.count;
+
+    let waitlist_data = waitlist_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut waitlist_data[..], waitlist)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], world)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Load a world's waitlist, verifying its PDA and owner
+fn load_waitlist(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    waitlist_account: &BorrowedAccount,
+) -> Result<WorldWaitlist, InstructionError> {
+    let (expected_pda, _) = WorldWaitlist::derive_pda(world_key, program_id);
+    if expected_pda != *waitlist_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+    if waitlist_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+    WorldWaitlist::try_from_slice(waitlist_account.get_data())
+        .map_err(|_| world_error(WorldError::NotOnWaitlist))
+}
+
+/// Get in line for a world
+fn process_join_waitlist(
+    invoke_context: &mut InvokeContext,
+    name: [u8; 16],
+    password: [u8; 32],
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=waitlist, 2=authority, 3=payer, 4=system_program, 5=allowlist (optional)
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut waitlist_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    if world_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    // Only players who could join may wait
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let allowlist = load_allowlist(invoke_context, world_account.get_key())?;
+    world
+        .check_access(authority_account.get_key(), allowlist.as_ref(), Some(&password))
+        .map_err(world_error)?;
+
+    // Catch bad names now (whether the name is free is checked on admission)
+    let reserved = load_reserved_names(invoke_context, world_account.get_key(), &world)?;
+    world
+        .validate_name(authority_account.get_key(), &name, reserved.as_ref())
+        .map_err(world_error)?;
+
+    // Missing PDAs are synthesized zeroed by the loader - initialize on first use
+    let mut waitlist = if waitlist_account.get_data().iter().all(|byte| *byte == 0) {
+        let (expected_pda, bump) = WorldWaitlist::derive_pda(world_account.get_key(), program_id);
+        if expected_pda != *waitlist_account.get_key() {
+            return Err(InstructionError::InvalidSeeds);
+        }
+        waitlist_account.set_data_length(WorldWaitlist::LEN)?;
+        WorldWaitlist {
+            world: *world_account.get_key(),
+            bump,
+            ..Default::default()
+        }
+    } else {
+        load_waitlist(program_id, world_account.get_key(), &waitlist_account)?
+    };
+
+    let position = waitlist
+        .push(*authority_account.get_key(), name)
+        .map_err(world_error)?;
+    store_waitlist(&mut world_account, &mut world, &mut waitlist_account, &waitlist)?;
+
+    program_log(invoke_context, &format!("Waitlist position {} for {}", position, world.name_str()));
+
+    Ok(())
+}
+
+/// Leave a world's waitlist
+fn process_leave_waitlist(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=waitlist, 2=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut waitlist_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    if world_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut waitlist = load_waitlist(program_id, world_account.get_key(), &waitlist_account)?;
+    waitlist
+        .remove(authority_account.get_key())
+        .map_err(world_error)?;
+
+    store_waitlist(&mut world_account, &mut world, &mut waitlist_account, &waitlist)
+}
+
+/// Admit the head of the waitlist into an open regular slot
+fn process_admit_from_waitlist(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=waitlist, 2=player, 3=payer, 4=system_program, 5=team_scores (optional)
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut waitlist_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+    if world_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if !world.has_open_slot() {
+        return Err(InstructionError::Custom(1)); // WorldFull
+    }
+
+    let mut waitlist = load_waitlist(program_id, world_account.get_key(), &waitlist_account)?;
+    let head = waitlist
+        .pop_front()
+        .ok_or(world_error(WorldError::NotOnWaitlist))?;
+
+    // Already in the world (joined another way) - just drop the entry
+    if player_account.get_data().iter().any(|byte| *byte != 0) {
+        let (expected_pda, _) = WorldPlayer::derive_pda(world_account.get_key(), &head.wallet, program_id);
+        if expected_pda != *player_account.get_key() {
+            return Err(InstructionError::InvalidSeeds);
+        }
+        store_waitlist(&mut world_account, &mut world, &mut waitlist_account, &waitlist)?;
+        program_log(invoke_context, &format!("Waitlist dropped {}: already in {}", head.wallet, world.name_str()));
+        return Ok(());
+    }
+
+    // Name no longer allowed or free - drop the entry so the line keeps moving
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if let Err(err) = check_name(
+        invoke_context,
+        world_account.get_key(),
+        &world,
+        &head.wallet,
+        &head.name,
+        clock.slot,
+    )? {
+        store_waitlist(&mut world_account, &mut world, &mut waitlist_account, &waitlist)?;
+        program_log(invoke_context, &format!("Waitlist dropped {}: {}", head.wallet, err));
+        return Ok(());
+    }
+
+    spawn_player(
+        invoke_context,
+        &mut world,
+        world_account.get_key(),
+        &mut player_account,
+        &head.wallet,
+        head.name,
+    )?;
+    store_waitlist(&mut world_account, &mut world, &mut waitlist_account, &waitlist)?;
+
+    program_log(invoke_context, &format!("Waitlist admitted {} into {}", head.wallet, world.name_str()));
+
+    Ok(())
+}
+
+/// Admit a VIP ahead of the waitlist (world authority only)
+fn process_admit_vip(invoke_context: &mut InvokeContext, name: [u8; 16]) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=player, 2=vip, 3=authority, 4=payer, 5=system_program, 6=team_scores (optional)
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let vip_key = *transaction_context.get_key_of_account_at_index(
+        instruction_context.get_index_of_instruction_account_in_transaction(2)?,
+    )?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 3)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    // VIPs may take reserved slots
+    if world.is_full() {
+        return Err(InstructionError::Custom(1)); // WorldFull
+    }
+
+    spawn_player(
+        invoke_context,
+        &mut world,
+        world_account.get_key(),
+        &mut player_account,
+        &vip_key,
+        name,
+    )?;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    program_log(invoke_context, &format!("VIP admitted {} into {}", vip_key, world.name_str()));
+
+    Ok(())
+}
+
+/// Set the world's idle timeout (world authority only)
+fn process_set_idle_timeout(
+    invoke_context: &mut InvokeContext,
+    idle_timeout_slots: u64,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    world.idle_timeout_slots = idle_timeout_slots;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Evict a player that has been idle past the world's timeout (permissionless)
+fn process_evict_idle_player(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=player, 2=player wallet
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let player = WorldPlayer::try_from_slice(player_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if player.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+
+    // Rent goes back to the player, never to whoever submitted the eviction
+    let wallet_key = transaction_context.get_key_of_account_at_index(
+        instruction_context.get_index_of_instruction_account_in_transaction(2)?,
+    )?;
+    if player.authority != *wallet_key {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if !world.is_idle(&player, clock.slot) {
+        return Err(world_error(WorldError::PlayerNotIdle));
+    }
+
+    remove_player(invoke_context, &mut world_account, &mut player_account, &player, 2)?;
+
+    program_log(
+        invoke_context,
+        &format!(
+            "Player evicted: {} ({}) idle since slot {}",
+            player.name_str(),
+            player.authority,
+            player.last_action_slot
+        ),
+    );
+
+    Ok(())
+}
+
+/// Set the world's player name rules (world authority only)
+fn process_configure_names(
+    invoke_context: &mut InvokeContext,
+    unique_names: bool,
+    name_charset: u8,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if name_charset > NAME_CHARSET_IDENTIFIER {
+        return Err(world_error(WorldError::InvalidInstructionData));
+    }
+
+    world.unique_names = unique_names;
+    world.name_charset = name_charset;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Reserve a word or release it (world authority only)
+fn process_update_reserved_names(
+    invoke_context: &mut InvokeContext,
+    word: [u8; 16],
+    reserved: bool,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=reserved_names, 2=authority, 3=payer, 4=system_program
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut reserved_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 2)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify reserved-name list PDA
+    let (expected_pda, bump) = ReservedNames::derive_pda(world_account.get_key(), program_id);
+    if expected_pda != *reserved_account.get_key() {
+        return Err(InstructionError::InvalidSeeds);
+    }
+
+    // Missing PDAs are synthesized zeroed by the loader - initialize on first use
+    let mut list = if reserved_account.get_data().iter().all(|byte| *byte == 0) {
+        reserved_account.set_data_length(ReservedNames::LEN)?;
+        ReservedNames {
+            world: *world_account.get_key(),
+            bump,
+            ..Default::default()
+        }
+    } else {
+        ReservedNames::try_from_slice(reserved_account.get_data())
+            .map_err(|_| InstructionError::InvalidAccountData)?
+    };
+
+    if reserved {
+        list.add(&word).map_err(world_error)?;
+    } else {
+        list.remove(&word);
+    }
+
+    let reserved_data = reserved_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut reserved_data[..], &list)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    world.reserved_name_count = list.count;
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Set the world's ability timings (world authority only)
+fn process_configure_abilities(
+    invoke_context: &mut InvokeContext,
+    dash_speed: i16,
+    dash_cooldown_slots: u64,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if dash_speed < 0 {
+        return Err(world_error(WorldError::InvalidInstructionData));
+    }
+
+    world.dash_speed = dash_speed;
+    world.dash_cooldown_slots = dash_cooldown_slots;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Set how far attacks may rewind targets (world authority only)
+fn process_set_max_rewind(
+    invoke_context: &mut InvokeContext,
+    max_rewind_slots: u64,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    // Position histories only reach back POSITION_HISTORY_SLOTS slots
+    if max_rewind_slots > MAX_REWIND_SLOTS {
+        return Err(world_error(WorldError::InvalidInstructionData));
+    }
+
+    world.max_rewind_slots = max_rewind_slots;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Dead-reckon a slot of missing movement input (permissionless)
+fn process_extrapolate_movement(invoke_context: &mut InvokeContext) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=player
+    let world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let mut player_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let program_id = instruction_context
+        .get_last_program_key(transaction_context)
+        .map_err(|_| InstructionError::UnsupportedProgramId)?;
+
+    // Verify account owners
+    if world_account.get_owner() != program_id || player_account.get_owner() != program_id {
+        return Err(world_error(WorldError::InvalidAccountOwner));
+    }
+
+    let world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut player = WorldPlayer::try_from_slice(player_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if player.world != *world_account.get_key() {
+        return Err(InstructionError::Custom(3)); // InvalidWorld
+    }
+
+    // Predicted movement isn't an action, so idle eviction still applies
+    let clock = invoke_context.get_sysvar_cache().get_clock()
+        .map_err(|_| InstructionError::UnsupportedSysvar)?;
+    if !player.extrapolate(&world, clock.slot) {
+        return Err(world_error(WorldError::InputNotMissing));
+    }
+
+    let player_data = player_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut player_data[..], &player)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
+
+/// Set how many missing inputs in a row are extrapolated (world authority only)
+fn process_set_max_extrapolation(
+    invoke_context: &mut InvokeContext,
+    max_extrapolation_slots: u64,
+) -> Result<(), InstructionError> {
+    let transaction_context = &*invoke_context.transaction_context;
+    let instruction_context = transaction_context
+        .get_current_instruction_context()
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+
+    // Account indices: 0=world, 1=authority
+    let mut world_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 0)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    let authority_account = instruction_context
+        .try_borrow_instruction_account(transaction_context, 1)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    // Verify authority is signer
+    if !authority_account.is_signer() {
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(world_account.get_data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if world.authority != *authority_account.get_key() {
+        return Err(InstructionError::Custom(2)); // InvalidAuthority
+    }
+    if max_extrapolation_slots > MAX_EXTRAPOLATION_SLOTS {
+        return Err(world_error(WorldError::InvalidInstructionData));
+    }
+
+    world.max_extrapolation_slots = max_extrapolation_slots;
+
+    let world_data = world_account.get_data_mut()
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    borsh::to_writer(&mut world_data[..], &world)
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+
+    Ok(())
+}
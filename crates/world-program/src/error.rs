@@ -47,6 +47,78 @@ pub enum WorldError {
 
     #[error("Invalid instruction data")]
     InvalidInstructionData,
+
+    #[error("Invalid session")]
+    InvalidSession,
+
+    #[error("Session expired")]
+    SessionExpired,
+
+    #[error("Instruction not allowed for session")]
+    SessionNotAllowed,
+
+    #[error("NPC not found")]
+    NpcNotFound,
+
+    #[error("Cannot attack a teammate")]
+    FriendlyFire,
+
+    #[error("Team is full")]
+    TeamFull,
+
+    #[error("Invalid team")]
+    InvalidTeam,
+
+    #[error("Invalid game mode")]
+    InvalidGameMode,
+
+    #[error("Queue ticket is not waiting for a match")]
+    TicketNotWaiting,
+
+    #[error("World still has players")]
+    WorldNotEmpty,
+
+    #[error("Wallet is not on the world's allowlist")]
+    NotAllowlisted,
+
+    #[error("Invalid world password")]
+    InvalidPassword,
+
+    #[error("Invalid world visibility")]
+    InvalidVisibility,
+
+    #[error("World allowlist is full")]
+    AllowlistFull,
+
+    #[error("World waitlist is full")]
+    WaitlistFull,
+
+    #[error("Already on the waitlist")]
+    AlreadyWaiting,
+
+    #[error("Not on the waitlist")]
+    NotOnWaitlist,
+
+    #[error("Player is not idle")]
+    PlayerNotIdle,
+
+    #[error("Invalid player name")]
+    InvalidName,
+
+    #[error("Player name is taken")]
+    NameTaken,
+
+    #[error("Player name is reserved")]
+    NameReserved,
+
+    #[error("Reserved-name list is full")]
+    ReservedNamesFull,
+
+    #[error("Player is stunned")]
+    PlayerStunned,
+
+    #[error("Player input is not missing")]
+    InputNotMissing,
 }
 
 impl From<WorldError> for ProgramError {
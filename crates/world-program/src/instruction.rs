@@ -1,7 +1,12 @@
 //! World Program Instructions
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::state::{MovementInput, MovementInput3D, WeaponStats};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    constants::MAX_TEAMS,
+    state::{MovementInput, MovementInput3D, WeaponStats},
+};
 
 /// World program instructions
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
@@ -26,12 +31,25 @@ pub enum WorldInstruction {
 
     /// Join the world (create player account)
     ///
+    /// In team worlds the player is auto-assigned to the smallest team.
+    /// Password worlds must be joined with `JoinWorldWithPassword`. Fails with
+    /// `WorldFull` while no regular slot is open or players are waiting; use
+    /// `JoinWaitlist` then. The name must pass the world's name rules
+    /// (`ConfigureNames`) and, in unique-name worlds, is claimed for the wallet.
+    ///
+    /// Accounts after the system program are found by address, so only the
+    /// ones the world needs have to be passed.
+    ///
     /// Accounts:
     /// 0. `[writable]` World config account (player_count is incremented)
     /// 1. `[writable]` World player account (PDA)
     /// 2. `[signer]` Player authority (wallet)
     /// 3. `[signer, writable]` Payer
     /// 4. `[]` System program
+    /// 5. `[writable]` Team scores account (required when the world has teams)
+    /// 6. `[]` World allowlist account (required in allowlist worlds)
+    /// 7. `[writable]` Name claim account (PDA of the normalized name, required in unique-name worlds)
+    /// 8. `[]` Reserved-name list account (required when the world reserves names)
     JoinWorld {
         /// Player name (max 16 bytes)
         name: [u8; 16],
@@ -42,7 +60,8 @@ pub enum WorldInstruction {
     /// Accounts:
     /// 0. `[]` World config account
     /// 1. `[writable]` World player account
-    /// 2. `[signer]` Player authority
+    /// 2. `[signer]` Player authority or session key
+    /// 3. `[]` Player session account (optional, required when signed by a session key)
     MovePlayer {
         /// Movement input
         input: MovementInput,
@@ -50,14 +69,29 @@ pub enum WorldInstruction {
 
     /// Attack another player
     ///
+    /// A surviving target is knocked back away from the attacker (harder for
+    /// heavier hits) and hit-stunned for `HIT_STUN_SLOTS`. Stunned players
+    /// can't attack and their movement input is ignored.
+    ///
+    /// Range is checked against where the target stood at `observed_slot`
+    /// (the slot the attacker's client was showing), clamped to the world's
+    /// maximum rewind.
+    ///
     /// Accounts:
     /// 0. `[]` World config account
     /// 1. `[writable]` Attacker player account
     /// 2. `[writable]` Target player account
-    /// 3. `[signer]` Attacker authority
+    /// 3. `[signer]` Attacker authority or session key
+    /// 4. `[]` Attacker session account (optional, required when signed by a session key)
+    /// 5. `[writable]` Team scores account (required when the world has teams; may
+    ///    take index 4 when no session account is passed)
+    /// 6. `[]` World position history account (optional, found by its PDA; without
+    ///    it the target's current position is used)
     Attack {
         /// Optional weapon stats from L1 (uses default if None)
         weapon_stats: Option<WeaponStats>,
+        /// Slot the attacker saw the target at (None = current slot)
+        observed_slot: Option<u64>,
     },
 
     /// Heal self
@@ -65,7 +99,8 @@ pub enum WorldInstruction {
     /// Accounts:
     /// 0. `[]` World config account
     /// 1. `[writable]` Player account
-    /// 2. `[signer]` Player authority
+    /// 2. `[signer]` Player authority or session key
+    /// 3. `[]` Player session account (optional, required when signed by a session key)
     Heal {
         /// Heal amount (0 = use default)
         amount: u16,
@@ -78,6 +113,8 @@ pub enum WorldInstruction {
     /// 1. `[writable]` World player account
     /// 2. `[signer]` Player authority
     /// 3. `[writable]` Rent destination
+    /// 4. `[writable]` Team scores account (required when the world has teams)
+    /// 5. `[writable]` Name claim account (releases the player's name; otherwise it expires)
     LeaveWorld,
 
     /// Update world config (admin only)
@@ -104,9 +141,457 @@ pub enum WorldInstruction {
     /// Accounts:
     /// 0. `[]` World config account
     /// 1. `[writable]` World player account
-    /// 2. `[signer]` Player authority
+    /// 2. `[signer]` Player authority or session key
+    /// 3. `[]` Player session account (optional, required when signed by a session key)
     MovePlayer3D {
         /// 3D movement input (camera-relative with jump)
         input: MovementInput3D,
     },
+
+    /// Register an ephemeral session key that may sign gameplay instructions
+    ///
+    /// Replaces any existing session for the player.
+    ///
+    /// Accounts:
+    /// 0. `[]` World player account
+    /// 1. `[writable]` Player session account (PDA)
+    /// 2. `[signer]` Player authority (wallet)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    CreateSession {
+        /// Ephemeral public key allowed to sign
+        session_key: Pubkey,
+        /// Last slot (inclusive) the session is valid for
+        expiry_slot: u64,
+        /// Bitmask of SESSION_ALLOW_* instructions
+        allowed_instructions: u8,
+    },
+
+    /// Revoke the player's session key (close session account)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` Player session account
+    /// 1. `[signer]` Player authority or the session key itself
+    /// 2. `[writable]` Rent destination
+    RevokeSession,
+
+    /// Spawn an NPC (world authority only)
+    ///
+    /// Fails if the NPC was already spawned; dead NPCs respawn through `NpcAct`.
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` NPC account (PDA)
+    /// 2. `[signer]` World authority
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    SpawnNpc {
+        /// NPC identifier within the world
+        npc_id: u32,
+        /// NPC kind (NPC_KIND_*)
+        kind: u8,
+        /// NPC name (max 16 bytes)
+        name: [u8; 16],
+        /// Max health (0 = default)
+        max_health: u16,
+        /// Damage per attack (0 = default)
+        damage: u16,
+        /// Attack range in fixed-point units (0 = default)
+        attack_range: u32,
+        /// Spawn X position (fixed-point)
+        spawn_x: i32,
+        /// Spawn Z position (fixed-point)
+        spawn_z: i32,
+        /// Slots between death and respawn (0 = default)
+        respawn_delay_slots: u64,
+        /// Loot dropped on death
+        loot_amount: u32,
+    },
+
+    /// Drive an NPC for one tick (world authority only)
+    ///
+    /// Dead NPCs ignore the action and respawn once their timer expires.
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` NPC account
+    /// 2. `[signer]` World authority
+    /// 3. `[writable]` Target player account (required for `NpcAction::Attack`)
+    NpcAct {
+        /// Action chosen by the AI driver
+        action: NpcAction,
+    },
+
+    /// Attack an NPC (knockback, hit-stun and lag compensation as for `Attack`)
+    ///
+    /// Killing the NPC drops loot and schedules its respawn.
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` Attacker player account
+    /// 2. `[writable]` NPC account
+    /// 3. `[signer, writable]` Attacker authority or session key (pays for the loot drop)
+    /// 4. `[writable]` Loot drop account (empty PDA for the NPC's current death count)
+    /// 5. `[]` System program
+    /// 6. `[]` Attacker session account (optional, required when signed by a session key)
+    /// 7. `[]` World position history account (optional, found by its PDA; without
+    ///    it the NPC's current position is used)
+    AttackNpc {
+        /// Optional weapon stats from L1 (uses default if None)
+        weapon_stats: Option<WeaponStats>,
+        /// Slot the attacker saw the NPC at (None = current slot)
+        observed_slot: Option<u64>,
+    },
+
+    /// Configure teams and friendly-fire rules (world authority only)
+    ///
+    /// Creates the team scores account on first use. `team_count` can only
+    /// change while the world is empty.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` Team scores account (PDA)
+    /// 2. `[signer]` World authority
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    ConfigureTeams {
+        /// Number of teams (0 = free-for-all, max MAX_TEAMS)
+        team_count: u8,
+        /// Whether teammates can damage each other
+        friendly_fire: bool,
+        /// Maximum players per team (0 = unlimited)
+        max_team_size: u16,
+    },
+
+    /// Switch to a different team (within the world's team-size limit)
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` World player account
+    /// 2. `[signer]` Player authority (wallet)
+    /// 3. `[writable]` Team scores account
+    ChooseTeam {
+        /// Team to join (1..=team_count)
+        team: u8,
+    },
+
+    /// Set up a game mode for a team world and start a new match (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` Game mode account (PDA)
+    /// 2. `[writable]` Team scores account
+    /// 3. `[signer]` World authority
+    /// 4. `[signer, writable]` Payer
+    /// 5. `[]` System program
+    ConfigureGameMode {
+        /// Game mode (GAME_MODE_*)
+        mode: u8,
+        /// Hill center X (fixed-point)
+        objective_x: i32,
+        /// Hill center Z (fixed-point)
+        objective_z: i32,
+        /// Hill radius, and flag pickup / capture radius (fixed-point)
+        objective_radius: u32,
+        /// Flag base X per team (fixed-point, index = team - 1)
+        flag_base_x: [i32; MAX_TEAMS],
+        /// Flag base Z per team (fixed-point, index = team - 1)
+        flag_base_z: [i32; MAX_TEAMS],
+        /// Match length in slots (0 = no time limit)
+        match_duration_slots: u64,
+        /// Score that ends the match (0 = no score limit)
+        score_limit: u32,
+    },
+
+    /// Evaluate objectives for one slot (world authority only, submitted by the leader)
+    ///
+    /// Ends the match once the timer or score limit is hit: the final scores are
+    /// written to a new match result account and the next round starts.
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` Game mode account
+    /// 2. `[writable]` Team scores account
+    /// 3. `[signer, writable]` World authority (pays for the match result)
+    /// 4. `[writable]` Match result account (PDA for the current match index)
+    /// 5. `[]` System program
+    /// 6. `[]` World player accounts near the objectives, one per account from here on
+    TickGameMode,
+
+    /// Enter the matchmaking queue (re-entering refreshes the ticket)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` Queue ticket account (PDA)
+    /// 1. `[signer]` Player authority (wallet)
+    /// 2. `[signer, writable]` Payer
+    /// 3. `[]` System program
+    JoinQueue {
+        /// Skill rating used to group similar players
+        rating: u32,
+    },
+
+    /// Leave the matchmaking queue
+    ///
+    /// Accounts:
+    /// 0. `[writable]` Queue ticket account
+    /// 1. `[signer]` Player authority (wallet)
+    LeaveQueue,
+
+    /// Assign a waiting ticket to a world instance (world authority only, submitted by the matchmaker)
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account (instance)
+    /// 1. `[writable]` Queue ticket account
+    /// 2. `[signer]` World authority
+    IssueJoinTicket,
+
+    /// Tear down an empty world (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    /// 2. `[writable]` Destination for the world's lamports
+    CloseWorld,
+
+    /// Move a player to another world through a portal
+    ///
+    /// Closes the player in the source world and recreates it at the
+    /// destination's spawn point, keeping health, stats and name. Team worlds
+    /// also need their team scores accounts (source and/or destination), and an
+    /// allowlist destination its allowlist account, appended after the system
+    /// program. Password worlds cannot be entered through a portal. The name
+    /// must pass the destination's name rules; name claim accounts claim it at
+    /// the destination and release it at the source.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` Source world config account
+    /// 1. `[writable]` Source world player account
+    /// 2. `[writable]` Destination world config account
+    /// 3. `[writable]` Destination world player account (PDA)
+    /// 4. `[signer, writable]` Player authority (wallet, receives the source account's lamports)
+    /// 5. `[signer, writable]` Payer
+    /// 6. `[]` System program
+    TransferWorld,
+
+    /// Set who may join the world (world authority only)
+    ///
+    /// The password hash is `WorldConfig::hash_password(salt, password)`,
+    /// computed by the client so the password never appears on chain here.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    SetWorldVisibility {
+        /// WORLD_VISIBILITY_* setting
+        visibility: u8,
+        /// Salt mixed into the password hash
+        password_salt: [u8; 16],
+        /// Salted hash of the zero-padded password (password worlds only)
+        password_hash: [u8; 32],
+    },
+
+    /// Add a wallet to or remove it from the world's allowlist (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` World allowlist account (PDA, created on first use)
+    /// 2. `[signer]` World authority
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    UpdateAllowlist {
+        /// Wallet to add or remove
+        wallet: Pubkey,
+        /// true = add, false = remove
+        allowed: bool,
+    },
+
+    /// Join a password-protected world
+    ///
+    /// Same accounts as `JoinWorld`.
+    JoinWorldWithPassword {
+        /// Player name (max 16 bytes)
+        name: [u8; 16],
+        /// World password (zero-padded)
+        password: [u8; 32],
+    },
+
+    /// Hold back slots for VIPs (world authority only)
+    ///
+    /// Regular joins and waitlist admissions stop `reserved_slots` short of
+    /// `max_players`; only `AdmitVip` fills the rest.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    SetReservedSlots {
+        /// Slots kept for VIPs
+        reserved_slots: u16,
+    },
+
+    /// Get in line for a world (first in, first admitted)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` World waitlist account (PDA, created on first use)
+    /// 2. `[signer]` Player authority (wallet)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    /// 5. `[]` World allowlist account (required in allowlist worlds)
+    /// 6. `[]` Reserved-name list account (required when the world reserves names)
+    JoinWaitlist {
+        /// Player name (max 16 bytes)
+        name: [u8; 16],
+        /// World password (zero-padded, password worlds only)
+        password: [u8; 32],
+    },
+
+    /// Leave a world's waitlist
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` World waitlist account
+    /// 2. `[signer]` Player authority (wallet)
+    LeaveWaitlist,
+
+    /// Admit the head of the waitlist into an open regular slot
+    ///
+    /// Anyone may submit this; the leader's waitlist driver does every tick.
+    /// A head that already has a player in the world, or whose name is no
+    /// longer allowed or free, is dropped instead.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` World waitlist account
+    /// 2. `[writable]` World player account (PDA of the head's wallet)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    /// 5. `[writable]` Team scores account (required when the world has teams)
+    /// 6. `[writable]` Name claim account (required in unique-name worlds)
+    /// 7. `[]` Reserved-name list account (required when the world reserves names)
+    AdmitFromWaitlist,
+
+    /// Admit a VIP ahead of the waitlist, using reserved slots if needed (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` World player account (PDA of the VIP's wallet)
+    /// 2. `[]` VIP wallet
+    /// 3. `[signer]` World authority
+    /// 4. `[signer, writable]` Payer
+    /// 5. `[]` System program
+    /// 6. `[writable]` Team scores account (required when the world has teams)
+    /// 7. `[writable]` Name claim account (required in unique-name worlds)
+    /// 8. `[]` Reserved-name list account (required when the world reserves names)
+    AdmitVip {
+        /// Player name (max 16 bytes)
+        name: [u8; 16],
+    },
+
+    /// Set how long a player may go without acting before being evicted (admin only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    SetIdleTimeout {
+        /// Inactivity limit in slots (0 = never evict)
+        idle_timeout_slots: u64,
+    },
+
+    /// Evict a player whose last action is older than the world's idle timeout
+    ///
+    /// Anyone may submit this; the leader's idle evictor does every tick.
+    /// Cleans up like `LeaveWorld`, returning rent to the player's wallet.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` World player account
+    /// 2. `[writable]` Player authority (wallet, receives the rent)
+    /// 3. `[writable]` Team scores account (required when the world has teams)
+    /// 4. `[writable]` Name claim account (releases the player's name)
+    EvictIdlePlayer,
+
+    /// Set the world's player name rules (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    ConfigureNames {
+        /// One wallet per normalized name, enforced with name claim accounts
+        unique_names: bool,
+        /// Characters allowed in names (NAME_CHARSET_*)
+        name_charset: u8,
+    },
+
+    /// Reserve a word or release it (world authority only)
+    ///
+    /// Names containing a reserved word (case-insensitive) are rejected.
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[writable]` Reserved-name list account (PDA, created on first use)
+    /// 2. `[signer]` World authority
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    UpdateReservedNames {
+        /// Word to reserve or release
+        word: [u8; 16],
+        /// true = reserve, false = release
+        reserved: bool,
+    },
+
+    /// Set the world's ability timings (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    ConfigureAbilities {
+        /// Dash burst speed (0 = dashing disabled)
+        dash_speed: i16,
+        /// Slots a player waits between dashes
+        dash_cooldown_slots: u64,
+    },
+
+    /// Set how far attacks may rewind targets for lag compensation (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    SetMaxRewind {
+        /// Rewind limit in slots (0 = no rewind, at most `MAX_REWIND_SLOTS`)
+        max_rewind_slots: u64,
+    },
+
+    /// Repeat a moving player's last input for a slot they sent none (dead reckoning)
+    ///
+    /// Anyone may submit this; the leader's movement extrapolator does every
+    /// tick. Fails unless the player's input for the slot is missing and the
+    /// world's extrapolation limit hasn't been reached.
+    ///
+    /// Accounts:
+    /// 0. `[]` World config account
+    /// 1. `[writable]` World player account
+    ExtrapolateMovement,
+
+    /// Set how many missing inputs in a row are extrapolated (world authority only)
+    ///
+    /// Accounts:
+    /// 0. `[writable]` World config account
+    /// 1. `[signer]` World authority
+    SetMaxExtrapolation {
+        /// Limit in slots (0 = off, at most `MAX_EXTRAPOLATION_SLOTS`)
+        max_extrapolation_slots: u64,
+    },
+}
+
+/// Action submitted for an NPC by the AI driver
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug)]
+pub enum NpcAction {
+    /// Do nothing this tick (still advances respawn)
+    Idle,
+    /// Move with the player physics model
+    Move {
+        input: MovementInput3D,
+    },
+    /// Attack the target player passed as account 3
+    Attack,
 }
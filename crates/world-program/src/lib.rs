@@ -7,18 +7,11 @@
 //! - WorldConfig: Global world configuration
 //! - WorldPlayer: Per-player state (position, health, etc.)
 
-use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
-    clock::Clock,
+    account_info::AccountInfo,
     entrypoint,
     entrypoint::ProgramResult,
-    msg,
-    program_error::ProgramError,
     pubkey::Pubkey,
-    rent::Rent,
-    system_instruction,
-    sysvar::Sysvar,
 };
 
 pub mod state;
@@ -27,8 +20,12 @@ pub mod processor;
 pub mod error;
 pub mod builtin;
 
-pub use state::{WorldConfig, WorldPlayer, MovementInput, MovementInput3D, WeaponStats};
-pub use instruction::WorldInstruction;
+pub use state::{
+    WorldConfig, WorldPlayer, WorldNpc, LootDrop, PlayerSession, TeamScores, GameMode, MatchResult,
+    QueueTicket, WorldAllowlist, WorldWaitlist, WaitlistEntry, NameClaim, ReservedNames, PositionHistory,
+    MovementInput, MovementInput3D, WeaponStats,
+};
+pub use instruction::{NpcAction, WorldInstruction};
 pub use error::WorldError;
 
 // World Program ID - unique identifier for the L2 game world program
@@ -60,11 +57,25 @@ pub mod constants {
     /// Default heal amount
     pub const DEFAULT_HEAL: u16 = 20;
 
+    // Hit reactions
+    /// Knockback speed from a hit before damage scaling
+    pub const KNOCKBACK_BASE: i16 = 150;
+    /// Extra knockback speed per point of damage
+    pub const KNOCKBACK_PER_DAMAGE: i16 = 10;
+    /// Strongest knockback a single hit can deal
+    pub const MAX_KNOCKBACK: i16 = 750;
+    /// Upward velocity from a hit (lifts the target off the ground)
+    pub const KNOCKBACK_LIFT: i16 = 150;
+    /// Slots a hit target loses control for (200ms at 30Hz)
+    pub const HIT_STUN_SLOTS: u64 = 6;
+
     // Movement speeds
     /// Sprint speed (units per tick)
     pub const SPRINT_SPEED: i16 = 500;
     /// Normal walking speed
     pub const NORMAL_SPEED: i16 = 250;
+    /// Crouch walking speed
+    pub const CROUCH_SPEED: i16 = 125;
     /// Acceleration per tick
     pub const ACCELERATION: i16 = 100;
     /// Friction/deceleration per tick when no input
@@ -82,6 +93,32 @@ pub mod constants {
     /// Maximum height for jumping
     pub const MAX_HEIGHT: i32 = 50_000; // 50 world units
 
+    // Hitbox
+    /// Standing hitbox height (fixed-point, 1.8 world units)
+    pub const STANDING_HEIGHT: i32 = 1_800;
+    /// Crouching hitbox height (fixed-point, 1.1 world units)
+    pub const CROUCH_HEIGHT: i32 = 1_100;
+
+    // Abilities
+    /// Default dash speed (0 disables dashing)
+    pub const DEFAULT_DASH_SPEED: i16 = 1_200;
+    /// Default slots between dashes (1.5 seconds at 30Hz)
+    pub const DEFAULT_DASH_COOLDOWN_SLOTS: u64 = 45;
+
+    // Lag compensation
+    /// Slots of positions each world's history covers for rewinding hits
+    pub const POSITION_HISTORY_SLOTS: u64 = 10;
+    /// Longest rewind a world may allow (the history always covers it)
+    pub const MAX_REWIND_SLOTS: u64 = POSITION_HISTORY_SLOTS - 1;
+    /// Default rewind limit (200ms at 30Hz)
+    pub const DEFAULT_MAX_REWIND_SLOTS: u64 = 6;
+
+    // Dead reckoning
+    /// Longest run of missing inputs a world may fill in (500ms at 30Hz)
+    pub const MAX_EXTRAPOLATION_SLOTS: u64 = 15;
+    /// Default extrapolation limit (100ms at 30Hz)
+    pub const DEFAULT_MAX_EXTRAPOLATION_SLOTS: u64 = 3;
+
     // Scale
     /// Fixed point scale (1000 = 1.0)
     pub const FIXED_POINT_SCALE: i32 = 1000;
@@ -91,6 +128,108 @@ pub mod constants {
     pub const WORLD_SEED: &[u8] = b"world";
     /// World player seed
     pub const WORLD_PLAYER_SEED: &[u8] = b"world_player";
+    /// Player session seed
+    pub const SESSION_SEED: &[u8] = b"session";
+    /// NPC seed
+    pub const NPC_SEED: &[u8] = b"npc";
+    /// Loot drop seed
+    pub const LOOT_SEED: &[u8] = b"loot";
+    /// Team scores seed
+    pub const TEAM_SCORES_SEED: &[u8] = b"team_scores";
+    /// Game mode seed
+    pub const GAME_MODE_SEED: &[u8] = b"game_mode";
+    /// Match result seed
+    pub const MATCH_RESULT_SEED: &[u8] = b"match_result";
+    /// Matchmaking queue ticket seed
+    pub const QUEUE_SEED: &[u8] = b"queue";
+    /// World allowlist seed
+    pub const ALLOWLIST_SEED: &[u8] = b"allowlist";
+    /// World waitlist seed
+    pub const WAITLIST_SEED: &[u8] = b"waitlist";
+    /// World position history seed
+    pub const POSITION_HISTORY_SEED: &[u8] = b"position_history";
+
+    // Teams
+    /// Maximum teams per world
+    pub const MAX_TEAMS: usize = 4;
+    /// Player is not on a team
+    pub const TEAM_NONE: u8 = 0;
+    /// Team score awarded per enemy kill
+    pub const TEAM_KILL_SCORE: u32 = 1;
+
+    // Game modes
+    /// King of the hill: the only team on the hill scores every slot
+    pub const GAME_MODE_KING_OF_THE_HILL: u8 = 0;
+    /// Capture the flag: carry an enemy flag to your own base to score
+    pub const GAME_MODE_CAPTURE_THE_FLAG: u8 = 1;
+    /// Score per slot for holding the hill
+    pub const KOTH_SCORE_PER_SLOT: u32 = 1;
+    /// Score per flag capture
+    pub const CTF_CAPTURE_SCORE: u32 = 1;
+
+    // Matchmaking
+    /// Ticket is not queued
+    pub const QUEUE_STATUS_IDLE: u8 = 0;
+    /// Ticket is waiting for a match
+    pub const QUEUE_STATUS_WAITING: u8 = 1;
+    /// Ticket was matched into a world instance
+    pub const QUEUE_STATUS_MATCHED: u8 = 2;
+
+    // World visibility
+    /// Anyone can join
+    pub const WORLD_VISIBILITY_PUBLIC: u8 = 0;
+    /// Only wallets on the world's allowlist can join
+    pub const WORLD_VISIBILITY_ALLOWLIST: u8 = 1;
+    /// Joining needs the world password
+    pub const WORLD_VISIBILITY_PASSWORD: u8 = 2;
+    /// Maximum wallets on one world's allowlist
+    pub const MAX_ALLOWLIST_SIZE: usize = 32;
+
+    // Waitlist
+    /// Maximum players waiting for one world
+    pub const MAX_WAITLIST_SIZE: usize = 32;
+
+    // Idle eviction
+    /// Default inactivity before a player is evicted (5 minutes at 30Hz)
+    pub const DEFAULT_IDLE_TIMEOUT_SLOTS: u64 = 9_000;
+
+    // Name registry
+    /// Seed for a world's name claim PDAs (followed by the normalized name)
+    pub const NAME_CLAIM_SEED: &[u8] = b"name";
+    /// Seed for a world's reserved-name list PDA
+    pub const RESERVED_NAMES_SEED: &[u8] = b"reserved_names";
+    /// Any bytes are allowed in names
+    pub const NAME_CHARSET_ANY: u8 = 0;
+    /// ASCII letters and digits only
+    pub const NAME_CHARSET_ALPHANUMERIC: u8 = 1;
+    /// ASCII letters, digits, '_' and '-'
+    pub const NAME_CHARSET_IDENTIFIER: u8 = 2;
+    /// How long an unreleased name claim holds (24 hours at 30Hz)
+    pub const NAME_CLAIM_EXPIRY_SLOTS: u64 = 2_592_000;
+    /// Maximum words on one world's reserved-name list
+    pub const MAX_RESERVED_NAMES: usize = 32;
+
+    // NPCs
+    /// Hostile monster NPC
+    pub const NPC_KIND_MONSTER: u8 = 0;
+    /// Bot NPC (player-like)
+    pub const NPC_KIND_BOT: u8 = 1;
+    /// Default NPC respawn delay (10 seconds at 30Hz)
+    pub const DEFAULT_NPC_RESPAWN_SLOTS: u64 = 300;
+    /// Default NPC melee range (fixed-point, 2 world units)
+    pub const DEFAULT_NPC_ATTACK_RANGE: u32 = 2_000;
+
+    // Session keys
+    /// Longest session a player can register (1 hour at 30Hz)
+    pub const MAX_SESSION_DURATION_SLOTS: u64 = 108_000;
+    /// Session may sign MovePlayer / MovePlayer3D
+    pub const SESSION_ALLOW_MOVE: u8 = 1 << 0;
+    /// Session may sign Attack
+    pub const SESSION_ALLOW_ATTACK: u8 = 1 << 1;
+    /// Session may sign Heal
+    pub const SESSION_ALLOW_HEAL: u8 = 1 << 2;
+    /// All instructions a session may sign
+    pub const SESSION_ALLOW_ALL: u8 = SESSION_ALLOW_MOVE | SESSION_ALLOW_ATTACK | SESSION_ALLOW_HEAL;
 
     // Legacy (kept for compatibility)
     pub const MAX_SPEED: i16 = SPRINT_SPEED;
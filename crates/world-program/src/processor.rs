@@ -19,8 +19,12 @@ use solana_program::{
 use crate::{
     constants::*,
     error::WorldError,
-    instruction::WorldInstruction,
-    state::{MovementInput3D, WorldConfig, WorldPlayer},
+    instruction::{NpcAction, WorldInstruction},
+    state::{
+        GameMode, LootDrop, MatchResult, MovementInput3D, PlayerSession, PositionHistory, QueueTicket,
+        TeamScores, NameClaim, ReservedNames, WeaponStats, WorldAllowlist, WorldConfig, WorldNpc, WorldPlayer,
+        WorldWaitlist,
+    },
 };
 
 /// Process instruction
@@ -40,13 +44,14 @@ pub fn process(
             max_players,
         } => process_initialize_world(program_id, accounts, name, width, height, max_players),
 
-        WorldInstruction::JoinWorld { name } => process_join_world(program_id, accounts, name),
+        WorldInstruction::JoinWorld { name } => process_join_world(program_id, accounts, name, None),
 
         WorldInstruction::MovePlayer { input } => process_move_player(program_id, accounts, input),
 
-        WorldInstruction::Attack { weapon_stats } => {
-            process_attack(program_id, accounts, weapon_stats)
-        }
+        WorldInstruction::Attack {
+            weapon_stats,
+            observed_slot,
+        } => process_attack(program_id, accounts, weapon_stats, observed_slot),
 
         WorldInstruction::Heal { amount } => process_heal(program_id, accounts, amount),
 
@@ -63,7 +68,498 @@ pub fn process(
         WorldInstruction::MovePlayer3D { input } => {
             process_move_player_3d(program_id, accounts, input)
         }
+
+        WorldInstruction::CreateSession {
+            session_key,
+            expiry_slot,
+            allowed_instructions,
+        } => process_create_session(
+            program_id,
+            accounts,
+            session_key,
+            expiry_slot,
+            allowed_instructions,
+        ),
+
+        WorldInstruction::RevokeSession => process_revoke_session(program_id, accounts),
+
+        WorldInstruction::SpawnNpc {
+            npc_id,
+            kind,
+            name,
+            max_health,
+            damage,
+            attack_range,
+            spawn_x,
+            spawn_z,
+            respawn_delay_slots,
+            loot_amount,
+        } => {
+            let npc = WorldNpc {
+                npc_id,
+                kind,
+                damage,
+                attack_range,
+                spawn_x,
+                spawn_z,
+                respawn_delay_slots,
+                loot_amount,
+                body: WorldPlayer {
+                    max_health,
+                    name,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            process_spawn_npc(program_id, accounts, npc)
+        }
+
+        WorldInstruction::NpcAct { action } => process_npc_act(program_id, accounts, action),
+
+        WorldInstruction::AttackNpc {
+            weapon_stats,
+            observed_slot,
+        } => process_attack_npc(program_id, accounts, weapon_stats, observed_slot),
+
+        WorldInstruction::ConfigureTeams {
+            team_count,
+            friendly_fire,
+            max_team_size,
+        } => process_configure_teams(program_id, accounts, team_count, friendly_fire, max_team_size),
+
+        WorldInstruction::ChooseTeam { team } => process_choose_team(program_id, accounts, team),
+
+        WorldInstruction::ConfigureGameMode {
+            mode,
+            objective_x,
+            objective_z,
+            objective_radius,
+            flag_base_x,
+            flag_base_z,
+            match_duration_slots,
+            score_limit,
+        } => {
+            let game_mode = GameMode {
+                mode,
+                objective_x,
+                objective_z,
+                objective_radius,
+                flag_base_x,
+                flag_base_z,
+                match_duration_slots,
+                score_limit,
+                ..Default::default()
+            };
+            process_configure_game_mode(program_id, accounts, game_mode)
+        }
+
+        WorldInstruction::TickGameMode => process_tick_game_mode(program_id, accounts),
+
+        WorldInstruction::JoinQueue { rating } => process_join_queue(program_id, accounts, rating),
+
+        WorldInstruction::LeaveQueue => process_leave_queue(program_id, accounts),
+
+        WorldInstruction::IssueJoinTicket => process_issue_join_ticket(program_id, accounts),
+
+        WorldInstruction::CloseWorld => process_close_world(program_id, accounts),
+
+        WorldInstruction::TransferWorld => process_transfer_world(program_id, accounts),
+
+        WorldInstruction::SetWorldVisibility {
+            visibility,
+            password_salt,
+            password_hash,
+        } => process_set_world_visibility(program_id, accounts, visibility, password_salt, password_hash),
+
+        WorldInstruction::UpdateAllowlist { wallet, allowed } => {
+            process_update_allowlist(program_id, accounts, wallet, allowed)
+        }
+
+        WorldInstruction::JoinWorldWithPassword { name, password } => {
+            process_join_world(program_id, accounts, name, Some(password))
+        }
+
+        WorldInstruction::SetReservedSlots { reserved_slots } => {
+            process_set_reserved_slots(program_id, accounts, reserved_slots)
+        }
+
+        WorldInstruction::JoinWaitlist { name, password } => {
+            process_join_waitlist(program_id, accounts, name, password)
+        }
+
+        WorldInstruction::LeaveWaitlist => process_leave_waitlist(program_id, accounts),
+
+        WorldInstruction::AdmitFromWaitlist => process_admit_from_waitlist(program_id, accounts),
+
+        WorldInstruction::AdmitVip { name } => process_admit_vip(program_id, accounts, name),
+
+        WorldInstruction::SetIdleTimeout { idle_timeout_slots } => {
+            process_set_idle_timeout(program_id, accounts, idle_timeout_slots)
+        }
+
+        WorldInstruction::EvictIdlePlayer => process_evict_idle_player(program_id, accounts),
+
+        WorldInstruction::ConfigureNames {
+            unique_names,
+            name_charset,
+        } => process_configure_names(program_id, accounts, unique_names, name_charset),
+
+        WorldInstruction::UpdateReservedNames { word, reserved } => {
+            process_update_reserved_names(program_id, accounts, word, reserved)
+        }
+
+        WorldInstruction::ConfigureAbilities {
+            dash_speed,
+            dash_cooldown_slots,
+        } => process_configure_abilities(program_id, accounts, dash_speed, dash_cooldown_slots),
+
+        WorldInstruction::SetMaxRewind { max_rewind_slots } => {
+            process_set_max_rewind(program_id, accounts, max_rewind_slots)
+        }
+
+        WorldInstruction::ExtrapolateMovement => process_extrapolate_movement(program_id, accounts),
+
+        WorldInstruction::SetMaxExtrapolation {
+            max_extrapolation_slots,
+        } => process_set_max_extrapolation(program_id, accounts, max_extrapolation_slots),
+    }
+}
+
+/// Find a world's team scores account among the instruction accounts
+fn find_team_scores<'a, 'info>(
+    program_id: &Pubkey,
+    world: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>, ProgramError> {
+    let (scores_pda, _) = TeamScores::derive_pda(world, program_id);
+    let scores_account = accounts
+        .iter()
+        .find(|account| *account.key == scores_pda)
+        .ok_or(WorldError::AccountNotInitialized)?;
+
+    if scores_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    Ok(scores_account)
+}
+
+/// Load a world's allowlist if it is among the instruction accounts
+fn find_allowlist(
+    program_id: &Pubkey,
+    world: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Option<WorldAllowlist>, ProgramError> {
+    let (allowlist_pda, _) = WorldAllowlist::derive_pda(world, program_id);
+    let Some(allowlist_account) = accounts.iter().find(|account| *account.key == allowlist_pda) else {
+        return Ok(None);
+    };
+
+    // Not created yet - nobody is on the list
+    if allowlist_account.data_len() != WorldAllowlist::LEN {
+        return Ok(None);
+    }
+    if allowlist_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    Ok(Some(WorldAllowlist::try_from_slice(&allowlist_account.data.borrow())?))
+}
+
+/// Copy of `body` where it stood at `slot`, for lag-compensated hit tests
+///
+/// Positions come from the world's position history when it is among the
+/// instruction accounts. The current slot's moves are only recorded once
+/// it is sealed, so rewinding to it (or without a history) uses `body`.
+fn find_rewound(
+    program_id: &Pubkey,
+    world: &Pubkey,
+    entity: &Pubkey,
+    body: &WorldPlayer,
+    slot: u64,
+    current_slot: u64,
+    accounts: &[AccountInfo],
+) -> Result<WorldPlayer, ProgramError> {
+    if slot >= current_slot {
+        return Ok(body.clone());
+    }
+    let (history_pda, _) = PositionHistory::derive_pda(world, program_id);
+    let Some(history_account) = accounts.iter().find(|account| *account.key == history_pda) else {
+        return Ok(body.clone());
+    };
+
+    // Not recorded yet - nobody has moved
+    if history_account.data_is_empty() {
+        return Ok(body.clone());
+    }
+    if history_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    let history = PositionHistory::try_from_slice(&history_account.data.borrow())?;
+    Ok(history.rewound(entity, body, slot))
+}
+
+/// Load a world's reserved-name list (required while the world reserves names)
+fn find_reserved_names(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+    accounts: &[AccountInfo],
+) -> Result<Option<ReservedNames>, ProgramError> {
+    if world.reserved_name_count == 0 {
+        return Ok(None);
+    }
+    let (reserved_pda, _) = ReservedNames::derive_pda(world_key, program_id);
+    let reserved_account = accounts
+        .iter()
+        .find(|account| *account.key == reserved_pda)
+        .ok_or(WorldError::AccountNotInitialized)?;
+
+    if reserved_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    Ok(Some(ReservedNames::try_from_slice(&reserved_account.data.borrow())?))
+}
+
+/// Find the claim account for `name` (required in unique-name worlds)
+fn find_name_claim<'a, 'info>(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+    name: &[u8; 16],
+) -> Option<&'a AccountInfo<'info>> {
+    let (claim_pda, _) = NameClaim::derive_pda(world_key, &NameClaim::normalize(name), program_id);
+    accounts.iter().find(|account| *account.key == claim_pda)
+}
+
+/// Load a name claim, or None if the account hasn't been created
+fn load_name_claim(program_id: &Pubkey, claim_account: &AccountInfo) -> Result<Option<NameClaim>, ProgramError> {
+    if claim_account.data_len() == 0 {
+        return Ok(None);
+    }
+    if claim_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    Ok(Some(NameClaim::try_from_slice(&claim_account.data.borrow())?))
+}
+
+/// Check `name` for `wallet` against the world's name rules and claims
+///
+/// Account problems are returned as the outer error, rule violations as the inner one.
+fn check_name(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+    accounts: &[AccountInfo],
+    wallet: &Pubkey,
+    name: &[u8; 16],
+    slot: u64,
+) -> Result<Result<(), WorldError>, ProgramError> {
+    let reserved = find_reserved_names(program_id, world_key, world, accounts)?;
+    let claim = if world.unique_names {
+        let claim_account = find_name_claim(program_id, world_key, accounts, name)
+            .ok_or(WorldError::AccountNotInitialized)?;
+        load_name_claim(program_id, claim_account)?
+    } else {
+        None
+    };
+    Ok(world.check_name(wallet, name, reserved.as_ref(), claim.as_ref(), slot))
+}
+
+/// Claim a checked name for `wallet` (unique-name worlds only)
+#[allow(clippy::too_many_arguments)]
+fn claim_name<'info>(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    world: &WorldConfig,
+    accounts: &[AccountInfo<'info>],
+    wallet: &Pubkey,
+    name: &[u8; 16],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    slot: u64,
+) -> ProgramResult {
+    if !world.unique_names {
+        return Ok(());
+    }
+    let claim_account = find_name_claim(program_id, world_key, accounts, name)
+        .ok_or(WorldError::AccountNotInitialized)?;
+    let normalized = NameClaim::normalize(name);
+    let (_, bump) = NameClaim::derive_pda(world_key, &normalized, program_id);
+
+    // Create claim account on first use of the name
+    if claim_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = NameClaim::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                claim_account.key,
+                rent.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), claim_account.clone(), system_program.clone()],
+            &[&[NAME_CLAIM_SEED, world_key.as_ref(), &normalized, &[bump]]],
+        )?;
+    }
+
+    let claim = NameClaim {
+        world: *world_key,
+        owner: *wallet,
+        name: normalized,
+        expires_slot: slot.saturating_add(NAME_CLAIM_EXPIRY_SLOTS),
+        bump,
+    };
+    claim.serialize(&mut *claim_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Release a player's name claim if it is among the instruction accounts
+///
+/// Claims taken over by another wallet after expiring are left alone.
+fn release_name(
+    program_id: &Pubkey,
+    world_key: &Pubkey,
+    accounts: &[AccountInfo],
+    player: &WorldPlayer,
+    destination: &AccountInfo,
+) -> ProgramResult {
+    let Some(claim_account) = find_name_claim(program_id, world_key, accounts, &player.name) else {
+        return Ok(());
+    };
+    match load_name_claim(program_id, claim_account)? {
+        Some(claim) if claim.owner == player.authority => {}
+        _ => return Ok(()),
+    }
+
+    let lamports = claim_account.lamports();
+    **claim_account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(WorldError::ArithmeticOverflow)?;
+    claim_account.data.borrow_mut().fill(0);
+
+    Ok(())
+}
+
+/// Create a player account for `wallet` at the world's spawn point
+///
+/// Shared by every way into a world: verifies the player PDA, checks and
+/// claims the name, auto-balances teams, creates the account and stores the
+/// updated world. Capacity and access checks are up to the caller.
+#[allow(clippy::too_many_arguments)]
+fn spawn_player<'info>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    world_account: &AccountInfo<'info>,
+    world: &mut WorldConfig,
+    player_account: &AccountInfo<'info>,
+    wallet: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    name: [u8; 16],
+) -> Result<WorldPlayer, ProgramError> {
+    // Derive player PDA
+    let (player_pda, bump) = WorldPlayer::derive_pda(world_account.key, wallet, program_id);
+    if player_pda != *player_account.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Enforce the world's name rules
+    let clock = Clock::get()?;
+    check_name(program_id, world_account.key, world, accounts, wallet, &name, clock.slot)??;
+    claim_name(
+        program_id,
+        world_account.key,
+        world,
+        accounts,
+        wallet,
+        &name,
+        payer,
+        system_program,
+        clock.slot,
+    )?;
+
+    // Auto-balance onto the smallest team in team worlds
+    let team = if world.teams_enabled() {
+        let scores_account = find_team_scores(program_id, world_account.key, accounts)?;
+        let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+        let team = scores.auto_assign(world)?;
+        scores.add_member(team);
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+        team
+    } else {
+        TEAM_NONE
+    };
+
+    // Create player account
+    let rent = Rent::get()?;
+    let space = WorldPlayer::LEN;
+    let lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            player_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            player_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            WORLD_PLAYER_SEED,
+            world_account.key.as_ref(),
+            wallet.as_ref(),
+            &[bump],
+        ]],
+    )?;
+
+    // Initialize player at the world's spawn point
+    let player = WorldPlayer::spawn(*wallet, *world_account.key, world, name, bump, team, clock.slot);
+    player.serialize(&mut *player_account.data.borrow_mut())?;
+
+    // Update world player count
+    world.player_count += 1;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    Ok(player)
+}
+
+/// Verify the signer is the player's wallet authority or a valid session key
+fn verify_player_signer(
+    program_id: &Pubkey,
+    player_account: &AccountInfo,
+    player: &WorldPlayer,
+    signer: &AccountInfo,
+    session_account: Option<&AccountInfo>,
+    permission: u8,
+) -> ProgramResult {
+    if player.authority == *signer.key {
+        return Ok(());
+    }
+
+    // Not the wallet - must be a registered session key
+    let session_account = session_account.ok_or(WorldError::InvalidAuthority)?;
+    if session_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let (session_pda, _) = PlayerSession::derive_pda(player_account.key, program_id);
+    if session_pda != *session_account.key {
+        return Err(WorldError::InvalidSession.into());
     }
+
+    let session = PlayerSession::try_from_slice(&session_account.data.borrow())
+        .map_err(|_| WorldError::InvalidSession)?;
+    let clock = Clock::get()?;
+    session.validate(player_account.key, signer.key, permission, clock.slot)?;
+
+    Ok(())
 }
 
 /// Initialize a new world
@@ -122,6 +618,22 @@ fn process_initialize_world(
         bump,
         l1_game: Pubkey::default(),
         init_ts: clock.unix_timestamp,
+        team_count: 0,
+        friendly_fire: false,
+        max_team_size: 0,
+        visibility: WORLD_VISIBILITY_PUBLIC,
+        password_salt: [0; 16],
+        password_hash: [0; 32],
+        reserved_slots: 0,
+        waiting_count: 0,
+        idle_timeout_slots: DEFAULT_IDLE_TIMEOUT_SLOTS,
+        unique_names: false,
+        name_charset: NAME_CHARSET_ANY,
+        reserved_name_count: 0,
+        dash_speed: DEFAULT_DASH_SPEED,
+        dash_cooldown_slots: DEFAULT_DASH_COOLDOWN_SLOTS,
+        max_rewind_slots: DEFAULT_MAX_REWIND_SLOTS,
+        max_extrapolation_slots: DEFAULT_MAX_EXTRAPOLATION_SLOTS,
     };
 
     world.serialize(&mut *world_account.data.borrow_mut())?;
@@ -136,6 +648,7 @@ fn process_join_world(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     name: [u8; 16],
+    password: Option<[u8; 32]>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let world_account = next_account_info(accounts_iter)?;
@@ -152,70 +665,26 @@ fn process_join_world(
     // Load world config
     let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
 
-    // Check if world is full
-    if world.is_full() {
+    // Check if world is full (or players are waiting for the next slot)
+    if !world.can_join_directly() {
         return Err(WorldError::WorldFull.into());
     }
 
-    // Derive player PDA
-    let (player_pda, bump) = WorldPlayer::derive_pda(world_account.key, authority.key, program_id);
-    if player_pda != *player_account.key {
-        return Err(WorldError::InvalidAuthority.into());
-    }
-
-    // Create player account
-    let rent = Rent::get()?;
-    let space = WorldPlayer::LEN;
-    let lamports = rent.minimum_balance(space);
-
-    invoke_signed(
-        &system_instruction::create_account(
-            payer.key,
-            player_account.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
-        &[
-            payer.clone(),
-            player_account.clone(),
-            system_program.clone(),
-        ],
-        &[&[
-            WORLD_PLAYER_SEED,
-            world_account.key.as_ref(),
-            authority.key.as_ref(),
-            &[bump],
-        ]],
-    )?;
-
-    // Initialize player at world center
-    let clock = Clock::get()?;
-    let player = WorldPlayer {
-        authority: *authority.key,
-        world: *world_account.key,
-        position_x: (world.width as i32 / 2) * FIXED_POINT_SCALE,
-        position_z: (world.depth as i32 / 2) * FIXED_POINT_SCALE,
-        position_y: 0, // Start on ground
-        velocity_x: 0,
-        velocity_z: 0,
-        velocity_y: 0,
-        yaw: 0,
-        health: DEFAULT_HEALTH,
-        max_health: DEFAULT_MAX_HEALTH,
-        last_action_slot: clock.slot,
-        last_combat_ts: 0,
-        in_pvp_zone: false,
-        is_grounded: true,
-        bump,
+    // Enforce the world's visibility settings
+    let allowlist = find_allowlist(program_id, world_account.key, accounts)?;
+    world.check_access(authority.key, allowlist.as_ref(), password.as_ref())?;
+
+    let player = spawn_player(
+        program_id,
+        accounts,
+        world_account,
+        &mut world,
+        player_account,
+        authority.key,
+        payer,
+        system_program,
         name,
-    };
-
-    player.serialize(&mut *player_account.data.borrow_mut())?;
-
-    // Update world player count
-    world.player_count += 1;
-    world.serialize(&mut *world_account.data.borrow_mut())?;
+    )?;
 
     msg!("Player joined: {}", player.name_str());
 
@@ -232,6 +701,7 @@ fn process_move_player(
     let world_account = next_account_info(accounts_iter)?;
     let player_account = next_account_info(accounts_iter)?;
     let authority = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter).ok();
 
     // Verify authority is signer
     if !authority.is_signer {
@@ -247,10 +717,15 @@ fn process_move_player(
     let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
     let mut player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
 
-    // Verify authority
-    if player.authority != *authority.key {
-        return Err(WorldError::InvalidAuthority.into());
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        program_id,
+        player_account,
+        &player,
+        authority,
+        session_account,
+        SESSION_ALLOW_MOVE,
+    )?;
 
     // Verify world
     if player.world != *world_account.key {
@@ -262,11 +737,13 @@ fn process_move_player(
         return Err(WorldError::PlayerDead.into());
     }
 
-    // Apply movement
-    player.apply_movement(input.direction, input.sprint, &world);
+    // Apply movement (dropped while hit-stunned)
+    let clock = Clock::get()?;
+    if !player.is_stunned(clock.slot) {
+        player.apply_movement(input.direction, input.sprint, &world);
+    }
 
     // Update last action slot
-    let clock = Clock::get()?;
     player.last_action_slot = clock.slot;
 
     // Save player
@@ -280,12 +757,14 @@ fn process_attack(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     weapon_stats: Option<crate::state::WeaponStats>,
+    observed_slot: Option<u64>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let world_account = next_account_info(accounts_iter)?;
     let attacker_account = next_account_info(accounts_iter)?;
     let target_account = next_account_info(accounts_iter)?;
     let authority = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter).ok();
 
     // Verify authority is signer
     if !authority.is_signer {
@@ -306,26 +785,73 @@ fn process_attack(
     let mut attacker = WorldPlayer::try_from_slice(&attacker_account.data.borrow())?;
     let mut target = WorldPlayer::try_from_slice(&target_account.data.borrow())?;
 
-    // Verify attacker authority
-    if attacker.authority != *authority.key {
-        return Err(WorldError::InvalidAuthority.into());
-    }
+    // Verify attacker authority (wallet or session key)
+    verify_player_signer(
+        program_id,
+        attacker_account,
+        &attacker,
+        authority,
+        session_account,
+        SESSION_ALLOW_ATTACK,
+    )?;
 
     // Both must be alive
     if !attacker.is_alive() || !target.is_alive() {
         return Err(WorldError::PlayerDead.into());
     }
 
+    // No striking back while reeling from a hit
+    let clock = Clock::get()?;
+    if attacker.is_stunned(clock.slot) {
+        return Err(WorldError::PlayerStunned.into());
+    }
+
+    // Team rules
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if attacker.world != *world_account.key || target.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+    world.check_friendly_fire(&attacker, &target)?;
+
+    // Weapon reach is measured to the target's hitbox (lower when crouching),
+    // where the attacker's client showed it
+    let seen = find_rewound(
+        program_id,
+        world_account.key,
+        target_account.key,
+        &target,
+        world.rewind_slot(observed_slot, clock.slot),
+        clock.slot,
+        accounts,
+    )?;
+    if weapon_stats.is_some_and(|weapon| !weapon.in_range(&attacker, &seen)) {
+        return Err(WorldError::TargetOutOfRange.into());
+    }
+
     // Calculate damage - use L1 stats if provided, else defaults
     let damage = weapon_stats
         .map(|w| w.damage)
         .unwrap_or(DEFAULT_DAMAGE);
 
-    // Apply damage
+    // Apply damage, knocking the target back if it survives
     target.apply_damage(damage);
+    if target.is_alive() {
+        target.apply_knockback(attacker.position_x, attacker.position_z, attacker.yaw, damage, clock.slot);
+    }
+
+    // Enemy kills score for the attacker's team
+    if !target.is_alive()
+        && world.teams_enabled()
+        && attacker.team != TEAM_NONE
+        && attacker.team != target.team
+    {
+        let scores_account = find_team_scores(program_id, world_account.key, accounts)?;
+        let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+        scores.add_score(attacker.team, TEAM_KILL_SCORE);
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+    }
 
     // Update timestamps
-    let clock = Clock::get()?;
     attacker.last_combat_ts = clock.unix_timestamp;
     attacker.last_action_slot = clock.slot;
 
@@ -349,6 +875,7 @@ fn process_heal(program_id: &Pubkey, accounts: &[AccountInfo], amount: u16) -> P
     let _world_account = next_account_info(accounts_iter)?;
     let player_account = next_account_info(accounts_iter)?;
     let authority = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter).ok();
 
     // Verify authority is signer
     if !authority.is_signer {
@@ -363,10 +890,15 @@ fn process_heal(program_id: &Pubkey, accounts: &[AccountInfo], amount: u16) -> P
     // Load player
     let mut player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
 
-    // Verify authority
-    if player.authority != *authority.key {
-        return Err(WorldError::InvalidAuthority.into());
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        program_id,
+        player_account,
+        &player,
+        authority,
+        session_account,
+        SESSION_ALLOW_HEAL,
+    )?;
 
     // Use provided amount or default
     let heal_amount = if amount > 0 { amount } else { DEFAULT_HEAL };
@@ -412,18 +944,44 @@ fn process_leave_world(program_id: &Pubkey, accounts: &[AccountInfo]) -> Program
         return Err(WorldError::InvalidAuthority.into());
     }
 
+    remove_player(program_id, accounts, world_account, player_account, &player, destination)?;
+
+    msg!("Player left: {}", player.name_str());
+
+    Ok(())
+}
+
+/// Remove a player from its world and close the player account
+///
+/// Shared cleanup of `LeaveWorld` and `EvictIdlePlayer`.
+fn remove_player(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    world_account: &AccountInfo,
+    player_account: &AccountInfo,
+    player: &WorldPlayer,
+    destination: &AccountInfo,
+) -> ProgramResult {
     // Load and update world
     let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
     world.player_count = world.player_count.saturating_sub(1);
     world.serialize(&mut *world_account.data.borrow_mut())?;
 
+    // Free the player's team slot
+    if world.teams_enabled() && player.team != TEAM_NONE {
+        let scores_account = find_team_scores(program_id, world_account.key, accounts)?;
+        let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+        scores.remove_member(player.team);
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+    }
+
+    release_name(program_id, world_account.key, accounts, player, destination)?;
+
     // Close player account (transfer lamports)
     let lamports = player_account.lamports();
     **player_account.lamports.borrow_mut() = 0;
     **destination.lamports.borrow_mut() += lamports;
 
-    msg!("Player left: {}", player.name_str());
-
     Ok(())
 }
 
@@ -513,6 +1071,7 @@ fn process_move_player_3d(
     let world_account = next_account_info(accounts_iter)?;
     let player_account = next_account_info(accounts_iter)?;
     let authority = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter).ok();
 
     // Verify authority is signer
     if !authority.is_signer {
@@ -530,10 +1089,15 @@ fn process_move_player_3d(
     // Load player
     let mut player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
 
-    // Verify authority
-    if player.authority != *authority.key {
-        return Err(WorldError::InvalidAuthority.into());
-    }
+    // Verify authority (wallet or session key)
+    verify_player_signer(
+        program_id,
+        player_account,
+        &player,
+        authority,
+        session_account,
+        SESSION_ALLOW_MOVE,
+    )?;
 
     // Verify world
     if player.world != *world_account.key {
@@ -545,11 +1109,13 @@ fn process_move_player_3d(
         return Err(WorldError::PlayerDead.into());
     }
 
-    // Apply 3D movement with physics
-    player.apply_movement_3d(&input, &world);
+    // Apply 3D movement with physics (input is limited while hit-stunned)
+    let clock = Clock::get()?;
+    let limited = player.limit_input(&input, clock.slot);
+    player.apply_movement_3d(&limited, &world, clock.slot);
+    player.record_input(&input, clock.slot);
 
     // Update last action slot
-    let clock = Clock::get()?;
     player.last_action_slot = clock.slot;
 
     // Save player
@@ -557,3 +1123,1726 @@ fn process_move_player_3d(
 
     Ok(())
 }
+
+/// Register an ephemeral session key for a player
+fn process_create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_key: Pubkey,
+    expiry_slot: u64,
+    allowed_instructions: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let player_account = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if player_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Only the wallet authority can register sessions
+    let player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
+    if player.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Derive session PDA
+    let (session_pda, bump) = PlayerSession::derive_pda(player_account.key, program_id);
+    if session_pda != *session_account.key {
+        return Err(WorldError::InvalidSession.into());
+    }
+
+    // Sessions are short-lived and must allow at least one instruction
+    let clock = Clock::get()?;
+    if expiry_slot <= clock.slot || expiry_slot > clock.slot + MAX_SESSION_DURATION_SLOTS {
+        return Err(WorldError::InvalidSession.into());
+    }
+    if allowed_instructions == 0 || allowed_instructions & !SESSION_ALLOW_ALL != 0 {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Create session account on first use
+    if session_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = PlayerSession::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                session_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                session_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[SESSION_SEED, player_account.key.as_ref(), &[bump]]],
+        )?;
+    }
+
+    let session = PlayerSession {
+        player: *player_account.key,
+        authority: *authority.key,
+        session_key,
+        expiry_slot,
+        allowed_instructions,
+        created_slot: clock.slot,
+        bump,
+    };
+
+    session.serialize(&mut *session_account.data.borrow_mut())?;
+
+    msg!(
+        "Session created for {} until slot {}",
+        player.name_str(),
+        expiry_slot
+    );
+
+    Ok(())
+}
+
+/// Revoke a player's session key
+fn process_revoke_session(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let session_account = next_account_info(accounts_iter)?;
+    let signer = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if session_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Either the wallet or the session key itself may revoke
+    let session = PlayerSession::try_from_slice(&session_account.data.borrow())
+        .map_err(|_| WorldError::InvalidSession)?;
+    if session.authority != *signer.key && session.session_key != *signer.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Close session account (transfer lamports)
+    let lamports = session_account.lamports();
+    **session_account.lamports.borrow_mut() = 0;
+    **destination.lamports.borrow_mut() += lamports;
+    session_account.data.borrow_mut().fill(0);
+
+    msg!("Session revoked for player {}", session.player);
+
+    Ok(())
+}
+
+/// Spawn an NPC (world authority only)
+fn process_spawn_npc(program_id: &Pubkey, accounts: &[AccountInfo], mut npc: WorldNpc) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let npc_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Only the world authority can spawn NPCs
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Derive NPC PDA
+    let (npc_pda, bump) = WorldNpc::derive_pda(world_account.key, npc.npc_id, program_id);
+    if npc_pda != *npc_account.key {
+        return Err(WorldError::NpcNotFound.into());
+    }
+
+    // Never re-spawn over an existing NPC - its death count keys its loot drops
+    if npc_account.data_len() != 0 {
+        return Err(WorldError::AccountAlreadyInitialized.into());
+    }
+
+    let rent = Rent::get()?;
+    let space = WorldNpc::LEN;
+    let lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            npc_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), npc_account.clone(), system_program.clone()],
+        &[&[
+            NPC_SEED,
+            world_account.key.as_ref(),
+            &npc.npc_id.to_le_bytes(),
+            &[bump],
+        ]],
+    )?;
+
+    let clock = Clock::get()?;
+    npc.initialize(&world, *world_account.key, bump, clock.slot);
+    npc.serialize(&mut *npc_account.data.borrow_mut())?;
+
+    msg!("NPC {} spawned: {}", npc.npc_id, npc.body.name_str());
+
+    Ok(())
+}
+
+/// Drive an NPC for one tick (world authority only)
+fn process_npc_act(program_id: &Pubkey, accounts: &[AccountInfo], action: NpcAction) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let npc_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let target_account = next_account_info(accounts_iter).ok();
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if npc_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let mut npc = WorldNpc::try_from_slice(&npc_account.data.borrow())
+        .map_err(|_| WorldError::NpcNotFound)?;
+
+    // Only the world authority drives NPCs
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if npc.body.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+
+    let clock = Clock::get()?;
+
+    if !npc.is_alive() {
+        // Dead NPCs only wait for their respawn timer
+        if npc.can_respawn(clock.slot) {
+            npc.respawn(clock.slot);
+            msg!("NPC {} respawned", npc.npc_id);
+        }
+        npc.serialize(&mut *npc_account.data.borrow_mut())?;
+        return Ok(());
+    }
+
+    match action {
+        NpcAction::Idle => {}
+        NpcAction::Move { input } => {
+            let input = npc.body.limit_input(&input, clock.slot);
+            npc.body.apply_movement_3d(&input, &world, clock.slot);
+        }
+        NpcAction::Attack => {
+            if npc.body.is_stunned(clock.slot) {
+                return Err(WorldError::PlayerStunned.into());
+            }
+            let target_account = target_account.ok_or(WorldError::PlayerNotFound)?;
+            if target_account.owner != program_id {
+                return Err(WorldError::InvalidAccountOwner.into());
+            }
+
+            let mut target = WorldPlayer::try_from_slice(&target_account.data.borrow())?;
+            if target.world != *world_account.key {
+                return Err(WorldError::InvalidWorld.into());
+            }
+            if !target.is_alive() {
+                return Err(WorldError::PlayerDead.into());
+            }
+            if !npc.in_range(&target) {
+                return Err(WorldError::TargetOutOfRange.into());
+            }
+
+            target.apply_damage(npc.damage);
+            if target.is_alive() {
+                let body = &npc.body;
+                target.apply_knockback(body.position_x, body.position_z, body.yaw, npc.damage, clock.slot);
+            }
+            npc.body.last_combat_ts = clock.unix_timestamp;
+            target.serialize(&mut *target_account.data.borrow_mut())?;
+        }
+    }
+
+    npc.body.last_action_slot = clock.slot;
+    npc.serialize(&mut *npc_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Attack an NPC (drops loot on kill)
+fn process_attack_npc(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    weapon_stats: Option<WeaponStats>,
+    observed_slot: Option<u64>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let attacker_account = next_account_info(accounts_iter)?;
+    let npc_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let loot_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter).ok();
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owners
+    if attacker_account.owner != program_id || npc_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let mut attacker = WorldPlayer::try_from_slice(&attacker_account.data.borrow())?;
+    let mut npc = WorldNpc::try_from_slice(&npc_account.data.borrow())
+        .map_err(|_| WorldError::NpcNotFound)?;
+
+    // Verify attacker authority (wallet or session key)
+    verify_player_signer(
+        program_id,
+        attacker_account,
+        &attacker,
+        authority,
+        session_account,
+        SESSION_ALLOW_ATTACK,
+    )?;
+
+    // Both must be in this world and alive
+    if attacker.world != *world_account.key || npc.body.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+    if !attacker.is_alive() || !npc.is_alive() {
+        return Err(WorldError::PlayerDead.into());
+    }
+    let clock = Clock::get()?;
+    if attacker.is_stunned(clock.slot) {
+        return Err(WorldError::PlayerStunned.into());
+    }
+
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let seen = find_rewound(
+        program_id,
+        world_account.key,
+        npc_account.key,
+        &npc.body,
+        world.rewind_slot(observed_slot, clock.slot),
+        clock.slot,
+        accounts,
+    )?;
+    if weapon_stats.is_some_and(|weapon| !weapon.in_range(&attacker, &seen)) {
+        return Err(WorldError::TargetOutOfRange.into());
+    }
+
+    let damage = weapon_stats.map(|w| w.damage).unwrap_or(DEFAULT_DAMAGE);
+    npc.body.apply_damage(damage);
+    if npc.is_alive() {
+        npc.body.apply_knockback(attacker.position_x, attacker.position_z, attacker.yaw, damage, clock.slot);
+    }
+
+    attacker.last_combat_ts = clock.unix_timestamp;
+    attacker.last_action_slot = clock.slot;
+
+    if !npc.is_alive() {
+        // Drop loot at the NPC's position, keyed by its death count
+        let death_index = npc.deaths;
+        let (loot_pda, bump) = LootDrop::derive_pda(npc_account.key, death_index, program_id);
+        if loot_pda != *loot_account.key {
+            return Err(WorldError::InvalidInstructionData.into());
+        }
+
+        // Unclaimed loot is never replaced
+        if loot_account.data_len() != 0 {
+            return Err(WorldError::AccountAlreadyInitialized.into());
+        }
+
+        let rent = Rent::get()?;
+        let space = LootDrop::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                loot_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), loot_account.clone(), system_program.clone()],
+            &[&[
+                LOOT_SEED,
+                npc_account.key.as_ref(),
+                &death_index.to_le_bytes(),
+                &[bump],
+            ]],
+        )?;
+
+        let loot = LootDrop {
+            world: *world_account.key,
+            npc: *npc_account.key,
+            killer: *attacker_account.key,
+            position_x: npc.body.position_x,
+            position_z: npc.body.position_z,
+            amount: npc.loot_amount,
+            dropped_slot: clock.slot,
+            bump,
+        };
+        loot.serialize(&mut *loot_account.data.borrow_mut())?;
+
+        npc.on_death(clock.slot);
+        msg!(
+            "NPC {} killed by {}, respawn at slot {}",
+            npc.npc_id,
+            attacker.name_str(),
+            npc.respawn_at_slot
+        );
+    }
+
+    attacker.serialize(&mut *attacker_account.data.borrow_mut())?;
+    npc.serialize(&mut *npc_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Configure teams and friendly-fire rules (world authority only)
+fn process_configure_teams(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    team_count: u8,
+    friendly_fire: bool,
+    max_team_size: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let scores_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Team count is bounded and fixed once players are in the world
+    if team_count as usize > MAX_TEAMS {
+        return Err(WorldError::InvalidTeam.into());
+    }
+    if team_count != world.team_count && world.player_count > 0 {
+        return Err(WorldError::InvalidTeam.into());
+    }
+
+    // Derive team scores PDA
+    let (scores_pda, bump) = TeamScores::derive_pda(world_account.key, program_id);
+    if scores_pda != *scores_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Create team scores account on first use
+    if scores_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = TeamScores::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                scores_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), scores_account.clone(), system_program.clone()],
+            &[&[TEAM_SCORES_SEED, world_account.key.as_ref(), &[bump]]],
+        )?;
+
+        let scores = TeamScores {
+            world: *world_account.key,
+            bump,
+            ..Default::default()
+        };
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+    }
+
+    world.team_count = team_count;
+    world.friendly_fire = friendly_fire;
+    world.max_team_size = max_team_size;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!(
+        "Teams configured: {} teams, friendly fire {}, max size {}",
+        team_count,
+        friendly_fire,
+        max_team_size
+    );
+
+    Ok(())
+}
+
+/// Switch a player to a different team
+fn process_choose_team(program_id: &Pubkey, accounts: &[AccountInfo], team: u8) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let player_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if player_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let mut player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
+
+    // Only the wallet can change teams
+    if player.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if player.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+    if player.team == team {
+        return Ok(());
+    }
+
+    let scores_account = find_team_scores(program_id, world_account.key, accounts)?;
+    let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+    scores.check_join(team, &world)?;
+    scores.remove_member(player.team);
+    scores.add_member(team);
+    player.team = team;
+
+    scores.serialize(&mut *scores_account.data.borrow_mut())?;
+    player.serialize(&mut *player_account.data.borrow_mut())?;
+
+    msg!("{} switched to team {}", player.name_str(), team);
+
+    Ok(())
+}
+
+/// Configure the world's game mode and start a new match (world authority only)
+fn process_configure_game_mode(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mut game_mode: GameMode,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let game_mode_account = next_account_info(accounts_iter)?;
+    let scores_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    game_mode.validate(&world)?;
+
+    // Derive game mode PDA
+    let (game_mode_pda, bump) = GameMode::derive_pda(world_account.key, program_id);
+    if game_mode_pda != *game_mode_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Verify team scores account
+    let (scores_pda, _) = TeamScores::derive_pda(world_account.key, program_id);
+    if scores_pda != *scores_account.key || scores_account.owner != program_id {
+        return Err(WorldError::AccountNotInitialized.into());
+    }
+    let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+
+    // Create game mode account on first use; reconfiguring keeps the match history
+    if game_mode_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = GameMode::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                game_mode_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), game_mode_account.clone(), system_program.clone()],
+            &[&[GAME_MODE_SEED, world_account.key.as_ref(), &[bump]]],
+        )?;
+    } else {
+        let existing = GameMode::try_from_slice(&game_mode_account.data.borrow())?;
+        game_mode.match_index = existing.match_index;
+    }
+
+    let clock = Clock::get()?;
+    game_mode.world = *world_account.key;
+    game_mode.bump = bump;
+    game_mode.start_match(&mut scores, clock.slot);
+
+    game_mode.serialize(&mut *game_mode_account.data.borrow_mut())?;
+    scores.serialize(&mut *scores_account.data.borrow_mut())?;
+
+    msg!("Game mode {} configured, match {} started", game_mode.mode, game_mode.match_index);
+
+    Ok(())
+}
+
+/// Evaluate game mode objectives for one slot (world authority only)
+fn process_tick_game_mode(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let game_mode_account = next_account_info(accounts_iter)?;
+    let scores_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let result_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Verify game mode account
+    if game_mode_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    let mut game_mode = GameMode::try_from_slice(&game_mode_account.data.borrow())?;
+    if game_mode.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+
+    // Verify team scores account
+    let (scores_pda, _) = TeamScores::derive_pda(world_account.key, program_id);
+    if scores_pda != *scores_account.key || scores_account.owner != program_id {
+        return Err(WorldError::AccountNotInitialized.into());
+    }
+    let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+
+    // Remaining accounts are the players near the objectives
+    let mut players = Vec::new();
+    for player_account in accounts_iter {
+        if player_account.owner != program_id {
+            return Err(WorldError::InvalidAccountOwner.into());
+        }
+        let player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
+        if player.world != *world_account.key {
+            return Err(WorldError::InvalidWorld.into());
+        }
+        players.push((*player_account.key, player));
+    }
+
+    let clock = Clock::get()?;
+    game_mode.evaluate(&world, &mut scores, &players);
+
+    if game_mode.is_over(&scores, clock.slot) {
+        // Record the final scores under the finished match's index
+        let match_index = game_mode.match_index;
+        let (result_pda, bump) = MatchResult::derive_pda(world_account.key, match_index, program_id);
+        if result_pda != *result_account.key {
+            return Err(WorldError::InvalidInstructionData.into());
+        }
+
+        if result_account.data_len() != 0 {
+            return Err(WorldError::AccountAlreadyInitialized.into());
+        }
+
+        let rent = Rent::get()?;
+        let space = MatchResult::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                result_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), result_account.clone(), system_program.clone()],
+            &[&[
+                MATCH_RESULT_SEED,
+                world_account.key.as_ref(),
+                &match_index.to_le_bytes(),
+                &[bump],
+            ]],
+        )?;
+
+        let result = game_mode.finish_match(&mut scores, clock.slot, bump);
+        result.serialize(&mut *result_account.data.borrow_mut())?;
+
+        msg!("Match {} over, winner: team {}", result.match_index, result.winner);
+    }
+
+    game_mode.serialize(&mut *game_mode_account.data.borrow_mut())?;
+    scores.serialize(&mut *scores_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Enter the matchmaking queue
+fn process_join_queue(program_id: &Pubkey, accounts: &[AccountInfo], rating: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let ticket_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Derive ticket PDA
+    let (ticket_pda, bump) = QueueTicket::derive_pda(authority.key, program_id);
+    if ticket_pda != *ticket_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Create ticket account on first use
+    if ticket_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = QueueTicket::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                ticket_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), ticket_account.clone(), system_program.clone()],
+            &[&[QUEUE_SEED, authority.key.as_ref(), &[bump]]],
+        )?;
+    }
+
+    let clock = Clock::get()?;
+    let mut ticket = QueueTicket {
+        authority: *authority.key,
+        bump,
+        ..Default::default()
+    };
+    ticket.enqueue(rating, clock.slot);
+    ticket.serialize(&mut *ticket_account.data.borrow_mut())?;
+
+    msg!("Queued with rating {}", rating);
+
+    Ok(())
+}
+
+/// Leave the matchmaking queue
+fn process_leave_queue(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let ticket_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if ticket_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let mut ticket = QueueTicket::try_from_slice(&ticket_account.data.borrow())?;
+    if ticket.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if !ticket.is_waiting() {
+        return Err(WorldError::TicketNotWaiting.into());
+    }
+
+    ticket.status = QUEUE_STATUS_IDLE;
+    ticket.serialize(&mut *ticket_account.data.borrow_mut())?;
+
+    msg!("Left matchmaking queue");
+
+    Ok(())
+}
+
+/// Assign a waiting ticket to a world instance (world authority only)
+fn process_issue_join_ticket(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let ticket_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owners
+    if world_account.owner != program_id || ticket_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    let clock = Clock::get()?;
+    let mut ticket = QueueTicket::try_from_slice(&ticket_account.data.borrow())?;
+    ticket.issue(*world_account.key, clock.slot)?;
+    ticket.serialize(&mut *ticket_account.data.borrow_mut())?;
+
+    msg!("Join ticket issued for {}", world.name_str());
+
+    Ok(())
+}
+
+/// Tear down an empty world (world authority only)
+fn process_close_world(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if world.player_count > 0 {
+        return Err(WorldError::WorldNotEmpty.into());
+    }
+
+    // Drain lamports and wipe the data
+    let lamports = world_account.lamports();
+    **world_account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(WorldError::ArithmeticOverflow)?;
+    world_account.data.borrow_mut().fill(0);
+
+    msg!("World closed: {}", world.name_str());
+
+    Ok(())
+}
+
+/// Move a player to another world through a portal
+fn process_transfer_world(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source_world_account = next_account_info(accounts_iter)?;
+    let source_player_account = next_account_info(accounts_iter)?;
+    let dest_world_account = next_account_info(accounts_iter)?;
+    let dest_player_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owners
+    if source_player_account.owner != program_id
+        || source_world_account.owner != program_id
+        || dest_world_account.owner != program_id
+    {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Load the player (wallet only - sessions are bound to the source player)
+    let player = WorldPlayer::try_from_slice(&source_player_account.data.borrow())?;
+    if player.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if player.world != *source_world_account.key || source_world_account.key == dest_world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+    if !player.is_alive() {
+        return Err(WorldError::PlayerDead.into());
+    }
+
+    // Respect the destination's capacity
+    let mut source_world = WorldConfig::try_from_slice(&source_world_account.data.borrow())?;
+    let mut dest_world = WorldConfig::try_from_slice(&dest_world_account.data.borrow())?;
+    if !dest_world.can_join_directly() {
+        return Err(WorldError::WorldFull.into());
+    }
+    let allowlist = find_allowlist(program_id, dest_world_account.key, accounts)?;
+    dest_world.check_access(authority.key, allowlist.as_ref(), None)?;
+
+    // Derive the destination player PDA
+    let (dest_pda, bump) = WorldPlayer::derive_pda(dest_world_account.key, authority.key, program_id);
+    if dest_pda != *dest_player_account.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if dest_player_account.data_len() != 0 {
+        return Err(WorldError::PlayerAlreadyExists.into());
+    }
+
+    // The name moves with the player: claim it at the destination, release it at the source
+    let clock = Clock::get()?;
+    check_name(
+        program_id,
+        dest_world_account.key,
+        &dest_world,
+        accounts,
+        authority.key,
+        &player.name,
+        clock.slot,
+    )??;
+    claim_name(
+        program_id,
+        dest_world_account.key,
+        &dest_world,
+        accounts,
+        authority.key,
+        &player.name,
+        payer,
+        system_program,
+        clock.slot,
+    )?;
+    release_name(program_id, source_world_account.key, accounts, &player, authority)?;
+
+    // Swap team slots
+    if source_world.teams_enabled() && player.team != TEAM_NONE {
+        let scores_account = find_team_scores(program_id, source_world_account.key, accounts)?;
+        let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+        scores.remove_member(player.team);
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+    }
+    let team = if dest_world.teams_enabled() {
+        let scores_account = find_team_scores(program_id, dest_world_account.key, accounts)?;
+        let mut scores = TeamScores::try_from_slice(&scores_account.data.borrow())?;
+        let team = scores.auto_assign(&dest_world)?;
+        scores.add_member(team);
+        scores.serialize(&mut *scores_account.data.borrow_mut())?;
+        team
+    } else {
+        TEAM_NONE
+    };
+
+    // Create the destination player account
+    let rent = Rent::get()?;
+    let space = WorldPlayer::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            dest_player_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            dest_player_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            WORLD_PLAYER_SEED,
+            dest_world_account.key.as_ref(),
+            authority.key.as_ref(),
+            &[bump],
+        ]],
+    )?;
+
+    let moved = player.transfer_to(*dest_world_account.key, &dest_world, bump, team, clock.slot);
+    moved.serialize(&mut *dest_player_account.data.borrow_mut())?;
+
+    // Update both player counts
+    source_world.player_count = source_world.player_count.saturating_sub(1);
+    source_world.serialize(&mut *source_world_account.data.borrow_mut())?;
+    dest_world.player_count += 1;
+    dest_world.serialize(&mut *dest_world_account.data.borrow_mut())?;
+
+    // Close the source player account
+    let lamports = source_player_account.lamports();
+    **source_player_account.try_borrow_mut_lamports()? = 0;
+    **authority.try_borrow_mut_lamports()? = authority
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(WorldError::ArithmeticOverflow)?;
+    source_player_account.data.borrow_mut().fill(0);
+
+    msg!("Player {} moved to {}", moved.name_str(), dest_world.name_str());
+
+    Ok(())
+}
+
+/// Set who may join the world (world authority only)
+fn process_set_world_visibility(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    visibility: u8,
+    password_salt: [u8; 16],
+    password_hash: [u8; 32],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if visibility > WORLD_VISIBILITY_PASSWORD {
+        return Err(WorldError::InvalidVisibility.into());
+    }
+
+    world.visibility = visibility;
+    world.password_salt = password_salt;
+    world.password_hash = password_hash;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("World visibility set to {}", visibility);
+
+    Ok(())
+}
+
+/// Add a wallet to or remove it from the world's allowlist (world authority only)
+fn process_update_allowlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet: Pubkey,
+    allowed: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let allowlist_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify world authority
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Derive allowlist PDA
+    let (allowlist_pda, bump) = WorldAllowlist::derive_pda(world_account.key, program_id);
+    if allowlist_pda != *allowlist_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Create allowlist account on first use
+    let mut allowlist = if allowlist_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = WorldAllowlist::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                allowlist_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), allowlist_account.clone(), system_program.clone()],
+            &[&[ALLOWLIST_SEED, world_account.key.as_ref(), &[bump]]],
+        )?;
+
+        WorldAllowlist {
+            world: *world_account.key,
+            bump,
+            ..Default::default()
+        }
+    } else {
+        WorldAllowlist::try_from_slice(&allowlist_account.data.borrow())?
+    };
+
+    if allowed {
+        allowlist.add(wallet)?;
+    } else {
+        allowlist.remove(&wallet);
+    }
+    allowlist.serialize(&mut *allowlist_account.data.borrow_mut())?;
+
+    msg!("Allowlist updated: {} wallets", allowlist.count);
+
+    Ok(())
+}
+
+/// Hold back slots for VIPs (world authority only)
+fn process_set_reserved_slots(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reserved_slots: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if reserved_slots > world.max_players {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    world.reserved_slots = reserved_slots;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Reserved slots set to {}", reserved_slots);
+
+    Ok(())
+}
+
+/// Load a world's waitlist, verifying its PDA and owner
+fn load_waitlist(
+    program_id: &Pubkey,
+    world: &Pubkey,
+    waitlist_account: &AccountInfo,
+) -> Result<WorldWaitlist, ProgramError> {
+    let (waitlist_pda, _) = WorldWaitlist::derive_pda(world, program_id);
+    if waitlist_pda != *waitlist_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+    if waitlist_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+    Ok(WorldWaitlist::try_from_slice(&waitlist_account.data.borrow())?)
+}
+
+/// Get in line for a world
+fn process_join_waitlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: [u8; 16],
+    password: [u8; 32],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let waitlist_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Only players who could join may wait
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let allowlist = find_allowlist(program_id, world_account.key, accounts)?;
+    world.check_access(authority.key, allowlist.as_ref(), Some(&password))?;
+
+    // Catch bad names now (whether the name is free is checked on admission)
+    let reserved = find_reserved_names(program_id, world_account.key, &world, accounts)?;
+    world.validate_name(authority.key, &name, reserved.as_ref())?;
+
+    // Create waitlist account on first use
+    let (waitlist_pda, bump) = WorldWaitlist::derive_pda(world_account.key, program_id);
+    let mut waitlist = if waitlist_account.data_len() == 0 {
+        if waitlist_pda != *waitlist_account.key {
+            return Err(WorldError::InvalidInstructionData.into());
+        }
+
+        let rent = Rent::get()?;
+        let space = WorldWaitlist::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                waitlist_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), waitlist_account.clone(), system_program.clone()],
+            &[&[WAITLIST_SEED, world_account.key.as_ref(), &[bump]]],
+        )?;
+
+        WorldWaitlist {
+            world: *world_account.key,
+            bump,
+            ..Default::default()
+        }
+    } else {
+        load_waitlist(program_id, world_account.key, waitlist_account)?
+    };
+
+    let position = waitlist.push(*authority.key, name)?;
+    waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+
+    world.waiting_count = waitlist.count;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Waitlist position {} for {}", position, world.name_str());
+
+    Ok(())
+}
+
+/// Leave a world's waitlist
+fn process_leave_waitlist(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let waitlist_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let mut waitlist = load_waitlist(program_id, world_account.key, waitlist_account)?;
+    waitlist.remove(authority.key)?;
+    waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+
+    world.waiting_count = waitlist.count;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Left waitlist for {}", world.name_str());
+
+    Ok(())
+}
+
+/// Admit the head of the waitlist into an open regular slot
+fn process_admit_from_waitlist(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let waitlist_account = next_account_info(accounts_iter)?;
+    let player_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if !world.has_open_slot() {
+        return Err(WorldError::WorldFull.into());
+    }
+
+    let mut waitlist = load_waitlist(program_id, world_account.key, waitlist_account)?;
+    let head = waitlist.pop_front().ok_or(WorldError::NotOnWaitlist)?;
+    waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+    world.waiting_count = waitlist.count;
+
+    // Already in the world (joined another way) - just drop the entry
+    if player_account.data_len() != 0 {
+        let (player_pda, _) = WorldPlayer::derive_pda(world_account.key, &head.wallet, program_id);
+        if player_pda != *player_account.key {
+            return Err(WorldError::InvalidAuthority.into());
+        }
+        world.serialize(&mut *world_account.data.borrow_mut())?;
+        msg!("Waitlist dropped {}: already in {}", head.wallet, world.name_str());
+        return Ok(());
+    }
+
+    // Name no longer allowed or free - drop the entry so the line keeps moving
+    let clock = Clock::get()?;
+    if let Err(err) = check_name(
+        program_id,
+        world_account.key,
+        &world,
+        accounts,
+        &head.wallet,
+        &head.name,
+        clock.slot,
+    )? {
+        world.serialize(&mut *world_account.data.borrow_mut())?;
+        msg!("Waitlist dropped {}: {}", head.wallet, err);
+        return Ok(());
+    }
+
+    spawn_player(
+        program_id,
+        accounts,
+        world_account,
+        &mut world,
+        player_account,
+        &head.wallet,
+        payer,
+        system_program,
+        head.name,
+    )?;
+
+    msg!("Waitlist admitted {} into {}", head.wallet, world.name_str());
+
+    Ok(())
+}
+
+/// Admit a VIP ahead of the waitlist (world authority only)
+fn process_admit_vip(program_id: &Pubkey, accounts: &[AccountInfo], name: [u8; 16]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let player_account = next_account_info(accounts_iter)?;
+    let vip = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // VIPs may take reserved slots
+    if world.is_full() {
+        return Err(WorldError::WorldFull.into());
+    }
+
+    spawn_player(
+        program_id,
+        accounts,
+        world_account,
+        &mut world,
+        player_account,
+        vip.key,
+        payer,
+        system_program,
+        name,
+    )?;
+
+    msg!("VIP admitted {} into {}", vip.key, world.name_str());
+
+    Ok(())
+}
+
+/// Set the world's idle timeout (world authority only)
+fn process_set_idle_timeout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    idle_timeout_slots: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    world.idle_timeout_slots = idle_timeout_slots;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Idle timeout set to {} slots", idle_timeout_slots);
+
+    Ok(())
+}
+
+/// Evict a player that has been idle past the world's timeout (permissionless)
+fn process_evict_idle_player(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let player_account = next_account_info(accounts_iter)?;
+    let wallet = next_account_info(accounts_iter)?;
+
+    // Verify account owners
+    if world_account.owner != program_id || player_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
+    if player.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+    // Rent goes back to the player, never to whoever submitted the eviction
+    if player.authority != *wallet.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    let clock = Clock::get()?;
+    if !world.is_idle(&player, clock.slot) {
+        return Err(WorldError::PlayerNotIdle.into());
+    }
+
+    remove_player(program_id, accounts, world_account, player_account, &player, wallet)?;
+
+    msg!(
+        "Player evicted: {} ({}) idle since slot {}",
+        player.name_str(),
+        player.authority,
+        player.last_action_slot
+    );
+
+    Ok(())
+}
+
+/// Set the world's player name rules (world authority only)
+fn process_configure_names(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    unique_names: bool,
+    name_charset: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if name_charset > NAME_CHARSET_IDENTIFIER {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    world.unique_names = unique_names;
+    world.name_charset = name_charset;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Name rules: unique={} charset={}", unique_names, name_charset);
+
+    Ok(())
+}
+
+/// Reserve a word or release it (world authority only)
+fn process_update_reserved_names(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    word: [u8; 16],
+    reserved: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let reserved_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+
+    // Derive reserved-name list PDA
+    let (reserved_pda, bump) = ReservedNames::derive_pda(world_account.key, program_id);
+    if reserved_pda != *reserved_account.key {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    // Create reserved-name list on first use
+    let mut list = if reserved_account.data_len() == 0 {
+        let rent = Rent::get()?;
+        let space = ReservedNames::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                reserved_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), reserved_account.clone(), system_program.clone()],
+            &[&[RESERVED_NAMES_SEED, world_account.key.as_ref(), &[bump]]],
+        )?;
+
+        ReservedNames {
+            world: *world_account.key,
+            bump,
+            ..Default::default()
+        }
+    } else {
+        ReservedNames::try_from_slice(&reserved_account.data.borrow())?
+    };
+
+    if reserved {
+        list.add(&word)?;
+    } else {
+        list.remove(&word);
+    }
+    list.serialize(&mut *reserved_account.data.borrow_mut())?;
+
+    world.reserved_name_count = list.count;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Reserved names updated: {} words", list.count);
+
+    Ok(())
+}
+
+/// Set the world's ability timings (world authority only)
+fn process_configure_abilities(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    dash_speed: i16,
+    dash_cooldown_slots: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if dash_speed < 0 {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    world.dash_speed = dash_speed;
+    world.dash_cooldown_slots = dash_cooldown_slots;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Abilities: dash speed={} cooldown={} slots", dash_speed, dash_cooldown_slots);
+
+    Ok(())
+}
+
+/// Set how far attacks may rewind targets (world authority only)
+fn process_set_max_rewind(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_rewind_slots: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    // Position histories only reach back POSITION_HISTORY_SLOTS slots
+    if max_rewind_slots > MAX_REWIND_SLOTS {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    world.max_rewind_slots = max_rewind_slots;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Max rewind set to {} slots", max_rewind_slots);
+
+    Ok(())
+}
+
+/// Dead-reckon a slot of missing movement input (permissionless)
+fn process_extrapolate_movement(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let player_account = next_account_info(accounts_iter)?;
+
+    // Verify account owners
+    if world_account.owner != program_id || player_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    let world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    let mut player = WorldPlayer::try_from_slice(&player_account.data.borrow())?;
+    if player.world != *world_account.key {
+        return Err(WorldError::InvalidWorld.into());
+    }
+
+    // Predicted movement isn't an action, so idle eviction still applies
+    let clock = Clock::get()?;
+    if !player.extrapolate(&world, clock.slot) {
+        return Err(WorldError::InputNotMissing.into());
+    }
+
+    player.serialize(&mut *player_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Set how many missing inputs in a row are extrapolated (world authority only)
+fn process_set_max_extrapolation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_extrapolation_slots: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let world_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account owner
+    if world_account.owner != program_id {
+        return Err(WorldError::InvalidAccountOwner.into());
+    }
+
+    // Verify world authority
+    let mut world = WorldConfig::try_from_slice(&world_account.data.borrow())?;
+    if world.authority != *authority.key {
+        return Err(WorldError::InvalidAuthority.into());
+    }
+    if max_extrapolation_slots > MAX_EXTRAPOLATION_SLOTS {
+        return Err(WorldError::InvalidInstructionData.into());
+    }
+
+    world.max_extrapolation_slots = max_extrapolation_slots;
+    world.serialize(&mut *world_account.data.borrow_mut())?;
+
+    msg!("Max extrapolation set to {} slots", max_extrapolation_slots);
+
+    Ok(())
+}
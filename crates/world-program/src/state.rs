@@ -7,6 +7,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
 use crate::constants::*;
+use crate::error::WorldError;
 
 /// World configuration - singleton per world
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
@@ -31,11 +32,43 @@ pub struct WorldConfig {
     pub l1_game: Pubkey,
     /// Initialization timestamp
     pub init_ts: i64,
+    /// Number of teams (0 = free-for-all, max MAX_TEAMS)
+    pub team_count: u8,
+    /// Whether teammates can damage each other
+    pub friendly_fire: bool,
+    /// Maximum players per team (0 = unlimited)
+    pub max_team_size: u16,
+    /// Who may join (WORLD_VISIBILITY_*)
+    pub visibility: u8,
+    /// Salt mixed into the password hash
+    pub password_salt: [u8; 16],
+    /// SHA-256 of salt + password (password worlds only)
+    pub password_hash: [u8; 32],
+    /// Slots held back for VIPs admitted by the authority
+    pub reserved_slots: u16,
+    /// Players on the world's waitlist
+    pub waiting_count: u16,
+    /// Slots without an action before a player is evicted (0 = never)
+    pub idle_timeout_slots: u64,
+    /// Each normalized name may only be used by one wallet (see `NameClaim`)
+    pub unique_names: bool,
+    /// Characters allowed in player names (NAME_CHARSET_*)
+    pub name_charset: u8,
+    /// Words on the world's reserved-name list
+    pub reserved_name_count: u16,
+    /// Speed of a dash burst (0 = dashing disabled)
+    pub dash_speed: i16,
+    /// Slots a player waits between dashes
+    pub dash_cooldown_slots: u64,
+    /// How far back attacks may rewind targets to the attacker's view (slots)
+    pub max_rewind_slots: u64,
+    /// Missing inputs in a row filled in by repeating the last one (0 = off)
+    pub max_extrapolation_slots: u64,
 }
 
 impl WorldConfig {
     /// Account size
-    pub const LEN: usize = 32 + 32 + 4 + 4 + 2 + 2 + 1 + 1 + 32 + 8;
+    pub const LEN: usize = 32 + 32 + 4 + 4 + 2 + 2 + 1 + 1 + 32 + 8 + 1 + 1 + 2 + 1 + 16 + 32 + 2 + 2 + 8 + 1 + 1 + 2 + 2 + 8 + 8 + 8;
 
     /// Derive PDA for world config
     pub fn derive_pda(name: &[u8], program_id: &Pubkey) -> (Pubkey, u8) {
@@ -53,6 +86,135 @@ impl WorldConfig {
     pub fn is_full(&self) -> bool {
         self.player_count >= self.max_players
     }
+
+    /// Check if a regular (non-reserved) slot is open
+    pub fn has_open_slot(&self) -> bool {
+        (self.player_count as u32 + self.reserved_slots as u32) < self.max_players as u32
+    }
+
+    /// Check if a newcomer can take a slot directly (nobody waiting ahead of them)
+    pub fn can_join_directly(&self) -> bool {
+        self.has_open_slot() && self.waiting_count == 0
+    }
+
+    /// Check if `player` has been inactive long enough to be evicted at `slot`
+    pub fn is_idle(&self, player: &WorldPlayer, slot: u64) -> bool {
+        self.idle_timeout_slots > 0
+            && slot.saturating_sub(player.last_action_slot) >= self.idle_timeout_slots
+    }
+
+    /// Spawn point (fixed-point X/Z) - the world center
+    pub fn spawn_point(&self) -> (i32, i32) {
+        (
+            (self.width as i32 / 2) * FIXED_POINT_SCALE,
+            (self.depth as i32 / 2) * FIXED_POINT_SCALE,
+        )
+    }
+
+    /// Slot to rewind targets to for an attack the client saw at `observed_slot`
+    ///
+    /// Clamped to the world's maximum rewind; no observed slot means now.
+    pub fn rewind_slot(&self, observed_slot: Option<u64>, slot: u64) -> u64 {
+        observed_slot.map_or(slot, |observed| {
+            observed.clamp(slot.saturating_sub(self.max_rewind_slots), slot)
+        })
+    }
+
+    /// Check if the world is team-based
+    pub fn teams_enabled(&self) -> bool {
+        self.team_count > 0
+    }
+
+    /// Salted hash of a zero-padded world password
+    pub fn hash_password(salt: &[u8; 16], password: &[u8; 32]) -> [u8; 32] {
+        solana_program::hash::hashv(&[salt, password]).to_bytes()
+    }
+
+    /// Check whether `wallet` may join under the world's visibility settings
+    ///
+    /// The world authority can always join its own world.
+    pub fn check_access(
+        &self,
+        wallet: &Pubkey,
+        allowlist: Option<&WorldAllowlist>,
+        password: Option<&[u8; 32]>,
+    ) -> Result<(), WorldError> {
+        if *wallet == self.authority {
+            return Ok(());
+        }
+        match self.visibility {
+            WORLD_VISIBILITY_PUBLIC => Ok(()),
+            WORLD_VISIBILITY_ALLOWLIST => match allowlist {
+                Some(list) if list.contains(wallet) => Ok(()),
+                _ => Err(WorldError::NotAllowlisted),
+            },
+            WORLD_VISIBILITY_PASSWORD => match password {
+                Some(password)
+                    if Self::hash_password(&self.password_salt, password) == self.password_hash =>
+                {
+                    Ok(())
+                }
+                _ => Err(WorldError::InvalidPassword),
+            },
+            _ => Err(WorldError::InvalidVisibility),
+        }
+    }
+
+    /// Check a player name against the world's character set and reserved words
+    ///
+    /// The world authority may use reserved words.
+    pub fn validate_name(
+        &self,
+        wallet: &Pubkey,
+        name: &[u8; 16],
+        reserved: Option<&ReservedNames>,
+    ) -> Result<(), WorldError> {
+        let len = name.iter().position(|&byte| byte == 0).unwrap_or(name.len());
+        if len == 0 || name[len..].iter().any(|&byte| byte != 0) {
+            return Err(WorldError::InvalidName);
+        }
+        let allowed = |byte: u8| match self.name_charset {
+            NAME_CHARSET_ANY => true,
+            NAME_CHARSET_ALPHANUMERIC => byte.is_ascii_alphanumeric(),
+            NAME_CHARSET_IDENTIFIER => byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-',
+            _ => false,
+        };
+        if !name[..len].iter().all(|&byte| allowed(byte)) {
+            return Err(WorldError::InvalidName);
+        }
+        if *wallet != self.authority && reserved.is_some_and(|list| list.matches(name)) {
+            return Err(WorldError::NameReserved);
+        }
+        Ok(())
+    }
+
+    /// Check a name for `wallet`: name rules, then the name's claim (unique-name worlds)
+    pub fn check_name(
+        &self,
+        wallet: &Pubkey,
+        name: &[u8; 16],
+        reserved: Option<&ReservedNames>,
+        claim: Option<&NameClaim>,
+        slot: u64,
+    ) -> Result<(), WorldError> {
+        self.validate_name(wallet, name, reserved)?;
+        if claim.is_some_and(|claim| !claim.can_claim(wallet, slot)) {
+            return Err(WorldError::NameTaken);
+        }
+        Ok(())
+    }
+
+    /// Check friendly-fire rules for an attack
+    pub fn check_friendly_fire(&self, attacker: &WorldPlayer, target: &WorldPlayer) -> Result<(), WorldError> {
+        if self.teams_enabled()
+            && !self.friendly_fire
+            && attacker.team != TEAM_NONE
+            && attacker.team == target.team
+        {
+            return Err(WorldError::FriendlyFire);
+        }
+        Ok(())
+    }
 }
 
 /// Player state in the world (3D)
@@ -97,11 +259,66 @@ pub struct WorldPlayer {
     pub bump: u8,
     /// Player name (max 16 bytes)
     pub name: [u8; 16],
+    /// Team (TEAM_NONE = no team)
+    pub team: u8,
+    /// Hit-stunned until this slot (movement input ignored, no attacks)
+    pub stun_until_slot: u64,
+    /// Is player crouching (shorter hitbox, slower movement)
+    pub is_crouching: bool,
+    /// First slot the player may dash again
+    pub dash_ready_slot: u64,
+    /// Last movement input the player sent
+    pub last_input: MovementInput3D,
+    /// Slot of `last_input`
+    pub last_input_slot: u64,
+    /// Slots predicted from `last_input` since then (0 = last move was real input)
+    pub extrapolated_slots: u8,
 }
 
 impl WorldPlayer {
-    /// Account size: 32 + 32 + 4 + 4 + 4 + 2 + 2 + 2 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 1 + 16 = 123
-    pub const LEN: usize = 32 + 32 + 4 + 4 + 4 + 2 + 2 + 2 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 1 + 16;
+    /// Account size: 32 + 32 + 4 + 4 + 4 + 2 + 2 + 2 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 1 + 16 + 1 + 8 + 1 + 8
+    /// + 8 + 8 + 1 = 158
+    pub const LEN: usize = 32 + 32 + 4 + 4 + 4 + 2 + 2 + 2 + 2 + 2 + 2 + 8 + 8 + 1 + 1 + 1 + 16 + 1 + 8 + 1 + 8
+        + MovementInput3D::LEN + 8 + 1;
+
+    /// New player at the world's spawn point with full health
+    pub fn spawn(
+        authority: Pubkey,
+        world_key: Pubkey,
+        world: &WorldConfig,
+        name: [u8; 16],
+        bump: u8,
+        team: u8,
+        slot: u64,
+    ) -> WorldPlayer {
+        let (position_x, position_z) = world.spawn_point();
+        WorldPlayer {
+            authority,
+            world: world_key,
+            position_x,
+            position_z,
+            position_y: 0, // Start on ground
+            velocity_x: 0,
+            velocity_z: 0,
+            velocity_y: 0,
+            yaw: 0,
+            health: DEFAULT_HEALTH,
+            max_health: DEFAULT_MAX_HEALTH,
+            last_action_slot: slot,
+            last_combat_ts: 0,
+            in_pvp_zone: false,
+            is_grounded: true,
+            bump,
+            name,
+            team,
+            stun_until_slot: 0,
+            is_crouching: false,
+            dash_ready_slot: 0,
+            last_input: MovementInput3D::default(),
+            last_input_slot: slot,
+            extrapolated_slots: 0,
+        }
+    }
 
     /// Derive PDA for world player
     pub fn derive_pda(world: &Pubkey, authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
@@ -128,13 +345,129 @@ impl WorldPlayer {
         self.health = self.health.saturating_sub(damage);
     }
 
+    /// Knock the player back from a hit at (from_x, from_z)
+    ///
+    /// The impulse points away from the attacker and grows with damage. It
+    /// replaces the horizontal velocity, lifts the player off the ground and
+    /// starts a hit-stun window; `apply_movement_3d` carries it from there.
+    /// `from_yaw` is used when both stand on the same spot.
+    pub fn apply_knockback(&mut self, from_x: i32, from_z: i32, from_yaw: i16, damage: u16, slot: u64) {
+        let impulse = (KNOCKBACK_BASE as i32 + damage as i32 * KNOCKBACK_PER_DAMAGE as i32)
+            .min(MAX_KNOCKBACK as i32) as f32;
+
+        let dx = (self.position_x - from_x) as f32;
+        let dz = (self.position_z - from_z) as f32;
+        let length = (dx * dx + dz * dz).sqrt();
+        let (dir_x, dir_z) = if length > 0.0 {
+            (dx / length, dz / length)
+        } else {
+            // yaw: 0 = +Z, 16384 = +X (see camera_to_world_direction)
+            let yaw_rad = (from_yaw as f32) * std::f32::consts::PI * 2.0 / 65536.0;
+            (yaw_rad.sin(), yaw_rad.cos())
+        };
+
+        self.velocity_x = (dir_x * impulse) as i16;
+        self.velocity_z = (dir_z * impulse) as i16;
+        self.velocity_y = self.velocity_y.max(KNOCKBACK_LIFT);
+        self.is_grounded = false;
+        self.stun_until_slot = slot + HIT_STUN_SLOTS;
+    }
+
+    /// Check if the player is still reeling from a hit
+    pub fn is_stunned(&self, slot: u64) -> bool {
+        slot < self.stun_until_slot
+    }
+
+    /// Movement input the player is allowed this slot
+    ///
+    /// While stunned only the camera turns: movement, sprint and jump are
+    /// dropped so knockback, friction and gravity play out.
+    pub fn limit_input(&self, input: &MovementInput3D, slot: u64) -> MovementInput3D {
+        if !self.is_stunned(slot) {
+            return *input;
+        }
+        MovementInput3D {
+            camera_yaw: input.camera_yaw,
+            ..Default::default()
+        }
+    }
+
+    /// Remember the input the player sent at `slot` (ends any extrapolation)
+    pub fn record_input(&mut self, input: &MovementInput3D, slot: u64) {
+        self.last_input = *input;
+        self.last_input_slot = slot;
+        self.extrapolated_slots = 0;
+    }
+
+    /// Input to repeat at `slot` if the player's input for it is missing
+    ///
+    /// Only moving players are extrapolated, for at most the world's limit of
+    /// slots in a row and once per slot. One-shot inputs (jump, dash) are not
+    /// repeated.
+    pub fn extrapolation_input(&self, world: &WorldConfig, slot: u64) -> Option<MovementInput3D> {
+        let predicted_through = self.last_input_slot + self.extrapolated_slots as u64;
+        let missing = self.is_alive()
+            && self.last_input.is_moving()
+            && slot > predicted_through
+            && (self.extrapolated_slots as u64) < world.max_extrapolation_slots;
+        missing.then(|| self.last_input.repeated())
+    }
+
+    /// Dead-reckon one slot of missing input by repeating the last one
+    ///
+    /// Returns false (and leaves the player alone) if nothing is missing.
+    pub fn extrapolate(&mut self, world: &WorldConfig, slot: u64) -> bool {
+        let Some(input) = self.extrapolation_input(world, slot) else {
+            return false;
+        };
+        let input = self.limit_input(&input, slot);
+        self.apply_movement_3d(&input, world, slot);
+        self.extrapolated_slots += 1;
+        true
+    }
+
     /// Apply healing to player
     pub fn apply_heal(&mut self, heal: u16) {
         self.health = std::cmp::min(self.health.saturating_add(heal), self.max_health);
     }
 
+    /// Copy of this player moved into another world at its spawn point
+    ///
+    /// Health, stats and cooldowns carry over; position, motion, last input,
+    /// hit-stun, crouch, PVP zone and team are reset for the destination.
+    pub fn transfer_to(
+        &self,
+        world_key: Pubkey,
+        world: &WorldConfig,
+        bump: u8,
+        team: u8,
+        slot: u64,
+    ) -> WorldPlayer {
+        let (position_x, position_z) = world.spawn_point();
+        WorldPlayer {
+            world: world_key,
+            position_x,
+            position_z,
+            position_y: 0,
+            velocity_x: 0,
+            velocity_z: 0,
+            velocity_y: 0,
+            last_action_slot: slot,
+            in_pvp_zone: false,
+            is_grounded: true,
+            bump,
+            team,
+            stun_until_slot: 0,
+            is_crouching: false,
+            last_input: MovementInput3D::default(),
+            last_input_slot: slot,
+            extrapolated_slots: 0,
+            ..self.clone()
+        }
+    }
+
     /// Apply 3D movement with physics
-    pub fn apply_movement_3d(&mut self, input: &MovementInput3D, world: &WorldConfig) {
+    pub fn apply_movement_3d(&mut self, input: &MovementInput3D, world: &WorldConfig, slot: u64) {
         // Convert camera-relative input to world-space direction
         let (world_dx, world_dz) = self.camera_to_world_direction(
             input.move_x,
@@ -142,8 +475,17 @@ impl WorldPlayer {
             input.camera_yaw,
         );
 
+        // Crouching shrinks the hitbox and overrides sprint
+        self.is_crouching = input.crouch;
+
         // Target velocity based on input
-        let speed = if input.sprint { SPRINT_SPEED } else { NORMAL_SPEED };
+        let speed = if self.is_crouching {
+            CROUCH_SPEED
+        } else if input.sprint {
+            SPRINT_SPEED
+        } else {
+            NORMAL_SPEED
+        };
         let target_vx = if world_dx != 0 {
             (world_dx as i32 * speed as i32 / 127) as i16
         } else {
@@ -177,13 +519,28 @@ impl WorldPlayer {
             self.velocity_z = self.apply_friction(self.velocity_z, FRICTION);
         }
 
+        // Dash: burst in the input direction (or facing), then cool down
+        if input.dash && world.dash_speed > 0 && slot >= self.dash_ready_slot {
+            let (dir_x, dir_z) = if world_dx != 0 || world_dz != 0 {
+                let (dx, dz) = (world_dx as f32, world_dz as f32);
+                let length = (dx * dx + dz * dz).sqrt();
+                (dx / length, dz / length)
+            } else {
+                let yaw_rad = (input.camera_yaw as f32) * std::f32::consts::PI * 2.0 / 65536.0;
+                (yaw_rad.sin(), yaw_rad.cos())
+            };
+            self.velocity_x = (dir_x * world.dash_speed as f32) as i16;
+            self.velocity_z = (dir_z * world.dash_speed as f32) as i16;
+            self.dash_ready_slot = slot + world.dash_cooldown_slots;
+        }
+
         // Update positions
         self.position_x = (self.position_x + self.velocity_x as i32)
             .clamp(0, (world.width as i32) * FIXED_POINT_SCALE);
         self.position_z = (self.position_z + self.velocity_z as i32)
             .clamp(0, (world.depth as i32) * FIXED_POINT_SCALE);
         self.position_y = (self.position_y + self.velocity_y as i32)
-            .clamp(GROUND_LEVEL, MAX_HEIGHT);
+            .clamp(GROUND_LEVEL, MAX_HEIGHT - self.hitbox_height());
 
         // Ground collision
         if self.position_y <= GROUND_LEVEL {
@@ -271,6 +628,962 @@ impl WorldPlayer {
         let dy = (self.position_y - other.position_y) as i64;
         dx * dx + dz * dz + dy * dy
     }
+
+    /// Height of the player's hitbox above their feet
+    pub fn hitbox_height(&self) -> i32 {
+        if self.is_crouching {
+            CROUCH_HEIGHT
+        } else {
+            STANDING_HEIGHT
+        }
+    }
+
+    /// Squared distance from this player's center to the nearest point of the
+    /// target's hitbox (a vertical segment from feet to head)
+    ///
+    /// Used for attack range checks, so a crouching target is harder to reach
+    /// from above.
+    pub fn reach_distance_squared(&self, target: &WorldPlayer) -> i64 {
+        let dx = (self.position_x - target.position_x) as i64;
+        let dz = (self.position_z - target.position_z) as i64;
+        let center_y = self.position_y + self.hitbox_height() / 2;
+        let bottom = target.position_y;
+        let top = target.position_y + target.hitbox_height();
+        let dy = if center_y < bottom {
+            (bottom - center_y) as i64
+        } else {
+            (center_y - top).max(0) as i64
+        };
+        dx * dx + dz * dz + dy * dy
+    }
+}
+
+/// Where a player or NPC stood from `slot` until its next sample
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionSample {
+    /// Player or NPC account
+    pub entity: Pubkey,
+    /// First slot at this position
+    pub slot: u64,
+    /// X position (fixed-point)
+    pub position_x: i32,
+    /// Y position (fixed-point)
+    pub position_y: i32,
+    /// Z position (fixed-point)
+    pub position_z: i32,
+    /// Crouching (shorter hitbox)
+    pub is_crouching: bool,
+}
+
+impl PositionSample {
+    /// Serialized size: 32 + 8 + 4 + 4 + 4 + 1 = 53
+    pub const LEN: usize = 32 + 8 + 4 + 4 + 4 + 1;
+
+    /// Sample of where `body` stands at `slot`
+    pub fn of(entity: Pubkey, body: &WorldPlayer, slot: u64) -> Self {
+        Self {
+            entity,
+            slot,
+            position_x: body.position_x,
+            position_y: body.position_y,
+            position_z: body.position_z,
+            is_crouching: body.is_crouching,
+        }
+    }
+
+    /// Same position and hitbox, ignoring the slot
+    fn same_place(&self, other: &PositionSample) -> bool {
+        (self.position_x, self.position_y, self.position_z, self.is_crouching)
+            == (other.position_x, other.position_y, other.position_z, other.is_crouching)
+    }
+}
+
+/// Recent positions of a world's players and NPCs, for lag-compensated hits
+///
+/// The runtime records every moved player and NPC when it seals a slot, so
+/// moves don't contend on this account; attacks only read it. Samples older
+/// than `POSITION_HISTORY_SLOTS` are dropped unless they are the last known
+/// position of an entity that moved since.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct PositionHistory {
+    /// World the samples belong to
+    pub world: Pubkey,
+    /// Samples, oldest first
+    pub samples: Vec<PositionSample>,
+}
+
+impl PositionHistory {
+    /// Derive PDA for a world's position history
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[POSITION_HISTORY_SEED, world.as_ref()], program_id)
+    }
+
+    /// Empty history for `world`
+    pub fn new(world: Pubkey) -> Self {
+        Self {
+            world,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Account size with `samples` samples: 32 + 4 + 53 * samples
+    pub fn len_for(samples: usize) -> usize {
+        32 + 4 + PositionSample::LEN * samples
+    }
+
+    /// Remember where an entity stands from `sample.slot` on
+    ///
+    /// Positions hold until the next sample, so an unchanged position is not
+    /// recorded again.
+    pub fn record(&mut self, sample: PositionSample) {
+        let last = self.samples.iter().rev().find(|last| last.entity == sample.entity);
+        if last.is_some_and(|last| last.same_place(&sample)) {
+            return;
+        }
+        self.samples.push(sample);
+    }
+
+    /// Drop samples no rewind from after `slot` can reach
+    ///
+    /// Keeps the last `POSITION_HISTORY_SLOTS` slots, plus the sample each
+    /// entity in them stood at before its first move in that window.
+    pub fn prune(&mut self, slot: u64) {
+        let cutoff = slot.saturating_sub(POSITION_HISTORY_SLOTS);
+        let mut keep = vec![false; self.samples.len()];
+        let mut moved_since = Vec::new();
+        for (index, sample) in self.samples.iter().enumerate().rev() {
+            if sample.slot > cutoff {
+                keep[index] = true;
+                if !moved_since.contains(&sample.entity) {
+                    moved_since.push(sample.entity);
+                }
+            } else if let Some(position) = moved_since.iter().position(|entity| *entity == sample.entity) {
+                keep[index] = true;
+                moved_since.swap_remove(position);
+            }
+        }
+        let mut keep = keep.into_iter();
+        self.samples.retain(|_| keep.next().unwrap_or(false));
+    }
+
+    /// Copy of `body` where it stood at `slot`, for lag-compensated hit tests
+    ///
+    /// Uses the entity's newest sample at or before `slot`, or its oldest one
+    /// if the history doesn't reach back that far. An entity without samples
+    /// hasn't moved within the history, so it is where it is now. `slot` must
+    /// be before the current slot, whose moves are only recorded once it is
+    /// sealed.
+    pub fn rewound(&self, entity: &Pubkey, body: &WorldPlayer, slot: u64) -> WorldPlayer {
+        let mut samples = self.samples.iter().filter(|sample| sample.entity == *entity);
+        let Some(oldest) = samples.next() else {
+            return body.clone();
+        };
+        let sample = std::iter::once(oldest)
+            .chain(samples)
+            .take_while(|sample| sample.slot <= slot)
+            .last()
+            .unwrap_or(oldest);
+        WorldPlayer {
+            position_x: sample.position_x,
+            position_y: sample.position_y,
+            position_z: sample.position_z,
+            is_crouching: sample.is_crouching,
+            ..body.clone()
+        }
+    }
+}
+
+/// Authority-driven NPC (monster or bot)
+///
+/// Shares the player combat model: the `body` is a regular `WorldPlayer`
+/// whose authority is the world authority, so movement, damage and healing
+/// go through the same code paths as human players.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct WorldNpc {
+    /// Position, physics and health (authority = world authority)
+    pub body: WorldPlayer,
+    /// NPC identifier within the world (part of the PDA seeds)
+    pub npc_id: u32,
+    /// NPC kind (NPC_KIND_*)
+    pub kind: u8,
+    /// Damage dealt per attack
+    pub damage: u16,
+    /// Attack range (fixed-point units)
+    pub attack_range: u32,
+    /// Spawn X position (fixed-point)
+    pub spawn_x: i32,
+    /// Spawn Z position (fixed-point)
+    pub spawn_z: i32,
+    /// Slots between death and respawn
+    pub respawn_delay_slots: u64,
+    /// Slot the NPC respawns at (0 while alive)
+    pub respawn_at_slot: u64,
+    /// Loot dropped on death
+    pub loot_amount: u32,
+    /// Number of times this NPC has died (seeds the next loot drop PDA)
+    pub deaths: u32,
+}
+
+impl WorldNpc {
+    /// Account size: WorldPlayer + 4 + 1 + 2 + 4 + 4 + 4 + 8 + 8 + 4 + 4
+    pub const LEN: usize = WorldPlayer::LEN + 4 + 1 + 2 + 4 + 4 + 4 + 8 + 8 + 4 + 4;
+
+    /// Derive PDA for an NPC
+    pub fn derive_pda(world: &Pubkey, npc_id: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[NPC_SEED, world.as_ref(), &npc_id.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Initialize a freshly spawned NPC from its spawn parameters
+    ///
+    /// Zero-valued stats fall back to defaults and the spawn point is clamped to the world.
+    pub fn initialize(&mut self, world: &WorldConfig, world_key: Pubkey, bump: u8, slot: u64) {
+        let max_x = (world.width as i32) * FIXED_POINT_SCALE;
+        let max_z = (world.depth as i32) * FIXED_POINT_SCALE;
+
+        self.body.authority = world.authority;
+        self.body.world = world_key;
+        self.body.bump = bump;
+        self.body.yaw = 0;
+        self.body.last_combat_ts = 0;
+        self.body.in_pvp_zone = false;
+        if self.body.max_health == 0 {
+            self.body.max_health = DEFAULT_MAX_HEALTH;
+        }
+        if self.damage == 0 {
+            self.damage = DEFAULT_DAMAGE;
+        }
+        if self.attack_range == 0 {
+            self.attack_range = DEFAULT_NPC_ATTACK_RANGE;
+        }
+        if self.respawn_delay_slots == 0 {
+            self.respawn_delay_slots = DEFAULT_NPC_RESPAWN_SLOTS;
+        }
+        self.spawn_x = self.spawn_x.clamp(0, max_x);
+        self.spawn_z = self.spawn_z.clamp(0, max_z);
+        self.deaths = 0;
+        self.respawn(slot);
+    }
+
+    /// Check if the NPC is alive
+    pub fn is_alive(&self) -> bool {
+        self.body.is_alive()
+    }
+
+    /// Check if a dead NPC is due to respawn
+    pub fn can_respawn(&self, slot: u64) -> bool {
+        !self.is_alive() && slot >= self.respawn_at_slot
+    }
+
+    /// Mark the NPC dead and schedule its respawn
+    pub fn on_death(&mut self, slot: u64) {
+        self.respawn_at_slot = slot + self.respawn_delay_slots;
+        self.deaths = self.deaths.saturating_add(1);
+        self.body.velocity_x = 0;
+        self.body.velocity_z = 0;
+        self.body.velocity_y = 0;
+    }
+
+    /// Respawn at the spawn point with full health
+    pub fn respawn(&mut self, slot: u64) {
+        self.body.position_x = self.spawn_x;
+        self.body.position_z = self.spawn_z;
+        self.body.position_y = GROUND_LEVEL;
+        self.body.velocity_x = 0;
+        self.body.velocity_z = 0;
+        self.body.velocity_y = 0;
+        self.body.is_grounded = true;
+        self.body.stun_until_slot = 0;
+        self.body.is_crouching = false;
+        self.body.health = self.body.max_health;
+        self.body.last_action_slot = slot;
+        self.respawn_at_slot = 0;
+    }
+
+    /// Check if a player's hitbox is within attack range
+    pub fn in_range(&self, target: &WorldPlayer) -> bool {
+        let range = self.attack_range as i64;
+        self.body.reach_distance_squared(target) <= range * range
+    }
+}
+
+/// Loot dropped where an NPC died
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct LootDrop {
+    /// World the loot is in
+    pub world: Pubkey,
+    /// NPC that dropped it
+    pub npc: Pubkey,
+    /// Player account credited with the kill
+    pub killer: Pubkey,
+    /// X position (fixed-point)
+    pub position_x: i32,
+    /// Z position (fixed-point)
+    pub position_z: i32,
+    /// Loot amount
+    pub amount: u32,
+    /// Slot the loot was dropped
+    pub dropped_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl LootDrop {
+    /// Account size: 32 + 32 + 32 + 4 + 4 + 4 + 8 + 1 = 117
+    pub const LEN: usize = 32 + 32 + 32 + 4 + 4 + 4 + 8 + 1;
+
+    /// Derive PDA for the loot of an NPC's `death_index`-th death
+    pub fn derive_pda(npc: &Pubkey, death_index: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[LOOT_SEED, npc.as_ref(), &death_index.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+/// Per-world team membership and score aggregation
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct TeamScores {
+    /// World this account belongs to
+    pub world: Pubkey,
+    /// Players on each team (index = team - 1)
+    pub member_counts: [u16; MAX_TEAMS],
+    /// Score of each team (index = team - 1)
+    pub scores: [u32; MAX_TEAMS],
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TeamScores {
+    /// Account size: 32 + 2 * MAX_TEAMS + 4 * MAX_TEAMS + 1 = 57
+    pub const LEN: usize = 32 + 2 * MAX_TEAMS + 4 * MAX_TEAMS + 1;
+
+    /// Derive PDA for a world's team scores
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TEAM_SCORES_SEED, world.as_ref()], program_id)
+    }
+
+    /// Pick the smallest team with room (lowest id wins ties)
+    pub fn auto_assign(&self, world: &WorldConfig) -> Result<u8, WorldError> {
+        (1..=world.team_count)
+            .filter(|team| self.has_room(*team, world))
+            .min_by_key(|team| (self.member_counts[*team as usize - 1], *team))
+            .ok_or(WorldError::TeamFull)
+    }
+
+    /// Validate that a player may join `team`
+    pub fn check_join(&self, team: u8, world: &WorldConfig) -> Result<(), WorldError> {
+        if team == TEAM_NONE || team > world.team_count {
+            return Err(WorldError::InvalidTeam);
+        }
+        if !self.has_room(team, world) {
+            return Err(WorldError::TeamFull);
+        }
+        Ok(())
+    }
+
+    /// Check if a team is below the world's size limit
+    pub fn has_room(&self, team: u8, world: &WorldConfig) -> bool {
+        world.max_team_size == 0 || self.member_counts[team as usize - 1] < world.max_team_size
+    }
+
+    /// Record a player joining a team
+    pub fn add_member(&mut self, team: u8) {
+        if let Some(count) = self.member_counts.get_mut((team as usize).wrapping_sub(1)) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Record a player leaving a team
+    pub fn remove_member(&mut self, team: u8) {
+        if let Some(count) = self.member_counts.get_mut((team as usize).wrapping_sub(1)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Add points to a team's score
+    pub fn add_score(&mut self, team: u8, points: u32) {
+        if let Some(score) = self.scores.get_mut((team as usize).wrapping_sub(1)) {
+            *score = score.saturating_add(points);
+        }
+    }
+}
+
+/// Objective game mode for a team world (king of the hill or capture the flag)
+///
+/// Scores for the running match live in the world's `TeamScores`; the leader
+/// submits `TickGameMode` every slot to evaluate the objectives.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct GameMode {
+    /// World this game mode runs in
+    pub world: Pubkey,
+    /// Game mode (GAME_MODE_*)
+    pub mode: u8,
+    /// Hill center X (fixed-point)
+    pub objective_x: i32,
+    /// Hill center Z (fixed-point)
+    pub objective_z: i32,
+    /// Hill radius, and flag pickup / capture radius (fixed-point)
+    pub objective_radius: u32,
+    /// Flag base X per team (index = team - 1)
+    pub flag_base_x: [i32; MAX_TEAMS],
+    /// Flag base Z per team (index = team - 1)
+    pub flag_base_z: [i32; MAX_TEAMS],
+    /// Player account carrying each team's flag (default = flag at its base)
+    pub flag_carriers: [Pubkey; MAX_TEAMS],
+    /// Match length in slots (0 = no time limit)
+    pub match_duration_slots: u64,
+    /// Score that ends the match (0 = no score limit)
+    pub score_limit: u32,
+    /// Index of the running match (seeds its match result PDA)
+    pub match_index: u32,
+    /// Slot the running match started
+    pub match_start_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl GameMode {
+    /// Account size: 32 + 1 + 4 + 4 + 4 + 4 * MAX_TEAMS + 4 * MAX_TEAMS + 32 * MAX_TEAMS + 8 + 4 + 4 + 8 + 1 = 230
+    pub const LEN: usize =
+        32 + 1 + 4 + 4 + 4 + 4 * MAX_TEAMS + 4 * MAX_TEAMS + 32 * MAX_TEAMS + 8 + 4 + 4 + 8 + 1;
+
+    /// Derive PDA for a world's game mode
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[GAME_MODE_SEED, world.as_ref()], program_id)
+    }
+
+    /// Check that the mode can run in `world`
+    pub fn validate(&self, world: &WorldConfig) -> Result<(), WorldError> {
+        match self.mode {
+            GAME_MODE_KING_OF_THE_HILL if world.teams_enabled() => Ok(()),
+            GAME_MODE_CAPTURE_THE_FLAG if world.team_count >= 2 => Ok(()),
+            GAME_MODE_KING_OF_THE_HILL | GAME_MODE_CAPTURE_THE_FLAG => Err(WorldError::InvalidTeam),
+            _ => Err(WorldError::InvalidGameMode),
+        }
+    }
+
+    /// Start a new round: scores zeroed, flags home, timer restarted
+    pub fn start_match(&mut self, scores: &mut TeamScores, slot: u64) {
+        scores.scores = [0; MAX_TEAMS];
+        self.flag_carriers = [Pubkey::default(); MAX_TEAMS];
+        self.match_start_slot = slot;
+    }
+
+    /// Score one slot of objective play
+    ///
+    /// `players` are the world players near the objectives. Flag carriers
+    /// missing from the list or dead drop the flag back to its base.
+    pub fn evaluate(&mut self, world: &WorldConfig, scores: &mut TeamScores, players: &[(Pubkey, WorldPlayer)]) {
+        let contenders: Vec<&(Pubkey, WorldPlayer)> = players
+            .iter()
+            .filter(|(_, player)| {
+                player.is_alive() && player.team != TEAM_NONE && player.team <= world.team_count
+            })
+            .collect();
+
+        match self.mode {
+            GAME_MODE_KING_OF_THE_HILL => {
+                let mut holder = None;
+                for (_, player) in contenders {
+                    if !self.is_near(player, self.objective_x, self.objective_z) {
+                        continue;
+                    }
+                    match holder {
+                        None => holder = Some(player.team),
+                        Some(team) if team == player.team => {}
+                        // Contested hills score for nobody
+                        Some(_) => return,
+                    }
+                }
+                if let Some(team) = holder {
+                    scores.add_score(team, KOTH_SCORE_PER_SLOT);
+                }
+            }
+            GAME_MODE_CAPTURE_THE_FLAG => {
+                for flag in 0..world.team_count as usize {
+                    let carrier_key = self.flag_carriers[flag];
+                    if carrier_key == Pubkey::default() {
+                        // First enemy at the base picks the flag up
+                        let taker = contenders.iter().find(|(key, player)| {
+                            player.team as usize - 1 != flag
+                                && !self.flag_carriers.contains(key)
+                                && self.is_near(player, self.flag_base_x[flag], self.flag_base_z[flag])
+                        });
+                        if let Some((key, _)) = taker {
+                            self.flag_carriers[flag] = *key;
+                        }
+                        continue;
+                    }
+
+                    let Some((_, carrier)) = contenders.iter().find(|(key, _)| *key == carrier_key) else {
+                        self.flag_carriers[flag] = Pubkey::default();
+                        continue;
+                    };
+
+                    // Capture at the carrier's own base while their flag is home
+                    let home = carrier.team as usize - 1;
+                    if self.flag_carriers[home] == Pubkey::default()
+                        && self.is_near(carrier, self.flag_base_x[home], self.flag_base_z[home])
+                    {
+                        scores.add_score(carrier.team, CTF_CAPTURE_SCORE);
+                        self.flag_carriers[flag] = Pubkey::default();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Check if the running match is over at `slot`
+    pub fn is_over(&self, scores: &TeamScores, slot: u64) -> bool {
+        let timed_out = self.match_duration_slots > 0
+            && slot >= self.match_start_slot.saturating_add(self.match_duration_slots);
+        let limit_reached = self.score_limit > 0
+            && scores.scores.iter().any(|score| *score >= self.score_limit);
+        timed_out || limit_reached
+    }
+
+    /// End the running match and start the next one, returning its result
+    ///
+    /// `bump` is the bump of the result PDA for the current match index.
+    pub fn finish_match(&mut self, scores: &mut TeamScores, slot: u64, bump: u8) -> MatchResult {
+        let best = scores.scores.iter().copied().max().unwrap_or(0);
+        let leaders: Vec<usize> = (0..MAX_TEAMS).filter(|i| scores.scores[*i] == best).collect();
+        let winner = match leaders.as_slice() {
+            [team] if best > 0 => *team as u8 + 1,
+            _ => TEAM_NONE,
+        };
+
+        let result = MatchResult {
+            world: self.world,
+            match_index: self.match_index,
+            mode: self.mode,
+            scores: scores.scores,
+            winner,
+            start_slot: self.match_start_slot,
+            end_slot: slot,
+            bump,
+        };
+
+        self.match_index = self.match_index.saturating_add(1);
+        self.start_match(scores, slot);
+        result
+    }
+
+    /// Check if a player is close enough to an objective to affect scoring
+    ///
+    /// Hill for king of the hill, any flag base for capture the flag.
+    pub fn is_near_objective(&self, player: &WorldPlayer) -> bool {
+        match self.mode {
+            GAME_MODE_KING_OF_THE_HILL => self.is_near(player, self.objective_x, self.objective_z),
+            GAME_MODE_CAPTURE_THE_FLAG => (0..MAX_TEAMS)
+                .any(|team| self.is_near(player, self.flag_base_x[team], self.flag_base_z[team])),
+            _ => false,
+        }
+    }
+
+    /// Check if a player is within the objective radius of (x, z) on the ground plane
+    fn is_near(&self, player: &WorldPlayer, x: i32, z: i32) -> bool {
+        let dx = (player.position_x - x) as i64;
+        let dz = (player.position_z - z) as i64;
+        let radius = self.objective_radius as i64;
+        dx * dx + dz * dz <= radius * radius
+    }
+}
+
+/// Final scores of a finished match (written once, never modified)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct MatchResult {
+    /// World the match was played in
+    pub world: Pubkey,
+    /// Match index within the world
+    pub match_index: u32,
+    /// Game mode played (GAME_MODE_*)
+    pub mode: u8,
+    /// Final score of each team (index = team - 1)
+    pub scores: [u32; MAX_TEAMS],
+    /// Winning team (TEAM_NONE on a tie)
+    pub winner: u8,
+    /// Slot the match started
+    pub start_slot: u64,
+    /// Slot the match ended
+    pub end_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MatchResult {
+    /// Account size: 32 + 4 + 1 + 4 * MAX_TEAMS + 1 + 8 + 8 + 1 = 71
+    pub const LEN: usize = 32 + 4 + 1 + 4 * MAX_TEAMS + 1 + 8 + 8 + 1;
+
+    /// Derive PDA for a world's `match_index`-th match result
+    pub fn derive_pda(world: &Pubkey, match_index: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[MATCH_RESULT_SEED, world.as_ref(), &match_index.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+/// Matchmaking queue entry for a player wallet
+///
+/// Doubles as the join ticket: once matched, `world` names the instance the
+/// player should join.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct QueueTicket {
+    /// Player wallet that queued
+    pub authority: Pubkey,
+    /// Skill rating used for grouping
+    pub rating: u32,
+    /// Queue status (QUEUE_STATUS_*)
+    pub status: u8,
+    /// World instance assigned by the matchmaker (default until matched)
+    pub world: Pubkey,
+    /// Slot the player entered the queue
+    pub enqueued_slot: u64,
+    /// Slot the ticket was matched
+    pub matched_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl QueueTicket {
+    /// Account size: 32 + 4 + 1 + 32 + 8 + 8 + 1 = 86
+    pub const LEN: usize = 32 + 4 + 1 + 32 + 8 + 8 + 1;
+
+    /// Derive PDA for a wallet's queue ticket
+    pub fn derive_pda(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[QUEUE_SEED, authority.as_ref()], program_id)
+    }
+
+    /// Check if the ticket is waiting for a match
+    pub fn is_waiting(&self) -> bool {
+        self.status == QUEUE_STATUS_WAITING
+    }
+
+    /// (Re-)enter the queue
+    pub fn enqueue(&mut self, rating: u32, slot: u64) {
+        self.rating = rating;
+        self.status = QUEUE_STATUS_WAITING;
+        self.world = Pubkey::default();
+        self.enqueued_slot = slot;
+        self.matched_slot = 0;
+    }
+
+    /// Assign the ticket to a world instance
+    pub fn issue(&mut self, world: Pubkey, slot: u64) -> Result<(), WorldError> {
+        if !self.is_waiting() {
+            return Err(WorldError::TicketNotWaiting);
+        }
+        self.status = QUEUE_STATUS_MATCHED;
+        self.world = world;
+        self.matched_slot = slot;
+        Ok(())
+    }
+}
+
+/// Wallets allowed into an allowlist world (authority-managed)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct WorldAllowlist {
+    /// World this allowlist belongs to
+    pub world: Pubkey,
+    /// Number of wallets in `members`
+    pub count: u16,
+    /// Allowed wallets (first `count` entries are used)
+    pub members: [Pubkey; MAX_ALLOWLIST_SIZE],
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl WorldAllowlist {
+    /// Account size: 32 + 2 + 32 * MAX_ALLOWLIST_SIZE + 1 = 1059
+    pub const LEN: usize = 32 + 2 + 32 * MAX_ALLOWLIST_SIZE + 1;
+
+    /// Derive PDA for a world's allowlist
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ALLOWLIST_SEED, world.as_ref()], program_id)
+    }
+
+    /// Wallets currently on the list
+    pub fn members(&self) -> &[Pubkey] {
+        &self.members[..(self.count as usize).min(MAX_ALLOWLIST_SIZE)]
+    }
+
+    /// Check if a wallet is on the list
+    pub fn contains(&self, wallet: &Pubkey) -> bool {
+        self.members().contains(wallet)
+    }
+
+    /// Add a wallet (no-op if already listed)
+    pub fn add(&mut self, wallet: Pubkey) -> Result<(), WorldError> {
+        if self.contains(&wallet) {
+            return Ok(());
+        }
+        let count = self.members().len();
+        if count == MAX_ALLOWLIST_SIZE {
+            return Err(WorldError::AllowlistFull);
+        }
+        self.members[count] = wallet;
+        self.count = count as u16 + 1;
+        Ok(())
+    }
+
+    /// Remove a wallet (no-op if not listed)
+    pub fn remove(&mut self, wallet: &Pubkey) {
+        if let Some(index) = self.members().iter().position(|member| member == wallet) {
+            let last = self.members().len() - 1;
+            self.members.swap(index, last);
+            self.members[last] = Pubkey::default();
+            self.count = last as u16;
+        }
+    }
+}
+
+/// A world's claim on a normalized player name (unique-name worlds)
+///
+/// Created when a player joins, closed when they leave. A claim that was
+/// never released can be taken by another wallet once it expires.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct NameClaim {
+    /// World the name is claimed in
+    pub world: Pubkey,
+    /// Wallet holding the name
+    pub owner: Pubkey,
+    /// Normalized name
+    pub name: [u8; 16],
+    /// Slot after which another wallet may take the name
+    pub expires_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl NameClaim {
+    /// Account size: 32 + 32 + 16 + 8 + 1 = 89
+    pub const LEN: usize = 32 + 32 + 16 + 8 + 1;
+
+    /// Derive PDA for a name in a world (`name` must be normalized)
+    pub fn derive_pda(world: &Pubkey, name: &[u8; 16], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NAME_CLAIM_SEED, world.as_ref(), name], program_id)
+    }
+
+    /// Normalize a name for comparison (ASCII lowercase)
+    pub fn normalize(name: &[u8; 16]) -> [u8; 16] {
+        name.map(|byte| byte.to_ascii_lowercase())
+    }
+
+    /// Check if `wallet` may (re)claim the name at `slot`
+    pub fn can_claim(&self, wallet: &Pubkey, slot: u64) -> bool {
+        self.owner == *wallet || slot >= self.expires_slot
+    }
+}
+
+/// Words players may not use in names (authority-managed)
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct ReservedNames {
+    /// World this list belongs to
+    pub world: Pubkey,
+    /// Number of words in `words`
+    pub count: u16,
+    /// Normalized reserved words (first `count` entries are used)
+    pub words: [[u8; 16]; MAX_RESERVED_NAMES],
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReservedNames {
+    /// Account size: 32 + 2 + 16 * MAX_RESERVED_NAMES + 1 = 547
+    pub const LEN: usize = 32 + 2 + 16 * MAX_RESERVED_NAMES + 1;
+
+    /// Derive PDA for a world's reserved-name list
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RESERVED_NAMES_SEED, world.as_ref()], program_id)
+    }
+
+    /// Words currently reserved
+    pub fn words(&self) -> &[[u8; 16]] {
+        &self.words[..(self.count as usize).min(MAX_RESERVED_NAMES)]
+    }
+
+    /// Check if a name contains any reserved word (case-insensitive)
+    pub fn matches(&self, name: &[u8; 16]) -> bool {
+        let name = NameClaim::normalize(name);
+        let name = &name[..name.iter().position(|&byte| byte == 0).unwrap_or(name.len())];
+        self.words().iter().any(|word| {
+            let word = &word[..word.iter().position(|&byte| byte == 0).unwrap_or(word.len())];
+            !word.is_empty() && name.windows(word.len()).any(|window| window == word)
+        })
+    }
+
+    /// Reserve a word (no-op if already reserved)
+    pub fn add(&mut self, word: &[u8; 16]) -> Result<(), WorldError> {
+        let word = NameClaim::normalize(word);
+        if self.words().contains(&word) {
+            return Ok(());
+        }
+        let count = self.words().len();
+        if count == MAX_RESERVED_NAMES {
+            return Err(WorldError::ReservedNamesFull);
+        }
+        self.words[count] = word;
+        self.count = count as u16 + 1;
+        Ok(())
+    }
+
+    /// Release a word (no-op if not reserved)
+    pub fn remove(&mut self, word: &[u8; 16]) {
+        let word = NameClaim::normalize(word);
+        if let Some(index) = self.words().iter().position(|reserved| *reserved == word) {
+            let last = self.words().len() - 1;
+            self.words.swap(index, last);
+            self.words[last] = [0; 16];
+            self.count = last as u16;
+        }
+    }
+}
+
+/// Player waiting for a slot in a full world
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct WaitlistEntry {
+    /// Waiting wallet
+    pub wallet: Pubkey,
+    /// Player name used when admitted
+    pub name: [u8; 16],
+}
+
+/// FIFO of players waiting to join a world
+///
+/// The leader's waitlist driver admits the head whenever a regular slot opens.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct WorldWaitlist {
+    /// World this waitlist belongs to
+    pub world: Pubkey,
+    /// Number of entries in use
+    pub count: u16,
+    /// Waiting players, oldest first (first `count` entries are used)
+    pub entries: [WaitlistEntry; MAX_WAITLIST_SIZE],
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl WorldWaitlist {
+    /// Account size: 32 + 2 + (32 + 16) * MAX_WAITLIST_SIZE + 1 = 1571
+    pub const LEN: usize = 32 + 2 + (32 + 16) * MAX_WAITLIST_SIZE + 1;
+
+    /// Derive PDA for a world's waitlist
+    pub fn derive_pda(world: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[WAITLIST_SEED, world.as_ref()], program_id)
+    }
+
+    /// Waiting players, oldest first
+    pub fn waiting(&self) -> &[WaitlistEntry] {
+        &self.entries[..(self.count as usize).min(MAX_WAITLIST_SIZE)]
+    }
+
+    /// 1-based place in line of a wallet
+    pub fn position_of(&self, wallet: &Pubkey) -> Option<usize> {
+        self.waiting().iter().position(|entry| entry.wallet == *wallet).map(|index| index + 1)
+    }
+
+    /// Next player to admit
+    pub fn head(&self) -> Option<&WaitlistEntry> {
+        self.waiting().first()
+    }
+
+    /// Add a player to the back of the line, returning their place
+    pub fn push(&mut self, wallet: Pubkey, name: [u8; 16]) -> Result<usize, WorldError> {
+        if self.position_of(&wallet).is_some() {
+            return Err(WorldError::AlreadyWaiting);
+        }
+        let count = self.waiting().len();
+        if count == MAX_WAITLIST_SIZE {
+            return Err(WorldError::WaitlistFull);
+        }
+        self.entries[count] = WaitlistEntry { wallet, name };
+        self.count = count as u16 + 1;
+        Ok(count + 1)
+    }
+
+    /// Remove a player, keeping everyone behind them in order
+    pub fn remove(&mut self, wallet: &Pubkey) -> Result<WaitlistEntry, WorldError> {
+        let index = self.position_of(wallet).ok_or(WorldError::NotOnWaitlist)? - 1;
+        let count = self.waiting().len();
+        let entry = self.entries[index];
+        self.entries.copy_within(index + 1..count, index);
+        self.entries[count - 1] = WaitlistEntry::default();
+        self.count = count as u16 - 1;
+        Ok(entry)
+    }
+
+    /// Take the head of the line
+    pub fn pop_front(&mut self) -> Option<WaitlistEntry> {
+        let wallet = self.head()?.wallet;
+        self.remove(&wallet).ok()
+    }
+}
+
+/// Ephemeral session key registered by a player
+///
+/// Lets a short-lived keypair (held by the browser) sign gameplay
+/// instructions on behalf of the player's wallet authority.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct PlayerSession {
+    /// World player account this session acts for
+    pub player: Pubkey,
+    /// Wallet authority that registered the session
+    pub authority: Pubkey,
+    /// Ephemeral signer
+    pub session_key: Pubkey,
+    /// Last slot (inclusive) the session is valid for
+    pub expiry_slot: u64,
+    /// Bitmask of SESSION_ALLOW_* instructions the session may sign
+    pub allowed_instructions: u8,
+    /// Slot the session was created
+    pub created_slot: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PlayerSession {
+    /// Account size: 32 + 32 + 32 + 8 + 1 + 8 + 1 = 114
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 1 + 8 + 1;
+
+    /// Derive PDA for a player's session
+    pub fn derive_pda(player: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SESSION_SEED, player.as_ref()], program_id)
+    }
+
+    /// Check if the session has expired at the given slot
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot > self.expiry_slot
+    }
+
+    /// Check if the session may sign the given SESSION_ALLOW_* instruction
+    pub fn allows(&self, permission: u8) -> bool {
+        self.allowed_instructions & permission == permission
+    }
+
+    /// Validate that `signer` may act for `player` via this session
+    pub fn validate(
+        &self,
+        player: &Pubkey,
+        signer: &Pubkey,
+        permission: u8,
+        slot: u64,
+    ) -> Result<(), WorldError> {
+        if self.player != *player || self.session_key != *signer {
+            return Err(WorldError::InvalidSession);
+        }
+        if self.is_expired(slot) {
+            return Err(WorldError::SessionExpired);
+        }
+        if !self.allows(permission) {
+            return Err(WorldError::SessionNotAllowed);
+        }
+        Ok(())
+    }
 }
 
 /// 3D Movement input from client
@@ -286,6 +1599,29 @@ pub struct MovementInput3D {
     pub sprint: bool,
     /// Jump input
     pub jump: bool,
+    /// Dash input (burst of speed, then a per-world cooldown)
+    pub dash: bool,
+    /// Crouch input (held: shorter hitbox, slower movement)
+    pub crouch: bool,
+}
+
+impl MovementInput3D {
+    /// Serialized size: 1 + 1 + 2 + 1 + 1 + 1 + 1 = 8
+    pub const LEN: usize = 1 + 1 + 2 + 1 + 1 + 1 + 1;
+
+    /// Has a movement direction
+    pub fn is_moving(&self) -> bool {
+        self.move_x != 0 || self.move_z != 0
+    }
+
+    /// This input held for another slot (without the one-shot jump and dash)
+    pub fn repeated(&self) -> MovementInput3D {
+        MovementInput3D {
+            jump: false,
+            dash: false,
+            ..*self
+        }
+    }
 }
 
 /// Legacy movement input (for compatibility)
@@ -301,10 +1637,19 @@ pub struct MovementInput {
 #[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
 pub struct WeaponStats {
     pub damage: u16,
+    /// Reach in fixed-point units (0 = unlimited)
     pub range: u16,
     pub attack_speed: u8,
 }
 
+impl WeaponStats {
+    /// Check if the target's hitbox is within this weapon's reach
+    pub fn in_range(&self, attacker: &WorldPlayer, target: &WorldPlayer) -> bool {
+        let range = self.range as i64;
+        range == 0 || attacker.reach_distance_squared(target) <= range * range
+    }
+}
+
 /// Convert direction (0-7) to unit vector (for legacy support)
 pub fn direction_to_vector(direction: u8) -> (i32, i32) {
     match direction {